@@ -20,7 +20,7 @@ import { CreateLocale, Locale, LocaleResource, Locales } from './admin/Locale';
 import { Dynamic } from 'solid-js/web';
 import { CreateRole, Role, Roles } from './admin/Role';
 import { CreateUser, User, Users } from './admin/User';
-import { Asset, Assets, UploadAsset } from './cms/Asset';
+import { Asset, Assets, OrphanAssets, UploadAsset } from './cms/Asset';
 import { AppearanceContext, AppearanceService } from './lib/appearance/context';
 import { Settings } from './admin/Settings';
 import { AlertService, AlertState, type DisposableAlert } from './lib/alert';
@@ -148,6 +148,7 @@ const App: Component = () => {
             <Route path="/auth" component={(props) => (<>{props.children}</>)}>
                 <Route path="/login" component={EmailLogin} />
                 <Route path="/oauth/cloud" component={OauthRedirect} />
+                <Route path="/oidc/:provider" component={OauthRedirect} />
                 <Route path="/oauth/login" component={OauthLogin} />
             </Route>
 
@@ -223,6 +224,7 @@ const App: Component = () => {
                 )}>
                     <Route path="/" component={Assets} />
                     <Route path="/upload" component={UploadAsset} />
+                    <Route path="/orphans" component={OrphanAssets} />
                     <Route path="/view/:id" component={Asset} />
                 </Route>
 
@@ -10,6 +10,7 @@ import Dashboard from 'bootstrap-icons/icons/speedometer2.svg';
 import Exclamation from 'bootstrap-icons/icons/exclamation-diamond.svg';
 import FileEarmarkFill from 'bootstrap-icons/icons/file-earmark-fill.svg';
 import FloppyFill from 'bootstrap-icons/icons/floppy-fill.svg';
+import FolderFill from 'bootstrap-icons/icons/folder-fill.svg';
 import GearFill from 'bootstrap-icons/icons/gear-fill.svg';
 import Images from 'bootstrap-icons/icons/images.svg';
 import Journals from 'bootstrap-icons/icons/journals.svg';
@@ -44,6 +45,7 @@ export {
     Exclamation,
     FileEarmarkFill,
     FloppyFill,
+    FolderFill,
     GearFill,
     Images,
     Journals,
@@ -1,6 +1,7 @@
 import { A, useLocation } from "@solidjs/router";
 import { type Component, createEffect, createMemo, createSignal, For, type JSX, onCleanup, Show, useContext } from "solid-js";
 import { Dynamic } from "solid-js/web";
+import { Api } from './lib/api';
 import config from './lib/config';
 import { UserContext } from "./lib/user/context";
 import { Braces, BoxArrowRight, CardText, Columns, Dashboard, Images, Journals, Person, Stack, Translate, ShieldLock, PeopleFill, List, XLg, GearFill, Moon } from "./Icons";
@@ -188,7 +189,11 @@ export function SideNav(): JSX.Element {
                             <a
                                 class="dropdown-item icon-link py-2"
                                 href={config.resolveBaseUrl('/auth/login')}
-                                on:click={_ => localStorage.removeItem('token')}
+                                on:click={ev => {
+                                    ev.preventDefault();
+
+                                    Api.logout().then(() => window.location.assign(config.resolveBaseUrl('/auth/login')));
+                                }}
                                 rel="external"
                             >
                                 <BoxArrowRight viewBox="0 0 16 16" />
@@ -1,9 +1,9 @@
 import { createSignal, Show, useContext } from "solid-js";
 import { Api, HttpError } from "../../lib/api";
-import config from "../../lib/config";
 import { LocaleContext } from "../../lib/i18n";
 import { AlertContext } from "../../lib/alert";
 import ProgressSpinner from "../../components/ProgressSpinner";
+import TwoFactor, { type ChallengeResult, finishLogin, isChallenge, type LoginResult } from "./TwoFactor";
 
 export default function() {
     enum ValidationError {
@@ -21,6 +21,7 @@ export default function() {
     const [inProgress, setInProgress] = createSignal(false);
     const [validationErrors, setValidationErrors] = createSignal(new Set<ValidationError>());
     const [serverError, setServerError] = createSignal(undefined as string | undefined);
+    const [challenge, setChallenge] = createSignal(undefined as ChallengeResult | undefined);
 
     const onSubmit = (ev: SubmitEvent) => {
         ev.preventDefault();
@@ -53,14 +54,16 @@ export default function() {
 
         setInProgress(true);
 
-        Api.post<{ email: string; password: string }, { token: string }>(
+        Api.post<{ email: string; password: string }, LoginResult>(
             '/auth/login',
             req
         )
-            .then((token) => {
-                localStorage.setItem('token', token.token);
-
-                window.location.assign(config.baseURL);
+            .then((result) => {
+                if (isChallenge(result)) {
+                    setChallenge(result);
+                } else {
+                    finishLogin(result);
+                }
             })
             .catch((e) => {
                 const msg = e.message in i18n.serverErrors ? i18n.serverErrors[e.message as keyof typeof i18n.serverErrors] : e.message;
@@ -78,61 +81,65 @@ export default function() {
         <div class="container-fluid w-100">
             <div class="row">
                 <div class="col-lg-6 d-flex flex-column justify-content-center align-items-center" style="min-height: 100vh">
-                    <form id="auth-form" onSubmit={onSubmit}>
-                        <div class="mb-4 text-center">
-                            <h3>{i18n.title()}</h3>
-                            <small class="text-secondary">{i18n.subtitle()}.</small>
-                        </div>
-                        <div class="mb-4">
-                            <label for="login-email" class="mb-2">{i18n.email()}</label>
-                            <input
-                                id="login-email"
-                                type="email"
-                                class="form-control"
-                                classList={{ 'is-invalid': validationErrors().has(ValidationError.Email) }}
-                                placeholder={i18n.email()}
-                                name="email"
-                                value={email()}
-                                onInput={(e) => setEmail(e.target.value)}
-                            />
-                            <Show when={validationErrors().has(ValidationError.Email)}>
-                                <small class="text-danger">{i18n.validationErrors.email()}.</small>
-                            </Show>
-                        </div>
-                        <div class="form-group mb-4">
-                            <label for="login-password" class="mb-2">{i18n.password()}</label>
-                            <input
-                                id="login-password"
-                                type="password"
-                                class="form-control"
-                                classList={{ 'is-invalid': validationErrors().has(ValidationError.Password) }}
-                                placeholder={i18n.password()}
-                                name="password"
-                                value={password()}
-                                onInput={(e) => setPassword(e.target.value)}
-                            />
-                            <Show when={validationErrors().has(ValidationError.Password)}>
-                                <small class="text-danger">{i18n.validationErrors.password()}.</small>
+                    <Show when={challenge()} fallback={
+                        <form id="auth-form" onSubmit={onSubmit}>
+                            <div class="mb-4 text-center">
+                                <h3>{i18n.title()}</h3>
+                                <small class="text-secondary">{i18n.subtitle()}.</small>
+                            </div>
+                            <div class="mb-4">
+                                <label for="login-email" class="mb-2">{i18n.email()}</label>
+                                <input
+                                    id="login-email"
+                                    type="email"
+                                    class="form-control"
+                                    classList={{ 'is-invalid': validationErrors().has(ValidationError.Email) }}
+                                    placeholder={i18n.email()}
+                                    name="email"
+                                    value={email()}
+                                    onInput={(e) => setEmail(e.target.value)}
+                                />
+                                <Show when={validationErrors().has(ValidationError.Email)}>
+                                    <small class="text-danger">{i18n.validationErrors.email()}.</small>
+                                </Show>
+                            </div>
+                            <div class="form-group mb-4">
+                                <label for="login-password" class="mb-2">{i18n.password()}</label>
+                                <input
+                                    id="login-password"
+                                    type="password"
+                                    class="form-control"
+                                    classList={{ 'is-invalid': validationErrors().has(ValidationError.Password) }}
+                                    placeholder={i18n.password()}
+                                    name="password"
+                                    value={password()}
+                                    onInput={(e) => setPassword(e.target.value)}
+                                />
+                                <Show when={validationErrors().has(ValidationError.Password)}>
+                                    <small class="text-danger">{i18n.validationErrors.password()}.</small>
+                                </Show>
+                            </div>
+
+                            <Show when={serverError()}>
+                                <div class="mb-2">
+                                    <small class="text-danger">{serverError()}</small>
+                                </div>
                             </Show>
-                        </div>
 
-                        <Show when={serverError()}>
-                            <div class="mb-2">
-                                <small class="text-danger">{serverError()}</small>
+                            <div>
+                                <button
+                                    type="submit"
+                                    class="btn btn-primary icon-link justify-content-center w-100 py-2"
+                                    disabled={inProgress()}
+                                >
+                                    <ProgressSpinner show={inProgress()} />
+                                    {i18n.login()}
+                                </button>
                             </div>
-                        </Show>
-
-                        <div>
-                            <button
-                                type="submit"
-                                class="btn btn-primary icon-link justify-content-center w-100 py-2"
-                                disabled={inProgress()}
-                            >
-                                <ProgressSpinner show={inProgress()} />
-                                {i18n.login()}
-                            </button>
-                        </div>
-                    </form>
+                        </form>
+                    }>
+                        {(challenge) => (<TwoFactor challengeToken={challenge().challengeToken} challenge={challenge().challenge} />)}
+                    </Show>
                 </div>
                 <div class="col-lg-6 d-none d-lg-flex flex-column justify-content-center align-items-center" style="background: var(--custom-bg)">
                     <a class="py-3" rel="external" href="/">
@@ -1,6 +1,7 @@
-import { useSearchParams } from "@solidjs/router";
-import { createSignal, Show } from "solid-js";
+import { useParams, useSearchParams } from "@solidjs/router";
+import { createSignal, Match, Switch } from "solid-js";
 import config from "../../lib/config";
+import TwoFactor, { type ChallengeResult, finishLogin, isChallenge, type LoginResult } from "./TwoFactor";
 
 const STATE_LENGTH = 32;
 const STATE_KEY = 'oauth_state';
@@ -20,6 +21,8 @@ function generateState(length: number) {
 }
 
 export const OauthRedirect = () => {
+    // Generic OpenID Connect providers are routed with their key, the cloud provider is not
+    const params = useParams();
     const state = generateState(STATE_LENGTH);
 
     const oauthState: OauthState = {
@@ -29,12 +32,16 @@ export const OauthRedirect = () => {
 
     localStorage.setItem(STATE_KEY, JSON.stringify(oauthState));
 
-    window.location.assign(config.resolveApiURL(`/auth/oauth/redirect?state=${state}`))
+    const redirectPath = params.provider
+        ? `/auth/oidc/${encodeURIComponent(params.provider)}/redirect`
+        : '/auth/oauth/redirect';
+
+    window.location.assign(config.resolveApiURL(`${redirectPath}?state=${state}`))
 
     return (<></>);
 }
 
-function finishOauth(params: Record<string, string | string[] | undefined>) {
+function finishOauth(params: Record<string, string | string[] | undefined>): LoginResult {
     const item = localStorage.getItem(STATE_KEY);
 
     if (item === null) {
@@ -61,28 +68,48 @@ function finishOauth(params: Record<string, string | string[] | undefined>) {
         throw new Error('mismatched_state');
     }
 
-    if (typeof params.token !== 'string') {
+    localStorage.removeItem(STATE_KEY);
+
+    // Users with two-factor authentication are redirected with a challenge instead of a session
+    if (typeof params.challenge_token === 'string' && (params.challenge === 'verify' || params.challenge === 'enroll')) {
+        return { challengeToken: params.challenge_token, challenge: params.challenge };
+    }
+
+    if (typeof params.token !== 'string' || typeof params.refresh_token !== 'string') {
         throw new Error('missing_token_in_query');
     }
 
-    localStorage.setItem('token', params.token);
-    localStorage.removeItem(STATE_KEY);
+    return { token: params.token, refreshToken: params.refresh_token };
 }
 
 export const OauthLogin = () => {
     const [error, setError] = createSignal(undefined as string | undefined);
+    const [challenge, setChallenge] = createSignal(undefined as ChallengeResult | undefined);
     const [searchParams] = useSearchParams();
 
     try {
-        finishOauth(searchParams);
+        const result = finishOauth(searchParams);
 
-        window.location.assign(config.baseURL);
+        if (isChallenge(result)) {
+            setChallenge(result);
+        } else {
+            finishLogin(result);
+        }
     } catch (e) {
         setError(`${e}`);
     }
     return (
-        <Show when={error()}>
-            {(e) => <p>Failed to login {e()}</p>}
-        </Show>
+        <Switch>
+            <Match when={error()}>
+                {(e) => <p>Failed to login {e()}</p>}
+            </Match>
+            <Match when={challenge()}>
+                {(challenge) => (
+                    <div class="d-flex flex-column justify-content-center align-items-center" style="min-height: 100vh">
+                        <TwoFactor challengeToken={challenge().challengeToken} challenge={challenge().challenge} />
+                    </div>
+                )}
+            </Match>
+        </Switch>
     );
 }
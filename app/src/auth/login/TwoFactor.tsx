@@ -0,0 +1,225 @@
+import { createSignal, For, Match, Show, Switch, useContext } from "solid-js";
+import { Api, HttpError } from "../../lib/api";
+import config from "../../lib/config";
+import { LocaleContext } from "../../lib/i18n";
+import { AlertContext } from "../../lib/alert";
+import ProgressSpinner from "../../components/ProgressSpinner";
+
+export type Challenge = 'verify' | 'enroll';
+
+export interface SessionTokens {
+    token: string;
+    refreshToken: string;
+}
+
+export interface ChallengeResult {
+    challengeToken: string;
+    challenge: Challenge;
+}
+
+export type LoginResult = SessionTokens | ChallengeResult;
+
+export function isChallenge(result: LoginResult): result is ChallengeResult {
+    return 'challengeToken' in result;
+}
+
+export function finishLogin(tokens: SessionTokens) {
+    Api.storeTokens(tokens);
+
+    window.location.assign(config.baseURL);
+}
+
+interface Enrollment {
+    secret: string;
+    uri: string;
+}
+
+interface EnrolledLogin extends SessionTokens {
+    recoveryCodes: string[];
+}
+
+export default function TwoFactor(props: ChallengeResult) {
+    const alertCtx = useContext(AlertContext)!;
+    const localeCtx = useContext(LocaleContext)!;
+    const i18n = localeCtx.i18n.auth.login;
+
+    const [code, setCode] = createSignal('');
+    const [enrollment, setEnrollment] = createSignal(undefined as Enrollment | undefined);
+    const [enrolledLogin, setEnrolledLogin] = createSignal(undefined as EnrolledLogin | undefined);
+
+    const [inProgress, setInProgress] = createSignal(false);
+    const [validationError, setValidationError] = createSignal(false);
+    const [serverError, setServerError] = createSignal(undefined as string | undefined);
+
+    const onError = (e: Error) => {
+        const msg = e.message in i18n.serverErrors ? i18n.serverErrors[e.message as keyof typeof i18n.serverErrors] : e.message;
+
+        if (e instanceof HttpError) {
+            setServerError(msg);
+        } else {
+            alertCtx.fail(msg);
+        }
+    };
+
+    const startEnrollment = () => {
+        if (inProgress()) {
+            return;
+        }
+
+        setServerError(undefined);
+        setInProgress(true);
+
+        Api.post<{ challengeToken: string }, Enrollment>('/auth/login/two-factor/enroll', { challengeToken: props.challengeToken })
+            .then(setEnrollment)
+            .catch(onError)
+            .finally(() => setInProgress(false));
+    };
+
+    const onSubmit = (ev: SubmitEvent) => {
+        ev.preventDefault();
+
+        if (inProgress()) {
+            return;
+        }
+
+        setServerError(undefined);
+
+        const req = {
+            challengeToken: props.challengeToken,
+            code: code().trim(),
+        };
+
+        setValidationError(req.code.length === 0);
+
+        if (validationError()) {
+            return;
+        }
+
+        setInProgress(true);
+
+        const request = props.challenge === 'verify'
+            ? Api.post<typeof req, SessionTokens>('/auth/login/two-factor', req).then(finishLogin)
+            : Api.post<typeof req, EnrolledLogin>('/auth/login/two-factor/confirm', req).then(setEnrolledLogin);
+
+        request
+            .catch(onError)
+            .finally(() => setInProgress(false));
+    };
+
+    const codeInput = () => (
+        <div class="mb-4">
+            <label for="login-two-factor-code" class="mb-2">{i18n.twoFactor.code()}</label>
+            <input
+                id="login-two-factor-code"
+                type="text"
+                class="form-control"
+                classList={{ 'is-invalid': validationError() }}
+                placeholder={i18n.twoFactor.code()}
+                name="code"
+                autocomplete="one-time-code"
+                value={code()}
+                onInput={(e) => setCode(e.target.value)}
+            />
+            <Show when={validationError()}>
+                <small class="text-danger">{i18n.validationErrors.code()}.</small>
+            </Show>
+        </div>
+    );
+
+    const submitButton = (label: string) => (
+        <div>
+            <button
+                type="submit"
+                class="btn btn-primary icon-link justify-content-center w-100 py-2"
+                disabled={inProgress()}
+            >
+                <ProgressSpinner show={inProgress()} />
+                {label}
+            </button>
+        </div>
+    );
+
+    const serverErrorMessage = () => (
+        <Show when={serverError()}>
+            <div class="mb-2">
+                <small class="text-danger">{serverError()}</small>
+            </div>
+        </Show>
+    );
+
+    return (
+        <Switch>
+            <Match when={enrolledLogin()}>
+                {(login) => (
+                    <div id="auth-form">
+                        <div class="mb-4 text-center">
+                            <h3>{i18n.twoFactor.recoveryCodes()}</h3>
+                            <small class="text-secondary">{i18n.twoFactor.recoveryCodesDescription()}.</small>
+                        </div>
+                        <ul class="list-unstyled font-monospace text-center mb-4">
+                            <For each={login().recoveryCodes}>
+                                {(recoveryCode) => (<li>{recoveryCode}</li>)}
+                            </For>
+                        </ul>
+                        <button
+                            type="button"
+                            class="btn btn-primary w-100 py-2"
+                            onClick={() => finishLogin(login())}
+                        >
+                            {i18n.twoFactor.continue()}
+                        </button>
+                    </div>
+                )}
+            </Match>
+            <Match when={props.challenge === 'verify'}>
+                <form id="auth-form" onSubmit={onSubmit}>
+                    <div class="mb-4 text-center">
+                        <h3>{i18n.twoFactor.title()}</h3>
+                        <small class="text-secondary">{i18n.twoFactor.verifyDescription()}.</small>
+                    </div>
+                    {codeInput()}
+                    {serverErrorMessage()}
+                    {submitButton(i18n.twoFactor.verify())}
+                </form>
+            </Match>
+            <Match when={enrollment()}>
+                {(enrollment) => (
+                    <form id="auth-form" onSubmit={onSubmit}>
+                        <div class="mb-4 text-center">
+                            <h3>{i18n.twoFactor.title()}</h3>
+                            <small class="text-secondary">{i18n.twoFactor.confirmDescription()}.</small>
+                        </div>
+                        <div class="mb-4">
+                            <label class="mb-2">{i18n.twoFactor.secret()}</label>
+                            <input type="text" class="form-control font-monospace" value={enrollment().secret} readonly />
+                            <small class="text-secondary text-break">
+                                <a href={enrollment().uri}>{enrollment().uri}</a>
+                            </small>
+                        </div>
+                        {codeInput()}
+                        {serverErrorMessage()}
+                        {submitButton(i18n.twoFactor.confirm())}
+                    </form>
+                )}
+            </Match>
+            <Match when={props.challenge === 'enroll'}>
+                <div id="auth-form">
+                    <div class="mb-4 text-center">
+                        <h3>{i18n.twoFactor.title()}</h3>
+                        <small class="text-secondary">{i18n.twoFactor.enrollDescription()}.</small>
+                    </div>
+                    {serverErrorMessage()}
+                    <button
+                        type="button"
+                        class="btn btn-primary icon-link justify-content-center w-100 py-2"
+                        disabled={inProgress()}
+                        onClick={startEnrollment}
+                    >
+                        <ProgressSpinner show={inProgress()} />
+                        {i18n.twoFactor.enroll()}
+                    </button>
+                </div>
+            </Match>
+        </Switch>
+    );
+}
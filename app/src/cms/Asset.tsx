@@ -1,19 +1,75 @@
 import { createEffect, createMemo, createResource, createSignal, For, Match, onCleanup, Show, Switch, useContext } from "solid-js";
 import { CMSContext } from "../lib/cms/context";
 import { A, useNavigate, useParams, useSearchParams } from "@solidjs/router";
-import { FileEarmarkFill, FloppyFill, PencilSquare, ThreeDotsVertical, Trash, Upload } from "../Icons";
+import { FileEarmarkFill, FloppyFill, FolderFill, PencilSquare, ThreeDotsVertical, Trash, Upload } from "../Icons";
 import { AlertContext } from "../lib/alert";
 import { Api, HttpError } from "../lib/api";
 import { dropdownClickListener } from "../lib/utils";
 import config from '../lib/config';
-import { type Asset as AssetModel } from '../lib/cms/models';
+import { type Asset as AssetModel, type AssetFolder } from '../lib/cms/models';
+import { type SearchAssets } from '../lib/cms/requests';
 import { PaginationRequest } from "../lib/models";
 import { Pagination } from "../components/Pagination";
-import { createStore } from "solid-js/store";
+import { createStore, reconcile } from "solid-js/store";
 import ProgressSpinner from "../components/ProgressSpinner";
 import './Asset.scss';
 import DeleteConfirmModal from "../components/DeleteConfirmModal";
 import { LocaleContext } from "../lib/i18n";
+import { CommonContext } from "../lib/context";
+
+const RESIZABLE_IMAGE_TYPES = ['image/jpeg', 'image/png', 'image/webp', 'image/gif'];
+
+const thumbnailURL = (asset: AssetModel) => {
+    const url = config.resolveSiteURL(`/assets/content/${asset.filename}`);
+
+    return RESIZABLE_IMAGE_TYPES.includes(asset.filetype ?? '') ? `${url}?width=320` : url;
+};
+
+const FILETYPES = [
+    { prefix: 'image/', label: 'images' },
+    { prefix: 'video/', label: 'videos' },
+    { prefix: 'audio/', label: 'audios' },
+    { prefix: 'application/', label: 'documents' },
+] as const;
+
+const folderPath = (folders: AssetFolder[], id: number | null): AssetFolder[] => {
+    const path = [];
+    let folder = folders.find((f) => f.id === id);
+
+    while (folder && path.length < folders.length) {
+        path.unshift(folder);
+
+        const parentId = folder.parentId;
+        folder = folders.find((f) => f.id === parentId);
+    }
+
+    return path;
+};
+
+const formatSize = (size: number) => `${Math.ceil(size / 1024)} KB`;
+
+const FolderSelect = (props: { id: string, folders: AssetFolder[], value: string, disabled?: boolean, onChange: (value: string) => void }) => {
+    const localeCtx = useContext(LocaleContext)!;
+
+    const options = createMemo(() => props.folders
+        .map((folder) => ({ id: folder.id, path: folderPath(props.folders, folder.id).map((f) => f.name).join(' / ') }))
+        .sort((a, b) => a.path.localeCompare(b.path)));
+
+    return (
+        <select
+            id={props.id}
+            class="form-select"
+            value={props.value}
+            disabled={props.disabled}
+            onChange={(ev) => props.onChange(ev.target.value)}
+        >
+            <option value="">{localeCtx.i18n.asset.labels.rootFolder()}</option>
+            <For each={options()}>
+                {(option) => (<option value={option.id.toString()}>{option.path}</option>)}
+            </For>
+        </select>
+    );
+};
 
 export const PickAsset = (props: { close: () => void, pick: (asset: AssetModel) => void, }) => {
     const cmsContext = useContext(CMSContext)!;
@@ -58,7 +114,7 @@ export const PickAsset = (props: { close: () => void, pick: (asset: AssetModel)
                                                                         <FileEarmarkFill class="w-100 h-100 text-secondary-emphasis" viewBox="0 0 16 16" />
                                                                     }>
                                                                         <img
-                                                                            src={config.resolveSiteURL(`/assets/content/${asset.filename}`)}
+                                                                            src={thumbnailURL(asset)}
                                                                             alt={asset.name}
                                                                         />
                                                                     </Show>
@@ -112,11 +168,16 @@ export const UploadAsset = () => {
     let imageEl: HTMLImageElement | undefined;
 
     const alertCtx = useContext(AlertContext)!;
+    const cmsContext = useContext(CMSContext)!;
     const localeCtx = useContext(LocaleContext)!;
     const navigate = useNavigate();
+    const [searchParams] = useSearchParams();
 
     const i18n = localeCtx.i18n.asset;
 
+    const [folders] = createResource(() => cmsContext.fetchAssetFolders());
+    const [folderId, setFolderId] = createSignal((searchParams.folder as string | undefined) ?? '');
+
     const [detail, setDetail] = createSignal(undefined as AssetDetail | undefined);
     const [asset, setAsset] = createSignal(undefined as File | undefined);
 
@@ -196,6 +257,10 @@ export const UploadAsset = () => {
         const formdata = new FormData();
         formdata.append('asset', asset()!);
 
+        if (folderId()) {
+            formdata.append('folderId', folderId());
+        }
+
         Api.request<unknown, AssetModel>('/cms/asset/create', 'POST', { formdata })
             .then((asset) => {
                 alertCtx.success(i18n.actions.assetUploaded(asset.name));
@@ -236,6 +301,17 @@ export const UploadAsset = () => {
                             </Show>
                         </div>
 
+                        <div class="mb-4">
+                            <label for="assetFolder" class="form-label">{i18n.labels.folder()}</label>
+                            <FolderSelect
+                                id="assetFolder"
+                                folders={folders() ?? []}
+                                value={folderId()}
+                                disabled={inProgress() !== undefined}
+                                onChange={setFolderId}
+                            />
+                        </div>
+
                         <Show when={inProgress() === Action.Analyze}>
                             <div class="d-flex justify-content-center mb-4">
                                 <ProgressSpinner show={true} />
@@ -256,7 +332,7 @@ export const UploadAsset = () => {
                                         </tr>
                                         <tr>
                                             <td>{i18n.labels.size()}</td>
-                                            <td>{Math.ceil(detail().size / 1024)} KB</td>
+                                            <td>{formatSize(detail().size)}</td>
                                         </tr>
                                     </tbody>
                                 </table>
@@ -292,6 +368,11 @@ export const UploadAsset = () => {
 };
 
 export const Assets = () => {
+    enum Action {
+        CreateFolder,
+    }
+
+    const alertCtx = useContext(AlertContext)!;
     const cmsContext = useContext(CMSContext)!;
     const localeCtx = useContext(LocaleContext)!;
     const [searchParams, setSearchParams] = useSearchParams();
@@ -300,17 +381,184 @@ export const Assets = () => {
 
     const pagination = createMemo(() => PaginationRequest.fromParams(searchParams.page, searchParams.perPage));
 
-    const [assets] = createResource(pagination, (pagination) => cmsContext.fetchAssets(pagination));
+    const folderId = createMemo(() => searchParams.folder ? parseInt(searchParams.folder as string) : null);
+
+    const search = createMemo(() => {
+        const query = (searchParams.query as string | undefined) ?? '';
+        const req: SearchAssets = { query, filetype: searchParams.type as string | undefined };
+
+        const id = folderId();
+
+        // Searching from the root folder looks into every folder
+        if (id !== null) {
+            req.folderId = id;
+        } else if (query.length === 0) {
+            req.root = true;
+        }
+
+        return req;
+    });
+
+    const [folders, { refetch: refetchFolders }] = createResource(() => cmsContext.fetchAssetFolders());
+
+    const [assets] = createResource(
+        () => ({ search: search(), pagination: pagination() }),
+        ({ search, pagination }) => cmsContext.searchAssets(search, pagination),
+    );
+
+    const path = createMemo(() => folderPath(folders() ?? [], folderId()));
+    const subfolders = createMemo(() => (folders() ?? []).filter((f) => f.parentId === folderId()));
+
+    const [creatingFolder, setCreatingFolder] = createSignal(false);
+    const [folderName, setFolderName] = createSignal('');
+    const [folderNameError, setFolderNameError] = createSignal(false);
+    const [deletingFolder, setDeletingFolder] = createSignal(undefined as AssetFolder | undefined);
+
+    const [inProgress, setInProgress] = createSignal(undefined as Action | undefined);
+
+    const translateError = (e: string) => {
+        return (e in i18n.serverErrors)
+            ? i18n.serverErrors[e as keyof typeof i18n.serverErrors]()
+            : e;
+    };
+
+    const createFolder = (ev: SubmitEvent) => {
+        ev.preventDefault();
+
+        if (inProgress() !== undefined) {
+            return;
+        }
+
+        const name = folderName().trim();
+
+        setFolderNameError(name.length === 0 || name.length > 128 || name.includes('/'));
+
+        if (folderNameError()) {
+            return;
+        }
+
+        setInProgress(Action.CreateFolder);
+
+        cmsContext.createAssetFolder({ name, parentId: folderId() })
+            .then((folder) => {
+                alertCtx.success(i18n.actions.folderCreated(folder.name));
+
+                setCreatingFolder(false);
+                setFolderName('');
+
+                return refetchFolders();
+            })
+            .catch((e) => alertCtx.fail(translateError(e.message)))
+            .finally(() => setInProgress(undefined));
+    };
+
+    const deleteFolder = () => {
+        const folder = deletingFolder();
+
+        if (!folder) {
+            return;
+        }
+
+        return cmsContext.deleteAssetFolder(folder.id)
+            .then(() => {
+                setDeletingFolder(undefined);
+
+                alertCtx.success(i18n.actions.folderDeleted(folder.name));
+
+                setSearchParams({ folder: folder.parentId?.toString(), page: undefined });
+
+                return refetchFolders();
+            });
+    };
 
     return (
         <div class="container py-4 px-md-4">
-            <div class="d-flex align-items-center mb-5">
+            <div class="d-flex align-items-center mb-4">
                 <h1 class="flex-grow-1 m-0">{localeCtx.i18n.nav.links.assets()}</h1>
-                <A class="btn btn-outline-primary icon-link" href="/assets/upload">
+                <A class="btn icon-link me-2" href="/assets/orphans">
+                    {i18n.actions.orphanAssets()}
+                </A>
+                <button type="button" class="btn btn-outline-secondary icon-link me-2" onClick={() => setCreatingFolder(!creatingFolder())}>
+                    <FolderFill viewBox="0 0 16 16" />
+                    {i18n.actions.createFolder()}
+                </button>
+                <A class="btn btn-outline-primary icon-link" href={folderId() !== null ? `/assets/upload?folder=${folderId()}` : '/assets/upload'}>
                     <Upload viewBox="0 0 16 16" />
                     {i18n.actions.uploadAsset()}
                 </A>
             </div>
+
+            <Show when={creatingFolder()}>
+                <form class="d-flex mb-4 offset-md-8 col-md-4" onSubmit={createFolder}>
+                    <input
+                        type="text"
+                        class="form-control me-2"
+                        classList={{ 'is-invalid': folderNameError() }}
+                        placeholder={i18n.labels.folderName()}
+                        title={folderNameError() ? i18n.validationErrors.folderName() : undefined}
+                        value={folderName()}
+                        onInput={(ev) => setFolderName(ev.target.value)}
+                    />
+                    <button type="submit" class="btn btn-primary icon-link" disabled={inProgress() === Action.CreateFolder}>
+                        <ProgressSpinner show={inProgress() === Action.CreateFolder} small={true} />
+                        {localeCtx.i18n.common.actions.create()}
+                    </button>
+                </form>
+            </Show>
+
+            <div class="d-flex flex-wrap align-items-center gap-2 mb-4">
+                <nav aria-label="breadcrumb" class="flex-grow-1">
+                    <ol class="breadcrumb m-0">
+                        <li class="breadcrumb-item"><A href="/assets">{i18n.labels.rootFolder()}</A></li>
+                        <For each={path()}>
+                            {(folder) => (
+                                <li class="breadcrumb-item"><A href={`/assets?folder=${folder.id}`}>{folder.name}</A></li>
+                            )}
+                        </For>
+                    </ol>
+                </nav>
+                <Show when={path().at(-1)}>
+                    {(folder) => (
+                        <button type="button" class="btn btn-sm text-danger icon-link" onClick={() => setDeletingFolder(folder())}>
+                            <Trash viewBox="0 0 16 16" />
+                            {i18n.actions.deleteFolder()}
+                        </button>
+                    )}
+                </Show>
+                <input
+                    type="search"
+                    class="form-control w-auto"
+                    placeholder={i18n.labels.search()}
+                    value={(searchParams.query as string | undefined) ?? ''}
+                    onChange={(ev) => setSearchParams({ query: ev.target.value.trim() || undefined, page: undefined })}
+                />
+                <select
+                    class="form-select w-auto"
+                    value={(searchParams.type as string | undefined) ?? ''}
+                    onChange={(ev) => setSearchParams({ type: ev.target.value || undefined, page: undefined })}
+                >
+                    <option value="">{i18n.labels.anyType()}</option>
+                    <For each={FILETYPES}>
+                        {(filetype) => (<option value={filetype.prefix}>{i18n.labels[filetype.label]()}</option>)}
+                    </For>
+                </select>
+            </div>
+
+            <Show when={subfolders().length > 0}>
+                <ul class="mb-4 list-unstyled d-flex flex-wrap gap-2">
+                    <For each={subfolders()}>
+                        {(folder) => (
+                            <li>
+                                <A href={`/assets?folder=${folder.id}`} class="btn btn-outline-secondary icon-link">
+                                    <FolderFill viewBox="0 0 16 16" />
+                                    {folder.name}
+                                </A>
+                            </li>
+                        )}
+                    </For>
+                </ul>
+            </Show>
+
             <Switch>
                 <Match when={assets.loading}>
                     <p class="icon-link justify-content-center w-100"><ProgressSpinner show={true} /> {localeCtx.i18n.common.loading()} ...</p>
@@ -318,6 +566,9 @@ export const Assets = () => {
                 <Match when={assets.error}>
                     <p class="text-danger-emphasis text-center">{localeCtx.i18n.common.loadingItemError(localeCtx.i18n.nav.links.assets())}: <strong>{assets.error.message}</strong></p>
                 </Match>
+                <Match when={assets() && assets()!.currentPage === 1 && assets()!.items.length === 0 && (searchParams.query || searchParams.type)}>
+                    <p class="text-secondary text-center">{i18n.noAssetForSearch()}.</p>
+                </Match>
                 <Match when={assets() && assets()!.currentPage === 1 && assets()!.items.length === 0}>
                     <p class="text-secondary text-center">{i18n.noAsset()}. {i18n.canUploadAsset()}.</p>
                 </Match>
@@ -336,7 +587,7 @@ export const Assets = () => {
                                                         <FileEarmarkFill class="w-100 h-100 text-secondary-emphasis" viewBox="0 0 16 16" />
                                                     }>
                                                         <img
-                                                            src={config.resolveSiteURL(`/assets/content/${asset.filename}`)}
+                                                            src={thumbnailURL(asset)}
                                                             alt={asset.name}
                                                         />
                                                     </Show>
@@ -359,6 +610,117 @@ export const Assets = () => {
                     )}
                 </Match>
             </Switch>
+
+            <Show when={deletingFolder()}>
+                {(folder) => (
+                    <DeleteConfirmModal
+                        message={<p>{i18n.actions.confirmDeleteFolder(folder().name)}?</p>}
+                        close={() => setDeletingFolder(undefined)}
+                        confirm={deleteFolder}
+                        translateError={translateError}
+                    />
+                )}
+            </Show>
+        </div>
+    );
+};
+
+export const OrphanAssets = () => {
+    const alertCtx = useContext(AlertContext)!;
+    const cmsContext = useContext(CMSContext)!;
+    const localeCtx = useContext(LocaleContext)!;
+
+    const i18n = localeCtx.i18n.asset;
+
+    const [orphans, { refetch }] = createResource(() => cmsContext.fetchOrphanAssets());
+
+    const [purging, setPurging] = createSignal(false);
+
+    const translateError = (e: string) => {
+        return (e in i18n.serverErrors)
+            ? i18n.serverErrors[e as keyof typeof i18n.serverErrors]()
+            : e;
+    };
+
+    const purgeOrphans = () => {
+        const assetIds = orphans()?.map((asset) => asset.id);
+
+        if (!assetIds) {
+            return;
+        }
+
+        // Only the listed ones are purged, assets orphaned after listing are kept
+        return cmsContext.purgeOrphanAssets(assetIds)
+            .then((purged) => {
+                setPurging(false);
+
+                alertCtx.success(i18n.actions.orphansPurged(purged.length));
+
+                return refetch();
+            });
+    };
+
+    return (
+        <div class="container py-4 px-md-4">
+            <div class="d-flex align-items-center mb-5">
+                <div class="flex-grow-1">
+                    <h1 class="m-0">{i18n.actions.orphanAssets()}</h1>
+                    <small>{i18n.orphanAssetsDesc()}</small>
+                </div>
+                <button
+                    type="button"
+                    class="btn btn-outline-danger icon-link"
+                    disabled={!orphans() || orphans()!.length === 0}
+                    onClick={() => setPurging(true)}
+                >
+                    <Trash viewBox="0 0 16 16" />
+                    {i18n.actions.purgeOrphans()}
+                </button>
+            </div>
+            <Switch>
+                <Match when={orphans.loading}>
+                    <p class="icon-link justify-content-center w-100"><ProgressSpinner show={true} /> {localeCtx.i18n.common.loading()} ...</p>
+                </Match>
+                <Match when={orphans.error}>
+                    <p class="text-danger-emphasis text-center">{localeCtx.i18n.common.loadingItemError(i18n.actions.orphanAssets())}: <strong>{orphans.error.message}</strong></p>
+                </Match>
+                <Match when={orphans()?.length === 0}>
+                    <p class="text-secondary text-center">{i18n.noOrphanAsset()}.</p>
+                </Match>
+                <Match when={orphans()}>
+                    {(orphans) => (
+                        <ul class="mb-5 list-unstyled d-flex flex-wrap asset-masonry">
+                            <For each={orphans()}>
+                                {(asset) => (
+                                    <li class="p-1 flex-grow-1 d-flex justify-content-center">
+                                        <A href={`/assets/view/${asset.id}`} class="position-relative h-100 d-flex rounded overflow-hidden">
+                                            <Show when={asset.filetype?.startsWith('image')} fallback={
+                                                <FileEarmarkFill class="w-100 h-100 text-secondary-emphasis" viewBox="0 0 16 16" />
+                                            }>
+                                                <img
+                                                    src={thumbnailURL(asset)}
+                                                    alt={asset.name}
+                                                />
+                                            </Show>
+                                            <small class="text-body bg-secondary-subtle position-absolute text-center w-100 start-0 bottom-0 py-1">{asset.name}</small>
+                                        </A>
+                                    </li>
+                                )}
+                            </For>
+                            <li style="flex-grow: 10"></li>
+                        </ul>
+                    )}
+                </Match>
+            </Switch>
+
+            <Show when={purging()}>
+                <DeleteConfirmModal
+                    message={<p>{i18n.actions.confirmPurgeOrphans(orphans()?.length ?? 0)}?</p>}
+                    close={() => setPurging(false)}
+                    confirm={purgeOrphans}
+                    translateError={translateError}
+                />
+            </Show>
         </div>
     );
 };
@@ -366,6 +728,7 @@ export const Assets = () => {
 export const Asset = () => {
     enum Action {
         UpdateDetails,
+        UpdateAlts,
         Delete,
     }
 
@@ -375,6 +738,7 @@ export const Asset = () => {
 
     const alertCtx = useContext(AlertContext)!;
     const cmsContext = useContext(CMSContext)!;
+    const commonCtx = useContext(CommonContext)!;
     const localeCtx = useContext(LocaleContext)!;
     const navigate = useNavigate();
 
@@ -383,11 +747,19 @@ export const Asset = () => {
     const i18n = localeCtx.i18n.asset;
 
     const [asset, { mutate }] = createResource(() => parseInt(params.id), (id) => cmsContext.fetchAsset(id));
+    const [folders] = createResource(() => cmsContext.fetchAssetFolders());
+    const [usage] = createResource(() => parseInt(params.id), (id) => cmsContext.fetchAssetUsage(id));
 
-    const [assetDetails, setAssetDetails] = createStore({ name: '' });
+    const [assetDetails, setAssetDetails] = createStore({ name: '', folderId: '' });
     const [editingDetails, setEditingDetails] = createSignal(false);
 
-    createEffect(() => setAssetDetails({ name: asset()?.name ?? '' }));
+    createEffect(() => setAssetDetails({ name: asset()?.name ?? '', folderId: asset()?.folderId?.toString() ?? '' }));
+
+    const [alts, setAlts] = createStore({} as Record<string, string>);
+
+    createEffect(() => setAlts(reconcile({ ...asset()?.alts })));
+
+    const folderName = (id: number | null) => folderPath(folders() ?? [], id).map((f) => f.name).join(' / ') || i18n.labels.rootFolder();
 
     const [deletingAsset, setDeletingAsset] = createSignal(false);
 
@@ -420,16 +792,38 @@ export const Asset = () => {
 
         setInProgress(Action.UpdateDetails);
 
+        const folderId = assetDetails.folderId ? parseInt(assetDetails.folderId) : null;
+
         cmsContext.updateAsset(
             a.id,
             req.name,
         )
+            .then(() => folderId !== a.folderId ? cmsContext.moveAssets([a.id], folderId) : undefined)
             .then(() => {
                 setEditingDetails(false);
 
                 alertCtx.success(i18n.actions.assetUpdated(req.name));
 
-                mutate({ ...a, name: assetDetails.name.trim() });
+                mutate({ ...a, name: req.name, folderId });
+            })
+            .catch((e) => alertCtx.fail(translateError(e.message)))
+            .finally(() => setInProgress(undefined));
+    };
+
+    const saveAlts = () => {
+        const a = asset();
+
+        if (inProgress() !== undefined || !a) {
+            return;
+        }
+
+        setInProgress(Action.UpdateAlts);
+
+        cmsContext.updateAssetAlts(a.id, { ...alts })
+            .then((alts) => {
+                alertCtx.success(i18n.actions.altTextsUpdated());
+
+                mutate({ ...a, alts });
             })
             .catch((e) => alertCtx.fail(translateError(e.message)))
             .finally(() => setInProgress(undefined));
@@ -549,6 +943,39 @@ export const Asset = () => {
                                                         {asset().filetype}
                                                     </td>
                                                 </tr>
+                                                <tr>
+                                                    <td>{i18n.labels.folder()}</td>
+                                                    <td class="text-end text-truncate" classList={{ 'py-1': editingDetails() }}>
+                                                        <Show when={editingDetails()} fallback={folderName(asset().folderId)}>
+                                                            <FolderSelect
+                                                                id="assetFolder"
+                                                                folders={folders() ?? []}
+                                                                value={assetDetails.folderId}
+                                                                onChange={(value) => setAssetDetails('folderId', value)}
+                                                            />
+                                                        </Show>
+                                                    </td>
+                                                </tr>
+                                                <Show when={asset().size !== null}>
+                                                    <tr>
+                                                        <td>{i18n.labels.size()}</td>
+                                                        <td class="text-end text-truncate">{formatSize(asset().size!)}</td>
+                                                    </tr>
+                                                </Show>
+                                                <Show when={asset().width !== null && asset().height !== null}>
+                                                    <tr>
+                                                        <td>{i18n.labels.dimensions()}</td>
+                                                        <td class="text-end text-truncate">{asset().width} × {asset().height}</td>
+                                                    </tr>
+                                                </Show>
+                                                <Show when={asset().checksum}>
+                                                    {(checksum) => (
+                                                        <tr>
+                                                            <td>{i18n.labels.checksum()}</td>
+                                                            <td class="text-end text-truncate" title={checksum()}><code>{checksum()}</code></td>
+                                                        </tr>
+                                                    )}
+                                                </Show>
                                                 <tr>
                                                     <td>{i18n.labels.link()}</td>
                                                     <td class="text-end text-truncate">
@@ -558,6 +985,96 @@ export const Asset = () => {
                                             </tbody>
                                         </table>
                                     </div>
+
+                                    <Show when={asset().filetype?.startsWith('image')}>
+                                        <div class="border rounded p-3 mt-4">
+                                            <div class="d-flex justify-content-center">
+                                                <h5 class="flex-grow-1 m-0">{i18n.labels.altTexts()}</h5>
+                                                <button
+                                                    type="button"
+                                                    class="btn icon-link py-0 px-1"
+                                                    onClick={saveAlts}
+                                                    disabled={inProgress() === Action.UpdateAlts}
+                                                >
+                                                    <ProgressSpinner show={inProgress() === Action.UpdateAlts} small={true} />
+                                                    <FloppyFill viewBox="0 0 16 16" />
+                                                    {localeCtx.i18n.common.actions.save()}
+                                                </button>
+                                            </div>
+
+                                            <hr />
+
+                                            <For each={commonCtx.activeLocales()}>
+                                                {(locale) => (
+                                                    <div class="mb-3">
+                                                        <label for={`assetAlt-${locale.key}`} class="form-label">{locale.name}</label>
+                                                        <input
+                                                            id={`assetAlt-${locale.key}`}
+                                                            type="text"
+                                                            class="form-control"
+                                                            placeholder={i18n.labels.altTextPlaceholder()}
+                                                            value={alts[locale.key] ?? ''}
+                                                            onInput={(ev) => setAlts(locale.key, ev.target.value)}
+                                                        />
+                                                    </div>
+                                                )}
+                                            </For>
+                                        </div>
+                                    </Show>
+
+                                    <div class="border rounded p-3 mt-4">
+                                        <h5 class="m-0">{i18n.labels.usage()}</h5>
+
+                                        <hr />
+
+                                        <Show when={usage()} fallback={<ProgressSpinner show={usage.loading} />}>
+                                            {(usage) => (
+                                                <Show
+                                                    when={usage().contents.length + usage().revisions.length + usage().pages.length + usage().templates.length > 0}
+                                                    fallback={<p class="text-secondary m-0">{i18n.notUsed()}.</p>}
+                                                >
+                                                    <Show when={usage().contents.length > 0}>
+                                                        <h6>{i18n.labels.contents()}</h6>
+                                                        <ul>
+                                                            <For each={usage().contents}>
+                                                                {(content) => (
+                                                                    <li><A href={`/contents/view/${content.contentId}`}>{content.contentName}</A> ({content.field})</li>
+                                                                )}
+                                                            </For>
+                                                        </ul>
+                                                    </Show>
+                                                    <Show when={usage().revisions.length > 0}>
+                                                        <h6>{i18n.labels.revisions()}</h6>
+                                                        <ul>
+                                                            <For each={usage().revisions}>
+                                                                {(revision) => (
+                                                                    <li><A href={`/contents/view/${revision.contentId}`}>{revision.contentName}</A> (#{revision.revisionId})</li>
+                                                                )}
+                                                            </For>
+                                                        </ul>
+                                                    </Show>
+                                                    <Show when={usage().pages.length > 0}>
+                                                        <h6>{i18n.labels.pages()}</h6>
+                                                        <ul>
+                                                            <For each={usage().pages}>
+                                                                {(page) => (
+                                                                    <li><A href={page.namespace ? `/pages/view/${page.namespace}/${page.key}` : `/pages/view/${page.key}`}>{page.name}</A></li>
+                                                                )}
+                                                            </For>
+                                                        </ul>
+                                                    </Show>
+                                                    <Show when={usage().templates.length > 0}>
+                                                        <h6>{i18n.labels.templates()}</h6>
+                                                        <ul class="mb-0">
+                                                            <For each={usage().templates}>
+                                                                {(template) => (<li><code>{template}</code></li>)}
+                                                            </For>
+                                                        </ul>
+                                                    </Show>
+                                                </Show>
+                                            )}
+                                        </Show>
+                                    </div>
                                 </div>
                                 <div class="offset-md-1 col-md-5">
                                     <Show when={asset().filetype?.startsWith('image')} fallback={
@@ -1,7 +1,7 @@
 import { A, Navigate, useNavigate, useParams, useSearchParams } from "@solidjs/router";
 import { CMSContext } from "../lib/cms/context";
 import { createEffect, createMemo, createResource, createSignal, For, type JSX, Match, onCleanup, Show, Switch, useContext } from "solid-js";
-import { HttpError } from "../lib/api";
+import { HttpError, ValidationErrors } from "../lib/api";
 import { createStore, unwrap } from "solid-js/store";
 import { ContentStage, FieldKind, Model, type ContentValue, type ModelField } from "../lib/cms/models";
 import { Dynamic } from "solid-js/web";
@@ -23,6 +23,17 @@ function imageFile(filename: string): boolean {
     return ['.bmp', '.png', '.ico', '.tif', '.tiff', '.jpeg', '.jpg', '.webp', '.svg', '.gif'].findIndex((ext) => filename.endsWith(ext)) > -1;
 }
 
+const INPUT_TYPES: Partial<Record<FieldKind, string>> = {
+    [FieldKind.String]: 'text',
+    [FieldKind.Integer]: 'number',
+    [FieldKind.Float]: 'number',
+    [FieldKind.Date]: 'date',
+    [FieldKind.DateTime]: 'datetime-local',
+    [FieldKind.Url]: 'url',
+    [FieldKind.Email]: 'email',
+    [FieldKind.Reference]: 'number',
+};
+
 const ContentValueModal = (props: {
     close: () => void;
     create: (field: CreateContentValue) => Promise<void> | void;
@@ -42,6 +53,12 @@ const ContentValueModal = (props: {
 
     const i18n = localeCtx.i18n.content;
 
+    const translateValueError = (e: string) => {
+        return (e in i18n.valueErrors)
+            ? i18n.valueErrors[e as keyof typeof i18n.valueErrors]()
+            : e;
+    };
+
     const [store, setStore] = createStore(props.initial ?? {
         value: '',
         locale: '',
@@ -103,6 +120,10 @@ const ContentValueModal = (props: {
 
                     if (e instanceof HttpError) {
                         setServerError(msg);
+                    } else if (e instanceof ValidationErrors) {
+                        const messages = (e.fieldMessages.get(props.modelField.key) ?? []) as string[];
+
+                        setServerError(messages.map(translateValueError).join(', ') || msg);
                     } else {
                         alertCtx.fail(msg);
                     }
@@ -175,22 +196,43 @@ const ContentValueModal = (props: {
                                                         {i18n.actions.pickAsset()}
                                                     </button>
                                                 </Match>
-                                                <Match when={field().kind === FieldKind.Multiline}>
+                                                <Match when={[FieldKind.Multiline, FieldKind.Markdown, FieldKind.RichText, FieldKind.Json].includes(field().kind)}>
                                                     <textarea
                                                         id="modelFieldValue"
                                                         name="modelFieldValue"
                                                         class="form-control"
-                                                        classList={{ 'is-invalid': validationErrors().has(ValidationError.Value) }}
+                                                        classList={{ 'font-monospace': field().kind === FieldKind.Json, 'is-invalid': validationErrors().has(ValidationError.Value) }}
                                                         rows="5"
                                                         value={store.value}
                                                         onInput={(ev) => setStore('value', ev.target.value)}
                                                     ></textarea>
                                                 </Match>
-                                                <Match when={field().kind === FieldKind.String || field().kind === FieldKind.Integer}>
+                                                <Match when={field().kind === FieldKind.Bool || field().kind === FieldKind.Enum}>
+                                                    <select
+                                                        id="modelFieldValue"
+                                                        name="modelFieldValue"
+                                                        class="form-select"
+                                                        classList={{ 'is-invalid': validationErrors().has(ValidationError.Value) }}
+                                                        value={store.value}
+                                                        onChange={(ev) => setStore('value', ev.target.value)}
+                                                    >
+                                                        <option value="" disabled selected>{i18n.actions.selectValue()}</option>
+                                                        <Show when={field().kind === FieldKind.Bool} fallback={
+                                                            <For each={field().options}>
+                                                                {(option) => (<option value={option}>{option}</option>)}
+                                                            </For>
+                                                        }>
+                                                            <option value="true">{localeCtx.i18n.common.labels.yes()}</option>
+                                                            <option value="false">{localeCtx.i18n.common.labels.no()}</option>
+                                                        </Show>
+                                                    </select>
+                                                </Match>
+                                                <Match when={field().kind in INPUT_TYPES}>
                                                     <input
                                                         id="modelFieldValue"
                                                         name="modelFieldValue"
-                                                        type={field().kind === FieldKind.Integer ? 'number' : 'text'}
+                                                        type={INPUT_TYPES[field().kind]}
+                                                        step={field().kind === FieldKind.Float ? 'any' : undefined}
                                                         class="form-control"
                                                         classList={{ 'is-invalid': validationErrors().has(ValidationError.Value) }}
                                                         value={store.value}
@@ -419,6 +461,12 @@ export const CreateContent = () => {
 
     const i18n = localeCtx.i18n.content;
 
+    const translateValueError = (e: string) => {
+        return (e in i18n.valueErrors)
+            ? i18n.valueErrors[e as keyof typeof i18n.valueErrors]()
+            : e;
+    };
+
     const locales = commonCtx.activeLocales();
     const model = createMemo(() => cmsCtx.models().find(Model.searchWithParams(params.namespace, params.key)));
 
@@ -484,6 +532,11 @@ export const CreateContent = () => {
 
                 if (e instanceof HttpError) {
                     setServerError(msg);
+                } else if (e instanceof ValidationErrors) {
+                    const messages = Array.from(e.fieldMessages.entries())
+                        .flatMap(([key, messages]) => (messages as string[]).map((m) => `${key}: ${translateValueError(m)}`));
+
+                    setServerError(messages.join(', ') || msg);
                 } else {
                     alertCtx.fail(msg);
                 }
@@ -694,7 +747,7 @@ export const Content = () => {
 
     const i18n = localeCtx.i18n.content;
 
-    const [content, { mutate }] = createResource(() => parseInt(params.id), (id: number) => cmsCtx.fetchContent(id));
+    const [content, { mutate, refetch }] = createResource(() => parseInt(params.id), (id: number) => cmsCtx.fetchContent(id));
     const model = () => cmsCtx.models().find((m) => m.id === content()?.content.modelId);
 
     const [contentDetails, setContentDetails] = createStore({ name: '' });
@@ -716,25 +769,25 @@ export const Content = () => {
     onCleanup(dropdownClickListener('content-detail-dropdown', () => dropdown() === Dropdown.Details && setDropdown(undefined), () => !deletingContent()));
     onCleanup(dropdownClickListener('stage-detail-dropdown', () => dropdown() === Dropdown.Stage && setDropdown(undefined), () => inProgress() === undefined));
 
-    const updateStage = () => {
+    const updateStage = (stage: ContentStage) => {
         const c = content();
 
         if (inProgress() !== undefined || c === undefined) {
             return;
         }
 
-        const stage = c.content.stage === ContentStage.Published ? ContentStage.Draft : ContentStage.Published;
         setInProgress(Action.UpdateStage);
 
         cmsCtx.updateContentStage(c.content.id, stage)
             .then(() => {
                 setDropdown(undefined);
 
-                alertCtx.success(stage === ContentStage.Published ? i18n.actions.published(c.content.name) : i18n.actions.markedDraft(c.content.name));
+                alertCtx.success(i18n.actions.stageUpdated(c.content.name, i18n.stages[stage]()));
 
-                mutate({ ...c, content: { ...c.content, stage } });
+                // Stages the content can be moved to depend on its new stage
+                return refetch();
             })
-            .catch((e) => alertCtx.fail(e.message))
+            .catch((e) => alertCtx.fail(translateError(e.message)))
             .finally(() => setInProgress(undefined));
     };
 
@@ -854,9 +907,20 @@ export const Content = () => {
             });
     }
 
-    const contentStyle = () => content()?.content.stage === ContentStage.Published ?
-        { color: 'success', icon: CheckCircleFill } :
-        { color: 'secondary', icon: Bookmark };
+    const contentStyle = () => {
+        switch (content()?.content.stage) {
+            case ContentStage.Published:
+                return { color: 'success', icon: CheckCircleFill };
+            case ContentStage.InReview:
+                return { color: 'warning', icon: Bookmark };
+            case ContentStage.Approved:
+                return { color: 'info', icon: Bookmark };
+            case ContentStage.Archived:
+                return { color: 'dark', icon: Bookmark };
+            default:
+                return { color: 'secondary', icon: Bookmark };
+        }
+    };
 
     const translateError = (e: string) => {
         return (e in i18n.serverErrors)
@@ -914,14 +978,17 @@ export const Content = () => {
                                         </button>
                                     </div>
                                     <ul id="stage-detail-dropdown" class="dropdown-menu mt-1 show shadow" classList={{ 'show': dropdown() === Dropdown.Stage }} style="right: 0;">
-                                        <li>
-                                            <button class="dropdown-item py-2" onClick={updateStage} disabled={inProgress() !== undefined}>
-                                                <Switch>
-                                                    <Match when={content()?.content.stage === ContentStage.Draft}>{i18n.actions.publish()}</Match>
-                                                    <Match when={content()?.content.stage === ContentStage.Published}>{i18n.actions.markDraft()}</Match>
-                                                </Switch>
-                                            </button>
-                                        </li>
+                                        <For each={content().transitions} fallback={
+                                            <li><span class="dropdown-item-text text-secondary text-nowrap py-2">{i18n.labels.noTransition()}</span></li>
+                                        }>
+                                            {(stage) => (
+                                                <li>
+                                                    <button class="dropdown-item py-2" onClick={() => updateStage(stage)} disabled={inProgress() !== undefined}>
+                                                        {i18n.transitions[stage]()}
+                                                    </button>
+                                                </li>
+                                            )}
+                                        </For>
                                     </ul>
                                 </div>
                             </div>
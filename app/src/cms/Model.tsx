@@ -1,6 +1,6 @@
 import { createEffect, createMemo, createSignal, For, onCleanup, Show, useContext } from "solid-js";
 import { CMSContext } from "../lib/cms/context";
-import { Model as ModelModel, type ModelField } from "../lib/cms/models";
+import { FieldKind, Model as ModelModel, type ModelField } from "../lib/cms/models";
 import { A, useNavigate, useParams } from "@solidjs/router";
 import { createStore, unwrap } from "solid-js/store";
 import type { CreateModelField } from "../lib/cms/requests";
@@ -14,6 +14,8 @@ import ProgressSpinner from "../components/ProgressSpinner";
 import DeleteConfirmModal from "../components/DeleteConfirmModal";
 import { LocaleContext } from "../lib/i18n";
 
+const TEXTUAL_KINDS = [FieldKind.String, FieldKind.Multiline, FieldKind.Markdown, FieldKind.RichText, FieldKind.Url, FieldKind.Email];
+
 const ModelFieldModal = (props: {
     close: () => void;
     create: (field: CreateModelField) => Promise<void> | void;
@@ -32,7 +34,7 @@ const ModelFieldModal = (props: {
 
     const i18n = localeCtx.i18n.model;
 
-    const [store, setStore] = createStore(props.initial ?? {
+    const [store, setStore] = createStore(props.initial ? { validation: {}, ...props.initial } : {
         key: '',
         name: '',
         desc: '',
@@ -40,7 +42,16 @@ const ModelFieldModal = (props: {
         localized: false,
         multiple: false,
         required: false,
-    })
+        referenceModelId: null,
+        validation: {},
+    } as CreateModelField)
+
+    const kind = () => cmsContext.fields().find((f) => f.id === store.fieldId)?.kind;
+    const isReference = () => kind() === FieldKind.Reference;
+    const isTextual = () => TEXTUAL_KINDS.includes(kind() as FieldKind);
+    const isNumeric = () => kind() === FieldKind.Integer || kind() === FieldKind.Float;
+
+    const parseNumber = (value: string) => value.trim() === '' || isNaN(Number(value)) ? undefined : Number(value);
 
     const [inProgress, setInProgress] = createSignal(false);
 
@@ -73,6 +84,17 @@ const ModelFieldModal = (props: {
             localized: store.localized,
             multiple: store.multiple,
             required: store.required,
+            referenceModelId: isReference() ? store.referenceModelId ?? null : null,
+            validation: {
+                minLength: isTextual() ? store.validation?.minLength : undefined,
+                maxLength: isTextual() ? store.validation?.maxLength : undefined,
+                pattern: isTextual() ? store.validation?.pattern?.trim() || undefined : undefined,
+                min: isNumeric() ? store.validation?.min : undefined,
+                max: isNumeric() ? store.validation?.max : undefined,
+                unique: store.validation?.unique || undefined,
+                mimeTypes: kind() === FieldKind.Asset && store.validation?.mimeTypes?.length ? store.validation.mimeTypes : undefined,
+                maxItems: store.multiple ? store.validation?.maxItems : undefined,
+            },
         };
 
         if (req.key.length === 0) {
@@ -104,6 +126,10 @@ const ModelFieldModal = (props: {
 
                     if (e instanceof HttpError) {
                         setServerError(msg);
+                    } else if (e instanceof ValidationErrors) {
+                        const messages = (e.fieldMessages.get('validation') ?? []) as string[];
+
+                        setServerError(messages.map((m) => m in i18n.ruleErrors ? i18n.ruleErrors[m as keyof typeof i18n.ruleErrors]() : m).join(', ') || msg);
                     } else {
                         alertCtx.fail(msg);
                     }
@@ -190,6 +216,25 @@ const ModelFieldModal = (props: {
                                     <small class="invalid-feedback">{i18n.validationErrors.selectField()}.</small>
                                 </Show>
                             </div>
+                            <Show when={isReference()}>
+                                <div class="mb-4">
+                                    <label for="modelFieldReferenceModel" class="form-label">{i18n.labels.referenceModel()}</label>
+                                    <select
+                                        id="modelFieldReferenceModel"
+                                        class="form-select"
+                                        name="referenceModelId"
+                                        value={store.referenceModelId ?? ''}
+                                        onChange={(ev) => setStore('referenceModelId', ev.target.value ? parseInt(ev.target.value) : null)}
+                                    >
+                                        <option value="">{i18n.labels.anyModel()}</option>
+                                        <For each={cmsContext.models()}>
+                                            {(model) => (
+                                                <option value={model.id}>{model.title()}</option>
+                                            )}
+                                        </For>
+                                    </select>
+                                </div>
+                            </Show>
                             <div class="form-check mb-3">
                                 <input class="form-check-input" type="checkbox" checked={store.localized} onChange={(ev) => setStore('localized', ev.target.checked)} id="modelFieldLocalized" />
                                 <label class="form-check-label" for="modelFieldLocalized">
@@ -202,12 +247,65 @@ const ModelFieldModal = (props: {
                                     {i18n.fieldFeatures.multiple()}
                                 </label>
                             </div>
-                            <div class="form-check">
+                            <div class="form-check mb-3">
                                 <input class="form-check-input" type="checkbox" checked={store.required} onChange={(ev) => setStore('required', ev.target.checked)} id="modelFieldRequired" />
                                 <label class="form-check-label" for="modelFieldRequired">
                                     {i18n.fieldFeatures.required()}
                                 </label>
                             </div>
+                            <div class="form-check">
+                                <input class="form-check-input" type="checkbox" checked={store.validation?.unique ?? false} onChange={(ev) => setStore('validation', 'unique', ev.target.checked)} id="modelFieldUnique" />
+                                <label class="form-check-label" for="modelFieldUnique">
+                                    {i18n.validationRules.unique()}
+                                </label>
+                            </div>
+                            <Show when={isTextual()}>
+                                <div class="row g-2 mt-3">
+                                    <div class="col">
+                                        <label for="modelFieldMinLength" class="form-label">{i18n.validationRules.minLength()}</label>
+                                        <input type="number" min="0" id="modelFieldMinLength" class="form-control" value={store.validation?.minLength ?? ''} onChange={(ev) => setStore('validation', 'minLength', parseNumber(ev.target.value))} />
+                                    </div>
+                                    <div class="col">
+                                        <label for="modelFieldMaxLength" class="form-label">{i18n.validationRules.maxLength()}</label>
+                                        <input type="number" min="0" id="modelFieldMaxLength" class="form-control" value={store.validation?.maxLength ?? ''} onChange={(ev) => setStore('validation', 'maxLength', parseNumber(ev.target.value))} />
+                                    </div>
+                                </div>
+                                <div class="mt-3">
+                                    <label for="modelFieldPattern" class="form-label">{i18n.validationRules.pattern()} <small class="text-secondary">({localeCtx.i18n.common.labels.optional()})</small></label>
+                                    <input type="text" id="modelFieldPattern" class="form-control font-monospace" placeholder="^[a-z0-9-]+$" value={store.validation?.pattern ?? ''} onChange={(ev) => setStore('validation', 'pattern', ev.target.value)} />
+                                </div>
+                            </Show>
+                            <Show when={isNumeric()}>
+                                <div class="row g-2 mt-3">
+                                    <div class="col">
+                                        <label for="modelFieldMin" class="form-label">{i18n.validationRules.min()}</label>
+                                        <input type="number" step="any" id="modelFieldMin" class="form-control" value={store.validation?.min ?? ''} onChange={(ev) => setStore('validation', 'min', parseNumber(ev.target.value))} />
+                                    </div>
+                                    <div class="col">
+                                        <label for="modelFieldMax" class="form-label">{i18n.validationRules.max()}</label>
+                                        <input type="number" step="any" id="modelFieldMax" class="form-control" value={store.validation?.max ?? ''} onChange={(ev) => setStore('validation', 'max', parseNumber(ev.target.value))} />
+                                    </div>
+                                </div>
+                            </Show>
+                            <Show when={kind() === FieldKind.Asset}>
+                                <div class="mt-3">
+                                    <label for="modelFieldMimeTypes" class="form-label">{i18n.validationRules.mimeTypes()} <small class="text-secondary">({localeCtx.i18n.common.labels.optional()})</small></label>
+                                    <input
+                                        type="text"
+                                        id="modelFieldMimeTypes"
+                                        class="form-control"
+                                        placeholder="image/*, application/pdf"
+                                        value={store.validation?.mimeTypes?.join(', ') ?? ''}
+                                        onChange={(ev) => setStore('validation', 'mimeTypes', ev.target.value.split(',').map((m) => m.trim()).filter((m) => m.length > 0))}
+                                    />
+                                </div>
+                            </Show>
+                            <Show when={store.multiple}>
+                                <div class="mt-3">
+                                    <label for="modelFieldMaxItems" class="form-label">{i18n.validationRules.maxItems()}</label>
+                                    <input type="number" min="1" id="modelFieldMaxItems" class="form-control" value={store.validation?.maxItems ?? ''} onChange={(ev) => setStore('validation', 'maxItems', parseNumber(ev.target.value))} />
+                                </div>
+                            </Show>
                             <Show when={serverError()}>
                                 <div class="mb-2">
                                     <small class="text-danger-emphasis">{serverError()}</small>
@@ -583,6 +681,7 @@ export const Models = () => {
 export const Model = () => {
     enum Action {
         UpdateDetails,
+        UpdatePublic,
     }
 
     enum ValidationError {
@@ -674,6 +773,22 @@ export const Model = () => {
             .finally(() => setInProgress(undefined));
     }
 
+    const togglePublic = () => {
+        const m = model();
+
+        if (inProgress() !== undefined || !m) {
+            return;
+        }
+
+        setInProgress(Action.UpdatePublic);
+
+        cmsContext.updateModelPublic(m.id, !m.public)
+            .then(() => cmsContext.loadModels())
+            .then(() => alertCtx.success(i18n.actions.modelPublicUpdated(m.name)))
+            .catch((e) => alertCtx.fail(translateError(e.message)))
+            .finally(() => setInProgress(undefined));
+    };
+
     const saveField = async (id: number, updatedField: CreateModelField) => {
         return cmsContext.updateModelField(id, {
             name: updatedField.name,
@@ -681,6 +796,8 @@ export const Model = () => {
             localized: updatedField.localized,
             required: updatedField.required,
             multiple: updatedField.multiple,
+            referenceModelId: updatedField.referenceModelId,
+            validation: updatedField.validation,
         })
             .then(() => cmsContext.loadModels())
             .then(() => {
@@ -844,6 +961,25 @@ export const Model = () => {
                                                     </Show>
                                                 </td>
                                             </tr>
+                                            <tr>
+                                                <td>
+                                                    <label for="modelPublic">{i18n.labels.public()}</label>
+                                                </td>
+                                                <td>
+                                                    <div class="form-check form-switch float-end m-0">
+                                                        <input
+                                                            id="modelPublic"
+                                                            type="checkbox"
+                                                            role="switch"
+                                                            class="form-check-input"
+                                                            title={i18n.publicInfo()}
+                                                            checked={model().public}
+                                                            onChange={togglePublic}
+                                                            disabled={inProgress() === Action.UpdatePublic}
+                                                        />
+                                                    </div>
+                                                </td>
+                                            </tr>
                                         </tbody>
                                     </table>
                                 </div>
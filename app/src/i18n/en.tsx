@@ -16,10 +16,29 @@ export const auth = {
         validationErrors: {
             email: 'Please enter your email',
             password: 'Please enter your password',
+            code: 'Please enter the code',
         },
         serverErrors: {
             invalid_credentials: 'Invalid credentials',
             user_not_created_with_email: 'User has different login method',
+            invalid_token: 'Login session has expired, please log in again',
+            invalid_two_factor_code: 'Invalid code',
+            too_many_two_factor_attempts: 'Too many attempts, please try again later',
+            two_factor_enrollment_not_found: 'Enrollment has expired, please start again',
+        },
+        twoFactor: {
+            title: 'Two-Factor Authentication',
+            verifyDescription: 'Enter the code from your authenticator app or one of your recovery codes',
+            enrollDescription: 'Your role requires two-factor authentication, set up an authenticator app to continue',
+            confirmDescription: 'Add the secret to your authenticator app and enter the code it shows',
+            code: 'Code',
+            secret: 'Secret',
+            verify: 'Verify',
+            enroll: 'Set Up',
+            confirm: 'Confirm',
+            recoveryCodes: 'Recovery Codes',
+            recoveryCodesDescription: 'Store these codes somewhere safe, each of them can be used once when you cannot access your authenticator app',
+            continue: 'Continue',
         },
     }
 };
@@ -39,17 +58,53 @@ export const asset = {
         assetUpdated: (name: string) => `Asset "${name}" is updated successfully`,
         assetDeleted: (name: string) => `Asset "${name}" is deleted successfully`,
         confirmDelete: (name: string) => (<>Are you sure about deleting the asset <strong>{name}</strong></>),
+        createFolder: 'Create Folder',
+        deleteFolder: 'Delete Folder',
+        folderCreated: (name: string) => `Folder "${name}" is created successfully`,
+        folderDeleted: (name: string) => `Folder "${name}" is deleted successfully`,
+        confirmDeleteFolder: (name: string) => (<>Are you sure about deleting the folder <strong>{name}</strong></>),
+        assetMoved: (name: string) => `Asset "${name}" is moved successfully`,
+        altTextsUpdated: 'Alternative texts are updated successfully',
+        orphanAssets: 'Orphan Assets',
+        purgeOrphans: 'Purge Orphans',
+        orphansPurged: (count: number) => `${count} orphan asset(s) are purged successfully`,
+        confirmPurgeOrphans: (count: number) => (<>Are you sure about permanently deleting <strong>{count}</strong> orphan asset(s)</>),
     },
     labels: {
         link: 'Link',
         type: 'Type',
         size: 'Size',
+        dimensions: 'Dimensions',
+        checksum: 'Checksum',
+        folder: 'Folder',
+        folderName: 'Folder name',
+        rootFolder: 'All Assets',
+        altTexts: 'Alternative Texts',
+        altTextPlaceholder: 'Describe the image for screen readers',
+        search: 'Search assets by name',
+        anyType: 'Any type',
+        images: 'Images',
+        videos: 'Videos',
+        audios: 'Audios',
+        documents: 'Documents',
+        usage: 'Usage',
+        contents: 'Contents',
+        revisions: 'Revisions',
+        pages: 'Pages',
+        templates: 'Templates',
     },
     validationErrors: {
         asset: 'Please choose an asset file',
+        folderName: 'Please specify a folder name without slash, at most 128 characters',
     },
     serverErrors: {
         asset_not_found: 'Asset is not found',
+        folder_not_found: 'Folder is not found',
+        parent_folder_not_found: 'Parent folder is not found',
+        folder_already_exists: 'A folder with the same name already exists',
+        folder_not_empty: 'Folder is not empty, move or delete its assets and folders first',
+        unknown_locale: 'Alternative text is given for an unknown locale',
+        invalid_image: 'Image could not be processed',
     },
     analyzingAsset: 'Asset is being analyzed',
     analysisError: 'Analysis Error',
@@ -57,6 +112,10 @@ export const asset = {
     canUploadAsset: () => (<>You can upload a new one by using <strong>Upload Asset</strong> button</>),
     noAsset: 'There is no asset to display yet',
     noAssetForPage: (page?: number | string) => (<>There is no asset to display for <strong>page {page}</strong></>),
+    noAssetForSearch: 'There is no asset matching the search',
+    notUsed: 'This asset is not referenced by any content, page or template',
+    noOrphanAsset: 'There is no orphan asset, every asset is referenced by a content, page or template',
+    orphanAssetsDesc: 'Assets below are not referenced by any content, page or template',
     assetNotFound: (id: string) => (<>Could not find the asset with id <strong>{id}</strong></>),
 };
 
@@ -110,12 +169,10 @@ export const content = {
         contentUpdated: (name: string) => `Content "${name}" is updated successfully`,
         createContent: 'Create Content',
         editValue: 'Edit Value',
-        markDraft: 'Mark as draft',
-        markedDraft: (name: string) => `Content "${name}" is marked as draft`,
         pickAsset: 'Pick Asset',
-        publish: 'Publish',
-        published: (name: string) => `Content "${name}" is published`,
         selectLocale: 'Select a locale',
+        selectValue: 'Select a value',
+        stageUpdated: (name: string, stage: string) => `Content "${name}" is moved to ${stage}`,
         valueCreated: (field: string) => `Value for field "${field}" is created successfully`,
         valueDeleted: (field: string) => `Value for field "${field}" is deleted successfully`,
         valueUpdated: (field: string) => `Value for field "${field}" is updated successfully`,
@@ -127,6 +184,7 @@ export const content = {
         value: 'Value',
         values: 'Values',
         stage: 'Stage',
+        noTransition: 'Stage cannot be changed',
         unknownField: 'Unknown Field',
         unsupportedField: 'Unsupported Field',
     },
@@ -137,11 +195,38 @@ export const content = {
         value: (field: string) => `Please specify a value for ${field}`,
     },
     serverErrors: {
+        access_denied: 'You are not allowed to do this',
+        invalid_stage_transition: 'Content cannot be moved to this stage',
         missing_required_field: 'Missing a required field',
+        content_being_referenced: 'Content is referenced by other contents',
+    },
+    valueErrors: {
+        invalid_value: 'Value is not valid for the field',
+        unknown_option: 'Value is not one of the options',
+        referenced_content_not_found: 'Referenced content is not found',
+        asset_not_found: 'Asset is not found',
+        mime_type_not_allowed: 'File type of the asset is not allowed',
+        too_short: 'Value is too short',
+        too_long: 'Value is too long',
+        pattern_mismatch: 'Value does not match the pattern',
+        below_minimum: 'Value is below the minimum',
+        above_maximum: 'Value is above the maximum',
+        not_unique: 'Value is already used by another content',
+        too_many_items: 'Field has too many values',
     },
     stages: {
         [ContentStage.Draft]: 'Draft',
+        [ContentStage.InReview]: 'In Review',
+        [ContentStage.Approved]: 'Approved',
         [ContentStage.Published]: 'Published',
+        [ContentStage.Archived]: 'Archived',
+    },
+    transitions: {
+        [ContentStage.Draft]: 'Move back to draft',
+        [ContentStage.InReview]: 'Submit for review',
+        [ContentStage.Approved]: 'Approve',
+        [ContentStage.Published]: 'Publish',
+        [ContentStage.Archived]: 'Archive',
     },
     noContent: (model: string) => (<>There is no content for the <strong>{model}</strong> model to display yet. You can create a new one by using <strong>Create Content</strong> button</>),
     noContentForPage: (page: string) => (<>There is no content to display for <strong>page {page}</strong></>),
@@ -207,23 +292,55 @@ export const model = {
         modelCreated: (name: string) => `Model "${name}" is created successfully`,
         modelDeleted: (name: string) => `Model "${name}" is deleted successfully`,
         modelUpdated: (name: string) => `Model "${name}" is updated successfully`,
+        modelPublicUpdated: (name: string) => `Visibility of model "${name}" is updated successfully`,
         selectField: 'Select a field',
     },
     labels: {
+        anyModel: 'Any model',
         fields: 'Fields',
         field: 'Field',
+        public: 'Public',
+        referenceModel: 'Referenced Model',
     },
     fields: {
         asset: 'Asset',
+        boolean: 'Boolean',
+        date: 'Date',
+        datetime: 'Date Time',
+        decimal: 'Decimal',
+        email: 'Email',
         integer: 'Integer',
+        json: 'JSON',
+        markdown: 'Markdown',
         multiline: 'Multiline',
+        reference: 'Reference',
+        richtext: 'Rich Text',
         text: 'Text',
+        url: 'URL',
     },
     fieldFeatures: {
         localized: 'Localized',
         required: 'Required',
         multiple: 'Multiple',
     },
+    validationRules: {
+        unique: 'Unique',
+        minLength: 'Minimum Length',
+        maxLength: 'Maximum Length',
+        pattern: 'Pattern',
+        min: 'Minimum',
+        max: 'Maximum',
+        mimeTypes: 'Allowed File Types',
+        maxItems: 'Maximum Items',
+    },
+    ruleErrors: {
+        length_and_pattern_only_for_text: 'Length and pattern rules can only be used with text fields',
+        range_only_for_numbers: 'Minimum and maximum can only be used with number fields',
+        mime_types_only_for_assets: 'Allowed file types can only be used with asset fields',
+        max_items_only_for_multiple: 'Maximum items can only be used with multiple value fields',
+        invalid_range: 'Minimum must not be greater than maximum',
+        invalid_pattern: 'Pattern is not a valid regular expression',
+    },
     validationErrors: {
         key: 'Please enter a key',
         name: 'Please enter a name',
@@ -233,6 +350,7 @@ export const model = {
     serverErrors: {
         model_already_exists: 'Model with given key already exists',
     },
+    publicInfo: 'Published contents of public models can be read by anyone through the content delivery API',
     modelNotFound: (key: string) => (<>Could not find the model with key <strong>{key}</strong></>),
     noModel: () => (<>There is no model to display yet. You can create a new one by using <strong>Create Model</strong> button</>),
 };
@@ -331,6 +449,8 @@ export const role = {
         [Permission.AssetWrite]: 'Asset Write',
         [Permission.CMSRead]: 'CMS Read',
         [Permission.ContentWrite]: 'Content Write',
+        [Permission.ContentReview]: 'Content Review',
+        [Permission.ContentPublish]: 'Content Publish',
         [Permission.ModelWrite]: 'Model Write',
         [Permission.PageWrite]: 'Page Write',
         [Permission.TemplateWrite]: 'Template Write',
@@ -17,10 +17,29 @@ export const auth: typeof en.auth = {
         validationErrors: {
             email: 'Lütfen e-posta adresinizi girin',
             password: 'Lütfen parolanızı girin',
+            code: 'Lütfen kodu girin',
         },
         serverErrors: {
             invalid_credentials: 'Bilgiler geçersiz',
             user_not_created_with_email: 'Kullanıcı başka bir yöntem ile giriş yapıyor',
+            invalid_token: 'Giriş oturumunun süresi doldu, lütfen tekrar giriş yapın',
+            invalid_two_factor_code: 'Kod geçersiz',
+            too_many_two_factor_attempts: 'Çok fazla deneme yapıldı, lütfen daha sonra tekrar deneyin',
+            two_factor_enrollment_not_found: 'Kurulumun süresi doldu, lütfen tekrar başlayın',
+        },
+        twoFactor: {
+            title: 'İki Aşamalı Doğrulama',
+            verifyDescription: 'Doğrulama uygulamanızdaki kodu veya kurtarma kodlarınızdan birini girin',
+            enrollDescription: 'Rolünüz iki aşamalı doğrulama gerektiriyor, devam etmek için bir doğrulama uygulaması kurun',
+            confirmDescription: 'Anahtarı doğrulama uygulamanıza ekleyin ve gösterdiği kodu girin',
+            code: 'Kod',
+            secret: 'Anahtar',
+            verify: 'Doğrula',
+            enroll: 'Kur',
+            confirm: 'Onayla',
+            recoveryCodes: 'Kurtarma Kodları',
+            recoveryCodesDescription: 'Bu kodları güvenli bir yerde saklayın, doğrulama uygulamanıza erişemediğinizde her biri bir kez kullanılabilir',
+            continue: 'Devam Et',
         },
     }
 };
@@ -40,17 +59,53 @@ export const asset: typeof en.asset = {
         assetUpdated: (name: string) => `"${name}" isimli kaynak başarılı bir şekilde güncellendi`,
         assetDeleted: (name: string) => `"${name}" isimli kaynak başarılı bir şekilde silindi`,
         confirmDelete: (name: string) => (<><strong>{name}</strong> isimli kaynağı silmek istediğinizden emin misiniz</>),
+        createFolder: 'Klasör Oluştur',
+        deleteFolder: 'Klasörü Sil',
+        folderCreated: (name: string) => `"${name}" isimli klasör başarılı bir şekilde oluşturuldu`,
+        folderDeleted: (name: string) => `"${name}" isimli klasör başarılı bir şekilde silindi`,
+        confirmDeleteFolder: (name: string) => (<><strong>{name}</strong> isimli klasörü silmek istediğinizden emin misiniz</>),
+        assetMoved: (name: string) => `"${name}" isimli kaynak başarılı bir şekilde taşındı`,
+        altTextsUpdated: 'Alternatif metinler başarılı bir şekilde güncellendi',
+        orphanAssets: 'Sahipsiz Kaynaklar',
+        purgeOrphans: 'Sahipsizleri Temizle',
+        orphansPurged: (count: number) => `${count} sahipsiz kaynak başarılı bir şekilde temizlendi`,
+        confirmPurgeOrphans: (count: number) => (<><strong>{count}</strong> sahipsiz kaynağı kalıcı olarak silmek istediğinizden emin misiniz</>),
     },
     labels: {
         link: 'Link',
         type: 'Tür',
         size: 'Boyut',
+        dimensions: 'Ölçüler',
+        checksum: 'Sağlama Toplamı',
+        folder: 'Klasör',
+        folderName: 'Klasör adı',
+        rootFolder: 'Tüm Kaynaklar',
+        altTexts: 'Alternatif Metinler',
+        altTextPlaceholder: 'Görseli ekran okuyucular için tanımlayın',
+        search: 'Kaynakları isme göre ara',
+        anyType: 'Herhangi bir tür',
+        images: 'Görseller',
+        videos: 'Videolar',
+        audios: 'Sesler',
+        documents: 'Belgeler',
+        usage: 'Kullanım',
+        contents: 'İçerikler',
+        revisions: 'Sürümler',
+        pages: 'Sayfalar',
+        templates: 'Şablonlar',
     },
     validationErrors: {
         asset: 'Lütfen bir kaynak dosyası seçin',
+        folderName: 'Lütfen eğik çizgi içermeyen, en fazla 128 karakterlik bir klasör adı belirtin',
     },
     serverErrors: {
         asset_not_found: 'Kaynak bulunamadı',
+        folder_not_found: 'Klasör bulunamadı',
+        parent_folder_not_found: 'Üst klasör bulunamadı',
+        folder_already_exists: 'Aynı isimde bir klasör zaten bulunuyor',
+        folder_not_empty: 'Klasör boş değil, önce içindeki kaynakları ve klasörleri taşıyın veya silin',
+        unknown_locale: 'Bilinmeyen bir dil için alternatif metin verildi',
+        invalid_image: 'Görsel işlenemedi',
     },
     analyzingAsset: 'Kaynak inceleniyor',
     analysisError: 'İnceleme Hatası',
@@ -58,6 +113,10 @@ export const asset: typeof en.asset = {
     canUploadAsset: () => (<><strong>Kaynak Yükle</strong> butonunu kullanarak yeni bir tane yükleyebilirsin</>),
     noAsset: 'Herhangi bir kaynak bulunmuyor',
     noAssetForPage: (page?: number | string) => (<><strong>Sayfa {page}</strong> için gösterilebilecek herhangi bir kaynak bulunmuyor</>),
+    noAssetForSearch: 'Aramayla eşleşen herhangi bir kaynak bulunmuyor',
+    notUsed: 'Bu kaynak herhangi bir içerik, sayfa veya şablon tarafından kullanılmıyor',
+    noOrphanAsset: 'Sahipsiz kaynak bulunmuyor, her kaynak bir içerik, sayfa veya şablon tarafından kullanılıyor',
+    orphanAssetsDesc: 'Aşağıdaki kaynaklar herhangi bir içerik, sayfa veya şablon tarafından kullanılmıyor',
     assetNotFound: (id: string) => (<><strong>{id}</strong> ile tanımlanan kaynak bulunamadı</>),
 };
 
@@ -111,12 +170,10 @@ export const content: typeof en.content = {
         contentUpdated: (name: string) => `"${name}" isimli içerik başarılı bir şekilde güncellendi`,
         createContent: 'İçerik Oluştur',
         editValue: 'Değer Düzenle',
-        markDraft: 'Taslak olarak işaretle',
-        markedDraft: (name: string) => `"${name}" isimli içerik taslak olarak işaretlendi`,
         pickAsset: 'Kaynak Seçin',
-        publish: 'Yayınla',
-        published: (name: string) => `"${name}" isimli içerik yayınlandı`,
         selectLocale: 'Bir dil seçin',
+        selectValue: 'Bir değer seçin',
+        stageUpdated: (name: string, stage: string) => `"${name}" isimli içerik ${stage} aşamasına taşındı`,
         valueCreated: (field: string) => `"${field}" alanı için olan değer başarılı bir şekilde oluşturuldu`,
         valueDeleted: (field: string) => `"${field}" alanı için olan değer başarılı bir şekilde silindi`,
         valueUpdated: (field: string) => `"${field}" alanı için olan değer başarılı bir şekilde güncellendi`,
@@ -128,6 +185,7 @@ export const content: typeof en.content = {
         value: 'Değer',
         values: 'Değerler',
         stage: 'Aşama',
+        noTransition: 'Aşama değiştirilemez',
         unknownField: 'Bilinmeyen Alan',
         unsupportedField: 'Desteklenmeyen Alan',
     },
@@ -138,11 +196,38 @@ export const content: typeof en.content = {
         value: (field: string) => `Lütfen ${field} için bir değer girin`,
     },
     serverErrors: {
+        access_denied: 'Bu işlem için yetkiniz yok',
+        invalid_stage_transition: 'İçerik bu aşamaya taşınamaz',
         missing_required_field: 'Zorunlu bir alan eksik',
+        content_being_referenced: 'İçerik başka içerikler tarafından referans veriliyor',
+    },
+    valueErrors: {
+        invalid_value: 'Değer alan için geçerli değil',
+        unknown_option: 'Değer seçeneklerden biri değil',
+        referenced_content_not_found: 'Referans verilen içerik bulunamadı',
+        asset_not_found: 'Dosya bulunamadı',
+        mime_type_not_allowed: 'Dosya türüne izin verilmiyor',
+        too_short: 'Değer çok kısa',
+        too_long: 'Değer çok uzun',
+        pattern_mismatch: 'Değer desene uymuyor',
+        below_minimum: 'Değer en az değerin altında',
+        above_maximum: 'Değer en fazla değerin üstünde',
+        not_unique: 'Değer başka bir içerik tarafından kullanılıyor',
+        too_many_items: 'Alanda çok fazla değer var',
     },
     stages: {
         [ContentStage.Draft]: 'Taslak',
+        [ContentStage.InReview]: 'İncelemede',
+        [ContentStage.Approved]: 'Onaylandı',
         [ContentStage.Published]: 'Yayınlandı',
+        [ContentStage.Archived]: 'Arşivlendi',
+    },
+    transitions: {
+        [ContentStage.Draft]: 'Taslağa geri al',
+        [ContentStage.InReview]: 'İncelemeye gönder',
+        [ContentStage.Approved]: 'Onayla',
+        [ContentStage.Published]: 'Yayınla',
+        [ContentStage.Archived]: 'Arşivle',
     },
     noContent: (model: string) => (<><strong>{model}</strong> modeline ait herhangi bir içerik bulunamadı. <strong>İçerik Oluştur</strong> butonunu kullanarak yeni bir içerik oluşturabilirsin</>),
     noContentForPage: (page: string) => (<><strong>Sayfa {page}</strong> için gösterilebilecek herhangi bir içerik bulunmuyor</>),
@@ -208,23 +293,55 @@ export const model: typeof en.model = {
         modelCreated: (name: string) => `"${name}" isimli model başarılı bir şekilde oluşturuldu`,
         modelDeleted: (name: string) => `"${name}" isimli model başarılı bir şekilde silindi`,
         modelUpdated: (name: string) => `"${name}" isimli model başarılı bir şekilde güncellendi`,
+        modelPublicUpdated: (name: string) => `"${name}" isimli modelin görünürlüğü başarılı bir şekilde güncellendi`,
         selectField: 'Bir alan seçin',
     },
     labels: {
+        anyModel: 'Herhangi bir model',
         fields: 'Alanlar',
         field: 'Alan',
+        public: 'Herkese Açık',
+        referenceModel: 'Referans Verilen Model',
     },
     fields: {
         asset: 'Kaynak',
+        boolean: 'Mantıksal',
+        date: 'Tarih',
+        datetime: 'Tarih Saat',
+        decimal: 'Ondalık Sayı',
+        email: 'E-posta',
         integer: 'Sayı',
+        json: 'JSON',
+        markdown: 'Markdown',
         multiline: 'Çoklu Satır Metin',
+        reference: 'Referans',
+        richtext: 'Zengin Metin',
         text: 'Metin',
+        url: 'URL',
     },
     fieldFeatures: {
         localized: 'Çoklu Dil',
         required: 'Zorunlu',
         multiple: 'Çoklu Değer',
     },
+    validationRules: {
+        unique: 'Benzersiz',
+        minLength: 'En Az Uzunluk',
+        maxLength: 'En Fazla Uzunluk',
+        pattern: 'Desen',
+        min: 'En Az',
+        max: 'En Fazla',
+        mimeTypes: 'İzin Verilen Dosya Türleri',
+        maxItems: 'En Fazla Öğe',
+    },
+    ruleErrors: {
+        length_and_pattern_only_for_text: 'Uzunluk ve desen kuralları yalnızca metin alanlarında kullanılabilir',
+        range_only_for_numbers: 'En az ve en fazla değerleri yalnızca sayı alanlarında kullanılabilir',
+        mime_types_only_for_assets: 'İzin verilen dosya türleri yalnızca dosya alanlarında kullanılabilir',
+        max_items_only_for_multiple: 'En fazla öğe yalnızca çoklu değer alanlarında kullanılabilir',
+        invalid_range: 'En az değer en fazla değerden büyük olamaz',
+        invalid_pattern: 'Desen geçerli bir düzenli ifade değil',
+    },
     validationErrors: {
         key: 'Lütfen bir anahtar girin',
         name: 'Lütfen bir isim girin',
@@ -234,6 +351,7 @@ export const model: typeof en.model = {
     serverErrors: {
         model_already_exists: 'Belirtilen anahtara ait bir model zaten bulunuyor',
     },
+    publicInfo: 'Herkese açık modellerin yayınlanmış içerikleri içerik dağıtım API\'si üzerinden herkes tarafından okunabilir',
     modelNotFound: (key: string) => (<><strong>{key}</strong> anahtarı ile tanımlanan model bulunamadı</>),
     noModel: () => (<>Herhangi bir model bulunmuyor. <strong>Model Oluştur</strong> butonunu kullanarak yeni bir tane oluşturabilirsin</>),
 };
@@ -332,6 +450,8 @@ export const role: typeof en.role = {
         [Permission.AssetWrite]: 'Kaynak Oluşturma',
         [Permission.CMSRead]: 'CMS Okuma',
         [Permission.ContentWrite]: 'İçerik Oluşturma',
+        [Permission.ContentReview]: 'İçerik İnceleme',
+        [Permission.ContentPublish]: 'İçerik Yayınlama',
         [Permission.ModelWrite]: 'Model Oluşturma',
         [Permission.PageWrite]: 'Sayfa Oluşturma',
         [Permission.TemplateWrite]: 'Şablon Oluşturma',
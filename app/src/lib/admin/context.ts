@@ -16,6 +16,7 @@ export interface AdminStore {
     fetchRole(key: string): Promise<RoleDetail | undefined>;
     createRole(req: { name: string, key: string, desc: string | null }): Promise<Role>;
     updateRole(key: string, req: { name: string, desc: string | null }): Promise<void>;
+    updateRoleTwoFactor(key: string, required: boolean): Promise<void>;
     updateRolePermission(id: number, permissions: Permission[]): Promise<void>;
     deleteRole(key: string): Promise<void>;
 
@@ -76,6 +77,10 @@ export class AdminService implements AdminStore {
         return Api.put(`/admin/role/update/${key}`, req);
     }
 
+    async updateRoleTwoFactor(key: string, required: boolean): Promise<void> {
+        return Api.put(`/admin/role/two-factor/${key}`, { required });
+    }
+
     async updateRolePermission(id: number, permissions: Permission[]): Promise<void> {
         return Api.post(`/admin/permission/role/${id}/update`, permissions);
     }
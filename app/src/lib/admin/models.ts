@@ -16,6 +16,7 @@ export interface Role {
     name: string,
     key: string,
     desc: string | null,
+    requireTwoFactor: boolean,
 }
 
 export interface RoleDetail extends Role {
@@ -88,7 +88,56 @@ export class Api {
         return Api.request(path, 'DELETE');
     }
 
-    static async request<Req, Resp>(path: string, method: Method = 'GET', body?: { data: Req } | { formdata: FormData }): Promise<Resp> {
+    private static refreshing: Promise<boolean> | undefined;
+
+    static storeTokens(tokens: { token: string, refreshToken: string }) {
+        localStorage.setItem('token', tokens.token);
+        localStorage.setItem('refreshToken', tokens.refreshToken);
+    }
+
+    static clearTokens() {
+        localStorage.removeItem('token');
+        localStorage.removeItem('refreshToken');
+    }
+
+    // Exchanges the refresh token for a new pair of tokens. Concurrent requests share the same exchange
+    // since a refresh token can only be used once.
+    static async refresh(): Promise<boolean> {
+        const refreshToken = localStorage.getItem('refreshToken');
+
+        if (!refreshToken) {
+            return false;
+        }
+
+        if (!Api.refreshing) {
+            Api.refreshing = fetch(config.resolveApiURL('/auth/refresh'), {
+                body: JSON.stringify({ refreshToken }),
+                headers: { 'Content-Type': 'application/json' },
+                method: 'POST',
+            })
+                .then(async (resp) => {
+                    if (!resp.ok) {
+                        return false;
+                    }
+
+                    Api.storeTokens(await resp.json());
+
+                    return true;
+                })
+                .catch(() => false)
+                .finally(() => Api.refreshing = undefined);
+        }
+
+        return Api.refreshing;
+    }
+
+    static async logout(): Promise<void> {
+        await Api.post('/auth/logout', {}).catch(() => undefined);
+
+        Api.clearTokens();
+    }
+
+    static async request<Req, Resp>(path: string, method: Method = 'GET', body?: { data: Req } | { formdata: FormData }, retry = true): Promise<Resp> {
         const token = localStorage.getItem('token');
 
         const headers: Record<string, string> = {
@@ -107,6 +156,10 @@ export class Api {
 
         if (!resp.ok) {
             if (resp.status === 401) {
+                if (retry && await Api.refresh()) {
+                    return Api.request(path, method, body, false);
+                }
+
                 window.location.assign(config.resolveBaseUrl('/auth/login'));
             }
 
@@ -1,7 +1,7 @@
 import { createContext, createSignal, type Accessor, type Context, type Setter } from "solid-js";
 import { PaginationRequest } from '../models';
-import { Content, Model, type ModelResponse, type Asset, type ContentDetails, type ContentStage, type Field, type ModelField, type ContentResponse, type ContentDetailsResponse, type ContentValue } from "./models";
-import type { CreateContent, CreateContentValue, CreateModel, CreateModelField, UpdateModelField } from "./requests";
+import { Content, Model, type ModelResponse, type Asset, type AssetDetails, type AssetFolder, type AssetUsage, type ContentDetails, type ContentStage, type Field, type ModelField, type ContentResponse, type ContentDetailsResponse, type ContentValue } from "./models";
+import type { CreateAssetFolder, SearchAssets, CreateContent, CreateField, CreateContentValue, CreateModel, CreateModelField, UpdateModelField } from "./requests";
 import { Api } from "../api";
 import type { Pagination } from "../models";
 
@@ -13,9 +13,20 @@ export interface CMSStore {
     loadModels(): Promise<void>;
 
     fetchAssets(pagination?: PaginationRequest): Promise<Pagination<Asset>>;
-    fetchAsset(id: number): Promise<Asset | undefined>;
+    searchAssets(req: SearchAssets, pagination?: PaginationRequest): Promise<Pagination<Asset>>;
+    fetchAsset(id: number): Promise<AssetDetails | undefined>;
     updateAsset(id: number, name: string): Promise<void>;
+    updateAssetAlts(id: number, alts: Record<string, string>): Promise<Record<string, string>>;
+    moveAssets(assetIds: number[], folderId: number | null): Promise<void>;
     deleteAsset(id: number): Promise<void>;
+    fetchAssetUsage(id: number): Promise<AssetUsage>;
+    fetchOrphanAssets(): Promise<Asset[]>;
+    purgeOrphanAssets(assetIds?: number[]): Promise<Asset[]>;
+
+    fetchAssetFolders(): Promise<AssetFolder[]>;
+    createAssetFolder(req: CreateAssetFolder): Promise<AssetFolder>;
+    updateAssetFolder(id: number, req: CreateAssetFolder): Promise<AssetFolder>;
+    deleteAssetFolder(id: number): Promise<void>;
 
     fetchContents(modelId: number, pagination?: PaginationRequest): Promise<Pagination<Content>>;
     fetchContent(id: number): Promise<ContentDetails | undefined>;
@@ -27,9 +38,11 @@ export interface CMSStore {
     deleteContent(id: number): Promise<void>;
     deleteContentValue(id: number): Promise<void>;
 
+    createField(req: CreateField): Promise<Field>;
     createModel(model: CreateModel): Promise<Model>;
     createModelField(id: number, req: CreateModelField): Promise<ModelField>;
     updateModelDetails(id: number, req: { name: string, desc: string | null }): Promise<void>;
+    updateModelPublic(id: number, isPublic: boolean): Promise<void>;
     updateModelField(id: number, req: UpdateModelField): Promise<void>;
     deleteModel(id: number): Promise<void>;
     deleteModelField(id: number): Promise<void>;
@@ -49,6 +62,10 @@ export class CMSService implements CMSStore {
         [this.fields, this.setFields] = createSignal(fields);
     }
 
+    async createField(req: CreateField): Promise<Field> {
+        return Api.post('/cms/field/create', req);
+    }
+
     async createModel(request: CreateModel): Promise<Model> {
         return Api.post<CreateModel, ModelResponse>('/cms/model/create', request).then(Model.fromResponse);
     }
@@ -61,6 +78,10 @@ export class CMSService implements CMSStore {
         return Api.put(`/cms/model/update/${id}`, req);
     }
 
+    async updateModelPublic(id: number, isPublic: boolean): Promise<void> {
+        return Api.put(`/cms/model/public/${id}`, { public: isPublic });
+    }
+
     async updateModelField(id: number, req: UpdateModelField): Promise<void> {
         return Api.put(`/cms/model/field/${id}/update`, req);
     }
@@ -101,18 +122,66 @@ export class CMSService implements CMSStore {
         return Api.get(url);
     }
 
-    async fetchAsset(id: number): Promise<Asset | undefined> {
-        return Api.get<Asset>(`/cms/asset/view/${id}`).catch(Api.handleNotFound);
+    async searchAssets(req: SearchAssets, pagination?: PaginationRequest): Promise<Pagination<Asset>> {
+        const params = pagination ? PaginationRequest.toSearchParams(pagination) : new URLSearchParams();
+
+        for (const [key, value] of Object.entries(req)) {
+            if (value !== undefined && value !== '') {
+                params.append(key, value.toString());
+            }
+        }
+
+        return Api.get(`/cms/asset/search?${params.toString()}`);
+    }
+
+    async fetchAsset(id: number): Promise<AssetDetails | undefined> {
+        return Api.get<AssetDetails>(`/cms/asset/view/${id}`).catch(Api.handleNotFound);
     }
 
     async updateAsset(id: number, name: string): Promise<void> {
         return Api.put(`/cms/asset/update/${id}`, { name });
     }
 
+    async updateAssetAlts(id: number, alts: Record<string, string>): Promise<Record<string, string>> {
+        return Api.put(`/cms/asset/alt/${id}`, { alts });
+    }
+
+    async moveAssets(assetIds: number[], folderId: number | null): Promise<void> {
+        return Api.put('/cms/asset/move', { assetIds, folderId });
+    }
+
     async deleteAsset(id: number): Promise<void> {
         return Api.delete(`/cms/asset/delete/${id}`);
     }
 
+    async fetchAssetUsage(id: number): Promise<AssetUsage> {
+        return Api.get(`/cms/asset/usage/${id}`);
+    }
+
+    async fetchOrphanAssets(): Promise<Asset[]> {
+        return Api.get('/cms/asset/orphan/all');
+    }
+
+    async purgeOrphanAssets(assetIds?: number[]): Promise<Asset[]> {
+        return Api.post('/cms/asset/orphan/purge', { assetIds });
+    }
+
+    async fetchAssetFolders(): Promise<AssetFolder[]> {
+        return Api.get('/cms/asset/folder/all');
+    }
+
+    async createAssetFolder(req: CreateAssetFolder): Promise<AssetFolder> {
+        return Api.post('/cms/asset/folder/create', req);
+    }
+
+    async updateAssetFolder(id: number, req: CreateAssetFolder): Promise<AssetFolder> {
+        return Api.put(`/cms/asset/folder/update/${id}`, req);
+    }
+
+    async deleteAssetFolder(id: number): Promise<void> {
+        return Api.delete(`/cms/asset/folder/delete/${id}`);
+    }
+
     async fetchContents(modelId: number, pagination?: PaginationRequest): Promise<Pagination<Content>> {
         const params = pagination ? PaginationRequest.toSearchParams(pagination) : new URLSearchParams();
 
@@ -10,6 +10,19 @@ export interface ModelField {
     localized: boolean,
     multiple: boolean,
     required: boolean,
+    referenceModelId: number | null,
+    validation: ValidationRules,
+}
+
+export interface ValidationRules {
+    minLength?: number,
+    maxLength?: number,
+    pattern?: string,
+    min?: number,
+    max?: number,
+    unique?: boolean,
+    mimeTypes?: string[],
+    maxItems?: number,
 }
 
 export interface ModelResponse {
@@ -19,6 +32,7 @@ export interface ModelResponse {
     name: string,
     desc: string | null,
     fields: ModelField[],
+    public: boolean,
     createdAt: string,
 }
 
@@ -30,6 +44,7 @@ export class Model implements Omit<ModelResponse, 'createdAt'> {
         public name: string,
         public desc: string | null,
         public fields: ModelField[],
+        public public: boolean,
         public createdAt: Date,
     ) { }
 
@@ -41,6 +56,7 @@ export class Model implements Omit<ModelResponse, 'createdAt'> {
             response.name,
             response.desc,
             response.fields,
+            response.public,
             fromUTC(response.createdAt),
         )
     }
@@ -62,7 +78,18 @@ export enum FieldKind {
     String = 'string',
     Multiline = 'multiline',
     Integer = 'int',
+    Float = 'float',
+    Bool = 'bool',
+    Date = 'date',
+    DateTime = 'datetime',
+    Markdown = 'markdown',
+    RichText = 'richtext',
+    Json = 'json',
+    Url = 'url',
+    Email = 'email',
+    Enum = 'enum',
     Asset = 'asset',
+    Reference = 'reference',
 }
 
 export interface Field {
@@ -70,11 +97,15 @@ export interface Field {
     key: string,
     name: string,
     kind: FieldKind,
+    options: string[],
 }
 
 export enum ContentStage {
-    Published = 'published',
     Draft = 'draft',
+    InReview = 'in_review',
+    Approved = 'approved',
+    Published = 'published',
+    Archived = 'archived',
 }
 
 export interface ContentResponse {
@@ -122,6 +153,7 @@ export interface ContentDetailsResponse {
     content: ContentResponse,
     values: ContentValue[],
     user: { id: number, name: string } | null,
+    transitions: ContentStage[],
 }
 
 export interface ContentDetails extends Omit<ContentDetailsResponse, 'content'> {
@@ -136,4 +168,27 @@ export interface Asset {
     createdBy: number | null,
     createdAt: string,
     updatedAt: string,
+    folderId: number | null,
+    size: number | null,
+    checksum: string | null,
+    width: number | null,
+    height: number | null,
+}
+
+export interface AssetDetails extends Asset {
+    alts: Record<string, string>,
+}
+
+export interface AssetUsage {
+    contents: { contentId: number, contentName: string, field: string }[],
+    revisions: { revisionId: number, contentId: number, contentName: string }[],
+    pages: { id: number, key: string, name: string, namespace: string | null }[],
+    templates: string[],
+}
+
+export interface AssetFolder {
+    id: number,
+    parentId: number | null,
+    name: string,
+    createdAt: string,
 }
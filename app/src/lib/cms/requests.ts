@@ -1,3 +1,12 @@
+import type { FieldKind, ValidationRules } from "./models";
+
+export interface CreateField {
+    key: string,
+    name: string,
+    kind: FieldKind,
+    options: string[],
+}
+
 export interface CreateModelField {
     fieldId: number,
     key: string,
@@ -6,6 +15,8 @@ export interface CreateModelField {
     localized: boolean,
     multiple: boolean,
     required: boolean,
+    referenceModelId?: number | null,
+    validation?: ValidationRules,
 }
 
 export interface UpdateModelField {
@@ -14,6 +25,8 @@ export interface UpdateModelField {
     localized: boolean,
     multiple: boolean,
     required: boolean,
+    referenceModelId?: number | null,
+    validation?: ValidationRules,
 }
 
 export interface CreateModel {
@@ -35,3 +48,17 @@ export interface CreateContent {
     modelId: number,
     values: CreateContentValue[],
 }
+
+export interface SearchAssets {
+    query?: string,
+    folderId?: number,
+    root?: boolean,
+    filetype?: string,
+    tag?: string,
+    createdBy?: number,
+}
+
+export interface CreateAssetFolder {
+    name: string,
+    parentId: number | null,
+}
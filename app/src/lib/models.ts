@@ -84,6 +84,8 @@ export enum Permission {
     CMSRead = 'cms.read',
     AssetWrite = 'asset.write',
     ContentWrite = 'content.write',
+    ContentReview = 'content.review',
+    ContentPublish = 'content.publish',
     ModelWrite = 'model.write',
     AppearanceRead = 'appearance.read',
     PageWrite = 'page.write',
@@ -1,5 +1,5 @@
 import { createContext, createSignal, type Accessor, type Context } from "solid-js";
-import type { User } from "./models";
+import type { ApiToken, CreateApiToken, CreatedApiToken, User } from "./models";
 import { Api } from "../api";
 
 export interface UserStore {
@@ -18,4 +18,16 @@ export class UserService implements UserStore {
     public static async fetchUser(): Promise<User> {
         return Api.get('/user/profile');
     }
+
+    public static async fetchApiTokens(): Promise<ApiToken[]> {
+        return Api.get('/user/token/all');
+    }
+
+    public static async createApiToken(req: CreateApiToken): Promise<CreatedApiToken> {
+        return Api.post('/user/token/create', req);
+    }
+
+    public static async revokeApiToken(id: number): Promise<void> {
+        return Api.delete(`/user/token/delete/${id}`);
+    }
 }
@@ -8,6 +8,7 @@ export enum UserState {
 export enum LoginKind {
     Email = 'email',
     Cloud = 'cloud',
+    Oidc = 'oidc',
 }
 
 export interface User {
@@ -21,3 +22,22 @@ export interface User {
     createdAt: string,
     permissions: Permission[],
 }
+
+export interface ApiToken {
+    id: number,
+    name: string,
+    expiresAt: string,
+    lastUsedAt: string | null,
+    createdAt: string,
+    permissions: Permission[],
+}
+
+export interface CreateApiToken {
+    name: string,
+    permissions: Permission[],
+    expiresAt: string,
+}
+
+export interface CreatedApiToken extends ApiToken {
+    secret: string,
+}
@@ -12,5 +12,14 @@ blog-title = Blog
 
 project-title = Projects
 
+tag-posts-title = Posts tagged "{$tag}"
+tag-projects-title = Projects tagged "{$tag}"
+
+search-title = Search
+search-placeholder = Search posts and projects
+search-submit = Search
+search-results = Results for "{$query}"
+search-no-results = No results found for "{$query}"
+
 not-found = Not Found
 page-does-not-exist = Page you are looking for does not exist
@@ -12,5 +12,14 @@ blog-title = Blog
 
 project-title = Projeler
 
+tag-posts-title = "{$tag}" etiketli yazılar
+tag-projects-title = "{$tag}" etiketli projeler
+
+search-title = Ara
+search-placeholder = Yazılarda ve projelerde ara
+search-submit = Ara
+search-results = "{$query}" için sonuçlar
+search-no-results = "{$query}" için sonuç bulunamadı
+
 not-found = Bulunamadı
 page-does-not-exist = Aradığınız sayfa bulunamadı
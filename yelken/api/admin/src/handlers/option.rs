@@ -1,7 +1,12 @@
 use std::collections::HashMap;
 
 use axum::{extract::State, Json};
-use base::{responses::HttpError, schema::options, AppState};
+use base::{
+    content::{load_workflow, validate_workflow, Transition, WORKFLOW_OPTION},
+    responses::HttpError,
+    schema::options,
+    AppState,
+};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 
@@ -52,3 +57,49 @@ pub async fn update_site_option(
 
     Ok(())
 }
+
+pub async fn fetch_workflow(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Transition>>, HttpError> {
+    load_workflow(&mut *state.pool.get().await?)
+        .await
+        .map(Json)
+        .map_err(Into::into)
+}
+
+pub async fn update_workflow(
+    State(state): State<AppState>,
+    Json(workflow): Json<Vec<Transition>>,
+) -> Result<(), HttpError> {
+    validate_workflow(&workflow).map_err(HttpError::unprocessable_entity)?;
+
+    let value = serde_json::to_string(&workflow).map_err(|e| {
+        HttpError::internal_server_error("failed_serializing_workflow")
+            .with_context(format!("{e:?}"))
+    })?;
+
+    let mut conn = state.pool.get().await?;
+
+    let effected_row: usize = diesel::update(options::table)
+        .filter(
+            options::namespace
+                .is_null()
+                .and(options::key.eq(WORKFLOW_OPTION)),
+        )
+        .set(options::value.eq(&value))
+        .execute(&mut conn)
+        .await?;
+
+    if effected_row == 0 {
+        diesel::insert_into(options::table)
+            .values((
+                options::namespace.eq(Option::<String>::None),
+                options::key.eq(WORKFLOW_OPTION),
+                options::value.eq(value),
+            ))
+            .execute(&mut conn)
+            .await?;
+    }
+
+    Ok(())
+}
@@ -178,6 +178,8 @@ mod tests {
         let auth_user = AuthUser {
             id: auth_user.id,
             name: auth_user.name,
+            session_id: None,
+            api_token_id: None,
         };
 
         let service = opendal::services::Memory::default();
@@ -8,17 +8,17 @@ use base::{
     middlewares::permission::Permission,
     models::Role,
     responses::HttpError,
-    schema::{permissions, roles},
+    schema::{permissions, roles, sessions, two_factors, users},
     AppState,
 };
 use diesel::{
     prelude::*,
     result::{DatabaseErrorKind, Error},
 };
-use diesel_async::RunQueryDsl;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
 
 use crate::{
-    requests::{CreateRole, UpdateRole},
+    requests::{CreateRole, UpdateRole, UpdateRoleTwoFactor},
     responses::RoleDetail,
 };
 
@@ -101,6 +101,51 @@ pub async fn update_role(
     Ok(())
 }
 
+/// Sets whether two-factor authentication is required for users of the role. Once required, the
+/// sessions of users without it are revoked, so that they need to enable it on their next login.
+pub async fn update_role_two_factor(
+    State(state): State<AppState>,
+    Path(role_key): Path<String>,
+    Json(req): Json<UpdateRoleTwoFactor>,
+) -> Result<(), HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    conn.transaction(|conn| {
+        async move {
+            let Some(role_id) = diesel::update(roles::table)
+                .filter(roles::key.eq(role_key))
+                .set(roles::require_two_factor.eq(req.required))
+                .returning(roles::id)
+                .get_result::<i32>(conn)
+                .await
+                .optional()?
+            else {
+                return Err(HttpError::not_found("role_not_found"));
+            };
+
+            if req.required {
+                let without_two_factor = users::table
+                    .filter(users::role_id.eq(role_id))
+                    .filter(diesel::dsl::not(diesel::dsl::exists(
+                        two_factors::table
+                            .filter(two_factors::user_id.eq(users::id))
+                            .filter(two_factors::confirmed_at.is_not_null()),
+                    )))
+                    .select(users::id);
+
+                diesel::delete(sessions::table)
+                    .filter(sessions::user_id.eq_any(without_two_factor))
+                    .execute(conn)
+                    .await?;
+            }
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
 pub async fn delete_role(
     State(state): State<AppState>,
     Path(role_key): Path<String>,
@@ -5,18 +5,23 @@ use axum::{
     Extension, Json,
 };
 use base::{
+    config::Options,
     crypto::Crypto,
+    mail::{Mailer, Template},
     middlewares::{auth::AuthUser, permission::Permission},
-    models::{User, UserState},
+    models::{LoginKind, User, UserState},
+    password::{self, PasswordToken, TokenPurpose},
     responses::HttpError,
+    runtime::spawn_blocking,
     schema::{permissions, users},
-    AppState,
+    session, two_factor, AppState,
 };
+use chrono::Utc;
 use diesel::{
     prelude::*,
     result::{DatabaseErrorKind, Error},
 };
-use diesel_async::RunQueryDsl;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
 use rand::{distr::Alphanumeric, rng, Rng};
 
 use crate::{
@@ -120,13 +125,14 @@ pub async fn fetch_user(
 pub async fn create_user(
     State(state): State<AppState>,
     Extension(crypto): Extension<Crypto>,
+    Extension(mailer): Extension<Mailer>,
+    Extension(options): Extension<Options>,
     Json(req): Json<CreateUser>,
 ) -> Result<Json<CreatedUser>, HttpError> {
-    let salt: String = (0..32)
-        .map(|_| rng().sample(Alphanumeric) as char)
-        .collect();
-
-    let password = crypto.sign512((req.password + salt.as_str()).as_bytes());
+    let password = match req.password {
+        Some(p) => Some(spawn_blocking(move || password::hash(&p)).await.unwrap()),
+        None => None,
+    };
 
     let mut conn = state.pool.get().await?;
 
@@ -135,7 +141,7 @@ pub async fn create_user(
             users::username.eq(generate_username(&req.name)),
             users::name.eq(&req.name),
             users::email.eq(&req.email),
-            users::password.eq(salt + password.as_str()),
+            users::password.eq(password),
         ))
         .get_result::<User>(&mut conn)
         .await
@@ -153,48 +159,182 @@ pub async fn create_user(
             e.into()
         })?;
 
+    // Users created without a password set it themselves by accepting the invitation. The user is
+    // already created, a failed invitation does not fail the request as it can be sent again.
+    let invitation_sent = user.password.is_none()
+        && send_invitation(&state, &crypto, &mailer, &options, &user)
+            .await
+            .inspect_err(|e| log::error!("Failed to send invitation to user {}, {e:?}", user.id))
+            .is_ok();
+
     Ok(Json(CreatedUser {
         id: user.id,
         username: user.username,
         name: user.name,
         email: user.email,
+        invitation_sent,
     }))
 }
 
-pub async fn update_user(
+/// Sends the invitation again, e.g. when the previous one is expired.
+pub async fn invite_user(
     State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    Extension(mailer): Extension<Mailer>,
+    Extension(options): Extension<Options>,
     Path(user_id): Path<i32>,
-    user: AuthUser,
-    Json(req): Json<UpdateUser>,
 ) -> Result<(), HttpError> {
-    if user_id == user.id {
-        return Err(HttpError::conflict("self_update_not_possible"));
+    let user = users::table
+        .filter(users::id.eq(user_id))
+        .first::<User>(&mut state.pool.get().await?)
+        .await
+        .optional()?
+        .ok_or_else(|| HttpError::not_found("user_not_found"))?;
+
+    if user.login_kind != LoginKind::Email {
+        return Err(HttpError::conflict("user_not_created_with_email"));
     }
 
-    let effected_row = diesel::update(users::table)
-        .filter(users::id.eq(user_id))
-        .set((
-            users::name.eq(req.name),
-            users::role_id.eq(req.role_id),
-            users::state.eq(req.state),
-        ))
-        .execute(&mut state.pool.get().await?)
+    if user.password.is_some() {
+        return Err(HttpError::conflict("invitation_already_accepted"));
+    }
+
+    send_invitation(&state, &crypto, &mailer, &options, &user).await
+}
+
+async fn send_invitation(
+    state: &AppState,
+    crypto: &Crypto,
+    mailer: &Mailer,
+    options: &Options,
+    user: &User,
+) -> Result<(), HttpError> {
+    const INVITATION_TOKEN_HOURS: i64 = 72;
+
+    let token = PasswordToken::issue(
+        crypto,
+        user.id,
+        TokenPurpose::Invitation,
+        user.password.as_deref(),
+        Utc::now().timestamp() + INVITATION_TOKEN_HOURS * 60 * 60,
+    )?;
+
+    let mut url = state.config.app_url.clone();
+
+    url.path_segments_mut()
+        .unwrap()
+        .pop_if_empty()
+        .extend(["auth", "accept-invitation"]);
+
+    url.query_pairs_mut().append_pair("token", &token);
+
+    mailer
+        .send(
+            &user.email,
+            &options.default_locale(),
+            Template::Invitation {
+                site: state.config.site_url.as_str(),
+                name: &user.name,
+                url: url.as_str(),
+                hours: INVITATION_TOKEN_HOURS,
+            },
+        )
         .await
         .map_err(|e| {
-            if let Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) = &e {
-                return HttpError::conflict("role_not_found");
-            }
+            HttpError::internal_server_error("failed_sending_invitation")
+                .with_context(format!("{e:?}"))
+        })
+}
 
-            e.into()
-        })?;
+/// Logs the user out of all devices by revoking every session of the user.
+pub async fn revoke_sessions(
+    State(state): State<AppState>,
+    Path(user_id): Path<i32>,
+) -> Result<(), HttpError> {
+    let mut conn = state.pool.get().await?;
 
-    if effected_row == 0 {
+    let exists = diesel::select(diesel::dsl::exists(
+        users::table.filter(users::id.eq(user_id)),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await?;
+
+    if !exists {
         return Err(HttpError::not_found("user_not_found"));
     }
 
+    session::revoke_all(&mut conn, user_id).await?;
+
     Ok(())
 }
 
+/// Disables two-factor authentication of the user, e.g. when both the device and the recovery codes
+/// are lost.
+pub async fn reset_two_factor(
+    State(state): State<AppState>,
+    Path(user_id): Path<i32>,
+) -> Result<(), HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let effected_row = two_factor::disable(&mut conn, user_id).await?;
+
+    if effected_row == 0 {
+        return Err(HttpError::not_found("two_factor_not_found"));
+    }
+
+    Ok(())
+}
+
+pub async fn update_user(
+    State(state): State<AppState>,
+    Path(user_id): Path<i32>,
+    user: AuthUser,
+    Json(req): Json<UpdateUser>,
+) -> Result<(), HttpError> {
+    if user_id == user.id {
+        return Err(HttpError::conflict("self_update_not_possible"));
+    }
+
+    let mut conn = state.pool.get().await?;
+
+    conn.transaction(|conn| {
+        async move {
+            let effected_row = diesel::update(users::table)
+                .filter(users::id.eq(user_id))
+                .set((
+                    users::name.eq(req.name),
+                    users::role_id.eq(req.role_id),
+                    users::state.eq(req.state),
+                ))
+                .execute(conn)
+                .await
+                .map_err(|e| {
+                    if let Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) = &e {
+                        return HttpError::conflict("role_not_found");
+                    }
+
+                    e.into()
+                })?;
+
+            if effected_row == 0 {
+                return Err(HttpError::not_found("user_not_found"));
+            }
+
+            // User needs to enable two-factor authentication on the next login if the new role
+            // requires it, existing sessions would skip it otherwise
+            if two_factor::is_required(conn, user_id).await?
+                && !two_factor::is_enabled(conn, user_id).await?
+            {
+                session::revoke_all(conn, user_id).await?;
+            }
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
 pub async fn delete_user(
     State(state): State<AppState>,
     Path(user_id): Path<i32>,
@@ -225,13 +365,17 @@ pub async fn delete_user(
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use axum::{
         extract::{Path, State},
         Extension, Json,
     };
     use base::{
-        config::Config,
+        config::{Config, Options},
         crypto::Crypto,
+        l10n::L10n,
+        mail::{MailConfig, Mailer, Transport},
         middlewares::auth::AuthUser,
         models::{User, UserState},
         schema::{roles, users},
@@ -265,6 +409,8 @@ mod tests {
         let auth_user = AuthUser {
             id: auth_user.id,
             name: auth_user.name,
+            session_id: None,
+            api_token_id: None,
         };
 
         let service = opendal::services::Memory::default();
@@ -282,12 +428,28 @@ mod tests {
         let req = CreateUser {
             name: "Merhabalar".to_string(),
             email: "merhaba@email.com".to_string(),
-            password: "password".to_string(),
+            password: Some("password".to_string()),
         };
 
-        let created_user = create_user(State(state.clone()), Extension(crypto), Json(req))
-            .await
-            .unwrap();
+        let mailer = Mailer::new(
+            MailConfig {
+                from: "Yelken <yelken@localhost>".to_string(),
+                transport: Transport::Log,
+            },
+            L10n::from_sources(&[], "en".parse().unwrap()).unwrap(),
+        );
+
+        let options = Options::new("default".into(), Arc::new([]), "en".parse().unwrap());
+
+        let created_user = create_user(
+            State(state.clone()),
+            Extension(crypto),
+            Extension(mailer),
+            Extension(options),
+            Json(req),
+        )
+        .await
+        .unwrap();
 
         assert_eq!("Merhabalar", created_user.name);
         assert_eq!("merhaba@email.com", created_user.email);
@@ -33,6 +33,9 @@ pub fn router(state: AppState) -> Router<AppState> {
 
     let user_write = Router::new()
         .route("/create", post(user::create_user))
+        .route("/invite/{user_id}", post(user::invite_user))
+        .route("/sessions/{user_id}/revoke", post(user::revoke_sessions))
+        .route("/two-factor/{user_id}/reset", post(user::reset_two_factor))
         .route("/update/{user_id}", put(user::update_user))
         .route("/delete/{user_id}", delete(user::delete_user));
 
@@ -43,6 +46,7 @@ pub fn router(state: AppState) -> Router<AppState> {
     let role_write = Router::new()
         .route("/create", post(role::create_role))
         .route("/update/{key}", put(role::update_role))
+        .route("/two-factor/{key}", put(role::update_role_two_factor))
         .route("/delete/{key}", delete(role::delete_role));
 
     let locale_write = Router::new()
@@ -61,9 +65,13 @@ pub fn router(state: AppState) -> Router<AppState> {
         .route("/delete/{key}", delete(locale::delete_locale))
         .route("/default", put(locale::update_default_locale));
 
-    let option_read = Router::new().route("/site/all", get(option::fetch_site_options));
+    let option_read = Router::new()
+        .route("/site/all", get(option::fetch_site_options))
+        .route("/workflow", get(option::fetch_workflow));
 
-    let option_write = Router::new().route("/site/update", put(option::update_site_option));
+    let option_write = Router::new()
+        .route("/site/update", put(option::update_site_option))
+        .route("/workflow/update", put(option::update_workflow));
 
     Router::new()
         .nest("/option", option_read.merge(option_write))
@@ -5,7 +5,8 @@ use serde::Deserialize;
 pub struct CreateUser {
     pub name: String,
     pub email: String,
-    pub password: String,
+    /// When not given, an invitation is sent to the user to set the password.
+    pub password: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -27,6 +28,11 @@ pub struct UpdateRole {
     pub desc: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct UpdateRoleTwoFactor {
+    pub required: bool,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateUser {
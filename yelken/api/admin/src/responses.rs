@@ -10,6 +10,9 @@ pub struct CreatedUser {
     pub username: String,
     pub name: String,
     pub email: String,
+    /// Whether an invitation is sent to the user. It needs to be sent again through the invite
+    /// endpoint if the user is created without a password and sending it failed.
+    pub invitation_sent: bool,
 }
 
 #[derive(Serialize)]
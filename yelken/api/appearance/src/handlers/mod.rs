@@ -51,39 +51,7 @@ fn resolve_locale<'a>(
         return locale;
     }
 
-    // Cookie based resolution
-    if let Some(cookie) = req
-        .headers()
-        .get(http::header::COOKIE)
-        .and_then(|header| header.to_str().ok())
-        .and_then(|cookie| cookie.split_once("yelken_locale=").map(|split| split.1))
-    {
-        let locale = cookie
-            .split_once(';')
-            .map(|split| split.0)
-            .unwrap_or(cookie);
-
-        if let Some(locale) = locales.clone().find(|l| &*l.key == locale) {
-            return locale;
-        }
-    }
-
-    // Header based resolution
-    if let Some(accept_language) = req
-        .headers()
-        .get(http::header::ACCEPT_LANGUAGE)
-        .and_then(|header| header.to_str().ok())
-    {
-        for lang in accept_language.split(',') {
-            let lang = lang.split_once(';').map(|split| split.0).unwrap_or(lang);
-
-            if let Some(locale) = locales.clone().find(|l| &*l.key == lang) {
-                return locale;
-            }
-        }
-    }
-
-    default
+    base::l10n::negotiate_locale(req.headers(), locales, |l| &*l.key).unwrap_or(default)
 }
 
 pub async fn serve_page(
@@ -405,12 +373,22 @@ mod tests {
     };
     use diesel::prelude::*;
     use diesel_async::RunQueryDsl;
-    use unic_langid::LanguageIdentifier;
 
-    use crate::{l10n::L10n, render::Render};
+    use crate::{
+        render::{context::Locale, Render},
+        L10n,
+    };
 
     use super::resolve_locale;
 
+    fn locale(key: &str) -> Locale {
+        Locale {
+            id: key.parse().unwrap(),
+            key: key.into(),
+            name: key.into(),
+        }
+    }
+
     async fn init_params(
         locales: &[&str],
         templates: Vec<(String, String)>,
@@ -430,6 +408,9 @@ mod tests {
             site_url: "http://127.0.0.1:3000".parse().unwrap(),
             app_url: "http://127.0.0.1:3000".parse().unwrap(),
             reload_templates: false,
+            upload_size_limit: 0,
+            image_max_dimension: 0,
+            image_sizes: [].into(),
         };
         let pool = create_pool(DB_CONFIG).await;
         let state = AppState::new(config, pool, storage.clone(), storage.clone());
@@ -476,9 +457,10 @@ mod tests {
                     .into_iter()
                     .map(|page| {
                         (
+                            pages::key.eq(page.0),
                             pages::name.eq(page.0),
                             pages::path.eq(page.1),
-                            pages::template.eq(page.2),
+                            pages::value.eq(page.2),
                             pages::locale.eq(page.3),
                         )
                     })
@@ -527,26 +509,34 @@ mod tests {
     #[tokio::test]
     async fn it_returns_307_when_two_pages_with_same_path_is_requested_and_user_has_non_default_locale(
     ) {
-        let (state, options, l10n, renderer) =
-            init_params(&["en", "tr"], vec![("".to_string(), "".to_string())]).await;
+        let (state, options, l10n, renderer) = init_params(
+            &["en", "tr"],
+            vec![("home.html".to_string(), "Home Page".to_string())],
+        )
+        .await;
 
         create_pages(
             &mut state.pool.get().await.unwrap(),
             &[
-                ("home", "/", "", Some("en")),
-                ("home", "/", "", Some("tr")),
-                ("test", "/test", "", Some("tr")),
+                ("home", "/", "home.html", Some("en")),
+                ("home", "/", "home.html", Some("tr")),
+                ("test", "/test", "home.html", Some("tr")),
             ],
         )
         .await;
 
         let cases = [
             ("/", StatusCode::TEMPORARY_REDIRECT, Some("/tr"), "tr"),
-            ("/en", StatusCode::TEMPORARY_REDIRECT, Some("/"), "en"),
+            (
+                "/en",
+                StatusCode::TEMPORARY_REDIRECT,
+                Some("http://127.0.0.1:3000/"),
+                "en",
+            ),
             (
                 "/en/test",
                 StatusCode::TEMPORARY_REDIRECT,
-                Some("/test"),
+                Some("http://127.0.0.1:3000/test"),
                 "en",
             ),
             ("/", StatusCode::OK, None, "en"),
@@ -585,41 +575,42 @@ mod tests {
     #[test]
     fn returns_default_when_no_locale_provided_or_path_not_start_with_slash() {
         // No locale case
-        let default_locale = "en".parse().unwrap();
+        let default_locale = locale("en");
 
         assert_eq!(
-            default_locale,
-            *resolve_locale(&Request::new(Body::empty()), &[], &default_locale)
+            "en",
+            &*resolve_locale(&Request::new(Body::empty()), [].iter(), &default_locale).key
         );
 
         // Request that does not start with '/'
         assert_eq!(
-            default_locale,
-            *resolve_locale(
+            "en",
+            &*resolve_locale(
                 &Request::builder()
                     .uri("not-slash")
                     .body(Body::empty())
                     .unwrap(),
-                &["tr".parse().unwrap()],
+                [locale("tr")].iter(),
                 &default_locale
             )
+            .key
         );
     }
 
     #[test]
     fn root_url_resolves_to_default_locale() {
-        let default_locale = "en".parse().unwrap();
-        let locales = ["tr".parse().unwrap()];
+        let default_locale = locale("en");
+        let locales = [locale("tr")];
         let req = Request::builder().uri("/").body(Body::empty()).unwrap();
 
-        let locale = resolve_locale(&req, &locales, &default_locale);
+        let locale = resolve_locale(&req, locales.iter(), &default_locale);
 
-        assert_eq!(default_locale, *locale);
+        assert_eq!("en", &*locale.key);
     }
 
     #[test]
     fn chooses_correct_locale_if_url_starts_with_locale() {
-        let locales = ["en".parse().unwrap(), "tr".parse().unwrap()];
+        let locales = [locale("en"), locale("tr")];
 
         let cases = [
             ("/en", "en"),
@@ -636,15 +627,15 @@ mod tests {
         for (path, expected_locale) in cases {
             let req = Request::builder().uri(path).body(Body::empty()).unwrap();
 
-            let locale = resolve_locale(&req, &locales, &locales[0]);
+            let locale = resolve_locale(&req, locales.iter(), &locales[0]);
 
-            assert_eq!(expected_locale, locale.language.as_str());
+            assert_eq!(expected_locale, &*locale.key);
         }
     }
 
     #[test]
     fn chooses_correct_locale_if_cookie_has_locale() {
-        let locales = ["en".parse().unwrap(), "tr".parse().unwrap()];
+        let locales = [locale("en"), locale("tr")];
 
         let cases = [
             ("/", "yelken_locale=en", "en"),
@@ -665,18 +656,15 @@ mod tests {
                 .body(Body::empty())
                 .unwrap();
 
-            let locale = resolve_locale(&req, &locales, &locales[0]);
+            let locale = resolve_locale(&req, locales.iter(), &locales[0]);
 
-            assert_eq!(
-                expected_locale.parse::<LanguageIdentifier>().unwrap(),
-                *locale
-            );
+            assert_eq!(expected_locale, &*locale.key);
         }
     }
 
     #[test]
     fn chooses_correct_locale_if_accept_language_has_locale() {
-        let locales = ["en".parse().unwrap(), "tr".parse().unwrap()];
+        let locales = [locale("en"), locale("tr")];
 
         let cases = [
             ("/", "en,zh-CN", "en"),
@@ -694,9 +682,9 @@ mod tests {
                 .body(Body::empty())
                 .unwrap();
 
-            let locale = resolve_locale(&req, &locales, &locales[0]);
+            let locale = resolve_locale(&req, locales.iter(), &locales[0]);
 
-            assert_eq!(expected_locale, locale.language.as_str());
+            assert_eq!(expected_locale, &*locale.key);
         }
     }
 }
@@ -13,13 +13,12 @@ use base::{
 };
 
 mod handlers;
-mod l10n;
 mod render;
 mod requests;
 mod responses;
 
 pub use handlers::serve_page;
-pub use l10n::L10n;
+pub use base::l10n::L10n;
 pub use render::Render;
 
 use handlers::{page, template, theme};
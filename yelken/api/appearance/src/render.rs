@@ -1,19 +1,23 @@
 use std::collections::{BTreeMap, HashMap};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use arc_swap::ArcSwap;
-use base::db::Pool;
-use base::models::ContentStage;
+use base::content::{
+    query_contents, visible_at, ContentQueryError, Filter, FilterOp, Order, SearchRank,
+};
+use base::crypto::Crypto;
+use base::db::{Connection, Pool};
+use base::field::{sanitize_html, FieldKind};
+use base::form::issue_form_token;
+use base::l10n::L10n;
 use base::runtime::{block_on, IntoSendFuture};
-use base::schema::{content_values, contents, fields, model_fields, models};
-use chrono::NaiveDateTime;
+use base::schema::{asset_alts, assets, content_values, contents, fields, model_fields, models};
+use chrono::{NaiveDateTime, Utc};
 use context::Context;
-use minijinja::value::Kwargs;
+use minijinja::value::{Enumerator, Kwargs, Object, ValueKind};
 use minijinja::{Environment, Error, ErrorKind, State, Value};
 use opendal::{EntryMode, Operator};
 
-use crate::l10n::L10n;
-
 #[derive(Debug)]
 enum RenderError {
     Database(diesel::result::Error),
@@ -192,9 +196,9 @@ pub mod context {
 }
 
 #[cfg(feature = "plugin")]
-pub type FnResources = (L10n, Pool, plugin::PluginHost);
+pub type FnResources = (L10n, Pool, Crypto, plugin::PluginHost);
 #[cfg(not(feature = "plugin"))]
-pub type FnResources = (L10n, Pool);
+pub type FnResources = (L10n, Pool, Crypto);
 
 async fn load_templates(storage: &Operator, locations: &[String]) -> Vec<(String, String)> {
     let mut templates = HashMap::<String, String>::new();
@@ -355,11 +359,57 @@ impl Render {
     }
 }
 
+/// Widths used by `asset_srcset` when no `widths` are given, matching the default image sizes.
+const SRCSET_WIDTHS: [u32; 5] = [320, 640, 960, 1280, 1920];
+
+fn asset_url(
+    mut base_url: url::Url,
+    kind: &str,
+    path: &str,
+    params: &[(&str, String)],
+) -> Result<url::Url, Error> {
+    match kind {
+        "theme" | "content" => {
+            base_url
+                .path_segments_mut()
+                .unwrap()
+                .push("assets")
+                .push(kind)
+                .extend(path.split('/'));
+
+            if !params.is_empty() {
+                base_url.query_pairs_mut().extend_pairs(params);
+            }
+
+            Ok(base_url)
+        }
+        unknown => Err(Error::new(
+            ErrorKind::InvalidOperation,
+            format!("unknown asset kind {unknown}"),
+        )),
+    }
+}
+
+/// Image transformation parameters, other than dimensions, given to `asset_url` or `asset_srcset`.
+fn image_params(kwargs: &Kwargs) -> Result<Vec<(&'static str, String)>, Error> {
+    let mut params = Vec::new();
+
+    if let Some(fit) = kwargs.get::<Option<String>>("fit")? {
+        params.push(("fit", fit));
+    }
+
+    if let Some(format) = kwargs.get::<Option<String>>("format")? {
+        params.push(("format", format));
+    }
+
+    Ok(params)
+}
+
 fn register_functions(env: &mut Environment, resources: FnResources) {
     #[cfg(feature = "plugin")]
-    let (l10n, pool, plugin_host) = resources;
+    let (l10n, pool, crypto, plugin_host) = resources;
     #[cfg(not(feature = "plugin"))]
-    let (l10n, pool) = resources;
+    let (l10n, pool, crypto) = resources;
 
     env.add_function(
         "localize",
@@ -386,28 +436,51 @@ fn register_functions(env: &mut Environment, resources: FnResources) {
                 .downcast_object()
                 .expect("context does not have expected type");
 
-            let base_url = ctx.internal.site_url.clone();
-
             let kind = kwargs.get::<&str>("kind").unwrap_or("theme");
 
-            match kind {
-                "theme" | "content" => {
-                    let mut base_url = base_url;
+            let mut params = image_params(&kwargs)?;
 
-                    base_url
-                        .path_segments_mut()
-                        .unwrap()
-                        .push("assets")
-                        .push(kind)
-                        .extend(path.split('/'));
+            if let Some(width) = kwargs.get::<Option<u32>>("width")? {
+                params.push(("width", width.to_string()));
+            }
 
-                    Ok(Value::from_safe_string(base_url.to_string()))
-                }
-                unknown => Err(Error::new(
-                    ErrorKind::InvalidOperation,
-                    format!("unknown asset kind {unknown}"),
-                )),
+            if let Some(height) = kwargs.get::<Option<u32>>("height")? {
+                params.push(("height", height.to_string()));
             }
+
+            asset_url(ctx.internal.site_url.clone(), kind, &path, &params)
+                .map(|url| Value::from_safe_string(url.to_string()))
+        },
+    );
+
+    env.add_function(
+        "asset_srcset",
+        |state: &State, path: String, kwargs: Kwargs| {
+            let ctx: Arc<Context> = state
+                .lookup("ctx")
+                .expect("could not find render context")
+                .downcast_object()
+                .expect("context does not have expected type");
+
+            let kind = kwargs.get::<&str>("kind").unwrap_or("theme");
+
+            let widths = kwargs
+                .get::<Option<Vec<u32>>>("widths")?
+                .unwrap_or_else(|| SRCSET_WIDTHS.to_vec());
+
+            let params = image_params(&kwargs)?;
+
+            widths
+                .into_iter()
+                .map(|width| {
+                    let mut params = params.clone();
+                    params.push(("width", width.to_string()));
+
+                    asset_url(ctx.internal.site_url.clone(), kind, &path, &params)
+                        .map(|url| format!("{url} {width}w"))
+                })
+                .collect::<Result<Vec<String>, Error>>()
+                .map(|srcset| Value::from_safe_string(srcset.join(", ")))
         },
     );
 
@@ -506,10 +579,15 @@ fn register_functions(env: &mut Environment, resources: FnResources) {
                         locale: ctx.request.locale.key.to_string(),
                         model,
                         fields: None,
-                        filter: Some((field, value)),
+                        filters: vec![Filter {
+                            field,
+                            op: FilterOp::Eq(value),
+                        }],
+                        order: None,
                         limit: Some(1),
                         offset: None,
                         count: false,
+                        now: Utc::now().naive_utc(),
                     }
                     .get(),
                 )
@@ -545,6 +623,9 @@ fn register_functions(env: &mut Environment, resources: FnResources) {
                 let page = args.get::<Option<i64>>("page")?.unwrap_or(1);
                 let offset = std::cmp::max(page - 1, 0) * per_page;
 
+                let filters = parse_filters(args.get("filter")?)?;
+                let order = parse_order(args.get("order_by")?)?;
+
                 block_on(
                     ContentSource {
                         pool: pool.clone(),
@@ -552,10 +633,12 @@ fn register_functions(env: &mut Environment, resources: FnResources) {
                         locale: ctx.request.locale.key.to_string(),
                         model,
                         fields: Some(fields),
-                        filter: None,
+                        filters,
+                        order,
                         limit: Some(limit),
                         offset: Some(offset),
                         count: true,
+                        now: Utc::now().naive_utc(),
                     }
                     .get(),
                 )
@@ -601,14 +684,8 @@ fn register_functions(env: &mut Environment, resources: FnResources) {
                 let limit: Option<i64> = args.get("limit")?;
                 let offset: Option<i64> = args.get("offset")?;
 
-                let filter: Option<Vec<String>> = args.get("filter")?;
-
-                if filter.is_none() && args.has("filter") {
-                    return Err(Error::new(
-                        ErrorKind::InvalidOperation,
-                        "invalid parameters",
-                    ));
-                }
+                let filters = parse_filters(args.get("filter")?)?;
+                let order = parse_order(args.get("order_by")?)?;
 
                 block_on(
                     ContentSource {
@@ -617,10 +694,12 @@ fn register_functions(env: &mut Environment, resources: FnResources) {
                         locale: ctx.request.locale.key.to_string(),
                         model,
                         fields: Some(fields),
-                        filter: None,
+                        filters,
+                        order,
                         limit,
                         offset,
                         count: false,
+                        now: Utc::now().naive_utc(),
                     }
                     .get(),
                 )
@@ -637,21 +716,166 @@ fn register_functions(env: &mut Environment, resources: FnResources) {
             },
         );
     }
+
+    {
+        let pool = pool.clone();
+
+        env.add_function(
+            "search",
+            move |state: &State, query: String, args: Kwargs| {
+                let ctx: Arc<Context> = state
+                    .lookup("ctx")
+                    .expect("could not find render context")
+                    .downcast_object()
+                    .expect("context does not have expected type");
+
+                let per_page = args.get::<Option<i64>>("per_page")?.unwrap_or(20);
+                let limit = std::cmp::min(per_page, 100);
+
+                let page = args.get::<Option<i64>>("page")?.unwrap_or(1);
+                let offset = std::cmp::max(page - 1, 0) * per_page;
+
+                let models: Option<Vec<String>> = args.get("models")?;
+
+                block_on(
+                    SearchSource {
+                        pool: pool.clone(),
+                        namespace: ctx.internal.namespace.clone(),
+                        locale: ctx.request.locale.key.to_string(),
+                        query,
+                        models,
+                        limit,
+                        offset,
+                        now: Utc::now().naive_utc(),
+                    }
+                    .get(),
+                )
+                .inspect_err(|e| match e {
+                    RenderError::Database(e) => {
+                        log::error!("Database error occurred during rendering, {e:?}")
+                    }
+                    RenderError::Pool(e) => {
+                        log::error!("Pool error occurred during rendering, {e:?}")
+                    }
+                })
+                .map(|(values, total_items)| {
+                    let total_pages = (total_items as f64 / per_page as f64).ceil() as i64;
+
+                    Value::from_dyn_object(Arc::new(context::Pagination {
+                        per_page,
+                        current_page: page,
+                        total_pages,
+                        total_items,
+                        items: values.into(),
+                    }))
+                })
+                .map_err(|_| Error::new(ErrorKind::InvalidOperation, "RenderError"))
+            },
+        );
+    }
+
+    {
+        let pool = pool.clone();
+
+        env.add_function("asset_alt", move |state: &State, filename: String| {
+            let ctx: Arc<Context> = state
+                .lookup("ctx")
+                .expect("could not find render context")
+                .downcast_object()
+                .expect("context does not have expected type");
+
+            block_on(async {
+                let mut conn = pool.get().await.map_err(RenderError::Pool)?;
+
+                load_asset_alt(
+                    &mut conn,
+                    &filename,
+                    &ctx.request.locale.key,
+                    &ctx.l10n.default.key,
+                )
+                .await
+                .map_err(RenderError::Database)
+            })
+            .inspect_err(|e| match e {
+                RenderError::Database(e) => {
+                    log::error!("Database error occurred during rendering, {e:?}")
+                }
+                RenderError::Pool(e) => {
+                    log::error!("Pool error occurred during rendering, {e:?}")
+                }
+            })
+            .map_err(|_| Error::new(ErrorKind::InvalidOperation, "RenderError"))
+        });
+    }
+
+    env.add_function("form_token", move |form: String| {
+        issue_form_token(&crypto, &form, Utc::now().timestamp())
+    });
+}
+
+/// Loads the alternative text of the content asset in the given locale, falling back to the
+/// default locale if the asset does not have one.
+async fn load_asset_alt(
+    conn: &mut Connection,
+    filename: &str,
+    locale: &str,
+    default_locale: &str,
+) -> Result<Option<String>, diesel::result::Error> {
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+
+    let alts = asset_alts::table
+        .inner_join(assets::table)
+        .filter(assets::filename.eq(filename))
+        .filter(asset_alts::locale.eq_any([locale, default_locale]))
+        .select((asset_alts::locale, asset_alts::value))
+        .load::<(String, String)>(conn)
+        .await?;
+
+    let alt = |key: &str| alts.iter().find(|(l, _)| l == key).map(|(_, v)| v.clone());
+
+    Ok(alt(locale).or_else(|| alt(default_locale)))
 }
 
+/// Converts the stored value into a typed template value. Values are validated on write, values
+/// that still cannot be converted fall back to their string form.
 fn string_to_value(field_kind: &str, value: String) -> Value {
-    match field_kind {
-        "string" | "multiline" => Value::from(value),
-        "asset" => Value::from(value),
-        "int" => Value::from(str::parse::<i64>(&value).unwrap_or(0)),
-        unknown => {
-            log::error!("Unhandled field kind is found, {unknown}");
-
-            Value::from(value)
-        }
+    match FieldKind::of(field_kind) {
+        FieldKind::Int | FieldKind::Reference => value
+            .parse::<i64>()
+            .map(Value::from)
+            .unwrap_or_else(|_| Value::from(value)),
+        FieldKind::Float => value
+            .parse::<f64>()
+            .map(Value::from)
+            .unwrap_or_else(|_| Value::from(value)),
+        FieldKind::Bool => Value::from(value == "true"),
+        FieldKind::Json => serde_json::from_str::<serde_json::Value>(&value)
+            .map(|json| Value::from_serialize(&json))
+            .unwrap_or_else(|_| Value::from(value)),
+        // Rich text and markdown are rendered as html, which is sanitized again since values may
+        // be written before sanitization or imported with themes
+        FieldKind::RichText => Value::from_safe_string(sanitize_html(&value)),
+        FieldKind::Markdown => Value::from_safe_string(sanitize_html(&markdown_to_html(&value))),
+        FieldKind::String
+        | FieldKind::Multiline
+        | FieldKind::Date
+        | FieldKind::DateTime
+        | FieldKind::Url
+        | FieldKind::Email
+        | FieldKind::Enum
+        | FieldKind::Asset => Value::from(value),
     }
 }
 
+fn markdown_to_html(markdown: &str) -> String {
+    let mut html = String::with_capacity(markdown.len());
+
+    pulldown_cmark::html::push_html(&mut html, pulldown_cmark::Parser::new(markdown));
+
+    html
+}
+
 fn replace_params(mut path: &str, mut params: &[String]) -> Option<String> {
     let mut path_with_params = String::with_capacity(path.len());
 
@@ -676,16 +900,128 @@ fn replace_params(mut path: &str, mut params: &[String]) -> Option<String> {
     Some(path_with_params)
 }
 
+/// Parses the `filter` argument of content functions. A filter is a list of conditions in the form
+/// of `[field, op, value]` which are combined with AND, e.g.
+/// `[["tags", "=", "rust"], ["weight", ">=", 2]]`. A single condition can be given without the
+/// outer list. Supported operators are `=`, `in`, `<`, `<=`, `>` and `>=`. Equality conditions can
+/// also be given as a map, e.g. `{"author": author.id}`, where a list value means `in`.
+fn parse_filters(filter: Option<Value>) -> Result<Vec<Filter>, Error> {
+    let invalid = || Error::new(ErrorKind::InvalidOperation, "invalid filter");
+
+    let Some(filter) = filter.filter(|f| !f.is_none() && !f.is_undefined()) else {
+        return Ok(vec![]);
+    };
+
+    let as_string = |v: &Value| {
+        v.as_str()
+            .map(ToString::to_string)
+            .or_else(|| i64::try_from(v.clone()).ok().map(|n| n.to_string()))
+            .ok_or_else(invalid)
+    };
+
+    if filter.kind() == ValueKind::Map {
+        return filter
+            .try_iter()?
+            .map(|field| {
+                let value = filter.get_item(&field)?;
+
+                let field = field.as_str().ok_or_else(invalid)?.to_string();
+
+                let op = if value.kind() == ValueKind::Seq {
+                    FilterOp::In(
+                        value
+                            .try_iter()?
+                            .map(|v| as_string(&v))
+                            .collect::<Result<_, _>>()?,
+                    )
+                } else {
+                    FilterOp::Eq(as_string(&value)?)
+                };
+
+                Ok(Filter { field, op })
+            })
+            .collect();
+    }
+
+    let conditions = if filter.get_item_by_index(0)?.as_str().is_some() {
+        vec![filter]
+    } else {
+        filter.try_iter()?.collect()
+    };
+
+    conditions
+        .into_iter()
+        .map(|condition| {
+            let items = condition.try_iter()?.collect::<Vec<_>>();
+
+            let [field, op, value] = <[Value; 3]>::try_from(items).map_err(|_| invalid())?;
+
+            let field = field.as_str().ok_or_else(invalid)?.to_string();
+
+            let as_int = |v: &Value| {
+                v.as_str()
+                    .map(|s| s.parse::<i64>().map_err(|_| invalid()))
+                    .unwrap_or_else(|| i64::try_from(v.clone()).map_err(|_| invalid()))
+            };
+
+            let op = match op.as_str().ok_or_else(invalid)? {
+                "=" => FilterOp::Eq(as_string(&value)?),
+                "in" if value.kind() == ValueKind::Seq => FilterOp::In(
+                    value
+                        .try_iter()?
+                        .map(|v| as_string(&v))
+                        .collect::<Result<_, _>>()?,
+                ),
+                "<" => FilterOp::Lt(as_int(&value)?),
+                "<=" => FilterOp::Le(as_int(&value)?),
+                ">" => FilterOp::Gt(as_int(&value)?),
+                ">=" => FilterOp::Ge(as_int(&value)?),
+                _ => return Err(invalid()),
+            };
+
+            Ok(Filter { field, op })
+        })
+        .collect()
+}
+
+/// Parses the `order_by` argument of content functions, a field name optionally followed by `asc`
+/// or `desc`, e.g. `"weight"` or `"created_at desc"`.
+fn parse_order(order_by: Option<String>) -> Result<Option<Order>, Error> {
+    let invalid = || Error::new(ErrorKind::InvalidOperation, "invalid order_by");
+
+    let Some(order_by) = order_by else {
+        return Ok(None);
+    };
+
+    let mut parts = order_by.split_whitespace();
+
+    let field = parts.next().ok_or_else(invalid)?.to_string();
+
+    let desc = match parts.next() {
+        None | Some("asc") => false,
+        Some("desc") => true,
+        _ => return Err(invalid()),
+    };
+
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+
+    Ok(Some(Order { field, desc }))
+}
+
 struct ContentSource {
     pool: Pool,
     namespace: String,
     locale: String,
     model: String,
     fields: Option<Vec<String>>,
-    filter: Option<(String, String)>,
+    filters: Vec<Filter>,
+    order: Option<Order>,
     limit: Option<i64>,
     offset: Option<i64>,
     count: bool,
+    now: NaiveDateTime,
 }
 
 impl ContentSource {
@@ -695,6 +1031,12 @@ impl ContentSource {
 
         let mut conn = self.pool.get().await.map_err(RenderError::Pool)?;
 
+        let loader = ReferenceLoader {
+            pool: self.pool.clone(),
+            locale: self.locale.clone(),
+            now: self.now,
+        };
+
         let Some(model_id) = models::table
             .filter(
                 models::key.eq(&self.model).and(
@@ -734,49 +1076,24 @@ impl ContentSource {
         .await
         .map_err(RenderError::Database)?;
 
-        let contents_query = contents::table
-            .filter(
-                contents::model_id
-                    .eq(model_id)
-                    .and(contents::stage.eq(ContentStage::Published)),
-            )
-            .order(contents::id.asc())
-            .select(contents::id);
-
-        let mut contents_query = if let Some(filter) = self.filter {
-            if filter.0 == "id" {
-                let Ok(id) = str::parse::<i32>(&filter.1) else {
-                    log::debug!("Could not parse value as integer, filtering \"id\" requires value to be a string containing an integer");
-
-                    return Ok(None);
-                };
-
-                contents_query.filter(contents::id.eq(id)).into_boxed()
-            } else {
-                contents_query
-                    .filter(
-                        contents::id.eq_any(
-                            content_values::table
-                                .inner_join(model_fields::table)
-                                .filter(
-                                    model_fields::model_id
-                                        .eq(model_id)
-                                        .and(model_fields::key.eq(filter.0)),
-                                )
-                                .filter(
-                                    content_values::value.eq(filter.1).and(
-                                        content_values::locale
-                                            .eq(&self.locale)
-                                            .or(content_values::locale.is_null()),
-                                    ),
-                                )
-                                .select(content_values::content_id),
-                        ),
-                    )
-                    .into_boxed()
+        let query = query_contents(
+            &mut conn,
+            model_id,
+            &self.locale,
+            self.filters,
+            self.order.as_ref(),
+            self.now,
+        )
+        .await;
+
+        let mut contents_query = match query {
+            Ok(query) => query,
+            Err(ContentQueryError::Invalid(e)) => {
+                log::debug!("{e}");
+
+                return Ok(None);
             }
-        } else {
-            contents_query.into_boxed()
+            Err(ContentQueryError::Database(e)) => return Err(RenderError::Database(e)),
         };
 
         if let Some(limit) = self.limit {
@@ -812,76 +1129,345 @@ impl ContentSource {
             )
         };
 
-        let mut content_values = content_values::table
-            .filter(
-                content_values::content_id
-                    .eq_any(&contents.iter().map(|c| c.0).collect::<Vec<i32>>()),
-            )
-            .filter(
-                content_values::model_field_id
-                    .eq_any(model_fields.iter().map(|mf| mf.0).collect::<Vec<i32>>()),
-            )
+        let mut content_values = load_values(
+            &mut conn,
+            contents.iter().map(|c| c.0).collect(),
+            model_fields.iter().map(|mf| mf.0).collect(),
+            &self.locale,
+        )
+        .await?;
+
+        let contents = contents
+            .into_iter()
+            .map(|(id, created_at)| {
+                Value::from(build_content(
+                    id,
+                    created_at,
+                    &mut content_values,
+                    model_fields.iter(),
+                    &loader,
+                ))
+            })
+            .collect();
+
+        Ok(Some((contents, total)))
+    }
+}
+
+async fn load_values(
+    conn: &mut Connection,
+    content_ids: Vec<i32>,
+    model_field_ids: Vec<i32>,
+    locale: &str,
+) -> Result<Vec<(i32, i32, String)>, RenderError> {
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+
+    content_values::table
+        .filter(content_values::content_id.eq_any(content_ids))
+        .filter(content_values::model_field_id.eq_any(model_field_ids))
+        .filter(
+            content_values::locale
+                .eq(locale)
+                .or(content_values::locale.is_null()),
+        )
+        .order((content_values::content_id.asc(), content_values::id.asc()))
+        .select((
+            content_values::content_id,
+            content_values::model_field_id,
+            content_values::value,
+        ))
+        .load::<(i32, i32, String)>(conn)
+        .await
+        .map_err(RenderError::Database)
+}
+
+/// Builds the content with given id out of its values, values of the content are taken out of `content_values`.
+fn build_content<'a>(
+    id: i32,
+    created_at: NaiveDateTime,
+    content_values: &mut Vec<(i32, i32, String)>,
+    model_fields: impl Iterator<Item = &'a (i32, String, bool, String)>,
+    loader: &ReferenceLoader,
+) -> BTreeMap<String, Value> {
+    let mut content = BTreeMap::<String, Value>::from_iter([
+        ("id".to_string(), Value::from(id)),
+        (
+            "created_at".to_string(),
+            Value::from(created_at.format("%Y-%m-%d").to_string()),
+        ),
+    ]);
+
+    let mut values = content_values
+        .extract_if(.., |v| v.0 == id)
+        .collect::<Vec<_>>();
+
+    for model_field in model_fields {
+        let mut values = values.extract_if(.., |v| v.1 == model_field.0);
+
+        let to_value = |value: String| match value.parse::<i32>() {
+            Ok(id) if FieldKind::of(&model_field.3) == FieldKind::Reference => {
+                Value::from_object(ContentRef {
+                    id,
+                    loader: loader.clone(),
+                    content: OnceLock::new(),
+                })
+            }
+            _ => string_to_value(model_field.3.as_str(), value),
+        };
+
+        let value = if model_field.2 {
+            Some(Value::from(
+                values.map(|v| to_value(v.2)).collect::<Vec<_>>(),
+            ))
+        } else {
+            values.next().map(|v| to_value(v.2))
+        };
+
+        if let Some(value) = value {
+            content.insert(model_field.1.clone(), value);
+        }
+    }
+
+    content
+}
+
+#[derive(Clone)]
+struct ReferenceLoader {
+    pool: Pool,
+    locale: String,
+    now: NaiveDateTime,
+}
+
+impl ReferenceLoader {
+    async fn load(&self, id: i32) -> Result<Option<BTreeMap<String, Value>>, RenderError> {
+        use diesel::prelude::*;
+        use diesel_async::RunQueryDsl;
+
+        let mut conn = self.pool.get().await.map_err(RenderError::Pool)?;
+
+        let Some((model_id, created_at)) = contents::table
+            .filter(contents::id.eq(id))
+            .filter(visible_at(self.now))
+            .select((contents::model_id, contents::created_at))
+            .first::<(i32, NaiveDateTime)>(&mut conn)
+            .await
+            .optional()
+            .map_err(RenderError::Database)?
+        else {
+            return Ok(None);
+        };
+
+        let model_fields = model_fields::table
+            .inner_join(fields::table)
+            .filter(model_fields::model_id.eq(model_id))
+            .select((
+                model_fields::id,
+                model_fields::key,
+                model_fields::multiple,
+                fields::kind,
+            ))
+            .load::<(i32, String, bool, String)>(&mut conn)
+            .await
+            .map_err(RenderError::Database)?;
+
+        let mut content_values = load_values(
+            &mut conn,
+            vec![id],
+            model_fields.iter().map(|mf| mf.0).collect(),
+            &self.locale,
+        )
+        .await?;
+
+        Ok(Some(build_content(
+            id,
+            created_at,
+            &mut content_values,
+            model_fields.iter(),
+            self,
+        )))
+    }
+}
+
+/// Content referred by a reference field. The content is loaded once one of its fields other than
+/// `id` is accessed, which lets templates traverse references like `post.author.name`.
+struct ContentRef {
+    id: i32,
+    loader: ReferenceLoader,
+    content: OnceLock<Option<BTreeMap<String, Value>>>,
+}
+
+impl ContentRef {
+    fn content(&self) -> Option<&BTreeMap<String, Value>> {
+        self.content
+            .get_or_init(|| {
+                block_on(self.loader.load(self.id))
+                    .inspect_err(|e| {
+                        log::error!("Failed to load referenced content {}, {e:?}", self.id)
+                    })
+                    .ok()
+                    .flatten()
+            })
+            .as_ref()
+    }
+}
+
+impl std::fmt::Debug for ContentRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContentRef").field("id", &self.id).finish()
+    }
+}
+
+impl Object for ContentRef {
+    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+        match key.as_str()? {
+            "id" => Some(Value::from(self.id)),
+            key => self.content()?.get(key).cloned(),
+        }
+    }
+
+    fn enumerate(self: &Arc<Self>) -> Enumerator {
+        match self.content() {
+            Some(content) => Enumerator::Values(content.keys().map(Value::from).collect()),
+            None => Enumerator::Empty,
+        }
+    }
+
+    fn is_true(self: &Arc<Self>) -> bool {
+        true
+    }
+
+    // Rendered as the id of the content, the stored value of the reference
+    fn render(self: &Arc<Self>, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+struct SearchSource {
+    pool: Pool,
+    namespace: String,
+    locale: String,
+    query: String,
+    models: Option<Vec<String>>,
+    limit: i64,
+    offset: i64,
+    now: NaiveDateTime,
+}
+
+impl SearchSource {
+    async fn get(self) -> Result<(Vec<Value>, i64), RenderError> {
+        use diesel::prelude::*;
+        use diesel_async::RunQueryDsl;
+
+        if self.query.trim().is_empty() {
+            return Ok((vec![], 0));
+        }
+
+        let mut conn = self.pool.get().await.map_err(RenderError::Pool)?;
+
+        let loader = ReferenceLoader {
+            pool: self.pool.clone(),
+            locale: self.locale.clone(),
+            now: self.now,
+        };
+
+        let mut models_query = models::table
             .filter(
-                content_values::locale
-                    .eq(&self.locale)
-                    .or(content_values::locale.is_null()),
+                models::namespace
+                    .is_null()
+                    .or(models::namespace.eq(self.namespace)),
             )
-            .order((content_values::content_id.asc(), content_values::id.asc()))
+            .select((models::id, models::key))
+            .into_boxed();
+
+        if let Some(models) = self.models {
+            models_query = models_query.filter(models::key.eq_any(models));
+        }
+
+        let models = models_query
+            .load::<(i32, String)>(&mut conn)
+            .await
+            .map_err(RenderError::Database)?;
+
+        let rank = SearchRank::new(&self.query, self.locale.clone());
+
+        let contents = contents::table
+            .filter(contents::model_id.eq_any(models.iter().map(|m| m.0).collect::<Vec<_>>()))
+            .filter(visible_at(self.now))
+            .filter(rank.clone().is_not_null())
+            .order((rank.desc(), contents::id.desc()))
+            .limit(self.limit)
+            .offset(self.offset)
             .select((
-                content_values::content_id,
-                content_values::model_field_id,
-                content_values::value,
+                contents::id,
+                contents::model_id,
+                contents::created_at,
+                base::paginate::CountStarOver,
             ))
-            .load::<(i32, i32, String)>(&mut conn)
+            .load::<(i32, i32, NaiveDateTime, i64)>(&mut conn)
             .await
             .map_err(RenderError::Database)?;
 
+        let total = <[_]>::first(&contents).map(|c| c.3).unwrap_or(0);
+
+        let model_fields = model_fields::table
+            .inner_join(fields::table)
+            .filter(model_fields::model_id.eq_any(contents.iter().map(|c| c.1).collect::<Vec<_>>()))
+            .select((
+                model_fields::model_id,
+                (
+                    model_fields::id,
+                    model_fields::key,
+                    model_fields::multiple,
+                    fields::kind,
+                ),
+            ))
+            .load::<(i32, (i32, String, bool, String))>(&mut conn)
+            .await
+            .map_err(RenderError::Database)?;
+
+        let mut content_values = load_values(
+            &mut conn,
+            contents.iter().map(|c| c.0).collect(),
+            model_fields.iter().map(|mf| mf.1 .0).collect(),
+            &self.locale,
+        )
+        .await?;
+
         let contents = contents
             .into_iter()
-            .map(|(id, created_at)| {
-                let mut content = BTreeMap::<String, Value>::from_iter([
-                    ("id".to_string(), Value::from(id)),
-                    (
-                        "created_at".to_string(),
-                        Value::from(created_at.format("%Y-%m-%d").to_string()),
-                    ),
-                ]);
-
-                let mut values = content_values
-                    .extract_if(.., |v| v.0 == id)
-                    .collect::<Vec<_>>();
-
-                for model_field in model_fields.iter() {
-                    let mut values = values.extract_if(.., |v| v.1 == model_field.0);
-
-                    let value = if model_field.2 {
-                        Some(Value::from(
-                            values
-                                .map(|v| string_to_value(model_field.3.as_str(), v.2))
-                                .collect::<Vec<_>>(),
-                        ))
-                    } else {
-                        values
-                            .next()
-                            .map(|v| Value::from(string_to_value(model_field.3.as_str(), v.2)))
-                    };
-
-                    if let Some(value) = value {
-                        content.insert(model_field.1.clone(), value);
-                    }
+            .map(|(id, model_id, created_at, _)| {
+                let mut content = build_content(
+                    id,
+                    created_at,
+                    &mut content_values,
+                    model_fields
+                        .iter()
+                        .filter(|mf| mf.0 == model_id)
+                        .map(|mf| &mf.1),
+                    &loader,
+                );
+
+                if let Some(model) = models.iter().find(|m| m.0 == model_id) {
+                    content.insert("model".to_string(), Value::from(model.1.clone()));
                 }
 
                 Value::from(content)
             })
             .collect();
 
-        Ok(Some((contents, total)))
+        Ok((contents, total))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{append_locale_to_path, replace_params};
+    use minijinja::Value;
+
+    use super::{
+        asset_url, parse_filters, parse_order, replace_params, string_to_value, Filter, FilterOp,
+        Order,
+    };
 
     #[test]
     fn it_replaces_parameters_inside_path_with_values_from_params() {
@@ -909,25 +1495,165 @@ mod tests {
     }
 
     #[test]
-    fn it_appends_locale_to_path() {
-        let tr = "tr".parse().unwrap();
-        let en = "en".parse().unwrap();
+    fn it_renders_sanitized_rich_text_and_markdown() {
+        let rich_text = string_to_value(
+            "richtext",
+            "<p onclick=\"steal()\">Hi</p><script>steal()</script>".to_string(),
+        );
 
-        assert_eq!("/tr/path", append_locale_to_path(&tr, &en, "/path"));
+        assert!(rich_text.is_safe());
+        assert_eq!("<p>Hi</p>", rich_text.to_string());
 
-        assert_eq!("/tr", append_locale_to_path(&tr, &en, "/"));
+        let markdown = string_to_value(
+            "markdown",
+            "# Title\n\n[link](javascript:steal()) <img src=x onerror=steal()>".to_string(),
+        );
 
-        assert_eq!("/trtest", append_locale_to_path(&tr, &en, "test"));
+        assert!(markdown.is_safe());
+        assert!(markdown.to_string().starts_with("<h1>Title</h1>"));
+        assert!(!markdown.to_string().contains("steal"));
     }
 
     #[test]
-    fn it_does_not_append_locale_to_path_if_it_is_default_one() {
-        let en = "en".parse().unwrap();
+    fn it_builds_asset_urls_with_image_params() {
+        let base_url: url::Url = "http://127.0.0.1:3000".parse().unwrap();
 
-        assert_eq!("/", append_locale_to_path(&en, &en, "/"));
+        assert_eq!(
+            "http://127.0.0.1:3000/assets/theme/images/logo.png",
+            asset_url(base_url.clone(), "theme", "images/logo.png", &[])
+                .unwrap()
+                .as_str()
+        );
+
+        assert_eq!(
+            "http://127.0.0.1:3000/assets/content/photo.jpg?format=webp&width=640",
+            asset_url(
+                base_url.clone(),
+                "content",
+                "photo.jpg",
+                &[("format", "webp".to_string()), ("width", "640".to_string())]
+            )
+            .unwrap()
+            .as_str()
+        );
+
+        assert!(asset_url(base_url, "plugin", "photo.jpg", &[]).is_err());
+    }
+
+    #[test]
+    fn it_parses_filters() {
+        let filters = parse_filters(Some(Value::from_serialize(serde_json::json!([
+            ["tags", "=", "rust"],
+            ["weight", ">=", 2],
+            ["parent", "<", "10"],
+            ["id", "in", [1, "2"]]
+        ]))))
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                Filter {
+                    field: "tags".to_string(),
+                    op: FilterOp::Eq("rust".to_string())
+                },
+                Filter {
+                    field: "weight".to_string(),
+                    op: FilterOp::Ge(2)
+                },
+                Filter {
+                    field: "parent".to_string(),
+                    op: FilterOp::Lt(10)
+                },
+                Filter {
+                    field: "id".to_string(),
+                    op: FilterOp::In(vec!["1".to_string(), "2".to_string()])
+                },
+            ],
+            filters
+        );
+    }
+
+    #[test]
+    fn it_parses_single_filter_without_outer_list() {
+        let filters = parse_filters(Some(Value::from_serialize(serde_json::json!([
+            "tags", "=", "rust"
+        ]))))
+        .unwrap();
+
+        assert_eq!(
+            vec![Filter {
+                field: "tags".to_string(),
+                op: FilterOp::Eq("rust".to_string())
+            }],
+            filters
+        );
+
+        assert!(parse_filters(None).unwrap().is_empty());
+        assert!(parse_filters(Some(Value::from(()))).unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_parses_filters_given_as_map() {
+        let filters = parse_filters(Some(Value::from_serialize(serde_json::json!({
+            "author": 3,
+        }))))
+        .unwrap();
+
+        assert_eq!(
+            vec![Filter {
+                field: "author".to_string(),
+                op: FilterOp::Eq("3".to_string())
+            }],
+            filters
+        );
+
+        let filters = parse_filters(Some(Value::from_serialize(serde_json::json!({
+            "tags": ["rust", "wasm"],
+        }))))
+        .unwrap();
+
+        assert_eq!(
+            vec![Filter {
+                field: "tags".to_string(),
+                op: FilterOp::In(vec!["rust".to_string(), "wasm".to_string()])
+            }],
+            filters
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_filters() {
+        for filter in [
+            serde_json::json!([["tags", "~", "rust"]]),
+            serde_json::json!([["weight", ">", "heavy"]]),
+            serde_json::json!([["tags", "="]]),
+            serde_json::json!([["tags", "in", "rust"]]),
+        ] {
+            assert!(parse_filters(Some(Value::from_serialize(filter))).is_err());
+        }
+    }
+
+    #[test]
+    fn it_parses_order() {
+        assert_eq!(
+            Some(Order {
+                field: "weight".to_string(),
+                desc: false
+            }),
+            parse_order(Some("weight".to_string())).unwrap()
+        );
+
+        assert_eq!(
+            Some(Order {
+                field: "created_at".to_string(),
+                desc: true
+            }),
+            parse_order(Some("created_at desc".to_string())).unwrap()
+        );
 
-        assert_eq!("test", append_locale_to_path(&en, &en, "test"));
+        assert!(parse_order(None).unwrap().is_none());
 
-        assert_eq!("/path", append_locale_to_path(&en, &en, "/path"));
+        assert!(parse_order(Some("weight down".to_string())).is_err());
+        assert!(parse_order(Some("".to_string())).is_err());
     }
 }
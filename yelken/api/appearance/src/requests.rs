@@ -40,7 +40,7 @@ pub struct UpdateTheme {
 pub struct UpdateTemplate {
     pub namespace: Option<SafePath<1>>,
     pub path: SafePath<3>,
-    #[sanitize(skip)]
+    #[sanitizer(skip)]
     pub template: String,
 }
 
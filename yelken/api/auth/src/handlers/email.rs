@@ -1,22 +1,28 @@
 use axum::{extract::State, http::StatusCode, Extension, Json};
 use base::{
+    config::Options,
     crypto::Crypto,
+    mail::{Mailer, Template},
     models::{LoginKind, UserState},
+    password::{self, PasswordToken, TokenPurpose},
     responses::HttpError,
+    runtime::{spawn, spawn_blocking},
     schema::users,
+    validate::{Errors, Valid, Validate},
     AppState,
 };
+use chrono::Utc;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 
 use serde::{Deserialize, Serialize};
 
-const SALT_LENGTH: usize = 32;
+use super::{
+    two_factor::{login_user, LoginResult},
+    INVALID_TOKEN,
+};
 
-#[derive(Deserialize, Serialize)]
-pub(crate) struct Token {
-    pub token: String,
-}
+const RESET_TOKEN_HOURS: i64 = 2;
 
 #[derive(Deserialize, Serialize)]
 pub(crate) struct Login {
@@ -28,45 +34,49 @@ pub async fn login(
     State(state): State<AppState>,
     crypto: Extension<Crypto>,
     Json(request): Json<Login>,
-) -> Result<Json<Token>, HttpError> {
+) -> Result<Json<LoginResult>, HttpError> {
     const INVALID_CREDENTIALS: HttpError = HttpError {
         code: StatusCode::FORBIDDEN,
         error: "invalid_credentials",
         context: None,
     };
 
-    let Some((user_id, login_kind, password, user_state)) = users::table
+    let mut conn = state.pool.get().await?;
+
+    let user = users::table
         .filter(users::email.eq(&request.email))
         .select((users::id, users::login_kind, users::password, users::state))
-        .first::<(i32, LoginKind, Option<String>, UserState)>(&mut state.pool.get().await?)
+        .first::<(i32, LoginKind, Option<String>, UserState)>(&mut conn)
         .await
-        .optional()?
-    else {
-        return Err(INVALID_CREDENTIALS);
-    };
+        .optional()?;
 
-    match login_kind {
-        LoginKind::Email => {}
-        _ => return Err(HttpError::conflict("user_not_created_with_email")),
-    }
-
-    let Some(password) = password else {
-        return Err(HttpError::internal_server_error(
-            "email_login_kind_has_null_password",
-        ));
+    // Unknown users and invited ones, which do not have a password until they accept the
+    // invitation, are verified against a dummy hash so that they take as long as the others
+    let (user, stored) = match user {
+        Some((user_id, LoginKind::Email, Some(password), user_state)) => {
+            (Some((user_id, user_state)), password)
+        }
+        Some((_, LoginKind::Email, None, _)) | None => (None, password::DUMMY_HASH.to_string()),
+        Some(_) => return Err(HttpError::conflict("user_not_created_with_email")),
     };
 
-    let Some((salt, password)) = password.split_at_checked(SALT_LENGTH) else {
-        return Err(HttpError::internal_server_error(
-            "invalid_password_and_salt",
-        ));
+    let (verified, stored) = {
+        let crypto = crypto.0.clone();
+        let password = request.password.clone();
+
+        spawn_blocking(move || (password::verify(&crypto, &password, &stored), stored))
+            .await
+            .unwrap()
     };
 
-    // TODO use verify
-    if crypto.sign512(format!("{salt}{}", request.password).as_bytes()) != password {
+    if !verified.map_err(HttpError::internal_server_error)? {
         return Err(INVALID_CREDENTIALS);
     }
 
+    let Some((user_id, user_state)) = user else {
+        return Err(INVALID_CREDENTIALS);
+    };
+
     if UserState::Enabled != user_state {
         return Err(HttpError {
             code: StatusCode::FORBIDDEN,
@@ -75,7 +85,180 @@ pub async fn login(
         });
     }
 
-    Ok(Json(Token {
-        token: crypto.encode(&base::middlewares::auth::Token::new(user_id))?,
-    }))
+    // Password is known only at this point, hashes of a legacy scheme or with outdated parameters
+    // are upgraded transparently
+    if password::needs_rehash(&stored) {
+        let rehashed = spawn_blocking(move || password::hash(&request.password))
+            .await
+            .unwrap();
+
+        if let Err(e) = diesel::update(users::table)
+            .filter(users::id.eq(user_id))
+            .filter(users::password.eq(&stored))
+            .set(users::password.eq(rehashed))
+            .execute(&mut conn)
+            .await
+        {
+            log::warn!("Failed to upgrade password hash of user {user_id}, {e:?}");
+        }
+    }
+
+    login_user(&mut conn, &crypto, user_id).await.map(Json)
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RequestPasswordReset {
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SetPassword {
+    pub token: String,
+    pub password: String,
+}
+
+impl Validate for SetPassword {
+    fn validate(&self) -> Result<(), Errors> {
+        let mut errors = Errors::new();
+
+        if self.password.chars().count() < 8 {
+            errors.insert_field("password", "at_least_8_chars");
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(())
+    }
+}
+
+/// Sends a password reset link to the user. It responds the same whether a user with given email
+/// exists or not, so that it cannot be used to find out registered emails. The mail is sent in the
+/// background for the same reason, otherwise sending it would make the response of existing users
+/// slower.
+pub async fn request_password_reset(
+    State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    Extension(mailer): Extension<Mailer>,
+    Extension(options): Extension<Options>,
+    Json(request): Json<RequestPasswordReset>,
+) -> Result<(), HttpError> {
+    let Some((user_id, name, password)) = users::table
+        .filter(users::email.eq(&request.email))
+        .filter(users::login_kind.eq(LoginKind::Email))
+        .filter(users::state.eq(UserState::Enabled))
+        .select((users::id, users::name, users::password))
+        .first::<(i32, String, Option<String>)>(&mut state.pool.get().await?)
+        .await
+        .optional()?
+    else {
+        return Ok(());
+    };
+
+    let token = PasswordToken::issue(
+        &crypto,
+        user_id,
+        TokenPurpose::Reset,
+        password.as_deref(),
+        Utc::now().timestamp() + RESET_TOKEN_HOURS * 60 * 60,
+    )?;
+
+    let mut url = state.config.app_url.clone();
+
+    url.path_segments_mut()
+        .unwrap()
+        .pop_if_empty()
+        .extend(["auth", "reset-password"]);
+
+    url.query_pairs_mut().append_pair("token", &token);
+
+    let locale = options.default_locale();
+
+    spawn(async move {
+        if let Err(e) = mailer
+            .send(
+                &request.email,
+                &locale,
+                Template::PasswordReset {
+                    name: &name,
+                    url: url.as_str(),
+                    hours: RESET_TOKEN_HOURS,
+                },
+            )
+            .await
+        {
+            log::error!("Failed to send password reset mail to user {user_id}, {e:?}");
+        }
+    })
+    .await;
+
+    Ok(())
+}
+
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    Valid(Json(request)): Valid<Json<SetPassword>>,
+) -> Result<(), HttpError> {
+    set_password_with_token(&state, &crypto, request, TokenPurpose::Reset).await
+}
+
+pub async fn accept_invitation(
+    State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    Valid(Json(request)): Valid<Json<SetPassword>>,
+) -> Result<(), HttpError> {
+    set_password_with_token(&state, &crypto, request, TokenPurpose::Invitation).await
+}
+
+async fn set_password_with_token(
+    state: &AppState,
+    crypto: &Crypto,
+    request: SetPassword,
+    purpose: TokenPurpose,
+) -> Result<(), HttpError> {
+    let token = PasswordToken::decode(crypto, &request.token, purpose).ok_or(INVALID_TOKEN)?;
+
+    let mut conn = state.pool.get().await?;
+
+    let Some(current) = users::table
+        .filter(users::id.eq(token.id))
+        .filter(users::login_kind.eq(LoginKind::Email))
+        .select(users::password)
+        .first::<Option<String>>(&mut conn)
+        .await
+        .optional()?
+    else {
+        return Err(INVALID_TOKEN);
+    };
+
+    if !token.is_current(crypto, current.as_deref()) {
+        return Err(INVALID_TOKEN);
+    }
+
+    let query = diesel::update(users::table)
+        .filter(users::id.eq(token.id))
+        .into_boxed();
+
+    // Only update if the password is still the same, a concurrent request may have used the token
+    let query = match current {
+        Some(current) => query.filter(users::password.eq(current)),
+        None => query.filter(users::password.is_null()),
+    };
+
+    let hash = spawn_blocking(move || password::hash(&request.password))
+        .await
+        .unwrap();
+
+    let effected_row = query
+        .set(users::password.eq(hash))
+        .execute(&mut conn)
+        .await?;
+
+    if effected_row == 0 {
+        return Err(INVALID_TOKEN);
+    }
+
+    Ok(())
 }
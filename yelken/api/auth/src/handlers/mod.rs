@@ -1,5 +1,67 @@
 #[cfg(feature = "email")]
 pub mod email;
 
+#[cfg(any(feature = "email", feature = "oauth", feature = "oidc"))]
+pub mod two_factor;
+
 #[cfg(feature = "oauth")]
 pub mod oauth;
+
+#[cfg(feature = "oidc")]
+pub mod oidc;
+
+pub mod session;
+
+#[cfg(any(feature = "email", feature = "oauth", feature = "oidc"))]
+pub(crate) const INVALID_TOKEN: base::responses::HttpError = base::responses::HttpError {
+    code: axum::http::StatusCode::FORBIDDEN,
+    error: "invalid_token",
+    context: None,
+};
+
+#[cfg(any(feature = "oauth", feature = "oidc"))]
+fn generate_username(name: &str) -> String {
+    use rand::{distr::Alphanumeric, rng, Rng};
+
+    name.chars()
+        .filter(|ch| ch.is_ascii_alphanumeric())
+        .collect::<String>()
+        + "_"
+        + (0..12)
+            .map(|_| rng().sample(Alphanumeric) as char)
+            .collect::<String>()
+            .as_str()
+}
+
+/// Url of the app page completing a login through a provider, carrying either the session tokens or
+/// the two-factor challenge the user needs to pass.
+#[cfg(any(feature = "oauth", feature = "oidc"))]
+fn login_redirect_url(
+    app_url: &url::Url,
+    result: two_factor::LoginResult,
+    client_state: &str,
+) -> String {
+    let params = match result {
+        two_factor::LoginResult::Session(tokens) => [
+            ("token", tokens.token),
+            ("refresh_token", tokens.refresh_token),
+        ],
+        two_factor::LoginResult::Challenge {
+            challenge_token,
+            challenge,
+        } => [
+            ("challenge_token", challenge_token),
+            ("challenge", challenge.as_str().to_string()),
+        ],
+    };
+
+    url::Url::parse_with_params(
+        &format!("{app_url}/auth/oauth/login"),
+        params
+            .iter()
+            .map(|(key, value)| (*key, value.as_str()))
+            .chain([("state", client_state)]),
+    )
+    .unwrap()
+    .into()
+}
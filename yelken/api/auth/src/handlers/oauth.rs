@@ -19,6 +19,8 @@ use rand::{distr::Alphanumeric, rng, Rng};
 use serde::Deserialize;
 use url::Url;
 
+use super::{generate_username, login_redirect_url, two_factor::login_user};
+
 pub(crate) struct OauthProviderConfig {
     pub redirect_endpoint: String,
     pub token_endpoint: String,
@@ -73,19 +75,6 @@ pub struct RedirectOauthProvider {
 
 const STATE_LENGTH: usize = 32;
 
-fn generate_username(name: &str) -> String {
-    use rand::{distr::Alphanumeric, rng, Rng};
-
-    name.chars()
-        .filter(|ch| ch.is_ascii_alphanumeric())
-        .collect::<String>()
-        + "_"
-        + (0..12)
-            .map(|_| rng().sample(Alphanumeric) as char)
-            .collect::<String>()
-            .as_str()
-}
-
 pub async fn redirect_oauth_provider(
     State(state): State<AppState>,
     crypto: Extension<Crypto>,
@@ -295,14 +284,9 @@ pub async fn cloud_oauth(
         }
     };
 
-    let token = crypto.encode(&base::middlewares::auth::Token::new(user_id))?;
+    let result = login_user(&mut conn, &crypto, user_id).await?;
 
-    let location = Url::parse_with_params(
-        &format!("{}/auth/oauth/login", state.config.app_url),
-        &[("token", token.as_str()), ("state", client_state)],
-    )
-    .unwrap()
-    .into();
+    let location = login_redirect_url(&state.config.app_url, result, client_state);
 
     Ok((StatusCode::FOUND, [(header::LOCATION, location)]))
 }
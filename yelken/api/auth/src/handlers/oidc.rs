@@ -0,0 +1,281 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderName, StatusCode},
+    Extension,
+};
+use base::{
+    crypto::Crypto,
+    models::{LoginKind, UserState},
+    responses::HttpError,
+    schema::{roles, user_identities, users},
+    session, AppState,
+};
+use diesel::prelude::*;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use rand::{distr::Alphanumeric, rng, Rng};
+use serde::Deserialize;
+use url::Url;
+
+use crate::oidc::{Claims, OidcConfig, Provider};
+
+use super::{generate_username, login_redirect_url, two_factor::login_user};
+
+const STATE_LENGTH: usize = 32;
+
+#[derive(Deserialize)]
+pub struct RedirectOidcProvider {
+    state: String,
+}
+
+#[derive(Deserialize)]
+pub struct OidcProviderResponse {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+fn provider<'a>(config: &'a OidcConfig, key: &str) -> Result<&'a Provider, HttpError> {
+    config
+        .provider(key)
+        .ok_or(HttpError::not_found("unknown_oidc_provider"))
+}
+
+fn callback_uri(state: &AppState, provider: &str) -> Url {
+    let mut redirect_uri = state.config.site_url.clone();
+    redirect_uri
+        .path_segments_mut()
+        .unwrap()
+        .pop_if_empty()
+        .extend(["api", "auth", "oidc", provider, "callback"]);
+
+    redirect_uri
+}
+
+/// PKCE verifier and nonce are derived from the state, so that they do not need to be stored
+/// anywhere between the redirect and the callback.
+fn derive_secret(crypto: &Crypto, purpose: &str, oauth_state: &str) -> String {
+    crypto
+        .sign512(format!("{purpose}:{oauth_state}").as_bytes())
+        .chars()
+        .filter(|ch| *ch != '=')
+        .map(|ch| match ch {
+            '+' => '-',
+            '/' => '_',
+            ch => ch,
+        })
+        .collect()
+}
+
+pub async fn redirect_oidc_provider(
+    State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    Extension(oidc_config): Extension<OidcConfig>,
+    Path(provider_key): Path<String>,
+    Query(query): Query<RedirectOidcProvider>,
+) -> Result<(StatusCode, [(HeaderName, String); 1]), HttpError> {
+    let provider = provider(&oidc_config, &provider_key)?;
+
+    if query.state.len() != STATE_LENGTH {
+        return Err(HttpError::unprocessable_entity("invalid_state_len"));
+    }
+
+    let client_state = query.state;
+    // Server state is appended to client state as salt
+    let server_state: String = (0..STATE_LENGTH)
+        .map(|_| rng().sample(Alphanumeric) as char)
+        .collect();
+    let oauth_state = client_state + server_state.as_str();
+
+    // State is bound to the provider so that it cannot be replayed against another one
+    let oauth_state_hash = crypto.sign512(format!("{provider_key}:{oauth_state}").as_bytes());
+
+    let location = provider
+        .authorization_url(
+            callback_uri(&state, &provider_key).as_str(),
+            &format!("{oauth_state}{oauth_state_hash}"),
+            &derive_secret(&crypto, "nonce", &oauth_state),
+            &derive_secret(&crypto, "pkce", &oauth_state),
+        )
+        .await
+        .map_err(|e| {
+            HttpError::internal_server_error("oidc_discovery_failed").with_context(format!("{e:?}"))
+        })?
+        .into();
+
+    Ok((StatusCode::FOUND, [(header::LOCATION, location)]))
+}
+
+pub async fn oidc_callback(
+    State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    Extension(oidc_config): Extension<OidcConfig>,
+    Path(provider_key): Path<String>,
+    Query(query): Query<OidcProviderResponse>,
+) -> Result<(StatusCode, [(HeaderName, String); 1]), HttpError> {
+    let provider = provider(&oidc_config, &provider_key)?;
+
+    if let Some(error) = query.error {
+        return Err(HttpError::unprocessable_entity("oauth_failed").with_context(error));
+    }
+
+    let (Some(code), Some(oauth_state)) = (query.code, query.state) else {
+        return Err(HttpError::unprocessable_entity("code_or_state_not_found"));
+    };
+
+    let Some((oauth_state, oauth_state_hash)) =
+        oauth_state.split_at_checked(STATE_LENGTH + STATE_LENGTH)
+    else {
+        return Err(HttpError::unprocessable_entity("invalid_state"));
+    };
+
+    if !crypto.verify512(
+        format!("{provider_key}:{oauth_state}").as_bytes(),
+        oauth_state_hash,
+    ) {
+        return Err(HttpError::unprocessable_entity("invalid_state"));
+    }
+
+    let (client_state, _) = oauth_state.split_at(STATE_LENGTH);
+
+    let claims = provider
+        .exchange(
+            &code,
+            callback_uri(&state, &provider_key).as_str(),
+            &derive_secret(&crypto, "pkce", oauth_state),
+            &derive_secret(&crypto, "nonce", oauth_state),
+        )
+        .await
+        .map_err(|e| {
+            HttpError::unprocessable_entity("invalid_id_token").with_context(format!("{e:?}"))
+        })?;
+
+    let mut conn = state.pool.get().await?;
+
+    // Roles are only synced if the provider is configured to carry them
+    let role_id = match provider.config.role_claim {
+        Some(_) => match provider.config.map_role(&claims) {
+            Some(key) => {
+                let role_id = roles::table
+                    .filter(roles::key.eq(key))
+                    .select(roles::id)
+                    .first::<i32>(&mut conn)
+                    .await
+                    .optional()?;
+
+                if role_id.is_none() {
+                    log::warn!(
+                        "Role {key} mapped from OIDC provider {provider_key} does not exist"
+                    );
+                }
+
+                Some(role_id)
+            }
+            None => Some(None),
+        },
+        None => None,
+    };
+
+    let user_id = match user_identities::table
+        .inner_join(users::table)
+        .filter(user_identities::provider.eq(&provider_key))
+        .filter(user_identities::subject.eq(&claims.sub))
+        .select((users::id, users::email, users::state, users::role_id))
+        .first::<(i32, String, UserState, Option<i32>)>(&mut conn)
+        .await
+        .optional()?
+    {
+        Some((_, _, UserState::Disabled, _)) => {
+            return Err(HttpError::forbidden("user_not_enabled"));
+        }
+        Some((user_id, email, UserState::Enabled, current_role_id)) => {
+            // Email can change at the provider, keep it in sync
+            if let Some(new_email) = claims.email.as_ref().filter(|new| **new != email) {
+                if let Err(e) = diesel::update(users::table)
+                    .filter(users::id.eq(user_id))
+                    .set(users::email.eq(new_email))
+                    .execute(&mut conn)
+                    .await
+                {
+                    log::error!("Failed to update email of existing oidc user with id {user_id} where new email is {new_email}, {e:?}");
+                }
+            }
+
+            if let Some(role_id) = role_id.filter(|role_id| *role_id != current_role_id) {
+                diesel::update(users::table)
+                    .filter(users::id.eq(user_id))
+                    .set(users::role_id.eq(role_id))
+                    .execute(&mut conn)
+                    .await?;
+
+                // Sessions opened with the previous role must not keep its permissions
+                session::revoke_all(&mut conn, user_id).await?;
+            }
+
+            user_id
+        }
+        None => create_user(&mut conn, &provider_key, claims, role_id.flatten()).await?,
+    };
+
+    let result = login_user(&mut conn, &crypto, user_id).await?;
+
+    let location = login_redirect_url(&state.config.app_url, result, client_state);
+
+    Ok((StatusCode::FOUND, [(header::LOCATION, location)]))
+}
+
+async fn create_user(
+    conn: &mut base::db::Connection,
+    provider_key: &str,
+    claims: Claims,
+    role_id: Option<i32>,
+) -> Result<i32, HttpError> {
+    let Some(email) = claims.email else {
+        return Err(HttpError::unprocessable_entity("email_claim_not_found"));
+    };
+
+    let name = claims
+        .name
+        .or(claims.preferred_username)
+        .unwrap_or_else(|| email.split('@').next().unwrap_or_default().to_string());
+
+    conn.transaction(|conn| {
+        async move {
+            let email_used = diesel::select(diesel::dsl::exists(
+                users::table.filter(users::email.eq(&email)),
+            ))
+            .get_result::<bool>(conn)
+            .await?;
+
+            // Linking to an existing account is not done implicitly, as the provider may not have
+            // verified the email
+            if email_used {
+                return Err(HttpError::conflict("email_already_used"));
+            }
+
+            let user_id = diesel::insert_into(users::table)
+                .values((
+                    users::role_id.eq(role_id),
+                    users::username.eq(generate_username(&name)),
+                    users::name.eq(&name),
+                    users::email.eq(&email),
+                    users::login_kind.eq(LoginKind::Oidc),
+                ))
+                .returning(users::id)
+                .get_result::<i32>(conn)
+                .await?;
+
+            diesel::insert_into(user_identities::table)
+                .values((
+                    user_identities::user_id.eq(user_id),
+                    user_identities::provider.eq(provider_key),
+                    user_identities::subject.eq(&claims.sub),
+                ))
+                .execute(conn)
+                .await?;
+
+            Ok(user_id)
+        }
+        .scope_boxed()
+    })
+    .await
+}
@@ -0,0 +1,53 @@
+use axum::{extract::State, Extension, Json};
+use base::{
+    crypto::Crypto,
+    middlewares::auth::AuthUser,
+    responses::HttpError,
+    session::{self, SessionTokens},
+    AppState,
+};
+use chrono::Utc;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Refresh {
+    pub refresh_token: String,
+}
+
+pub async fn refresh(
+    State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    Json(request): Json<Refresh>,
+) -> Result<Json<SessionTokens>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    session::refresh(
+        &mut conn,
+        &crypto,
+        &request.refresh_token,
+        Utc::now().naive_utc(),
+    )
+    .await
+    .map(Json)
+}
+
+pub async fn logout(State(state): State<AppState>, user: AuthUser) -> Result<(), HttpError> {
+    let session_id = user.require_session()?;
+
+    let mut conn = state.pool.get().await?;
+
+    session::revoke(&mut conn, session_id).await?;
+
+    Ok(())
+}
+
+pub async fn logout_all(State(state): State<AppState>, user: AuthUser) -> Result<(), HttpError> {
+    user.require_session()?;
+
+    let mut conn = state.pool.get().await?;
+
+    session::revoke_all(&mut conn, user.id).await?;
+
+    Ok(())
+}
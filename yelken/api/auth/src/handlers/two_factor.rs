@@ -0,0 +1,252 @@
+use axum::{extract::State, Extension, Json};
+use base::{
+    crypto::Crypto,
+    db::Connection,
+    middlewares::auth::AuthUser,
+    responses::HttpError,
+    schema::users,
+    session::{self, SessionTokens},
+    two_factor::{self, ChallengeKind, ChallengeToken, Enrollment, INVALID_CODE},
+    AppState,
+};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+
+use super::INVALID_TOKEN;
+
+/// Result of the first step of the login. Users with two-factor authentication, or whose role
+/// requires it, get a challenge token that is exchanged for a session in the second step.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub(crate) enum LoginResult {
+    Session(SessionTokens),
+    #[serde(rename_all = "camelCase")]
+    Challenge {
+        challenge_token: String,
+        challenge: ChallengeKind,
+    },
+}
+
+/// Logs in the user whose identity is verified by a password or a provider. A session is created
+/// unless the user needs to pass a two-factor challenge first.
+pub(crate) async fn login_user(
+    conn: &mut Connection,
+    crypto: &Crypto,
+    user_id: i32,
+) -> Result<LoginResult, HttpError> {
+    let challenge = if two_factor::is_enabled(conn, user_id).await? {
+        Some(ChallengeKind::Verify)
+    } else if two_factor::is_required(conn, user_id).await? {
+        Some(ChallengeKind::Enroll)
+    } else {
+        None
+    };
+
+    if let Some(challenge) = challenge {
+        return Ok(LoginResult::Challenge {
+            challenge_token: ChallengeToken::issue(
+                crypto,
+                user_id,
+                challenge,
+                Utc::now().timestamp(),
+            )?,
+            challenge,
+        });
+    }
+
+    session::create(conn, crypto, user_id, Utc::now().naive_utc())
+        .await
+        .map(LoginResult::Session)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Challenge {
+    pub challenge_token: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChallengeResponse {
+    pub challenge_token: String,
+    pub code: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Code {
+    pub code: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EnrolledLogin {
+    #[serde(flatten)]
+    pub session: SessionTokens,
+    pub recovery_codes: Vec<String>,
+}
+
+async fn start_enrollment(state: &AppState, user_id: i32) -> Result<Enrollment, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let email = users::table
+        .filter(users::id.eq(user_id))
+        .select(users::email)
+        .first::<String>(&mut conn)
+        .await?;
+
+    let issuer = state.config.site_url.host_str().unwrap_or("Yelken");
+
+    two_factor::start_enrollment(&mut conn, user_id, issuer, &email).await
+}
+
+/// Completes the login of a user with two-factor authentication, given the challenge token returned
+/// by the password step and either a code from the authenticator app or a recovery code.
+pub async fn verify_login(
+    State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    Json(request): Json<ChallengeResponse>,
+) -> Result<Json<SessionTokens>, HttpError> {
+    let token = ChallengeToken::decode(&crypto, &request.challenge_token, ChallengeKind::Verify)
+        .ok_or(INVALID_TOKEN)?;
+
+    let mut conn = state.pool.get().await?;
+    let now = Utc::now().naive_utc();
+
+    if !two_factor::verify(&mut conn, &crypto, token.id, &request.code, now).await? {
+        return Err(INVALID_CODE);
+    }
+
+    session::create(&mut conn, &crypto, token.id, now)
+        .await
+        .map(Json)
+}
+
+/// Starts enrollment for a user whose role requires two-factor authentication but has not enabled
+/// it yet, so that the user can log in once it is confirmed.
+pub async fn enroll_login(
+    State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    Json(request): Json<Challenge>,
+) -> Result<Json<Enrollment>, HttpError> {
+    let token = ChallengeToken::decode(&crypto, &request.challenge_token, ChallengeKind::Enroll)
+        .ok_or(INVALID_TOKEN)?;
+
+    start_enrollment(&state, token.id).await.map(Json)
+}
+
+pub async fn confirm_login(
+    State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    Json(request): Json<ChallengeResponse>,
+) -> Result<Json<EnrolledLogin>, HttpError> {
+    let token = ChallengeToken::decode(&crypto, &request.challenge_token, ChallengeKind::Enroll)
+        .ok_or(INVALID_TOKEN)?;
+
+    let mut conn = state.pool.get().await?;
+    let now = Utc::now().naive_utc();
+
+    let recovery_codes =
+        two_factor::confirm_enrollment(&mut conn, &crypto, token.id, &request.code, now).await?;
+
+    Ok(Json(EnrolledLogin {
+        session: session::create(&mut conn, &crypto, token.id, now).await?,
+        recovery_codes,
+    }))
+}
+
+pub async fn enroll(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<Enrollment>, HttpError> {
+    user.require_session()?;
+
+    start_enrollment(&state, user.id).await.map(Json)
+}
+
+pub async fn confirm(
+    State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    user: AuthUser,
+    Json(request): Json<Code>,
+) -> Result<Json<RecoveryCodes>, HttpError> {
+    user.require_session()?;
+
+    let mut conn = state.pool.get().await?;
+
+    let recovery_codes = two_factor::confirm_enrollment(
+        &mut conn,
+        &crypto,
+        user.id,
+        &request.code,
+        Utc::now().naive_utc(),
+    )
+    .await?;
+
+    Ok(Json(RecoveryCodes { recovery_codes }))
+}
+
+pub async fn disable(
+    State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    user: AuthUser,
+    Json(request): Json<Code>,
+) -> Result<(), HttpError> {
+    user.require_session()?;
+
+    let mut conn = state.pool.get().await?;
+
+    if two_factor::is_required(&mut conn, user.id).await? {
+        return Err(HttpError::conflict("two_factor_required_by_role"));
+    }
+
+    if !two_factor::verify(
+        &mut conn,
+        &crypto,
+        user.id,
+        &request.code,
+        Utc::now().naive_utc(),
+    )
+    .await?
+    {
+        return Err(INVALID_CODE);
+    }
+
+    two_factor::disable(&mut conn, user.id).await?;
+
+    Ok(())
+}
+
+pub async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    user: AuthUser,
+    Json(request): Json<Code>,
+) -> Result<Json<RecoveryCodes>, HttpError> {
+    user.require_session()?;
+
+    let mut conn = state.pool.get().await?;
+
+    if !two_factor::verify(
+        &mut conn,
+        &crypto,
+        user.id,
+        &request.code,
+        Utc::now().naive_utc(),
+    )
+    .await?
+    {
+        return Err(INVALID_CODE);
+    }
+
+    let recovery_codes = two_factor::regenerate_recovery_codes(&mut conn, &crypto, user.id).await?;
+
+    Ok(Json(RecoveryCodes { recovery_codes }))
+}
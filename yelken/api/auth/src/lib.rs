@@ -1,17 +1,55 @@
-use axum::Router;
-use base::AppState;
+use axum::{middleware, routing::post, Router};
+use base::{middlewares::auth::from_token, AppState};
+use handlers::session;
 
 mod handlers;
+#[cfg(feature = "oidc")]
+pub mod oidc;
 
-pub fn router() -> Router<AppState> {
-    let router = Router::new();
+pub fn router(state: AppState) -> Router<AppState> {
+    let authenticated = Router::new()
+        .route("/logout", post(session::logout))
+        .route("/logout/all", post(session::logout_all))
+        .layer(middleware::from_fn_with_state(state.clone(), from_token));
+
+    let router = Router::new()
+        .route("/refresh", post(session::refresh))
+        .merge(authenticated);
+
+    // Logins through any method may need to pass a two-factor challenge
+    #[cfg(any(feature = "email", feature = "oauth", feature = "oidc"))]
+    let router = {
+        use handlers::two_factor;
+
+        let two_factor_router = Router::new()
+            .route("/enroll", post(two_factor::enroll))
+            .route("/confirm", post(two_factor::confirm))
+            .route("/disable", post(two_factor::disable))
+            .route(
+                "/recovery-codes",
+                post(two_factor::regenerate_recovery_codes),
+            )
+            .layer(middleware::from_fn_with_state(state, from_token));
+
+        router
+            .route("/login/two-factor", post(two_factor::verify_login))
+            .route("/login/two-factor/enroll", post(two_factor::enroll_login))
+            .route("/login/two-factor/confirm", post(two_factor::confirm_login))
+            .nest("/two-factor", two_factor_router)
+    };
 
     #[cfg(feature = "email")]
     let router = {
-        use axum::routing::post;
         use handlers::email;
 
-        router.route("/login", post(email::login))
+        router
+            .route("/login", post(email::login))
+            .route(
+                "/password/reset/request",
+                post(email::request_password_reset),
+            )
+            .route("/password/reset/confirm", post(email::reset_password))
+            .route("/invitation/accept", post(email::accept_invitation))
     };
 
     #[cfg(feature = "oauth")]
@@ -27,5 +65,21 @@ pub fn router() -> Router<AppState> {
             .layer(Extension(auth_config))
     };
 
+    #[cfg(feature = "oidc")]
+    let router = {
+        use axum::{routing::get, Extension};
+        use handlers::oidc;
+
+        let oidc_config = crate::oidc::OidcConfig::from_env().unwrap();
+
+        router
+            .route(
+                "/oidc/{provider}/redirect",
+                get(oidc::redirect_oidc_provider),
+            )
+            .route("/oidc/{provider}/callback", get(oidc::oidc_callback))
+            .layer(Extension(oidc_config))
+    };
+
     router
 }
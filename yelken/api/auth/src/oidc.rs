@@ -0,0 +1,662 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::{anyhow, Context, Result};
+use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
+use jsonwebtoken::{
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk},
+    Algorithm, DecodingKey, Validation,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use url::Url;
+
+/// Settings of an OpenID Connect provider, read from env as `YELKEN_OIDC_<KEY>_*` variables where
+/// the keys of providers are listed in `YELKEN_OIDC_PROVIDERS`.
+pub struct ProviderConfig {
+    pub key: String,
+    /// Issuer identifier, discovery document is fetched from
+    /// `<issuer>/.well-known/openid-configuration`.
+    pub issuer: String,
+    pub client_id: String,
+    /// Public clients, which rely on PKCE only, do not have a secret.
+    pub client_secret: Option<String>,
+    pub scopes: String,
+    /// Path of the claim that carries roles of the user in the ID token, with nested objects
+    /// separated by dots, e.g. `realm_access.roles`.
+    pub role_claim: Option<String>,
+    /// Pairs of claim value and role key, checked in order.
+    pub role_map: Vec<(String, String)>,
+}
+
+impl ProviderConfig {
+    fn from_env(key: &str) -> Result<Self> {
+        let prefix = format!(
+            "YELKEN_OIDC_{}_",
+            key.to_ascii_uppercase().replace('-', "_")
+        );
+        let var = |name: &str| std::env::var(format!("{prefix}{name}")).ok();
+        let required =
+            |name: &str| var(name).with_context(|| format!("{prefix}{name} is not defined"));
+
+        let role_map = var("ROLE_MAP")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|pair| !pair.trim().is_empty())
+            .map(|pair| {
+                pair.split_once('=')
+                    .map(|(value, role)| (value.trim().to_string(), role.trim().to_string()))
+                    .with_context(|| format!("Invalid {prefix}ROLE_MAP entry {pair}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            key: key.to_string(),
+            issuer: required("ISSUER")?,
+            client_id: required("CLIENT_ID")?,
+            client_secret: var("CLIENT_SECRET"),
+            scopes: var("SCOPES").unwrap_or_else(|| "openid email profile".to_string()),
+            role_claim: var("ROLE_CLAIM"),
+            role_map,
+        })
+    }
+
+    /// Returns the key of the first role in [`ProviderConfig::role_map`] whose value is present in
+    /// the role claim.
+    pub fn map_role(&self, claims: &Claims) -> Option<&str> {
+        let claim = claims.claim(self.role_claim.as_deref()?)?;
+
+        let values: Vec<&str> = match claim {
+            Value::String(value) => vec![value.as_str()],
+            Value::Array(values) => values.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+
+        self.role_map
+            .iter()
+            .find(|(value, _)| values.contains(&value.as_str()))
+            .map(|(_, role)| role.as_str())
+    }
+}
+
+#[derive(Clone, Deserialize)]
+pub struct Metadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+    #[serde(default)]
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>,
+    nonce: Option<String>,
+    azp: Option<String>,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+impl Claims {
+    /// Returns the claim at the given path, nested objects are separated by dots.
+    pub fn claim(&self, path: &str) -> Option<&Value> {
+        let mut segments = path.split('.');
+
+        let first = self.extra.get(segments.next()?)?;
+
+        segments.try_fold(first, |value, segment| value.get(segment))
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+pub struct Provider {
+    pub config: ProviderConfig,
+    client: reqwest::Client,
+    metadata: RwLock<Option<Arc<Metadata>>>,
+    keys: RwLock<Arc<[Jwk]>>,
+}
+
+impl Provider {
+    pub fn new(config: ProviderConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            metadata: RwLock::new(None),
+            keys: RwLock::new(Arc::new([])),
+        }
+    }
+
+    /// Returns the discovery document of the provider, which is fetched once and cached.
+    pub async fn metadata(&self) -> Result<Arc<Metadata>> {
+        if let Some(metadata) = self.metadata.read().unwrap().as_ref() {
+            return Ok(metadata.clone());
+        }
+
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            self.config.issuer.trim_end_matches('/')
+        );
+
+        let metadata: Metadata = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Invalid discovery document")?;
+
+        if metadata.issuer.trim_end_matches('/') != self.config.issuer.trim_end_matches('/') {
+            return Err(anyhow!(
+                "Issuer {} in discovery document does not match the configured one",
+                metadata.issuer
+            ));
+        }
+
+        let metadata = Arc::new(metadata);
+
+        *self.metadata.write().unwrap() = Some(metadata.clone());
+
+        Ok(metadata)
+    }
+
+    pub async fn authorization_url(
+        &self,
+        redirect_uri: &str,
+        state: &str,
+        nonce: &str,
+        code_verifier: &str,
+    ) -> Result<Url> {
+        let metadata = self.metadata().await?;
+
+        Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", self.config.client_id.as_str()),
+                ("redirect_uri", redirect_uri),
+                ("scope", self.config.scopes.as_str()),
+                ("state", state),
+                ("nonce", nonce),
+                ("code_challenge", code_challenge(code_verifier).as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .context("Invalid authorization endpoint")
+    }
+
+    /// Exchanges the authorization code for tokens and returns the claims of the verified ID token.
+    pub async fn exchange(
+        &self,
+        code: &str,
+        redirect_uri: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<Claims> {
+        let metadata = self.metadata().await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect_uri),
+            ("code_verifier", code_verifier),
+        ];
+
+        let mut request = self.client.post(&metadata.token_endpoint);
+
+        match &self.config.client_secret {
+            Some(secret)
+                if metadata
+                    .token_endpoint_auth_methods_supported
+                    .iter()
+                    .any(|method| method == "client_secret_post")
+                    && !metadata
+                        .token_endpoint_auth_methods_supported
+                        .iter()
+                        .any(|method| method == "client_secret_basic") =>
+            {
+                form.push(("client_id", &self.config.client_id));
+                form.push(("client_secret", secret));
+            }
+            Some(secret) => {
+                request =
+                    request.basic_auth(urlencode(&self.config.client_id), Some(urlencode(secret)));
+            }
+            None => form.push(("client_id", &self.config.client_id)),
+        }
+
+        let response = request.form(&form).send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+
+            return Err(anyhow!("Token request failed with {status}, {body}"));
+        }
+
+        let tokens: TokenResponse = response.json().await.context("Invalid token response")?;
+
+        self.verify_id_token(&metadata, &tokens.id_token, nonce)
+            .await
+    }
+
+    async fn verify_id_token(
+        &self,
+        metadata: &Metadata,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<Claims> {
+        let header = jsonwebtoken::decode_header(id_token)?;
+
+        // Algorithms are pinned by the keys the token is verified with, not by its header
+        let mut validation = Validation::default();
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims = self
+            .decode_with_keys(metadata, id_token, header.kid.as_deref(), &validation)
+            .await?;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(anyhow!("Nonce of ID token does not match"));
+        }
+
+        if claims
+            .azp
+            .as_ref()
+            .is_some_and(|azp| azp != &self.config.client_id)
+        {
+            return Err(anyhow!("ID token is issued for another party"));
+        }
+
+        Ok(claims)
+    }
+
+    /// Verifies the token with the signing keys of the provider. Keys are fetched again once if none
+    /// of the cached ones matches, as providers rotate their keys.
+    async fn decode_with_keys(
+        &self,
+        metadata: &Metadata,
+        id_token: &str,
+        kid: Option<&str>,
+        validation: &Validation,
+    ) -> Result<Claims> {
+        let decode = |keys: &[Jwk]| -> Option<jsonwebtoken::errors::Result<Claims>> {
+            keys.iter()
+                .filter(|key| kid.is_none() || key.common.key_id.as_deref() == kid)
+                .filter_map(|key| {
+                    let algorithms = key_algorithms(key);
+
+                    if algorithms.is_empty() {
+                        return None;
+                    }
+
+                    Some((DecodingKey::from_jwk(key).ok()?, algorithms))
+                })
+                .map(|(key, algorithms)| {
+                    let mut validation = validation.clone();
+                    validation.algorithms = algorithms;
+
+                    jsonwebtoken::decode::<Claims>(id_token, &key, &validation).map(|t| t.claims)
+                })
+                .reduce(|res, next| res.or(next))
+        };
+
+        let cached = self.keys.read().unwrap().clone();
+
+        if let Some(res) = decode(&cached) {
+            return Ok(res?);
+        }
+
+        let jwks: Value = self
+            .client
+            .get(&metadata.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Invalid JWKS document")?;
+
+        // Keys that are not supported or meant for encryption are skipped instead of failing the
+        // whole set
+        let keys: Arc<[Jwk]> = jwks
+            .get("keys")
+            .and_then(Value::as_array)
+            .context("JWKS document does not have keys")?
+            .iter()
+            .filter(|key| key.get("use").and_then(Value::as_str) != Some("enc"))
+            .filter_map(|key| serde_json::from_value::<Jwk>(key.clone()).ok())
+            .collect();
+
+        *self.keys.write().unwrap() = keys.clone();
+
+        match decode(&keys) {
+            Some(res) => Ok(res?),
+            None => Err(anyhow!("No signing key found for ID token")),
+        }
+    }
+}
+
+/// Algorithms that tokens signed with the key may use. It is the algorithm of the key if the
+/// provider names one, otherwise the ones matching the type of the key. Symmetric keys are never
+/// used, so that tokens signed with a shared secret are rejected.
+fn key_algorithms(key: &Jwk) -> Vec<Algorithm> {
+    if let Some(alg) = key.common.key_algorithm {
+        return alg
+            .to_string()
+            .parse::<Algorithm>()
+            .into_iter()
+            .filter(|alg| !matches!(alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512))
+            .collect();
+    }
+
+    match &key.algorithm {
+        AlgorithmParameters::RSA(_) => vec![
+            Algorithm::RS256,
+            Algorithm::RS384,
+            Algorithm::RS512,
+            Algorithm::PS256,
+            Algorithm::PS384,
+            Algorithm::PS512,
+        ],
+        AlgorithmParameters::EllipticCurve(params) => match params.curve {
+            EllipticCurve::P256 => vec![Algorithm::ES256],
+            EllipticCurve::P384 => vec![Algorithm::ES384],
+            _ => vec![],
+        },
+        AlgorithmParameters::OctetKeyPair(params) if params.curve == EllipticCurve::Ed25519 => {
+            vec![Algorithm::EdDSA]
+        }
+        _ => vec![],
+    }
+}
+
+fn urlencode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+pub fn code_challenge(code_verifier: &str) -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(ring::digest::digest(
+        &ring::digest::SHA256,
+        code_verifier.as_bytes(),
+    ))
+}
+
+#[derive(Clone)]
+pub struct OidcConfig(Arc<HashMap<String, Provider>>);
+
+impl OidcConfig {
+    pub fn new(providers: Vec<ProviderConfig>) -> Self {
+        Self(Arc::new(
+            providers
+                .into_iter()
+                .map(|config| (config.key.clone(), Provider::new(config)))
+                .collect(),
+        ))
+    }
+
+    /// Reads providers listed in `YELKEN_OIDC_PROVIDERS`, e.g. `keycloak,google`. No provider is
+    /// configured if it is not defined.
+    pub fn from_env() -> Result<Self> {
+        let providers = std::env::var("YELKEN_OIDC_PROVIDERS").unwrap_or_default();
+
+        providers
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(|key| {
+                if !key
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+                {
+                    return Err(anyhow!("Invalid OIDC provider key {key}"));
+                }
+
+                ProviderConfig::from_env(key)
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Self::new)
+    }
+
+    pub fn provider(&self, key: &str) -> Option<&Provider> {
+        self.0.get(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        extract::State,
+        routing::{get, post},
+        Form, Json, Router,
+    };
+    use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
+    use jsonwebtoken::{Algorithm, EncodingKey, Header};
+    use ring::{
+        rand::SystemRandom,
+        signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING},
+    };
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use super::{code_challenge, Provider, ProviderConfig};
+
+    const VERIFIER: &str = "a_code_verifier_that_is_long_enough_for_pkce_0123456789";
+
+    struct MockProvider {
+        issuer: String,
+        pkcs8: Vec<u8>,
+        public_key: Vec<u8>,
+    }
+
+    async fn discovery(State(mock): State<Arc<MockProvider>>) -> Json<Value> {
+        Json(json!({
+            "issuer": mock.issuer,
+            "authorization_endpoint": format!("{}/auth", mock.issuer),
+            "token_endpoint": format!("{}/token", mock.issuer),
+            "jwks_uri": format!("{}/jwks", mock.issuer),
+            "token_endpoint_auth_methods_supported": ["client_secret_post"],
+        }))
+    }
+
+    async fn jwks(State(mock): State<Arc<MockProvider>>) -> Json<Value> {
+        let (x, y) = mock.public_key[1..].split_at(32);
+
+        Json(json!({
+            "keys": [
+                { "kty": "RSA", "use": "enc", "kid": "enc", "alg": "RSA-OAEP", "n": "AQAB", "e": "AQAB" },
+                {
+                    "kty": "EC",
+                    "use": "sig",
+                    "kid": "sig",
+                    "alg": "ES256",
+                    "crv": "P-256",
+                    "x": BASE64_URL_SAFE_NO_PAD.encode(x),
+                    "y": BASE64_URL_SAFE_NO_PAD.encode(y),
+                },
+            ]
+        }))
+    }
+
+    async fn token(
+        State(mock): State<Arc<MockProvider>>,
+        Form(form): Form<Vec<(String, String)>>,
+    ) -> Result<Json<Value>, axum::http::StatusCode> {
+        let param = |name: &str| {
+            form.iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+
+        // The mock expects the challenge sent in the authorization request to be of VERIFIER
+        let expected_challenge = code_challenge(VERIFIER);
+
+        if param("code") != Some("code")
+            || param("client_secret") != Some("secret")
+            || param("code_verifier").map(code_challenge).as_deref()
+                != Some(expected_challenge.as_str())
+        {
+            return Err(axum::http::StatusCode::BAD_REQUEST);
+        }
+
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some("sig".to_string());
+
+        let now = chrono::Utc::now().timestamp();
+
+        let id_token = jsonwebtoken::encode(
+            &header,
+            &json!({
+                "iss": mock.issuer,
+                "aud": "yelken",
+                "sub": "f81d4fae-7dec-11d0-a765-00a0c91e6bf6",
+                "iat": now,
+                "exp": now + 60,
+                "nonce": "nonce",
+                "email": "john@example.com",
+                "name": "John",
+                "realm_access": { "roles": ["offline_access", "yelken-editor"] },
+            }),
+            &EncodingKey::from_ec_der(&mock.pkcs8),
+        )
+        .unwrap();
+
+        Ok(Json(json!({
+            "access_token": "access",
+            "token_type": "Bearer",
+            "id_token": id_token,
+        })))
+    }
+
+    async fn mock_provider() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+
+        let pkcs8 =
+            EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &SystemRandom::new())
+                .unwrap();
+        let key_pair = EcdsaKeyPair::from_pkcs8(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            pkcs8.as_ref(),
+            &SystemRandom::new(),
+        )
+        .unwrap();
+
+        let mock = Arc::new(MockProvider {
+            issuer: issuer.clone(),
+            pkcs8: pkcs8.as_ref().to_vec(),
+            public_key: key_pair.public_key().as_ref().to_vec(),
+        });
+
+        let app = Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/jwks", get(jwks))
+            .route("/token", post(token))
+            .with_state(mock);
+
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        issuer
+    }
+
+    fn provider(issuer: String) -> Provider {
+        Provider::new(ProviderConfig {
+            key: "keycloak".to_string(),
+            issuer,
+            client_id: "yelken".to_string(),
+            client_secret: Some("secret".to_string()),
+            scopes: "openid email profile".to_string(),
+            role_claim: Some("realm_access.roles".to_string()),
+            role_map: vec![
+                ("yelken-admin".to_string(), "admin".to_string()),
+                ("yelken-editor".to_string(), "editor".to_string()),
+            ],
+        })
+    }
+
+    #[tokio::test]
+    async fn it_verifies_id_token_and_maps_roles() {
+        let provider = provider(mock_provider().await);
+
+        let url = provider
+            .authorization_url("http://localhost/callback", "state", "nonce", VERIFIER)
+            .await
+            .unwrap();
+
+        assert!(url
+            .query_pairs()
+            .any(|(key, value)| key == "code_challenge" && value == code_challenge(VERIFIER)));
+
+        let claims = provider
+            .exchange("code", "http://localhost/callback", VERIFIER, "nonce")
+            .await
+            .unwrap();
+
+        assert_eq!("f81d4fae-7dec-11d0-a765-00a0c91e6bf6", claims.sub);
+        assert_eq!(Some("john@example.com"), claims.email.as_deref());
+        assert_eq!(Some("editor"), provider.config.map_role(&claims));
+    }
+
+    #[tokio::test]
+    async fn it_rejects_mismatching_nonce_and_verifier() {
+        let provider = provider(mock_provider().await);
+
+        assert!(provider
+            .exchange("code", "http://localhost/callback", VERIFIER, "other")
+            .await
+            .is_err());
+
+        assert!(provider
+            .exchange(
+                "code",
+                "http://localhost/callback",
+                "another_verifier",
+                "nonce"
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn it_rejects_id_tokens_signed_with_client_secret() {
+        let provider = provider(mock_provider().await);
+
+        let metadata = provider.metadata().await.unwrap();
+
+        let now = chrono::Utc::now().timestamp();
+
+        let id_token = jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &json!({
+                "iss": metadata.issuer,
+                "aud": "yelken",
+                "sub": "f81d4fae-7dec-11d0-a765-00a0c91e6bf6",
+                "iat": now,
+                "exp": now + 60,
+                "nonce": "nonce",
+            }),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+
+        assert!(provider
+            .verify_id_token(&metadata, &id_token, "nonce")
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,973 @@
+//! GraphQL schema generated out of the public models. Each model gets its own object type whose
+//! fields are typed by the kinds of its model fields. The schema is built lazily and rebuilt after
+//! models or themes change.
+//!
+//! Like the content delivery API, the endpoint is served without authentication, so models that
+//! are not public are left out of the schema. Their contents stay reachable only through the
+//! CMS API, which checks the permissions of the user.
+
+use std::{
+    cmp::max,
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use arc_swap::ArcSwapOption;
+use async_graphql::{
+    dynamic::{
+        Field, FieldFuture, FieldValue, InputValue, Object, ResolverContext, Schema, TypeRef,
+    },
+    ErrorExtensions, Value,
+};
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, Method},
+    middleware::Next,
+    response::Response,
+    Extension, Json,
+};
+use base::{
+    config::Options,
+    content::{query_contents, Filter, FilterOp, Order},
+    field::FieldKind,
+    paginate::CountStarOver,
+    responses::HttpError,
+    schema::{assets, content_values, contents, fields, model_fields, models},
+    AppState,
+};
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use crate::handlers::delivery::{
+    parse_filter, parse_order, query_error, resolve_locale, MAX_PER_PAGE,
+};
+
+const DEFAULT_PER_PAGE: i64 = 20;
+
+/// Names that cannot be used by types generated out of models.
+const RESERVED_TYPES: [&str; 7] = ["Query", "Asset", "Int", "Float", "String", "Boolean", "ID"];
+
+/// Handle to the generated schema, shared between the GraphQL endpoint and the model and theme
+/// handlers. Setup installs its theme before the router is built, so no schema exists yet to
+/// invalidate at that point.
+#[derive(Clone, Default)]
+pub struct GraphQL(Arc<Inner>);
+
+#[derive(Default)]
+struct Inner {
+    generation: AtomicU64,
+    built: ArcSwapOption<Built>,
+}
+
+struct Built {
+    theme: Arc<str>,
+    generation: u64,
+    schema: Schema,
+}
+
+impl GraphQL {
+    async fn schema(&self, state: &AppState, theme: Arc<str>) -> Result<Schema, HttpError> {
+        let generation = AtomicU64::load(&self.0.generation, Ordering::Acquire);
+
+        if let Some(built) = self.0.built.load_full() {
+            if built.generation == generation && built.theme == theme {
+                return Ok(built.schema.clone());
+            }
+        }
+
+        let schema = build_schema(state, &theme).await?;
+
+        // A model may have been changed while building, such a schema is only used for this
+        // request and rebuilt on the next one.
+        self.0.built.store(Some(Arc::new(Built {
+            theme,
+            generation,
+            schema: schema.clone(),
+        })));
+
+        Ok(schema)
+    }
+
+    /// Marks the schema as outdated, it is rebuilt on the next request.
+    pub fn invalidate(&self) {
+        self.0.generation.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// Invalidates the schema after a successful change to models or themes.
+pub async fn invalidate_schema(
+    Extension(graphql): Extension<GraphQL>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let modifies = req.method() != Method::GET;
+
+    let res = next.run(req).await;
+
+    if modifies && res.status().is_success() {
+        graphql.invalidate();
+    }
+
+    res
+}
+
+/// Per request data available to resolvers.
+struct RequestData {
+    locale: String,
+    locales: Vec<String>,
+}
+
+impl RequestData {
+    fn locale(&self, ctx: &ResolverContext<'_>) -> async_graphql::Result<String> {
+        let Some(locale) = ctx.args.get("locale") else {
+            return Ok(self.locale.clone());
+        };
+
+        let locale = locale.string()?;
+
+        if !self.locales.iter().any(|l| l == locale) {
+            return Err(gql_error(HttpError::unprocessable_entity(
+                "unsupported_locale",
+            )));
+        }
+
+        Ok(locale.to_string())
+    }
+}
+
+pub async fn handle_graphql(
+    State(state): State<AppState>,
+    Extension(options): Extension<Options>,
+    Extension(graphql): Extension<GraphQL>,
+    headers: HeaderMap,
+    Json(req): Json<async_graphql::Request>,
+) -> Result<Json<async_graphql::Response>, HttpError> {
+    let schema = graphql.schema(&state, options.theme()).await?;
+
+    let data = RequestData {
+        locale: resolve_locale(&options, &headers, None)?,
+        locales: options.locales().iter().map(|l| l.to_string()).collect(),
+    };
+
+    Ok(Json(Schema::execute(&schema, req.data(data)).await))
+}
+
+fn gql_error(e: HttpError) -> async_graphql::Error {
+    async_graphql::Error::new(e.error).extend_with(|_, ext| {
+        ext.set("code", e.code.as_u16());
+
+        if let Some(context) = &e.context {
+            ext.set("context", context.as_str());
+        }
+    })
+}
+
+/// Converts the key into a GraphQL name, `blog-post` becomes `BlogPost` for types and `blogPost`
+/// for fields. Keys that cannot be represented yield `None`.
+fn graphql_name(key: &str, type_name: bool) -> Option<String> {
+    let mut name = String::with_capacity(key.len());
+
+    for (i, segment) in key
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .enumerate()
+    {
+        let mut chars = segment.chars();
+        let first = chars.next()?;
+
+        if i == 0 && !type_name {
+            name.push(first.to_ascii_lowercase());
+        } else {
+            name.push(first.to_ascii_uppercase());
+        }
+
+        name.extend(chars);
+    }
+
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    Some(name)
+}
+
+struct ModelFieldInfo {
+    id: i32,
+    model_id: i32,
+    key: String,
+    kind: String,
+    localized: bool,
+    multiple: bool,
+}
+
+struct ContentNode {
+    id: i32,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    locale: String,
+    values: Vec<(i32, String)>,
+}
+
+struct PageNode {
+    items: Vec<ContentNode>,
+    current_page: i64,
+    per_page: i64,
+    total_items: i64,
+}
+
+struct AssetNode {
+    id: i32,
+    name: String,
+    filename: String,
+    filetype: Option<String>,
+    url: String,
+}
+
+async fn build_schema(state: &AppState, theme: &str) -> Result<Schema, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let models = models::table
+        .filter(models::public.eq(true))
+        .filter(models::namespace.is_null().or(models::namespace.eq(theme)))
+        .select((models::id, models::key))
+        .order(models::id.asc())
+        .load::<(i32, String)>(&mut conn)
+        .await?;
+
+    let model_fields = model_fields::table
+        .inner_join(fields::table)
+        .filter(model_fields::model_id.eq_any(models.iter().map(|m| m.0)))
+        .select((
+            model_fields::id,
+            model_fields::model_id,
+            model_fields::key,
+            fields::kind,
+            model_fields::localized,
+            model_fields::multiple,
+        ))
+        .order(model_fields::id.asc())
+        .load::<(i32, i32, String, String, bool, bool)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(
+            |(id, model_id, key, kind, localized, multiple)| ModelFieldInfo {
+                id,
+                model_id,
+                key,
+                kind,
+                localized,
+                multiple,
+            },
+        )
+        .map(Arc::new)
+        .collect::<Vec<_>>();
+
+    let mut query = Object::new("Query").field(Field::new(
+        "locales",
+        TypeRef::named_nn_list_nn(TypeRef::STRING),
+        |ctx| {
+            FieldFuture::new(async move {
+                let data = ctx.data::<RequestData>()?;
+
+                Ok(Some(FieldValue::list(
+                    data.locales.iter().map(|l| FieldValue::value(l.as_str())),
+                )))
+            })
+        },
+    ));
+
+    let mut type_names = HashSet::from(RESERVED_TYPES.map(String::from));
+    let mut root_fields = HashSet::from(["locales".to_string()]);
+    let mut objects = vec![asset_object()];
+
+    for (model_id, key) in models {
+        let (Some(type_name), Some(field_name)) =
+            (graphql_name(&key, true), graphql_name(&key, false))
+        else {
+            log::warn!("Model {key} cannot be represented in GraphQL, skipping it");
+
+            continue;
+        };
+
+        let page_name = format!("{type_name}Page");
+        let list_name = format!("{field_name}List");
+
+        if type_names.contains(&type_name)
+            || type_names.contains(&page_name)
+            || root_fields.contains(&field_name)
+            || root_fields.contains(&list_name)
+        {
+            log::warn!("GraphQL name of model {key} collides with another one, skipping it");
+
+            continue;
+        }
+
+        let mut object = Object::new(&type_name)
+            .field(Field::new("id", TypeRef::named_nn(TypeRef::INT), |ctx| {
+                FieldFuture::new(async move {
+                    let node = ctx.parent_value.try_downcast_ref::<ContentNode>()?;
+
+                    Ok(Some(Value::from(node.id)))
+                })
+            }))
+            .field(Field::new(
+                "createdAt",
+                TypeRef::named_nn(TypeRef::STRING),
+                |ctx| {
+                    FieldFuture::new(async move {
+                        let node = ctx.parent_value.try_downcast_ref::<ContentNode>()?;
+
+                        Ok(Some(Value::from(format_datetime(node.created_at))))
+                    })
+                },
+            ))
+            .field(Field::new(
+                "updatedAt",
+                TypeRef::named_nn(TypeRef::STRING),
+                |ctx| {
+                    FieldFuture::new(async move {
+                        let node = ctx.parent_value.try_downcast_ref::<ContentNode>()?;
+
+                        Ok(Some(Value::from(format_datetime(node.updated_at))))
+                    })
+                },
+            ));
+
+        let mut field_names = HashSet::from(["id", "createdAt", "updatedAt"].map(String::from));
+
+        for mf in model_fields.iter().filter(|mf| mf.model_id == model_id) {
+            let Some(name) = graphql_name(&mf.key, false).filter(|n| field_names.insert(n.clone()))
+            else {
+                log::warn!(
+                    "Field {} of model {key} cannot be represented in GraphQL, skipping it",
+                    mf.key
+                );
+
+                continue;
+            };
+
+            object = object.field(value_field(name, mf.clone()));
+        }
+
+        query = query
+            .field(content_field(&field_name, &type_name, model_id))
+            .field(list_field(&list_name, &page_name, model_id));
+
+        objects.push(object);
+        objects.push(page_object(&page_name, &type_name));
+
+        type_names.extend([type_name, page_name]);
+        root_fields.extend([field_name, list_name]);
+    }
+
+    objects
+        .into_iter()
+        .fold(
+            Schema::build("Query", None, None)
+                .register(query)
+                .data(state.clone()),
+            |builder, object| builder.register(object),
+        )
+        .finish()
+        .map_err(|e| {
+            log::error!("Failed to build GraphQL schema, {e:?}");
+
+            HttpError::internal_server_error("failed_building_schema")
+        })
+}
+
+fn format_datetime(datetime: NaiveDateTime) -> String {
+    datetime.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+}
+
+fn value_field(name: String, mf: Arc<ModelFieldInfo>) -> Field {
+    let kind = FieldKind::of(&mf.kind);
+
+    let type_name = match kind {
+        FieldKind::Int | FieldKind::Reference => TypeRef::INT,
+        FieldKind::Float => TypeRef::FLOAT,
+        FieldKind::Bool => TypeRef::BOOLEAN,
+        FieldKind::Asset => "Asset",
+        _ => TypeRef::STRING,
+    };
+
+    let ty = if mf.multiple {
+        TypeRef::named_nn_list_nn(type_name)
+    } else {
+        TypeRef::named(type_name)
+    };
+
+    let localized = mf.localized;
+
+    let field = Field::new(name, ty, move |ctx| {
+        let mf = mf.clone();
+
+        FieldFuture::new(async move {
+            let node = ctx.parent_value.try_downcast_ref::<ContentNode>()?;
+            let state = ctx.data::<AppState>()?;
+
+            let values = match ctx.args.get("locale") {
+                Some(_) => {
+                    let locale = ctx.data::<RequestData>()?.locale(&ctx)?;
+
+                    if locale == node.locale {
+                        node_values(node, mf.id)
+                    } else {
+                        content_values::table
+                            .filter(content_values::content_id.eq(node.id))
+                            .filter(content_values::model_field_id.eq(mf.id))
+                            .filter(content_values::locale.eq(locale))
+                            .order(content_values::id.asc())
+                            .select(content_values::value)
+                            .load::<String>(&mut state.pool.get().await.map_err(HttpError::from)?)
+                            .await
+                            .map_err(|e| gql_error(e.into()))?
+                    }
+                }
+                None => node_values(node, mf.id),
+            };
+
+            let values = match kind {
+                FieldKind::Asset => load_assets(state, values)
+                    .await
+                    .map_err(gql_error)?
+                    .into_iter()
+                    .map(FieldValue::owned_any)
+                    .collect::<Vec<_>>(),
+                FieldKind::Int | FieldKind::Reference => values
+                    .into_iter()
+                    .filter_map(|v| v.parse::<i64>().ok())
+                    .map(FieldValue::value)
+                    .collect(),
+                FieldKind::Float => values
+                    .into_iter()
+                    .filter_map(|v| v.parse::<f64>().ok())
+                    .map(FieldValue::value)
+                    .collect(),
+                FieldKind::Bool => values
+                    .into_iter()
+                    .map(|v| FieldValue::value(v == "true"))
+                    .collect(),
+                _ => values.into_iter().map(FieldValue::value).collect(),
+            };
+
+            if mf.multiple {
+                Ok(Some(FieldValue::list(values)))
+            } else {
+                Ok(values.into_iter().next())
+            }
+        })
+    });
+
+    if localized {
+        field.argument(InputValue::new("locale", TypeRef::named(TypeRef::STRING)))
+    } else {
+        field
+    }
+}
+
+fn node_values(node: &ContentNode, model_field_id: i32) -> Vec<String> {
+    node.values
+        .iter()
+        .filter(|(id, _)| *id == model_field_id)
+        .map(|(_, value)| value.clone())
+        .collect()
+}
+
+/// Resolves assets referred by their filenames, keeping the order of given values.
+async fn load_assets(
+    state: &AppState,
+    filenames: Vec<String>,
+) -> Result<Vec<AssetNode>, HttpError> {
+    if filenames.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut assets = assets::table
+        .filter(assets::filename.eq_any(&filenames))
+        .select((assets::id, assets::name, assets::filename, assets::filetype))
+        .order(assets::id.asc())
+        .load::<(i32, String, String, Option<String>)>(&mut state.pool.get().await?)
+        .await?;
+
+    let site_url = state.config.site_url.as_str().trim_end_matches('/');
+
+    Ok(filenames
+        .iter()
+        .filter_map(|filename| {
+            let index = assets.iter().position(|a| &a.2 == filename)?;
+            let (id, name, filename, filetype) = assets.remove(index);
+
+            Some(AssetNode {
+                id,
+                url: format!("{site_url}/assets/content/{filename}"),
+                name,
+                filename,
+                filetype,
+            })
+        })
+        .collect())
+}
+
+fn asset_object() -> Object {
+    fn field(name: &'static str, ty: TypeRef, get: fn(&AssetNode) -> Option<Value>) -> Field {
+        Field::new(name, ty, move |ctx| {
+            FieldFuture::new(async move {
+                let asset = ctx.parent_value.try_downcast_ref::<AssetNode>()?;
+
+                Ok(get(asset))
+            })
+        })
+    }
+
+    Object::new("Asset")
+        .field(field("id", TypeRef::named_nn(TypeRef::INT), |a| {
+            Some(Value::from(a.id))
+        }))
+        .field(field("name", TypeRef::named_nn(TypeRef::STRING), |a| {
+            Some(Value::from(a.name.as_str()))
+        }))
+        .field(field("filename", TypeRef::named_nn(TypeRef::STRING), |a| {
+            Some(Value::from(a.filename.as_str()))
+        }))
+        .field(field("filetype", TypeRef::named(TypeRef::STRING), |a| {
+            a.filetype.as_deref().map(Value::from)
+        }))
+        .field(field("url", TypeRef::named_nn(TypeRef::STRING), |a| {
+            Some(Value::from(a.url.as_str()))
+        }))
+}
+
+fn page_object(page_name: &str, type_name: &str) -> Object {
+    fn field(name: &'static str, get: fn(&PageNode) -> i64) -> Field {
+        Field::new(name, TypeRef::named_nn(TypeRef::INT), move |ctx| {
+            FieldFuture::new(async move {
+                let page = ctx.parent_value.try_downcast_ref::<PageNode>()?;
+
+                Ok(Some(Value::from(get(page))))
+            })
+        })
+    }
+
+    Object::new(page_name)
+        .field(Field::new(
+            "items",
+            TypeRef::named_nn_list_nn(type_name),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let page = ctx.parent_value.try_downcast_ref::<PageNode>()?;
+
+                    Ok(Some(FieldValue::list(
+                        page.items.iter().map(|item| FieldValue::borrowed_any(item)),
+                    )))
+                })
+            },
+        ))
+        .field(field("currentPage", |p| p.current_page))
+        .field(field("perPage", |p| p.per_page))
+        .field(field("totalItems", |p| p.total_items))
+        .field(field("totalPages", |p| {
+            (p.total_items as f64 / p.per_page as f64).ceil() as i64
+        }))
+}
+
+fn content_field(name: &str, type_name: &str, model_id: i32) -> Field {
+    Field::new(name, TypeRef::named(type_name), move |ctx| {
+        FieldFuture::new(async move {
+            let state = ctx.data::<AppState>()?;
+            let locale = ctx.data::<RequestData>()?.locale(&ctx)?;
+            let id = ctx.args.try_get("id")?.i64()?;
+
+            let filter = Filter {
+                field: "id".to_string(),
+                op: FilterOp::Eq(id.to_string()),
+            };
+
+            let (mut nodes, _) = load_contents(state, model_id, locale, vec![filter], None, None)
+                .await
+                .map_err(gql_error)?;
+
+            Ok(nodes.pop().map(FieldValue::owned_any))
+        })
+    })
+    .argument(InputValue::new("id", TypeRef::named_nn(TypeRef::INT)))
+    .argument(InputValue::new("locale", TypeRef::named(TypeRef::STRING)))
+}
+
+/// Lists visible contents of the model. Filters and sort refer to model field keys and use the same
+/// syntax as the content delivery API, `weight:ge:3` and `-created_at` respectively.
+fn list_field(name: &str, page_name: &str, model_id: i32) -> Field {
+    Field::new(name, TypeRef::named_nn(page_name), move |ctx| {
+        FieldFuture::new(async move {
+            let state = ctx.data::<AppState>()?;
+            let locale = ctx.data::<RequestData>()?.locale(&ctx)?;
+
+            let filters = match ctx.args.get("filter") {
+                Some(filters) => filters
+                    .list()?
+                    .iter()
+                    .map(|f| parse_filter(f.string()?).map_err(gql_error))
+                    .collect::<async_graphql::Result<Vec<_>>>()?,
+                None => vec![],
+            };
+
+            let order = match ctx.args.get("sort") {
+                Some(sort) => Some(parse_order(sort.string()?.to_string())),
+                None => None,
+            };
+
+            let page = match ctx.args.get("page") {
+                Some(page) => page.i64()?,
+                None => 1,
+            };
+
+            let per_page = match ctx.args.get("perPage") {
+                Some(per_page) => per_page.i64()?.clamp(1, MAX_PER_PAGE),
+                None => DEFAULT_PER_PAGE,
+            };
+
+            let (items, total_items) = load_contents(
+                state,
+                model_id,
+                locale,
+                filters,
+                order.as_ref(),
+                Some((page, per_page)),
+            )
+            .await
+            .map_err(gql_error)?;
+
+            Ok(Some(FieldValue::owned_any(PageNode {
+                items,
+                current_page: page,
+                per_page,
+                total_items,
+            })))
+        })
+    })
+    .argument(InputValue::new("locale", TypeRef::named(TypeRef::STRING)))
+    .argument(InputValue::new(
+        "filter",
+        TypeRef::named_nn_list(TypeRef::STRING),
+    ))
+    .argument(InputValue::new("sort", TypeRef::named(TypeRef::STRING)))
+    .argument(InputValue::new("page", TypeRef::named(TypeRef::INT)))
+    .argument(InputValue::new("perPage", TypeRef::named(TypeRef::INT)))
+}
+
+/// Loads visible contents of the model along with their values in the locale. Returns the
+/// contents and the total number of contents matching the filters.
+async fn load_contents(
+    state: &AppState,
+    model_id: i32,
+    locale: String,
+    filters: Vec<Filter>,
+    order: Option<&Order>,
+    page: Option<(i64, i64)>,
+) -> Result<(Vec<ContentNode>, i64), HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let mut query = query_contents(
+        &mut conn,
+        model_id,
+        &locale,
+        filters,
+        order,
+        Utc::now().naive_utc(),
+    )
+    .await
+    .map_err(query_error)?;
+
+    if let Some((page, per_page)) = page {
+        query = query.limit(per_page).offset(max(page - 1, 0) * per_page);
+    }
+
+    let rows = query
+        .select((
+            contents::id,
+            contents::created_at,
+            contents::updated_at,
+            CountStarOver,
+        ))
+        .load::<(i32, NaiveDateTime, NaiveDateTime, i64)>(&mut conn)
+        .await?;
+
+    let total = <[_]>::first(&rows).map(|row| row.3).unwrap_or(0);
+
+    let mut values = content_values::table
+        .filter(content_values::content_id.eq_any(rows.iter().map(|row| row.0)))
+        .filter(
+            content_values::locale
+                .eq(&locale)
+                .or(content_values::locale.is_null()),
+        )
+        .order(content_values::id.asc())
+        .select((
+            content_values::content_id,
+            content_values::model_field_id,
+            content_values::value,
+        ))
+        .load::<(i32, i32, String)>(&mut conn)
+        .await?;
+
+    let nodes = rows
+        .into_iter()
+        .map(|(id, created_at, updated_at, _)| ContentNode {
+            id,
+            created_at,
+            updated_at,
+            locale: locale.clone(),
+            values: values
+                .extract_if(.., |v| v.0 == id)
+                .map(|(_, model_field_id, value)| (model_field_id, value))
+                .collect(),
+        })
+        .collect();
+
+    Ok((nodes, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_graphql::dynamic::Schema;
+    use base::{
+        config::Config,
+        db::BatchQuery,
+        models::ContentStage,
+        schema::{content_values, contents, fields, locales, model_fields, models},
+        test::{create_pool, DB_CONFIG},
+        AppState,
+    };
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+
+    use super::{graphql_name, GraphQL, RequestData};
+
+    async fn init_state() -> AppState {
+        let pool = create_pool(DB_CONFIG).await;
+
+        let storage = opendal::Operator::new(opendal::services::Memory::default())
+            .unwrap()
+            .finish();
+
+        let config = Config {
+            env: "test".to_string(),
+            site_url: "http://127.0.0.1".parse().unwrap(),
+            app_url: "http://127.0.0.1".parse().unwrap(),
+            reload_templates: false,
+            upload_size_limit: 0,
+            image_max_dimension: 0,
+            image_sizes: [].into(),
+        };
+
+        AppState::new(config, pool, storage.clone(), storage)
+    }
+
+    async fn execute(schema: &Schema, query: &str) -> async_graphql::Response {
+        let data = RequestData {
+            locale: "en".to_string(),
+            locales: vec!["en".to_string(), "tr".to_string()],
+        };
+
+        Schema::execute(schema, async_graphql::Request::new(query).data(data)).await
+    }
+
+    #[test]
+    fn it_converts_keys_into_graphql_names() {
+        assert_eq!(
+            Some("BlogPost".to_string()),
+            graphql_name("blog-post", true)
+        );
+        assert_eq!(
+            Some("blogPost".to_string()),
+            graphql_name("blog_post", false)
+        );
+        assert_eq!(Some("article".to_string()), graphql_name("Article", false));
+        assert_eq!(None, graphql_name("2024-posts", true));
+        assert_eq!(None, graphql_name("--", false));
+    }
+
+    #[tokio::test]
+    async fn it_resolves_contents_of_public_models() {
+        let state = init_state().await;
+        let graphql = GraphQL::default();
+        let theme: Arc<str> = Arc::from("default");
+
+        diesel::insert_into(locales::table)
+            .values(vec![
+                (locales::key.eq("en"), locales::name.eq("English")),
+                (locales::key.eq("tr"), locales::name.eq("Türkçe")),
+            ])
+            .batched()
+            .execute(&mut state.pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let (text_id, int_id) = {
+            let mut conn = state.pool.get().await.unwrap();
+
+            let insert = |key: &'static str, kind: &'static str| {
+                diesel::insert_into(fields::table)
+                    .values((
+                        fields::key.eq(key),
+                        fields::name.eq(key),
+                        fields::kind.eq(kind),
+                    ))
+                    .returning(fields::id)
+            };
+
+            (
+                insert("gql_text", "string")
+                    .get_result::<i32>(&mut conn)
+                    .await
+                    .unwrap(),
+                insert("gql_number", "int")
+                    .get_result::<i32>(&mut conn)
+                    .await
+                    .unwrap(),
+            )
+        };
+
+        let model_id = diesel::insert_into(models::table)
+            .values((
+                models::key.eq("blog-post"),
+                models::name.eq("Blog Post"),
+                models::public.eq(true),
+            ))
+            .returning(models::id)
+            .get_result::<i32>(&mut state.pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let secret_id = diesel::insert_into(models::table)
+            .values((models::key.eq("secret"), models::name.eq("Secret")))
+            .returning(models::id)
+            .get_result::<i32>(&mut state.pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let title_id = diesel::insert_into(model_fields::table)
+            .values((
+                model_fields::field_id.eq(text_id),
+                model_fields::model_id.eq(model_id),
+                model_fields::key.eq("title"),
+                model_fields::name.eq("Title"),
+                model_fields::localized.eq(true),
+            ))
+            .returning(model_fields::id)
+            .get_result::<i32>(&mut state.pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let weight_id = diesel::insert_into(model_fields::table)
+            .values((
+                model_fields::field_id.eq(int_id),
+                model_fields::model_id.eq(model_id),
+                model_fields::key.eq("weight"),
+                model_fields::name.eq("Weight"),
+            ))
+            .returning(model_fields::id)
+            .get_result::<i32>(&mut state.pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let mut content_ids = vec![];
+
+        for stage in [ContentStage::Published, ContentStage::Draft] {
+            let content_id = diesel::insert_into(contents::table)
+                .values((
+                    contents::model_id.eq(model_id),
+                    contents::name.eq("content"),
+                    contents::stage.eq(stage),
+                ))
+                .returning(contents::id)
+                .get_result::<i32>(&mut state.pool.get().await.unwrap())
+                .await
+                .unwrap();
+
+            content_ids.push(content_id);
+        }
+
+        let published = content_ids[0];
+
+        diesel::insert_into(content_values::table)
+            .values(vec![
+                (
+                    content_values::content_id.eq(published),
+                    content_values::model_field_id.eq(title_id),
+                    content_values::value.eq("Hello"),
+                    content_values::locale.eq(Some("en")),
+                ),
+                (
+                    content_values::content_id.eq(published),
+                    content_values::model_field_id.eq(title_id),
+                    content_values::value.eq("Merhaba"),
+                    content_values::locale.eq(Some("tr")),
+                ),
+                (
+                    content_values::content_id.eq(published),
+                    content_values::model_field_id.eq(weight_id),
+                    content_values::value.eq("3"),
+                    content_values::locale.eq(None),
+                ),
+            ])
+            .batched()
+            .execute(&mut state.pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let schema = graphql.schema(&state, theme.clone()).await.unwrap();
+
+        // Root fields are resolved concurrently, each of them is queried on its own since only
+        // the connection with the test transaction can see the inserted rows.
+        let res = execute(
+            &schema,
+            &format!(
+                r#"{{ blogPost(id: {published}) {{ id title trTitle: title(locale: "tr") weight }} }}"#
+            ),
+        )
+        .await;
+
+        assert!(res.errors.is_empty(), "{:?}", res.errors);
+        assert_eq!(
+            serde_json::json!({
+                "blogPost": { "id": published, "title": "Hello", "trTitle": "Merhaba", "weight": 3 },
+            }),
+            res.data.into_json().unwrap()
+        );
+
+        let res = execute(&schema, "{ blogPostList { totalItems items { id } } }").await;
+
+        assert!(res.errors.is_empty(), "{:?}", res.errors);
+        assert_eq!(
+            serde_json::json!({
+                "blogPostList": { "totalItems": 1, "items": [{ "id": published }] },
+            }),
+            res.data.into_json().unwrap()
+        );
+
+        let res = execute(&schema, "{ secret(id: 1) { id } }").await;
+
+        assert!(!res.errors.is_empty());
+
+        diesel::update(models::table)
+            .filter(models::id.eq(secret_id))
+            .set(models::public.eq(true))
+            .execute(&mut state.pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        graphql.invalidate();
+
+        let schema = graphql.schema(&state, theme).await.unwrap();
+        let res = execute(&schema, "{ secretList { totalItems } }").await;
+
+        assert!(res.errors.is_empty(), "{:?}", res.errors);
+    }
+}
@@ -1,24 +1,31 @@
+use std::collections::HashMap;
+
 use axum::{
     extract::{Multipart, Path, Query, State},
     Json,
 };
 use base::{
-    db::Pool,
+    db::{lower, BatchQuery, Connection, Pool},
     middlewares::auth::AuthUser,
     models::{Asset, TagResource},
     paginate::{CountStarOver, Paginate, Pagination, PaginationRequest},
     responses::HttpError,
     runtime::IntoSendFuture,
-    schema::{assets, tags},
+    schema::{asset_alts, asset_folders, assets, locales, tags},
+    validate::Valid,
     AppState,
 };
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
 use futures::StreamExt;
 use opendal::Operator;
 use rand::{distr::Alphanumeric, rng, Rng};
+use ring::digest;
 
-use crate::requests::UpdateAsset;
+use crate::{
+    requests::{MoveAssets, SearchAssets, UpdateAsset, UpdateAssetAlts},
+    responses::AssetDetails,
+};
 
 pub async fn fetch_assets(
     State(state): State<AppState>,
@@ -46,18 +53,95 @@ pub async fn fetch_assets(
         .map_err(Into::into)
 }
 
-pub async fn fetch_asset(
+pub async fn search_assets(
     State(state): State<AppState>,
-    Path(asset_id): Path<i32>,
-) -> Result<Json<Asset>, HttpError> {
-    assets::table
-        .filter(assets::id.eq(asset_id))
-        .first::<Asset>(&mut state.pool.get().await?)
+    Query(req): Query<SearchAssets>,
+    Query(page): Query<PaginationRequest>,
+) -> Result<Json<Pagination<Asset>>, HttpError> {
+    let mut query = assets::table.into_boxed();
+
+    if let Some(q) = req
+        .query
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+    {
+        query = query.filter(
+            lower(assets::name)
+                .like(format!("%{}%", escape_like(&q.to_lowercase())))
+                .escape('\\'),
+        );
+    }
+
+    if req.root {
+        query = query.filter(assets::folder_id.is_null());
+    } else if let Some(folder_id) = req.folder_id {
+        query = query.filter(assets::folder_id.eq(folder_id));
+    }
+
+    if let Some(filetype) = req.filetype.filter(|f| !f.is_empty()) {
+        query = query.filter(
+            assets::filetype
+                .like(format!("{}%", escape_like(&filetype)))
+                .escape('\\'),
+        );
+    }
+
+    if let Some(tag) = req.tag {
+        query = query.filter(diesel::dsl::exists(
+            tags::table.filter(
+                tags::resource
+                    .eq(TagResource::Asset)
+                    .and(tags::resource_id.eq(assets::id))
+                    .and(tags::key.eq(tag)),
+            ),
+        ));
+    }
+
+    if let Some(created_by) = req.created_by {
+        query = query.filter(assets::created_by.eq(created_by));
+    }
+
+    query
+        .select((assets::all_columns, CountStarOver))
+        .order((assets::created_at.desc(), assets::id.desc()))
+        .paginate(page.page)
+        .per_page(page.per_page)
+        .load_and_count_pages::<Asset>(&mut state.pool.get().await?)
         .await
         .map(Json)
         .map_err(Into::into)
 }
 
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+pub async fn fetch_asset(
+    State(state): State<AppState>,
+    Path(asset_id): Path<i32>,
+) -> Result<Json<AssetDetails>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let asset = assets::table
+        .filter(assets::id.eq(asset_id))
+        .first::<Asset>(&mut conn)
+        .await?;
+
+    let alts = asset_alts::table
+        .filter(asset_alts::asset_id.eq(asset_id))
+        .select((asset_alts::locale, asset_alts::value))
+        .load::<(String, String)>(&mut conn)
+        .await?
+        .into_iter()
+        .collect();
+
+    Ok(Json(AssetDetails { asset, alts }))
+}
+
 pub async fn create_asset(
     State(state): State<AppState>,
     user: AuthUser,
@@ -74,6 +158,7 @@ pub async fn create_asset(
         &state.tmp_storage,
         &tmp_dir,
         user.id,
+        state.config.image_max_dimension,
     )
     .await;
 
@@ -96,24 +181,24 @@ async fn store_and_insert_assert(
     tmp_storage: &Operator,
     tmp_dir: &str,
     user_id: i32,
+    #[cfg_attr(not(feature = "image"), allow(unused_variables))] image_max_dimension: u32,
 ) -> Result<Asset, HttpError> {
     let mut file = None;
+    let mut folder_id = None;
 
     while let Ok(Some(mut field)) = multipart.next_field().await {
-        if file.is_some() {
-            break;
-        }
-
         match field
             .name()
             .ok_or(HttpError::bad_request("invalid_multipart"))?
         {
-            "asset" => {
+            "asset" if file.is_none() => {
                 let name = field.file_name().unwrap_or("empty-name").to_string();
                 let filetype = field.content_type().map(|t| t.to_string());
                 let path = format!("{tmp_dir}/asset");
 
                 let mut sink = tmp_storage.writer(&path).into_send_future().await?;
+                let mut digest = digest::Context::new(&digest::SHA256);
+                let mut size = 0;
 
                 while let Some(chunk) = field
                     .chunk()
@@ -121,53 +206,99 @@ async fn store_and_insert_assert(
                     .inspect_err(|e| log::debug!("Failed reading multipart field, {e:?}"))
                     .map_err(|_| HttpError::bad_request("failed_reading_multipart_field"))?
                 {
+                    digest.update(&chunk);
+                    size += chunk.len();
+
                     sink.write(chunk).into_send_future().await?;
                 }
 
                 sink.close().into_send_future().await?;
 
-                file = Some((path, name, filetype));
+                file = Some((path, name, filetype, size, hex(digest.finish().as_ref())));
+            }
+            "folderId" => {
+                let value = field
+                    .text()
+                    .await
+                    .map_err(|_| HttpError::bad_request("failed_reading_multipart_field"))?;
+
+                folder_id = Some(
+                    value
+                        .parse::<i32>()
+                        .map_err(|_| HttpError::bad_request("invalid_folder_id"))?,
+                );
             }
             _ => continue,
         };
     }
 
-    let Some((file, name, filetype)) = file else {
+    let Some((file, name, filetype, size, checksum)) = file else {
         return Err(HttpError::bad_request("missing_field_in_multipart"));
     };
 
-    let filename = {
-        let (filename, ext) = name.rsplit_once('.').unwrap_or((name.as_str(), ""));
-
-        let mut filename = filename
-            .chars()
-            .filter(|ch| ch.is_ascii_alphanumeric() || *ch == '-' || *ch == '_' || *ch == '.')
-            .take(96)
-            .collect::<String>()
-            + "_"
-            + (0..12)
-                .map(|_| rng().sample(Alphanumeric) as char)
-                .collect::<String>()
-                .as_str();
-
-        if !ext.is_empty() {
-            filename = filename + "." + ext;
-        }
+    if let Some(folder_id) = folder_id {
+        folder_exists(&mut *pool.get().await?, folder_id).await?;
+    }
 
-        filename
+    #[cfg(feature = "image")]
+    let (prepared, dimensions) = prepare_image(tmp_storage, &file, image_max_dimension).await?;
+    #[cfg(not(feature = "image"))]
+    let (prepared, dimensions): (Option<Vec<u8>>, Option<(u32, u32)>) = (None, None);
+
+    // Prepared image replaces the uploaded file, so its metadata is used instead
+    let (size, checksum) = match &prepared {
+        Some(prepared) => (
+            prepared.len(),
+            hex(digest::digest(&digest::SHA256, prepared).as_ref()),
+        ),
+        None => (size, checksum),
     };
 
+    let mut conn = pool.get().await?;
+
+    // Stored files are deduplicated by their content, every upload still gets its own asset
+    let stored = assets::table
+        .filter(assets::checksum.eq(&checksum))
+        .select(assets::filename)
+        .order(assets::id.asc())
+        .first::<String>(&mut conn)
+        .await
+        .optional()?;
+
+    let filename = stored
+        .clone()
+        .unwrap_or_else(|| content_addressed_filename(&name, &checksum));
+
     let asset = diesel::insert_into(assets::table)
         .values((
             assets::name.eq(name),
-            assets::filename.eq(filename),
+            assets::filename.eq(&filename),
             assets::filetype.eq(filetype),
             assets::created_by.eq(user_id),
+            assets::folder_id.eq(folder_id),
+            assets::size.eq(size as i64),
+            assets::checksum.eq(&checksum),
+            assets::width.eq(dimensions.map(|d| d.0 as i32)),
+            assets::height.eq(dimensions.map(|d| d.1 as i32)),
         ))
-        .get_result::<Asset>(&mut pool.get().await?)
+        .get_result::<Asset>(&mut conn)
         .await?;
 
+    if stored.is_some() {
+        return Ok(asset);
+    }
+
+    drop(conn);
+
     let send_future = async move |filename: &str| {
+        if let Some(prepared) = prepared {
+            storage
+                .write(&format!("assets/{}", filename), prepared)
+                .await?;
+
+            return Result::<(), HttpError>::Ok(());
+        }
+
         let mut writer = storage.writer(&format!("assets/{}", filename)).await?;
 
         let mut stream = tmp_storage.reader(&file).await?.into_stream(..).await?;
@@ -188,6 +319,70 @@ async fn store_and_insert_assert(
     Ok(asset)
 }
 
+/// Strips metadata of the uploaded image and downscales it if it is too large. Returns the
+/// prepared image, `None` if the file is not an image that can be prepared, along with the
+/// dimensions of the image that is going to be stored.
+#[cfg(feature = "image")]
+async fn prepare_image(
+    tmp_storage: &Operator,
+    file: &str,
+    max_dimension: u32,
+) -> Result<(Option<Vec<u8>>, Option<(u32, u32)>), HttpError> {
+    let bytes = tmp_storage.read(file).into_send_future().await?.to_vec();
+
+    base::runtime::spawn_blocking(move || {
+        let prepared = base::image::prepare_upload(&bytes, max_dimension)?;
+        let dimensions = base::image::dimensions(prepared.as_deref().unwrap_or(&bytes));
+
+        Ok((prepared, dimensions))
+    })
+    .await
+    .unwrap()
+    .map_err(|e: base::image::ImageError| {
+        log::debug!("Failed to prepare uploaded image, {e:?}");
+
+        HttpError::unprocessable_entity("invalid_image")
+    })
+}
+
+/// Derives the stored filename from the uploaded file's name and its checksum. Name part keeps the
+/// links readable while the checksum part makes the same content always map to the same file.
+fn content_addressed_filename(name: &str, checksum: &str) -> String {
+    let (stem, ext) = name.rsplit_once('.').unwrap_or((name, ""));
+
+    let mut filename = stem
+        .chars()
+        .filter(|ch| ch.is_ascii_alphanumeric() || *ch == '-' || *ch == '_' || *ch == '.')
+        .take(96)
+        .collect::<String>()
+        + "_"
+        + &checksum[..16];
+
+    if !ext.is_empty() {
+        filename = filename + "." + ext;
+    }
+
+    filename
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+async fn folder_exists(conn: &mut Connection, folder_id: i32) -> Result<(), HttpError> {
+    let exists = diesel::dsl::select(diesel::dsl::exists(
+        asset_folders::table.filter(asset_folders::id.eq(folder_id)),
+    ))
+    .get_result::<bool>(conn)
+    .await?;
+
+    if !exists {
+        return Err(HttpError::not_found("folder_not_found"));
+    }
+
+    Ok(())
+}
+
 pub async fn update_asset(
     State(state): State<AppState>,
     Path(asset_id): Path<i32>,
@@ -206,27 +401,220 @@ pub async fn update_asset(
     Ok(())
 }
 
+pub async fn move_assets(
+    State(state): State<AppState>,
+    Valid(Json(req)): Valid<Json<MoveAssets>>,
+) -> Result<(), HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    if let Some(folder_id) = req.folder_id {
+        folder_exists(&mut conn, folder_id).await?;
+    }
+
+    // Assets are served by their filename, so moving them does not break any link
+    diesel::update(assets::table)
+        .filter(assets::id.eq_any(req.asset_ids))
+        .set(assets::folder_id.eq(req.folder_id))
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn update_asset_alts(
+    State(state): State<AppState>,
+    Path(asset_id): Path<i32>,
+    Json(req): Json<UpdateAssetAlts>,
+) -> Result<Json<HashMap<String, String>>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let exists = diesel::dsl::select(diesel::dsl::exists(
+        assets::table.filter(assets::id.eq(asset_id)),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await?;
+
+    if !exists {
+        return Err(HttpError::not_found("asset_not_found"));
+    }
+
+    let alts = req
+        .alts
+        .into_iter()
+        .map(|(locale, value)| (locale, value.trim().to_string()))
+        .filter(|(_, value)| !value.is_empty())
+        .collect::<HashMap<_, _>>();
+
+    let known_locales = locales::table
+        .filter(locales::key.eq_any(alts.keys()))
+        .count()
+        .get_result::<i64>(&mut conn)
+        .await?;
+
+    if known_locales as usize != alts.len() {
+        return Err(HttpError::unprocessable_entity("unknown_locale"));
+    }
+
+    let values = alts
+        .iter()
+        .map(|(locale, value)| {
+            (
+                asset_alts::asset_id.eq(asset_id),
+                asset_alts::locale.eq(locale.clone()),
+                asset_alts::value.eq(value.clone()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    conn.transaction(|conn| {
+        async move {
+            diesel::delete(asset_alts::table)
+                .filter(asset_alts::asset_id.eq(asset_id))
+                .execute(conn)
+                .await?;
+
+            if !values.is_empty() {
+                diesel::insert_into(asset_alts::table)
+                    .values(values)
+                    .batched()
+                    .execute(conn)
+                    .await?;
+            }
+
+            Ok::<_, HttpError>(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    Ok(Json(alts))
+}
+
 pub async fn delete_asset(
     State(state): State<AppState>,
     Path(asset_id): Path<i32>,
 ) -> Result<(), HttpError> {
-    let asset = assets::table
-        .filter(assets::id.eq(asset_id))
-        .first::<Asset>(&mut state.pool.get().await?)
-        .await
-        .optional()?
-        .ok_or_else(|| HttpError::not_found("asset_not_found"))?;
+    if !remove_asset(&mut *state.pool.get().await?, &state.storage, asset_id).await? {
+        return Err(HttpError::not_found("asset_not_found"));
+    }
+
+    Ok(())
+}
 
-    state
-        .storage
-        .delete(&format!("assets/{}", asset.filename))
+/// Deletes the asset, returns whether it existed. Its stored file is removed once no other asset
+/// shares it.
+pub(crate) async fn remove_asset(
+    conn: &mut Connection,
+    storage: &Operator,
+    asset_id: i32,
+) -> Result<bool, HttpError> {
+    let removed = conn
+        .transaction(|conn| {
+            async move {
+                let Some(filename) = diesel::delete(assets::table)
+                    .filter(assets::id.eq(asset_id))
+                    .returning(assets::filename)
+                    .get_result::<String>(conn)
+                    .await
+                    .optional()?
+                else {
+                    return Ok(None);
+                };
+
+                let shared = diesel::select(diesel::dsl::exists(
+                    assets::table.filter(assets::filename.eq(&filename)),
+                ))
+                .get_result::<bool>(conn)
+                .await?;
+
+                Ok::<_, HttpError>(Some((filename, shared)))
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    match removed {
+        None => Ok(false),
+        Some((_, true)) => Ok(true),
+        Some((filename, false)) => remove_asset_files(storage, &filename).await.map(|_| true),
+    }
+}
+
+/// Removes the stored file of an asset along with its derivatives.
+async fn remove_asset_files(storage: &Operator, filename: &str) -> Result<(), HttpError> {
+    storage
+        .delete(&format!("assets/{filename}"))
         .into_send_future()
         .await?;
 
-    diesel::delete(assets::table)
-        .filter(assets::id.eq(asset_id))
-        .execute(&mut state.pool.get().await?)
+    storage
+        .remove_all(&format!("derivatives/assets/{filename}/"))
+        .into_send_future()
         .await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use base::test::{create_pool, DB_CONFIG};
+
+    use super::*;
+
+    #[test]
+    fn it_derives_filenames_from_name_and_checksum() {
+        let checksum = hex(digest::digest(&digest::SHA256, b"sailing boat").as_ref());
+
+        assert_eq!(64, checksum.len());
+
+        assert_eq!(
+            format!("sailingboat_{}.png", &checksum[..16]),
+            content_addressed_filename("sailing boat!.png", &checksum)
+        );
+
+        assert_eq!(
+            content_addressed_filename("boat.png", &checksum),
+            content_addressed_filename("boat.png", &checksum)
+        );
+
+        assert_eq!(
+            format!("README_{}", &checksum[..16]),
+            content_addressed_filename("README", &checksum)
+        );
+    }
+
+    #[tokio::test]
+    async fn it_keeps_shared_files_until_their_last_asset_is_removed() {
+        let pool = create_pool(DB_CONFIG).await;
+        let mut conn = pool.get().await.unwrap();
+
+        let storage = Operator::new(opendal::services::Memory::default())
+            .unwrap()
+            .finish();
+
+        let filename = format!("shared-{}.png", rand::random::<u32>());
+
+        storage
+            .write(&format!("assets/{filename}"), "image")
+            .await
+            .unwrap();
+
+        let ids = diesel::insert_into(assets::table)
+            .values(vec![
+                (assets::name.eq("first"), assets::filename.eq(&filename)),
+                (assets::name.eq("second"), assets::filename.eq(&filename)),
+            ])
+            .returning(assets::id)
+            .get_results::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        assert!(remove_asset(&mut conn, &storage, ids[0]).await.unwrap());
+        assert!(storage.exists(&format!("assets/{filename}")).await.unwrap());
+
+        assert!(!remove_asset(&mut conn, &storage, ids[0]).await.unwrap());
+
+        assert!(remove_asset(&mut conn, &storage, ids[1]).await.unwrap());
+        assert!(!storage.exists(&format!("assets/{filename}")).await.unwrap());
+    }
+}
@@ -2,8 +2,8 @@ use std::collections::HashMap;
 
 use crate::{
     requests::{
-        ContentValue, CreateContent, FilterByModel, UpdateContent, UpdateContentStage,
-        UpdateContentValue,
+        ContentValue, CreateContent, FilterByModel, SearchContents, UpdateContent,
+        UpdateContentSchedule, UpdateContentStage, UpdateContentValue,
     },
     responses::ContentDetails,
 };
@@ -13,17 +13,23 @@ use axum::{
 };
 use base::{
     config::Options,
-    db::BatchQuery,
-    middlewares::auth::AuthUser,
-    models::{Content, ContentStage},
+    content::{load_workflow, modify_permission, transition_permission, visible_at, SearchRank},
+    db::{BatchQuery, Connection},
+    field::{sanitize_value, search_text, validate_values, FieldKind, FieldValue},
+    middlewares::{auth::AuthUser, permission::has_auth_user_permission},
+    models::{Content, ContentStage, RevisionAction},
     paginate::{CountStarOver, Paginate, Pagination, PaginationRequest},
     responses::HttpError,
     schema::{content_values, contents, fields, model_fields, models, users},
+    validate::{Errors, Valid},
     AppState,
 };
+use chrono::Utc;
 use diesel::prelude::*;
 use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
 
+use super::revision::create_revision;
+
 pub async fn fetch_contents(
     State(state): State<AppState>,
     Query(req): Query<FilterByModel>,
@@ -41,9 +47,44 @@ pub async fn fetch_contents(
         .map_err(Into::into)
 }
 
+/// Searches through the values of contents that are currently visible on the site, the most
+/// relevant contents come first.
+pub async fn search_contents(
+    State(state): State<AppState>,
+    Extension(options): Extension<Options>,
+    Query(req): Query<SearchContents>,
+    Query(page): Query<PaginationRequest>,
+) -> Result<Json<Pagination<Content>>, HttpError> {
+    let locale = req
+        .locale
+        .unwrap_or_else(|| options.default_locale().to_string());
+
+    let rank = SearchRank::new(&req.query, locale);
+
+    let mut query = contents::table
+        .filter(visible_at(Utc::now().naive_utc()))
+        .filter(rank.clone().is_not_null())
+        .into_boxed();
+
+    if let Some(model_id) = req.model_id {
+        query = query.filter(contents::model_id.eq(model_id));
+    }
+
+    query
+        .select((contents::all_columns, CountStarOver))
+        .order((rank.desc(), contents::id.desc()))
+        .paginate(page.page)
+        .per_page(page.per_page)
+        .load_and_count_pages::<Content>(&mut state.pool.get().await?)
+        .await
+        .map(Json)
+        .map_err(Into::into)
+}
+
 pub async fn fetch_content(
     State(state): State<AppState>,
     Path(content_id): Path<i32>,
+    auth_user: AuthUser,
 ) -> Result<Json<ContentDetails>, HttpError> {
     let mut conn = state.pool.get().await?;
 
@@ -60,12 +101,23 @@ pub async fn fetch_content(
         .load::<base::models::ContentValue>(&mut conn)
         .await?;
 
+    let mut transitions = vec![];
+
+    for transition in load_workflow(&mut conn).await? {
+        if transition.from == content.stage
+            && has_auth_user_permission(&mut conn, &auth_user, transition.permission).await?
+        {
+            transitions.push(transition.to);
+        }
+    }
+
     let user = user.map(|u| crate::responses::User { id: u.0, name: u.1 });
 
     Ok(Json(ContentDetails {
         content,
         values,
         user,
+        transitions,
     }))
 }
 
@@ -73,7 +125,7 @@ pub async fn create_content(
     State(state): State<AppState>,
     Extension(options): Extension<Options>,
     user: AuthUser,
-    Json(req): Json<CreateContent>,
+    Json(mut req): Json<CreateContent>,
 ) -> Result<Json<Content>, HttpError> {
     let mut conn = state.pool.get().await?;
 
@@ -138,15 +190,38 @@ pub async fn create_content(
         return Err(HttpError::bad_request(err));
     }
 
-    if req.values.iter().any(|v| {
-        model_fields
+    for value in req.values.iter_mut() {
+        if let Some((_, field)) = model_fields
             .iter()
-            .find(|mf| mf.0.id == v.model_field_id)
-            .is_none()
-    }) {
-        return Err(HttpError::not_found("model_field_not_found"));
+            .find(|mf| mf.0.id == value.model_field_id)
+        {
+            value.value =
+                sanitize_value(FieldKind::of(&field.kind), std::mem::take(&mut value.value));
+        }
     }
 
+    let values = req
+        .values
+        .iter()
+        .map(|value| {
+            let Some((model_field, field)) = model_fields
+                .iter()
+                .find(|mf| mf.0.id == value.model_field_id)
+            else {
+                return Err(HttpError::not_found("model_field_not_found"));
+            };
+
+            Ok(FieldValue {
+                model_field,
+                field,
+                locale: value.locale.as_deref(),
+                value: &value.value,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    validate_values(&mut conn, None, &values).await?;
+
     let content = conn
         .transaction(|conn| {
             async move {
@@ -165,11 +240,19 @@ pub async fn create_content(
                         req.values
                             .into_iter()
                             .map(|v| {
+                                let search_text = model_fields
+                                    .iter()
+                                    .find(|mf| mf.0.id == v.model_field_id)
+                                    .and_then(|(_, field)| {
+                                        search_text(FieldKind::of(&field.kind), &v.value)
+                                    });
+
                                 (
                                     content_values::content_id.eq(content.id),
                                     content_values::model_field_id.eq(v.model_field_id),
                                     content_values::locale.eq(v.locale),
                                     content_values::value.eq(v.value),
+                                    content_values::search_text.eq(search_text),
                                 )
                             })
                             .collect::<Vec<_>>(),
@@ -178,6 +261,8 @@ pub async fn create_content(
                     .execute(conn)
                     .await?;
 
+                create_revision(conn, content.id, user.id, RevisionAction::Create).await?;
+
                 Result::<Content, HttpError>::Ok(content)
             }
             .scope_boxed()
@@ -190,26 +275,40 @@ pub async fn create_content(
 pub async fn update_content(
     State(state): State<AppState>,
     Path(content_id): Path<i32>,
+    user: AuthUser,
     Json(req): Json<UpdateContent>,
 ) -> Result<(), HttpError> {
-    let effected_row: usize = diesel::update(contents::table)
-        .filter(contents::id.eq(content_id))
-        .set(contents::name.eq(req.name))
-        .execute(&mut state.pool.get().await?)
-        .await?;
+    state
+        .pool
+        .get()
+        .await?
+        .transaction(|conn| {
+            async move {
+                authorize_modification(conn, &user, content_id).await?;
 
-    if effected_row == 0 {
-        return Err(HttpError::not_found("content_not_found"));
-    }
+                let effected_row: usize = diesel::update(contents::table)
+                    .filter(contents::id.eq(content_id))
+                    .set(contents::name.eq(req.name))
+                    .execute(conn)
+                    .await?;
 
-    Ok(())
+                if effected_row == 0 {
+                    return Err(HttpError::not_found("content_not_found"));
+                }
+
+                create_revision(conn, content_id, user.id, RevisionAction::UpdateName).await
+            }
+            .scope_boxed()
+        })
+        .await
 }
 
 pub async fn create_content_value(
     State(state): State<AppState>,
     Extension(options): Extension<Options>,
     Path(content_id): Path<i32>,
-    Json(req): Json<ContentValue>,
+    user: AuthUser,
+    Json(mut req): Json<ContentValue>,
 ) -> Result<Json<base::models::ContentValue>, HttpError> {
     let mut conn = state.pool.get().await?;
 
@@ -224,6 +323,8 @@ pub async fn create_content_value(
         return Err(HttpError::not_found("content_not_found"));
     };
 
+    authorize_modification(&mut conn, &user, content_id).await?;
+
     let Some(model_field) = fields::table
         .inner_join(model_fields::table)
         .filter(
@@ -256,6 +357,38 @@ pub async fn create_content_value(
         }
     }
 
+    req.value = sanitize_value(FieldKind::of(&model_field.1.kind), req.value);
+
+    let value = FieldValue {
+        model_field: &model_field.0,
+        field: &model_field.1,
+        locale: req.locale.as_deref(),
+        value: &req.value,
+    };
+
+    validate_values(&mut conn, Some(content_id), &[value]).await?;
+
+    if let Some(max_items) = model_field.0.validation.max_items {
+        let mut query = content_values::table
+            .filter(content_values::content_id.eq(content_id))
+            .filter(content_values::model_field_id.eq(model_field.0.id))
+            .into_boxed();
+
+        query = match req.locale.as_ref() {
+            Some(locale) => query.filter(content_values::locale.eq(locale)),
+            None => query.filter(content_values::locale.is_null()),
+        };
+
+        let items = query.count().get_result::<i64>(&mut conn).await?;
+
+        if items >= i64::from(max_items) {
+            let mut errors = Errors::new();
+            errors.insert_field(model_field.0.key.clone(), "too_many_items");
+
+            return Err(HttpError::validation_errors(errors));
+        }
+    }
+
     if !model_field.0.multiple {
         let query = if let Some(locale) = req.locale.as_ref() {
             content_values::table
@@ -285,83 +418,285 @@ pub async fn create_content_value(
         }
     }
 
-    diesel::insert_into(content_values::table)
-        .values((
-            content_values::content_id.eq(content_id),
-            content_values::model_field_id.eq(model_field.0.id),
-            content_values::locale.eq(req.locale),
-            content_values::value.eq(req.value),
-        ))
-        .get_result::<base::models::ContentValue>(&mut conn)
-        .await
-        .map(Json)
-        .map_err(Into::into)
+    let search_text = search_text(FieldKind::of(&model_field.1.kind), &req.value);
+
+    let value = conn
+        .transaction(|conn| {
+            async move {
+                let value = diesel::insert_into(content_values::table)
+                    .values((
+                        content_values::content_id.eq(content_id),
+                        content_values::model_field_id.eq(model_field.0.id),
+                        content_values::locale.eq(req.locale),
+                        content_values::value.eq(req.value),
+                        content_values::search_text.eq(search_text),
+                    ))
+                    .get_result::<base::models::ContentValue>(conn)
+                    .await?;
+
+                create_revision(conn, content_id, user.id, RevisionAction::CreateValue).await?;
+
+                Result::<base::models::ContentValue, HttpError>::Ok(value)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(Json(value))
 }
 
 pub async fn update_content_stage(
     State(state): State<AppState>,
     Path(content_id): Path<i32>,
+    user: AuthUser,
     Json(req): Json<UpdateContentStage>,
 ) -> Result<(), HttpError> {
-    let effected_row: usize = diesel::update(contents::table)
-        .filter(contents::id.eq(content_id))
-        .set(contents::stage.eq(req.stage))
-        .execute(&mut state.pool.get().await?)
-        .await?;
+    state
+        .pool
+        .get()
+        .await?
+        .transaction(|conn| {
+            async move {
+                let Some(stage) = contents::table
+                    .filter(contents::id.eq(content_id))
+                    .select(contents::stage)
+                    .first::<ContentStage>(conn)
+                    .await
+                    .optional()?
+                else {
+                    return Err(HttpError::not_found("content_not_found"));
+                };
+
+                let workflow = load_workflow(conn).await?;
+
+                let Some(perm) = transition_permission(&workflow, &stage, &req.stage) else {
+                    return Err(HttpError::unprocessable_entity("invalid_stage_transition"));
+                };
+
+                if !has_auth_user_permission(conn, &user, perm).await? {
+                    return Err(HttpError::forbidden("access_denied"));
+                }
+
+                diesel::update(contents::table)
+                    .filter(contents::id.eq(content_id))
+                    .set(contents::stage.eq(req.stage))
+                    .execute(conn)
+                    .await?;
 
-    if effected_row == 0 {
-        return Err(HttpError::not_found("content_not_found"));
-    }
+                create_revision(conn, content_id, user.id, RevisionAction::UpdateStage).await
+            }
+            .scope_boxed()
+        })
+        .await
+}
 
-    Ok(())
+/// Sets the time window in which a published content is visible on the site. Passing `null` for
+/// both fields clears the schedule.
+pub async fn update_content_schedule(
+    State(state): State<AppState>,
+    Path(content_id): Path<i32>,
+    user: AuthUser,
+    Valid(Json(req)): Valid<Json<UpdateContentSchedule>>,
+) -> Result<(), HttpError> {
+    state
+        .pool
+        .get()
+        .await?
+        .transaction(|conn| {
+            async move {
+                authorize_modification(conn, &user, content_id).await?;
+
+                let effected_row: usize = diesel::update(contents::table)
+                    .filter(contents::id.eq(content_id))
+                    .set((
+                        contents::publish_at.eq(req.publish_at),
+                        contents::unpublish_at.eq(req.unpublish_at),
+                    ))
+                    .execute(conn)
+                    .await?;
+
+                if effected_row == 0 {
+                    return Err(HttpError::not_found("content_not_found"));
+                }
+
+                create_revision(conn, content_id, user.id, RevisionAction::UpdateSchedule).await
+            }
+            .scope_boxed()
+        })
+        .await
 }
 
 pub async fn update_content_value(
     State(state): State<AppState>,
     Path(value_id): Path<i32>,
+    user: AuthUser,
     Json(req): Json<UpdateContentValue>,
 ) -> Result<(), HttpError> {
-    let effected_row: usize = diesel::update(content_values::table)
-        .filter(content_values::id.eq(value_id))
-        .set(content_values::value.eq(req.value))
-        .execute(&mut state.pool.get().await?)
-        .await?;
-
-    if effected_row == 0 {
-        return Err(HttpError::not_found("content_value_not_found"));
-    }
-
-    Ok(())
+    state
+        .pool
+        .get()
+        .await?
+        .transaction(|conn| {
+            async move {
+                let Some((current, model_field, field)) = content_values::table
+                    .inner_join(model_fields::table.inner_join(fields::table))
+                    .filter(content_values::id.eq(value_id))
+                    .select((
+                        content_values::all_columns,
+                        model_fields::all_columns,
+                        fields::all_columns,
+                    ))
+                    .first::<(
+                        base::models::ContentValue,
+                        base::models::ModelField,
+                        base::models::Field,
+                    )>(conn)
+                    .await
+                    .optional()?
+                else {
+                    return Err(HttpError::not_found("content_value_not_found"));
+                };
+
+                authorize_modification(conn, &user, current.content_id).await?;
+
+                let new_value = sanitize_value(FieldKind::of(&field.kind), req.value);
+
+                let value = FieldValue {
+                    model_field: &model_field,
+                    field: &field,
+                    locale: current.locale.as_deref(),
+                    value: &new_value,
+                };
+
+                validate_values(conn, Some(current.content_id), &[value]).await?;
+
+                let search_text = search_text(FieldKind::of(&field.kind), &new_value);
+
+                let Some(content_id) = diesel::update(content_values::table)
+                    .filter(content_values::id.eq(value_id))
+                    .set((
+                        content_values::value.eq(new_value),
+                        content_values::search_text.eq(search_text),
+                    ))
+                    .returning(content_values::content_id)
+                    .get_result::<i32>(conn)
+                    .await
+                    .optional()?
+                else {
+                    return Err(HttpError::not_found("content_value_not_found"));
+                };
+
+                create_revision(conn, content_id, user.id, RevisionAction::UpdateValue).await
+            }
+            .scope_boxed()
+        })
+        .await
 }
 
 pub async fn delete_content(
     State(state): State<AppState>,
     Path(content_id): Path<i32>,
+    user: AuthUser,
 ) -> Result<(), HttpError> {
-    let effected_row: usize = diesel::delete(contents::table)
-        .filter(contents::id.eq(content_id))
-        .execute(&mut state.pool.get().await?)
-        .await?;
+    state
+        .pool
+        .get()
+        .await?
+        .transaction(|conn| {
+            async move {
+                authorize_modification(conn, &user, content_id).await?;
+
+                if is_referenced(conn, content_id).await? {
+                    return Err(HttpError::conflict("content_being_referenced"));
+                }
+
+                let effected_row: usize = diesel::delete(contents::table)
+                    .filter(contents::id.eq(content_id))
+                    .execute(conn)
+                    .await?;
+
+                if effected_row == 0 {
+                    return Err(HttpError::not_found("content_not_found"));
+                }
+
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await
+}
 
-    if effected_row == 0 {
+/// Makes sure that the user is allowed to modify the content in its current stage, see
+/// [`modify_permission`].
+pub(crate) async fn authorize_modification(
+    conn: &mut Connection,
+    user: &AuthUser,
+    content_id: i32,
+) -> Result<(), HttpError> {
+    let Some(stage) = contents::table
+        .filter(contents::id.eq(content_id))
+        .select(contents::stage)
+        .first::<ContentStage>(conn)
+        .await
+        .optional()?
+    else {
         return Err(HttpError::not_found("content_not_found"));
+    };
+
+    let workflow = load_workflow(conn).await?;
+
+    if !has_auth_user_permission(conn, user, modify_permission(&workflow, &stage)).await? {
+        return Err(HttpError::forbidden("access_denied"));
     }
 
     Ok(())
 }
 
+/// Checks whether the content is referred by a reference field of another content. References of
+/// the content to itself do not prevent it from being deleted.
+async fn is_referenced(conn: &mut Connection, content_id: i32) -> Result<bool, HttpError> {
+    let reference_fields = model_fields::table
+        .inner_join(fields::table)
+        .filter(fields::kind.eq(FieldKind::Reference.as_str()))
+        .select(model_fields::id);
+
+    diesel::dsl::select(diesel::dsl::exists(
+        content_values::table
+            .filter(content_values::model_field_id.eq_any(reference_fields))
+            .filter(content_values::value.eq(content_id.to_string()))
+            .filter(content_values::content_id.ne(content_id)),
+    ))
+    .get_result::<bool>(conn)
+    .await
+    .map_err(Into::into)
+}
+
 pub async fn delete_content_value(
     State(state): State<AppState>,
     Path(value_id): Path<i32>,
+    user: AuthUser,
 ) -> Result<(), HttpError> {
-    let effected_row: usize = diesel::delete(content_values::table)
-        .filter(content_values::id.eq(value_id))
-        .execute(&mut state.pool.get().await?)
-        .await?;
-
-    if effected_row == 0 {
-        return Err(HttpError::not_found("value_not_found"));
-    }
-
-    Ok(())
+    state
+        .pool
+        .get()
+        .await?
+        .transaction(|conn| {
+            async move {
+                let Some(content_id) = diesel::delete(content_values::table)
+                    .filter(content_values::id.eq(value_id))
+                    .returning(content_values::content_id)
+                    .get_result::<i32>(conn)
+                    .await
+                    .optional()?
+                else {
+                    return Err(HttpError::not_found("value_not_found"));
+                };
+
+                authorize_modification(conn, &user, content_id).await?;
+
+                create_revision(conn, content_id, user.id, RevisionAction::DeleteValue).await
+            }
+            .scope_boxed()
+        })
+        .await
 }
@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderName},
+    Extension, Json,
+};
+use base::{
+    config::Options,
+    content::{query_contents, ContentQueryError, Filter, FilterOp, Order},
+    db::Connection,
+    field::{to_json, FieldKind},
+    l10n::negotiate_locale,
+    paginate::{CountStarOver, Paginate, Pagination},
+    responses::HttpError,
+    schema::{content_values, contents, fields, model_fields, models},
+    AppState,
+};
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde_json::Value;
+
+use crate::responses::DeliveredContent;
+
+pub(crate) const MAX_PER_PAGE: i64 = 100;
+
+/// Contents are public, so shared caches may keep them for a short while. The locale is resolved
+/// from these headers, responses need to vary on them.
+const CACHE_HEADERS: [(HeaderName, &str); 2] = [
+    (header::CACHE_CONTROL, "public, max-age=60"),
+    (header::VARY, "Accept-Language, Cookie"),
+];
+
+#[derive(Debug, Default, PartialEq)]
+struct DeliveryQuery {
+    locale: Option<String>,
+    fields: Option<Vec<String>>,
+    filters: Vec<Filter>,
+    order: Option<Order>,
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+pub(crate) fn invalid_query(context: String) -> HttpError {
+    HttpError::unprocessable_entity("invalid_query").with_context(context)
+}
+
+fn parse_int(key: &str, value: &str) -> Result<i64, HttpError> {
+    value
+        .parse::<i64>()
+        .map_err(|_| invalid_query(format!("{key} requires an integer value, got {value}")))
+}
+
+/// Parses a filter given in `field:op:value` form.
+pub(crate) fn parse_filter(value: &str) -> Result<Filter, HttpError> {
+    let mut parts = value.splitn(3, ':');
+
+    let (Some(field), Some(op), Some(operand)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid_query(format!(
+            "Filter needs to be in field:op:value form, got {value}"
+        )));
+    };
+
+    let op = match op {
+        "eq" => FilterOp::Eq(operand.to_string()),
+        "in" => FilterOp::In(operand.split(',').map(ToString::to_string).collect()),
+        "lt" => FilterOp::Lt(parse_int("lt", operand)?),
+        "le" => FilterOp::Le(parse_int("le", operand)?),
+        "gt" => FilterOp::Gt(parse_int("gt", operand)?),
+        "ge" => FilterOp::Ge(parse_int("ge", operand)?),
+        op => return Err(invalid_query(format!("Unknown filter operator {op}"))),
+    };
+
+    Ok(Filter {
+        field: field.to_string(),
+        op,
+    })
+}
+
+/// Parses a field to order by, a leading `-` orders in descending order.
+pub(crate) fn parse_order(value: String) -> Order {
+    match value.strip_prefix('-') {
+        Some(field) => Order {
+            field: field.to_string(),
+            desc: true,
+        },
+        None => Order {
+            field: value,
+            desc: false,
+        },
+    }
+}
+
+/// Parses query parameters of the form
+/// `locale=en&fields=title,body&filter=weight:gt:3&sort=-created_at&page=2&perPage=10`.
+///
+/// Filters are given as `field:op:value` where op is one of `eq`, `in`, `lt`, `le`, `gt`, `ge`.
+/// Values of `in` are separated by commas.
+fn parse_query(params: Vec<(String, String)>) -> Result<DeliveryQuery, HttpError> {
+    let mut query = DeliveryQuery::default();
+
+    for (key, value) in params {
+        match key.as_str() {
+            "locale" => query.locale = Some(value),
+            "fields" => {
+                query.fields = Some(value.split(',').map(|f| f.trim().to_string()).collect())
+            }
+            "filter" => query.filters.push(parse_filter(&value)?),
+            "sort" => query.order = Some(parse_order(value)),
+            "page" => query.page = Some(parse_int("page", &value)?),
+            "perPage" => {
+                query.per_page = Some(parse_int("perPage", &value)?.clamp(1, MAX_PER_PAGE))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(query)
+}
+
+/// Resolves the locale the same way pages are served, except that an explicitly requested locale
+/// takes precedence.
+pub(crate) fn resolve_locale(
+    options: &Options,
+    headers: &HeaderMap,
+    requested: Option<&str>,
+) -> Result<String, HttpError> {
+    let locales = options
+        .locales()
+        .iter()
+        .map(|l| l.to_string())
+        .collect::<Vec<_>>();
+
+    if let Some(requested) = requested {
+        return locales
+            .into_iter()
+            .find(|l| l == requested)
+            .ok_or(HttpError::unprocessable_entity("unsupported_locale"));
+    }
+
+    Ok(negotiate_locale(headers, locales.iter(), |l| l.as_str())
+        .cloned()
+        .unwrap_or_else(|| options.default_locale().to_string()))
+}
+
+async fn public_model_id(
+    conn: &mut Connection,
+    options: &Options,
+    model: &str,
+) -> Result<i32, HttpError> {
+    models::table
+        .filter(
+            models::key.eq(model).and(
+                models::namespace
+                    .is_null()
+                    .or(models::namespace.eq(&*options.theme())),
+            ),
+        )
+        .filter(models::public.eq(true))
+        .select(models::id)
+        .first::<i32>(conn)
+        .await
+        .optional()?
+        .ok_or(HttpError::not_found("model_not_found"))
+}
+
+/// Loads the values of given contents in the locale, keyed by the model field key. Only the
+/// requested fields are loaded if `only` is given.
+async fn load_values(
+    conn: &mut Connection,
+    model_id: i32,
+    content_ids: Vec<i32>,
+    locale: &str,
+    only: Option<&[String]>,
+) -> Result<HashMap<i32, serde_json::Map<String, Value>>, HttpError> {
+    let mfs_query = model_fields::table
+        .inner_join(fields::table)
+        .filter(model_fields::model_id.eq(model_id))
+        .select((
+            model_fields::id,
+            model_fields::key,
+            model_fields::multiple,
+            fields::kind,
+        ))
+        .order(model_fields::id.asc())
+        .into_boxed();
+
+    let model_fields = if let Some(only) = only {
+        mfs_query.filter(model_fields::key.eq_any(only))
+    } else {
+        mfs_query
+    }
+    .load::<(i32, String, bool, String)>(conn)
+    .await?;
+
+    let values = content_values::table
+        .filter(content_values::content_id.eq_any(&content_ids))
+        .filter(content_values::model_field_id.eq_any(model_fields.iter().map(|mf| mf.0)))
+        .filter(
+            content_values::locale
+                .eq(locale)
+                .or(content_values::locale.is_null()),
+        )
+        .order(content_values::id.asc())
+        .select((
+            content_values::content_id,
+            content_values::model_field_id,
+            content_values::value,
+        ))
+        .load::<(i32, i32, String)>(conn)
+        .await?;
+
+    let mut contents = content_ids
+        .into_iter()
+        .map(|id| {
+            let map = model_fields
+                .iter()
+                .map(|(_, key, multiple, _)| {
+                    let empty = if *multiple {
+                        Value::Array(vec![])
+                    } else {
+                        Value::Null
+                    };
+
+                    (key.clone(), empty)
+                })
+                .collect();
+
+            (id, map)
+        })
+        .collect::<HashMap<_, serde_json::Map<_, _>>>();
+
+    for (content_id, model_field_id, value) in values {
+        let Some((_, key, multiple, kind)) = model_fields.iter().find(|mf| mf.0 == model_field_id)
+        else {
+            continue;
+        };
+
+        let Some(entry) = contents
+            .get_mut(&content_id)
+            .and_then(|content| content.get_mut(key))
+        else {
+            continue;
+        };
+
+        match entry {
+            Value::Array(values) if *multiple => values.push(to_json(FieldKind::of(kind), value)),
+            Value::Null => *entry = to_json(FieldKind::of(kind), value),
+            _ => {}
+        }
+    }
+
+    Ok(contents)
+}
+
+pub(crate) fn query_error(e: ContentQueryError) -> HttpError {
+    match e {
+        ContentQueryError::Invalid(context) => invalid_query(context),
+        ContentQueryError::Database(e) => e.into(),
+    }
+}
+
+/// Lists the contents of a public model that are visible on the site.
+pub async fn fetch_contents(
+    State(state): State<AppState>,
+    Extension(options): Extension<Options>,
+    Path(model): Path<String>,
+    Query(params): Query<Vec<(String, String)>>,
+    headers: HeaderMap,
+) -> Result<
+    (
+        [(HeaderName, &'static str); 2],
+        Json<Pagination<DeliveredContent>>,
+    ),
+    HttpError,
+> {
+    let query = parse_query(params)?;
+    let locale = resolve_locale(&options, &headers, query.locale.as_deref())?;
+
+    let mut conn = state.pool.get().await?;
+
+    let model_id = public_model_id(&mut conn, &options, &model).await?;
+
+    let contents = query_contents(
+        &mut conn,
+        model_id,
+        &locale,
+        query.filters,
+        query.order.as_ref(),
+        Utc::now().naive_utc(),
+    )
+    .await
+    .map_err(query_error)?
+    .select((
+        (contents::id, contents::created_at, contents::updated_at),
+        CountStarOver,
+    ))
+    .paginate(query.page)
+    .per_page(query.per_page)
+    .load_and_count_pages::<(i32, NaiveDateTime, NaiveDateTime)>(&mut conn)
+    .await?;
+
+    let mut values = load_values(
+        &mut conn,
+        model_id,
+        contents.items().iter().map(|c| c.0).collect(),
+        &locale,
+        query.fields.as_deref(),
+    )
+    .await?;
+
+    let contents = contents.map(|(id, created_at, updated_at)| DeliveredContent {
+        id,
+        created_at,
+        updated_at,
+        values: values.remove(&id).unwrap_or_default(),
+    });
+
+    Ok((CACHE_HEADERS, Json(contents)))
+}
+
+/// Fetches a single content of a public model, if it is visible on the site.
+pub async fn fetch_content(
+    State(state): State<AppState>,
+    Extension(options): Extension<Options>,
+    Path((model, content_id)): Path<(String, i32)>,
+    Query(params): Query<Vec<(String, String)>>,
+    headers: HeaderMap,
+) -> Result<([(HeaderName, &'static str); 2], Json<DeliveredContent>), HttpError> {
+    let query = parse_query(params)?;
+    let locale = resolve_locale(&options, &headers, query.locale.as_deref())?;
+
+    let mut conn = state.pool.get().await?;
+
+    let model_id = public_model_id(&mut conn, &options, &model).await?;
+
+    let filter = Filter {
+        field: "id".to_string(),
+        op: FilterOp::Eq(content_id.to_string()),
+    };
+
+    let (id, created_at, updated_at) = query_contents(
+        &mut conn,
+        model_id,
+        &locale,
+        vec![filter],
+        None,
+        Utc::now().naive_utc(),
+    )
+    .await
+    .map_err(query_error)?
+    .select((contents::id, contents::created_at, contents::updated_at))
+    .first::<(i32, NaiveDateTime, NaiveDateTime)>(&mut conn)
+    .await
+    .optional()?
+    .ok_or(HttpError::not_found("content_not_found"))?;
+
+    let mut values = load_values(
+        &mut conn,
+        model_id,
+        vec![id],
+        &locale,
+        query.fields.as_deref(),
+    )
+    .await?;
+
+    Ok((
+        CACHE_HEADERS,
+        Json(DeliveredContent {
+            id,
+            created_at,
+            updated_at,
+            values: values.remove(&id).unwrap_or_default(),
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use base::content::{Filter, FilterOp, Order};
+
+    use super::{parse_query, DeliveryQuery, MAX_PER_PAGE};
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn it_parses_delivery_queries() {
+        let query = parse_query(params(&[
+            ("locale", "en"),
+            ("fields", "title, weight"),
+            ("filter", "weight:ge:3"),
+            ("filter", "slug:in:a,b"),
+            ("filter", "title:eq:a:b"),
+            ("sort", "-weight"),
+            ("perPage", "1000"),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            DeliveryQuery {
+                locale: Some("en".to_string()),
+                fields: Some(vec!["title".to_string(), "weight".to_string()]),
+                filters: vec![
+                    Filter {
+                        field: "weight".to_string(),
+                        op: FilterOp::Ge(3),
+                    },
+                    Filter {
+                        field: "slug".to_string(),
+                        op: FilterOp::In(vec!["a".to_string(), "b".to_string()]),
+                    },
+                    Filter {
+                        field: "title".to_string(),
+                        op: FilterOp::Eq("a:b".to_string()),
+                    },
+                ],
+                order: Some(Order {
+                    field: "weight".to_string(),
+                    desc: true,
+                }),
+                page: None,
+                per_page: Some(MAX_PER_PAGE),
+            },
+            query
+        );
+    }
+
+    #[test]
+    fn it_rejects_malformed_filters() {
+        for filter in ["weight", "weight:gt", "weight:gt:three", "weight:like:3"] {
+            let err = parse_query(params(&[("filter", filter)])).unwrap_err();
+
+            assert_eq!("invalid_query", err.error);
+        }
+    }
+}
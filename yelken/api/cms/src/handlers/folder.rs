@@ -0,0 +1,202 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use base::{
+    db::Connection,
+    models::AssetFolder,
+    responses::HttpError,
+    schema::{asset_folders, assets},
+    validate::Valid,
+    AppState,
+};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use crate::requests::{CreateAssetFolder, UpdateAssetFolder};
+
+pub async fn fetch_folders(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<AssetFolder>>, HttpError> {
+    asset_folders::table
+        .order(asset_folders::name.asc())
+        .load::<AssetFolder>(&mut state.pool.get().await?)
+        .await
+        .map(Json)
+        .map_err(Into::into)
+}
+
+pub async fn create_folder(
+    State(state): State<AppState>,
+    Valid(Json(req)): Valid<Json<CreateAssetFolder>>,
+) -> Result<Json<AssetFolder>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    check_folder(&mut conn, None, req.parent_id, &req.name).await?;
+
+    diesel::insert_into(asset_folders::table)
+        .values((
+            asset_folders::name.eq(req.name),
+            asset_folders::parent_id.eq(req.parent_id),
+        ))
+        .get_result::<AssetFolder>(&mut conn)
+        .await
+        .map(Json)
+        .map_err(Into::into)
+}
+
+pub async fn update_folder(
+    State(state): State<AppState>,
+    Path(folder_id): Path<i32>,
+    Valid(Json(req)): Valid<Json<UpdateAssetFolder>>,
+) -> Result<Json<AssetFolder>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    check_folder(&mut conn, Some(folder_id), req.parent_id, &req.name).await?;
+
+    diesel::update(asset_folders::table)
+        .filter(asset_folders::id.eq(folder_id))
+        .set((
+            asset_folders::name.eq(req.name),
+            asset_folders::parent_id.eq(req.parent_id),
+        ))
+        .get_result::<AssetFolder>(&mut conn)
+        .await
+        .optional()?
+        .map(Json)
+        .ok_or_else(|| HttpError::not_found("folder_not_found"))
+}
+
+pub async fn delete_folder(
+    State(state): State<AppState>,
+    Path(folder_id): Path<i32>,
+) -> Result<(), HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let not_empty = diesel::dsl::select(
+        diesel::dsl::exists(assets::table.filter(assets::folder_id.eq(folder_id))).or(
+            diesel::dsl::exists(
+                asset_folders::table.filter(asset_folders::parent_id.eq(folder_id)),
+            ),
+        ),
+    )
+    .get_result::<bool>(&mut conn)
+    .await?;
+
+    if not_empty {
+        return Err(HttpError::conflict("folder_not_empty"));
+    }
+
+    let effected_row = diesel::delete(asset_folders::table)
+        .filter(asset_folders::id.eq(folder_id))
+        .execute(&mut conn)
+        .await?;
+
+    if effected_row == 0 {
+        return Err(HttpError::not_found("folder_not_found"));
+    }
+
+    Ok(())
+}
+
+/// Checks that the parent exists, placing the folder under it does not create a cycle and there is
+/// no other folder with the same name under it.
+async fn check_folder(
+    conn: &mut Connection,
+    folder_id: Option<i32>,
+    parent_id: Option<i32>,
+    name: &str,
+) -> Result<(), HttpError> {
+    let mut ancestor = parent_id;
+
+    while let Some(id) = ancestor {
+        if Some(id) == folder_id {
+            return Err(HttpError::validation_errors_with(
+                "parentId",
+                "folder_cannot_be_moved_into_itself",
+            ));
+        }
+
+        ancestor = asset_folders::table
+            .filter(asset_folders::id.eq(id))
+            .select(asset_folders::parent_id)
+            .first::<Option<i32>>(conn)
+            .await
+            .optional()?
+            .ok_or_else(|| HttpError::not_found("parent_folder_not_found"))?;
+    }
+
+    let mut query = asset_folders::table
+        .filter(asset_folders::name.eq(name))
+        .into_boxed();
+
+    query = match parent_id {
+        Some(parent_id) => query.filter(asset_folders::parent_id.eq(parent_id)),
+        None => query.filter(asset_folders::parent_id.is_null()),
+    };
+
+    if let Some(folder_id) = folder_id {
+        query = query.filter(asset_folders::id.ne(folder_id));
+    }
+
+    let exists = diesel::dsl::select(diesel::dsl::exists(query))
+        .get_result::<bool>(conn)
+        .await?;
+
+    if exists {
+        return Err(HttpError::conflict("folder_already_exists"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use base::test::{create_pool, DB_CONFIG};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn it_rejects_folder_cycles_and_duplicate_names() {
+        let pool = create_pool(DB_CONFIG).await;
+
+        let parent = diesel::insert_into(asset_folders::table)
+            .values(asset_folders::name.eq("photos"))
+            .get_result::<AssetFolder>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let child = diesel::insert_into(asset_folders::table)
+            .values((
+                asset_folders::name.eq("2025"),
+                asset_folders::parent_id.eq(parent.id),
+            ))
+            .get_result::<AssetFolder>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let mut conn = pool.get().await.unwrap();
+
+        assert!(
+            check_folder(&mut conn, Some(parent.id), Some(child.id), "photos")
+                .await
+                .is_err_and(|e| e.error == "validation_errors")
+        );
+
+        assert!(check_folder(&mut conn, None, None, "photos")
+            .await
+            .is_err_and(|e| e.error == "folder_already_exists"));
+
+        assert!(check_folder(&mut conn, Some(parent.id), None, "photos")
+            .await
+            .is_ok());
+
+        assert!(check_folder(&mut conn, None, Some(parent.id), "2026")
+            .await
+            .is_ok());
+
+        assert!(check_folder(&mut conn, None, Some(-1), "photos")
+            .await
+            .is_err_and(|e| e.error == "parent_folder_not_found"));
+    }
+}
@@ -1,17 +1,413 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Instant,
+};
 
 use axum::{
     extract::{Path, Query, State},
-    http::{header, HeaderMap, StatusCode},
-    Form, Json,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    Extension, Form, Json,
+};
+use base::{
+    config::Options,
+    crypto::Crypto,
+    db::BatchQuery,
+    mail::{Mailer, Template},
+    middlewares::client_ip::ClientIp,
+    models::{FormField, FormFieldKind, FormSubmission},
+    paginate::{CountStarOver, Paginate, Pagination, PaginationRequest},
+    responses::HttpError,
+    schema::{form_fields, form_submissions, forms},
+    validate::{is_email, Valid},
+    AppState,
 };
-use base::{responses::HttpError, schema::form_submissions, AppState};
+use chrono::Utc;
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
 use url::Url;
 
-pub async fn fetch_forms() -> Result<Json<Vec<()>>, HttpError> {
-    Ok(Json(vec![]))
+use crate::{
+    protection::{protect, FormProtection, Verdict},
+    requests::{CreateForm, FilterByForm, UpdateForm},
+    responses::Submission,
+};
+
+pub async fn fetch_forms(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<crate::responses::Form>>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let forms = forms::table
+        .order(forms::id.asc())
+        .load::<base::models::Form>(&mut conn)
+        .await?;
+
+    let mut fields = form_fields::table
+        .order(form_fields::id.asc())
+        .load::<FormField>(&mut conn)
+        .await?;
+
+    Ok(Json(
+        forms
+            .into_iter()
+            .map(|form| {
+                let fields = fields.extract_if(.., |f| f.form_id == form.id).collect();
+
+                crate::responses::Form { form, fields }
+            })
+            .collect(),
+    ))
+}
+
+pub async fn create_form(
+    State(state): State<AppState>,
+    Valid(Json(req)): Valid<Json<CreateForm>>,
+) -> Result<Json<crate::responses::Form>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let exists = diesel::dsl::select(diesel::dsl::exists(
+        forms::table.filter(forms::key.eq(&req.key)),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await?;
+
+    if exists {
+        return Err(HttpError::conflict("form_already_exists"));
+    }
+
+    let (form, fields) = conn
+        .transaction(|conn| {
+            async move {
+                let form = diesel::insert_into(forms::table)
+                    .values((
+                        forms::key.eq(req.key),
+                        forms::name.eq(req.name),
+                        forms::desc.eq(req.desc),
+                        forms::notify_email.eq(req.notify_email),
+                        forms::honeypot.eq(req.protection.honeypot),
+                        forms::min_fill_seconds.eq(req.protection.min_fill_seconds),
+                        forms::ip_hourly_limit.eq(req.protection.ip_hourly_limit),
+                        forms::hourly_limit.eq(req.protection.hourly_limit),
+                        forms::challenge.eq(req.protection.challenge),
+                    ))
+                    .get_result::<base::models::Form>(conn)
+                    .await?;
+
+                let fields = diesel::insert_into(form_fields::table)
+                    .values(
+                        req.fields
+                            .into_iter()
+                            .map(|f| {
+                                (
+                                    form_fields::form_id.eq(form.id),
+                                    form_fields::key.eq(f.key),
+                                    form_fields::name.eq(f.name),
+                                    form_fields::kind.eq(f.kind),
+                                    form_fields::required.eq(f.required),
+                                    form_fields::min_length.eq(f.min_length),
+                                    form_fields::max_length.eq(f.max_length),
+                                )
+                            })
+                            .collect::<Vec<_>>(),
+                    )
+                    .batched()
+                    .get_results::<FormField>(conn)
+                    .await?;
+
+                Result::<(base::models::Form, Vec<FormField>), HttpError>::Ok((form, fields))
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(Json(crate::responses::Form { form, fields }))
+}
+
+pub async fn update_form(
+    State(state): State<AppState>,
+    Path(form_id): Path<i32>,
+    Valid(Json(req)): Valid<Json<UpdateForm>>,
+) -> Result<Json<base::models::Form>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let clashes_with_field = match &req.protection.honeypot {
+        Some(honeypot) => {
+            diesel::dsl::select(diesel::dsl::exists(
+                form_fields::table
+                    .filter(form_fields::form_id.eq(form_id))
+                    .filter(form_fields::key.eq(honeypot)),
+            ))
+            .get_result::<bool>(&mut conn)
+            .await?
+        }
+        None => false,
+    };
+
+    if clashes_with_field {
+        return Err(HttpError::unprocessable_entity("honeypot_is_a_field_key"));
+    }
+
+    diesel::update(forms::table)
+        .filter(forms::id.eq(form_id))
+        .set((
+            forms::name.eq(req.name),
+            forms::desc.eq(req.desc),
+            forms::notify_email.eq(req.notify_email),
+            forms::honeypot.eq(req.protection.honeypot),
+            forms::min_fill_seconds.eq(req.protection.min_fill_seconds),
+            forms::ip_hourly_limit.eq(req.protection.ip_hourly_limit),
+            forms::hourly_limit.eq(req.protection.hourly_limit),
+            forms::challenge.eq(req.protection.challenge),
+        ))
+        .get_result::<base::models::Form>(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(|| HttpError::not_found("form_not_found"))
+        .map(Json)
+}
+
+/// Deletes the form along with its submissions.
+pub async fn delete_form(
+    State(state): State<AppState>,
+    Path(form_id): Path<i32>,
+) -> Result<(), HttpError> {
+    let effected_row = diesel::delete(forms::table)
+        .filter(forms::id.eq(form_id))
+        .execute(&mut state.pool.get().await?)
+        .await?;
+
+    if effected_row == 0 {
+        return Err(HttpError::not_found("form_not_found"));
+    }
+
+    Ok(())
+}
+
+fn into_submission(submission: FormSubmission) -> Submission {
+    // Submissions made before forms are defined do not hold JSON values
+    let values = serde_json::from_str(&submission.values)
+        .unwrap_or(serde_json::Value::String(submission.values));
+
+    Submission {
+        id: submission.id,
+        form_id: submission.form_id,
+        values,
+        created_at: submission.created_at,
+    }
+}
+
+pub async fn fetch_submissions(
+    State(state): State<AppState>,
+    Query(req): Query<FilterByForm>,
+    Query(page): Query<PaginationRequest>,
+) -> Result<Json<Pagination<Submission>>, HttpError> {
+    form_submissions::table
+        .filter(form_submissions::form_id.eq(req.form_id))
+        .select((form_submissions::all_columns, CountStarOver))
+        .order(form_submissions::id.desc())
+        .paginate(page.page)
+        .per_page(page.per_page)
+        .load_and_count_pages::<FormSubmission>(&mut state.pool.get().await?)
+        .await
+        .map(|page| Json(page.map(into_submission)))
+        .map_err(Into::into)
+}
+
+pub async fn fetch_submission(
+    State(state): State<AppState>,
+    Path(submission_id): Path<i32>,
+) -> Result<Json<Submission>, HttpError> {
+    form_submissions::table
+        .filter(form_submissions::id.eq(submission_id))
+        .first::<FormSubmission>(&mut state.pool.get().await?)
+        .await
+        .optional()?
+        .ok_or_else(|| HttpError::not_found("submission_not_found"))
+        .map(|s| Json(into_submission(s)))
+}
+
+pub async fn delete_submission(
+    State(state): State<AppState>,
+    Path(submission_id): Path<i32>,
+) -> Result<(), HttpError> {
+    let effected_row = diesel::delete(form_submissions::table)
+        .filter(form_submissions::id.eq(submission_id))
+        .execute(&mut state.pool.get().await?)
+        .await?;
+
+    if effected_row == 0 {
+        return Err(HttpError::not_found("submission_not_found"));
+    }
+
+    Ok(())
+}
+
+fn csv_field(value: &str, escape_formula: bool) -> String {
+    // Spreadsheet applications evaluate cells starting with these characters as formulas
+    let value = if escape_formula && value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn submissions_to_csv(fields: &[FormField], submissions: Vec<FormSubmission>) -> String {
+    let mut csv = ["id", "created_at"]
+        .into_iter()
+        .chain(fields.iter().map(|f| f.key.as_str()))
+        .map(|header| csv_field(header, true))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    csv.push_str("\r\n");
+
+    for submission in submissions {
+        let values =
+            serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&submission.values)
+                .unwrap_or_default();
+
+        let row = [
+            submission.id.to_string(),
+            submission
+                .created_at
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+        ]
+        .into_iter()
+        .chain(fields.iter().map(|f| {
+            let value = match values.get(&f.key) {
+                Some(serde_json::Value::String(s)) => s.clone(),
+                Some(serde_json::Value::Null) | None => String::new(),
+                Some(v) => v.to_string(),
+            };
+
+            csv_field(&value, f.kind != FormFieldKind::Int)
+        }))
+        .collect::<Vec<_>>()
+        .join(",");
+
+        csv.push_str(&row);
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+/// Exports all submissions of the form as CSV with a column for each field of the form.
+pub async fn export_submissions(
+    State(state): State<AppState>,
+    Path(form_id): Path<i32>,
+) -> Result<(HeaderMap, String), HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let key = forms::table
+        .filter(forms::id.eq(form_id))
+        .select(forms::key)
+        .first::<String>(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(|| HttpError::not_found("form_not_found"))?;
+
+    let fields = form_fields::table
+        .filter(form_fields::form_id.eq(form_id))
+        .order(form_fields::id.asc())
+        .load::<FormField>(&mut conn)
+        .await?;
+
+    let submissions = form_submissions::table
+        .filter(form_submissions::form_id.eq(form_id))
+        .order(form_submissions::id.asc())
+        .load::<FormSubmission>(&mut conn)
+        .await?;
+
+    let disposition =
+        HeaderValue::from_str(&format!("attachment; filename=\"{key}-submissions.csv\""))
+            .unwrap_or(HeaderValue::from_static("attachment"));
+
+    Ok((
+        HeaderMap::from_iter([
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/csv; charset=utf-8"),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+        ]),
+        submissions_to_csv(&fields, submissions),
+    ))
+}
+
+/// Validates submitted values against the fields of the form. Values not belonging to any field
+/// are dropped, empty values are treated as missing.
+fn validate_submission(
+    fields: &[FormField],
+    mut submitted: HashMap<String, String>,
+) -> Result<BTreeMap<String, serde_json::Value>, BTreeMap<String, Vec<&'static str>>> {
+    let mut values = BTreeMap::new();
+    let mut errors = BTreeMap::<String, Vec<&'static str>>::new();
+
+    for field in fields {
+        let Some(value) = submitted
+            .remove(&field.key)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+        else {
+            if field.required {
+                errors
+                    .entry(field.key.clone())
+                    .or_default()
+                    .push("required");
+            }
+
+            continue;
+        };
+
+        let mut field_errors = vec![];
+        let len = value.chars().count();
+
+        if field.min_length.is_some_and(|min| len < min as usize) {
+            field_errors.push("too_short");
+        }
+
+        if field.max_length.is_some_and(|max| len > max as usize) {
+            field_errors.push("too_long");
+        }
+
+        let value = match field.kind {
+            FormFieldKind::Int => match value.parse::<i64>() {
+                Ok(n) => serde_json::Value::from(n),
+                Err(_) => {
+                    field_errors.push("must_be_integer");
+                    serde_json::Value::Null
+                }
+            },
+            FormFieldKind::Email => {
+                if !is_email(&value) {
+                    field_errors.push("invalid_email");
+                }
+
+                serde_json::Value::String(value)
+            }
+            FormFieldKind::String | FormFieldKind::Multiline => serde_json::Value::String(value),
+        };
+
+        if field_errors.is_empty() {
+            values.insert(field.key.clone(), value);
+        } else {
+            errors.insert(field.key.clone(), field_errors);
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(values)
 }
 
 #[derive(serde::Deserialize)]
@@ -19,11 +415,18 @@ pub struct FormRedirect {
     redirect: Option<String>,
 }
 
+// Everything the handler needs is given by extractors
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_form_submissions(
     State(state): State<AppState>,
-    Path(name): Path<String>,
+    Extension(crypto): Extension<Crypto>,
+    Extension(mailer): Extension<Mailer>,
+    Extension(options): Extension<Options>,
+    Extension(protection): Extension<FormProtection>,
+    ClientIp(client_ip): ClientIp,
+    Path(key): Path<String>,
     Query(query): Query<FormRedirect>,
-    Form(form): Form<HashMap<String, String>>,
+    Form(mut submitted): Form<HashMap<String, String>>,
 ) -> Result<(StatusCode, HeaderMap), HttpError> {
     let redirect: header::HeaderValue = query
         .redirect
@@ -34,16 +437,209 @@ pub async fn handle_form_submissions(
         })
         .unwrap_or("/".parse().unwrap());
 
+    let mut conn = state.pool.get().await?;
+
+    let form = forms::table
+        .filter(forms::key.eq(&key))
+        .first::<base::models::Form>(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(|| HttpError::not_found("form_not_found"))?;
+
+    let verdict = protect(
+        &protection,
+        &crypto,
+        &form,
+        client_ip,
+        &mut submitted,
+        Instant::now(),
+        Utc::now().timestamp(),
+    )
+    .await?;
+
+    let response = (
+        StatusCode::SEE_OTHER,
+        HeaderMap::from_iter([(header::LOCATION, redirect)]),
+    );
+
+    if verdict == Verdict::Discard {
+        return Ok(response);
+    }
+
+    let fields = form_fields::table
+        .filter(form_fields::form_id.eq(form.id))
+        .order(form_fields::id.asc())
+        .load::<FormField>(&mut conn)
+        .await?;
+
+    let values = validate_submission(&fields, submitted).map_err(|errors| {
+        HttpError::unprocessable_entity("validation_errors").with_context(
+            serde_json::json!({ "fieldMessages": errors, "messages": [] }).to_string(),
+        )
+    })?;
+
+    let serialized = serde_json::to_string(&values).map_err(|e| {
+        HttpError::internal_server_error("failed_serializing_submission")
+            .with_context(format!("{e:?}"))
+    })?;
+
     diesel::insert_into(form_submissions::table)
         .values((
-            form_submissions::name.eq(name),
-            form_submissions::values.eq(format!("{form:?}")),
+            form_submissions::name.eq(key),
+            form_submissions::values.eq(serialized),
+            form_submissions::form_id.eq(form.id),
         ))
-        .execute(&mut state.pool.get().await?)
+        .execute(&mut conn)
         .await?;
 
-    Ok((
-        StatusCode::SEE_OTHER,
-        HeaderMap::from_iter([(header::LOCATION, redirect)]),
-    ))
+    if let Some(notify_email) = &form.notify_email {
+        let values = fields
+            .iter()
+            .filter_map(|f| match values.get(&f.key)? {
+                serde_json::Value::String(value) => Some(format!("{}: {value}", f.name)),
+                value => Some(format!("{}: {value}", f.name)),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // Submission is already stored, failing to notify should not be visible to the visitor
+        if let Err(e) = mailer
+            .send(
+                notify_email,
+                &options.default_locale(),
+                Template::FormSubmission {
+                    form: &form.name,
+                    values: &values,
+                    url: state.config.app_url.as_str(),
+                },
+            )
+            .await
+        {
+            log::error!(
+                "Failed to send notification of form {} submission, {e:?}",
+                form.key
+            );
+        }
+    }
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use base::models::{FormField, FormFieldKind, FormSubmission};
+    use chrono::NaiveDate;
+
+    use super::{submissions_to_csv, validate_submission};
+
+    fn field(key: &str, kind: FormFieldKind, required: bool) -> FormField {
+        FormField {
+            id: 1,
+            form_id: 1,
+            key: key.to_string(),
+            name: key.to_string(),
+            kind,
+            required,
+            min_length: None,
+            max_length: None,
+        }
+    }
+
+    fn submitted(values: &[(&str, &str)]) -> HashMap<String, String> {
+        values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn it_keeps_only_values_of_form_fields() {
+        let fields = [
+            field("email", FormFieldKind::Email, true),
+            field("age", FormFieldKind::Int, false),
+            field("message", FormFieldKind::Multiline, false),
+        ];
+
+        let values = validate_submission(
+            &fields,
+            submitted(&[
+                ("email", " john@example.com "),
+                ("age", "42"),
+                ("message", ""),
+                ("unknown", "value"),
+            ]),
+        )
+        .ok()
+        .unwrap();
+
+        assert_eq!(
+            r#"{"age":42,"email":"john@example.com"}"#,
+            serde_json::to_string(&values).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_reports_errors_of_invalid_values() {
+        let mut name = field("name", FormFieldKind::String, true);
+        name.min_length = Some(2);
+        name.max_length = Some(4);
+
+        let fields = [
+            name,
+            field("email", FormFieldKind::Email, true),
+            field("age", FormFieldKind::Int, false),
+            field("phone", FormFieldKind::String, true),
+        ];
+
+        let errors = validate_submission(
+            &fields,
+            submitted(&[("name", "Jonathan"), ("email", "john@"), ("age", "old")]),
+        )
+        .err()
+        .unwrap();
+
+        assert_eq!(vec!["too_long"], errors["name"]);
+        assert_eq!(vec!["invalid_email"], errors["email"]);
+        assert_eq!(vec!["must_be_integer"], errors["age"]);
+        assert_eq!(vec!["required"], errors["phone"]);
+    }
+
+    #[test]
+    fn it_exports_submissions_as_csv() {
+        let fields = [
+            field("name", FormFieldKind::String, true),
+            field("age", FormFieldKind::Int, false),
+        ];
+
+        let created_at = NaiveDate::from_ymd_opt(2026, 10, 17)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap();
+
+        let submissions = vec![
+            FormSubmission {
+                id: 1,
+                name: "contact".to_string(),
+                values: r#"{"name":"Doe, \"John\"","age":-3}"#.to_string(),
+                created_at,
+                form_id: Some(1),
+            },
+            FormSubmission {
+                id: 2,
+                name: "contact".to_string(),
+                values: r#"{"name":"=1+1"}"#.to_string(),
+                created_at,
+                form_id: Some(1),
+            },
+        ];
+
+        assert_eq!(
+            "id,created_at,name,age\r\n\
+            1,2026-10-17 09:30:00,\"Doe, \"\"John\"\"\",-3\r\n\
+            2,2026-10-17 09:30:00,'=1+1,\r\n",
+            submissions_to_csv(&fields, submissions)
+        );
+    }
 }
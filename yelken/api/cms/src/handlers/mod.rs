@@ -1,19 +1,119 @@
+use std::collections::HashMap;
+
 use axum::{extract::State, Json};
-use base::{models::Field, responses::HttpError, schema::fields, AppState};
+use base::{
+    db::BatchQuery,
+    models::Field,
+    responses::HttpError,
+    sanitize::Sanitized,
+    schema::{enum_options, fields},
+    validate::Valid,
+    AppState,
+};
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+
+use crate::{requests::CreateField, responses};
 
 pub mod asset;
 pub mod content;
+pub mod delivery;
+pub mod folder;
 pub mod form;
 pub mod model;
+pub mod revision;
 pub mod tag;
+pub mod usage;
 
-pub async fn fetch_fields(State(state): State<AppState>) -> Result<Json<Vec<Field>>, HttpError> {
-    fields::table
+pub async fn fetch_fields(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<responses::Field>>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let fields = fields::table
         .order(fields::id.asc())
-        .load::<Field>(&mut state.pool.get().await?)
-        .await
-        .map(Json)
-        .map_err(Into::into)
+        .load::<Field>(&mut conn)
+        .await?;
+
+    let mut options = enum_options::table
+        .order(enum_options::id.asc())
+        .select((enum_options::field_id, enum_options::value))
+        .load::<(i32, String)>(&mut conn)
+        .await?
+        .into_iter()
+        .fold(
+            HashMap::<i32, Vec<String>>::new(),
+            |mut options, (field_id, value)| {
+                options.entry(field_id).or_default().push(value);
+                options
+            },
+        );
+
+    Ok(Json(
+        fields
+            .into_iter()
+            .map(|field| responses::Field {
+                options: options.remove(&field.id).unwrap_or_default(),
+                field,
+            })
+            .collect(),
+    ))
+}
+
+pub async fn create_field(
+    State(state): State<AppState>,
+    Valid(Sanitized(Json(req))): Valid<Sanitized<Json<CreateField>>>,
+) -> Result<Json<responses::Field>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let exists = diesel::dsl::select(diesel::dsl::exists(
+        fields::table.filter(fields::key.eq(&req.key)),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await?;
+
+    if exists {
+        return Err(HttpError::conflict("field_already_exists"));
+    }
+
+    let field = conn
+        .transaction(|conn| {
+            async move {
+                let field = diesel::insert_into(fields::table)
+                    .values((
+                        fields::key.eq(req.key),
+                        fields::name.eq(req.name),
+                        fields::kind.eq(req.kind.as_str()),
+                    ))
+                    .get_result::<Field>(conn)
+                    .await?;
+
+                if !req.options.is_empty() {
+                    diesel::insert_into(enum_options::table)
+                        .values(
+                            req.options
+                                .iter()
+                                .map(|value| {
+                                    (
+                                        enum_options::field_id.eq(field.id),
+                                        enum_options::value.eq(value.clone()),
+                                    )
+                                })
+                                .collect::<Vec<_>>(),
+                        )
+                        .batched()
+                        .execute(conn)
+                        .await?;
+                }
+
+                Ok::<_, HttpError>(responses::Field {
+                    field,
+                    options: req.options,
+                })
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    Ok(Json(field))
 }
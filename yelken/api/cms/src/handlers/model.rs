@@ -1,5 +1,5 @@
 use crate::{
-    requests::{CreateModel, CreateModelField, UpdateModel, UpdateModelField},
+    requests::{CreateModel, CreateModelField, UpdateModel, UpdateModelField, UpdateModelPublic},
     responses::Model,
 };
 use axum::{
@@ -7,11 +7,12 @@ use axum::{
     Json,
 };
 use base::{
-    db::BatchQuery,
-    models::ModelField,
+    db::{BatchQuery, Connection},
+    field::{check_rules, FieldKind},
+    models::{ModelField, ValidationRules},
     responses::HttpError,
     sanitize::Sanitized,
-    schema::{model_fields, models, themes},
+    schema::{fields, model_fields, models, themes},
     validate::Valid,
     AppState,
 };
@@ -80,6 +81,17 @@ pub async fn create_model(
         return Err(HttpError::conflict("model_already_exists"));
     }
 
+    for mf in req.model_fields.iter() {
+        check_model_field(
+            &mut conn,
+            mf.field_id,
+            mf.multiple,
+            mf.reference_model_id,
+            &mf.validation,
+        )
+        .await?;
+    }
+
     let (model, fields) = conn
         .transaction(|conn| {
             async move {
@@ -107,6 +119,8 @@ pub async fn create_model(
                                     model_fields::localized.eq(mf.localized),
                                     model_fields::multiple.eq(mf.multiple),
                                     model_fields::required.eq(mf.required),
+                                    model_fields::reference_model_id.eq(mf.reference_model_id),
+                                    model_fields::validation.eq(mf.validation),
                                 )
                             })
                             .collect::<Vec<_>>(),
@@ -145,6 +159,25 @@ pub async fn update_model(
     Ok(())
 }
 
+/// Opts the model in or out of the content delivery API.
+pub async fn update_model_public(
+    State(state): State<AppState>,
+    Path(model_id): Path<i32>,
+    Json(req): Json<UpdateModelPublic>,
+) -> Result<(), HttpError> {
+    let effected_row: usize = diesel::update(models::table)
+        .filter(models::id.eq(model_id))
+        .set(models::public.eq(req.public))
+        .execute(&mut state.pool.get().await?)
+        .await?;
+
+    if effected_row == 0 {
+        return Err(HttpError::not_found("model_not_found"));
+    }
+
+    Ok(())
+}
+
 pub async fn delete_model(
     State(state): State<AppState>,
     Path(model_id): Path<i32>,
@@ -173,6 +206,17 @@ pub async fn create_model_field(
     Path(model_id): Path<i32>,
     Json(req): Json<CreateModelField>,
 ) -> Result<Json<ModelField>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    check_model_field(
+        &mut conn,
+        req.field_id,
+        req.multiple,
+        req.reference_model_id,
+        &req.validation,
+    )
+    .await?;
+
     diesel::insert_into(model_fields::table)
         .values((
             model_fields::field_id.eq(req.field_id),
@@ -183,14 +227,18 @@ pub async fn create_model_field(
             model_fields::localized.eq(req.localized),
             model_fields::multiple.eq(req.multiple),
             model_fields::required.eq(req.required),
+            model_fields::reference_model_id.eq(req.reference_model_id),
+            model_fields::validation.eq(req.validation),
         ))
-        .get_result::<ModelField>(&mut state.pool.get().await?)
+        .get_result::<ModelField>(&mut conn)
         .await
         .map(Json)
         .map_err(|e| match e {
             Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, ref info) => {
                 if let Some(name) = info.constraint_name() {
-                    if name.contains("model_id") {
+                    if name.contains("reference_model_id") {
+                        return HttpError::not_found("reference_model_not_found");
+                    } else if name.contains("model_id") {
                         return HttpError::conflict("model_not_found");
                     } else if name.contains("field_id") {
                         return HttpError::conflict("field_not_found");
@@ -211,6 +259,25 @@ pub async fn update_model_field(
     Path(model_field_id): Path<i32>,
     Json(req): Json<UpdateModelField>,
 ) -> Result<(), HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let field_id = model_fields::table
+        .filter(model_fields::id.eq(model_field_id))
+        .select(model_fields::field_id)
+        .first::<i32>(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(|| HttpError::not_found("model_field_not_found"))?;
+
+    check_model_field(
+        &mut conn,
+        field_id,
+        req.multiple,
+        req.reference_model_id,
+        &req.validation,
+    )
+    .await?;
+
     let effected_row: usize = diesel::update(model_fields::table)
         .filter(model_fields::id.eq(model_field_id))
         .set((
@@ -219,8 +286,10 @@ pub async fn update_model_field(
             model_fields::localized.eq(req.localized),
             model_fields::required.eq(req.required),
             model_fields::multiple.eq(req.multiple),
+            model_fields::reference_model_id.eq(req.reference_model_id),
+            model_fields::validation.eq(req.validation),
         ))
-        .execute(&mut state.pool.get().await?)
+        .execute(&mut conn)
         .await?;
 
     if effected_row == 0 {
@@ -252,3 +321,47 @@ pub async fn delete_model_field(
 
     Ok(())
 }
+
+/// Checks the settings of a model field that depend on the kind of its field. Only reference fields
+/// can be restricted to the contents of a model and validation rules must be applicable to the kind.
+async fn check_model_field(
+    conn: &mut Connection,
+    field_id: i32,
+    multiple: bool,
+    reference_model_id: Option<i32>,
+    validation: &ValidationRules,
+) -> Result<(), HttpError> {
+    let kind = fields::table
+        .filter(fields::id.eq(field_id))
+        .select(fields::kind)
+        .first::<String>(conn)
+        .await
+        .optional()?
+        .map(|kind| FieldKind::of(&kind))
+        .ok_or_else(|| HttpError::conflict("field_not_found"))?;
+
+    check_rules(kind, multiple, validation)
+        .map_err(|e| HttpError::validation_errors_with("validation", e))?;
+
+    let Some(reference_model_id) = reference_model_id else {
+        return Ok(());
+    };
+
+    if kind != FieldKind::Reference {
+        return Err(HttpError::unprocessable_entity(
+            "reference_model_for_non_reference_field",
+        ));
+    }
+
+    let exists = diesel::dsl::select(diesel::dsl::exists(
+        models::table.filter(models::id.eq(reference_model_id)),
+    ))
+    .get_result::<bool>(conn)
+    .await?;
+
+    if !exists {
+        return Err(HttpError::not_found("reference_model_not_found"));
+    }
+
+    Ok(())
+}
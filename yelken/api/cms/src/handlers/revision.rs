@@ -0,0 +1,427 @@
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use base::{
+    db::{BatchQuery, Connection},
+    field::{sanitize_value, search_text, validate_values, FieldKind, FieldValue},
+    middlewares::auth::AuthUser,
+    models::{ContentRevision, ContentStage, Field, ModelField, RevisionAction},
+    paginate::{CountStarOver, Paginate, Pagination, PaginationRequest},
+    responses::HttpError,
+    schema::{content_revisions, content_values, contents, fields, locales, model_fields, users},
+    AppState,
+};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+
+use crate::{
+    requests::{ContentValue, DiffRevisions, FilterByContent},
+    responses::{Change, Revision, RevisionDetail, RevisionDiff, ValueChange},
+};
+
+use super::content::authorize_modification;
+
+/// Records the current state of the content as a new revision. It is expected to be called
+/// in the same transaction that modifies the content.
+pub(crate) async fn create_revision(
+    conn: &mut Connection,
+    content_id: i32,
+    user_id: i32,
+    action: RevisionAction,
+) -> Result<(), HttpError> {
+    let (name, stage, publish_at, unpublish_at) = contents::table
+        .filter(contents::id.eq(content_id))
+        .select((
+            contents::name,
+            contents::stage,
+            contents::publish_at,
+            contents::unpublish_at,
+        ))
+        .first::<(
+            String,
+            ContentStage,
+            Option<NaiveDateTime>,
+            Option<NaiveDateTime>,
+        )>(conn)
+        .await?;
+
+    let values = content_values::table
+        .filter(content_values::content_id.eq(content_id))
+        .order(content_values::id.asc())
+        .select((
+            content_values::model_field_id,
+            content_values::value,
+            content_values::locale,
+        ))
+        .load::<(i32, String, Option<String>)>(conn)
+        .await?
+        .into_iter()
+        .map(|v| ContentValue {
+            model_field_id: v.0,
+            value: v.1,
+            locale: v.2,
+        })
+        .collect::<Vec<_>>();
+
+    let values = serde_json::to_string(&values).map_err(|e| {
+        HttpError::internal_server_error("failed_serializing_revision")
+            .with_context(format!("{e:?}"))
+    })?;
+
+    diesel::insert_into(content_revisions::table)
+        .values((
+            content_revisions::content_id.eq(content_id),
+            content_revisions::action.eq(action),
+            content_revisions::name.eq(name),
+            content_revisions::stage.eq(stage),
+            content_revisions::values.eq(values),
+            content_revisions::created_by.eq(user_id),
+            content_revisions::publish_at.eq(publish_at),
+            content_revisions::unpublish_at.eq(unpublish_at),
+        ))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+async fn fetch_content_revision(
+    conn: &mut Connection,
+    revision_id: i32,
+) -> Result<ContentRevision, HttpError> {
+    content_revisions::table
+        .filter(content_revisions::id.eq(revision_id))
+        .first::<ContentRevision>(conn)
+        .await
+        .optional()?
+        .ok_or_else(|| HttpError::not_found("revision_not_found"))
+}
+
+fn parse_values(revision: &ContentRevision) -> Result<Vec<ContentValue>, HttpError> {
+    serde_json::from_str(&revision.values).map_err(|e| {
+        HttpError::internal_server_error("invalid_revision_values").with_context(format!(
+            "Failed to parse values of revision {}, {e:?}",
+            revision.id
+        ))
+    })
+}
+
+fn diff_values(from: Vec<ContentValue>, to: Vec<ContentValue>) -> Vec<ValueChange> {
+    let mut grouped = BTreeMap::<(i32, Option<String>), (Vec<String>, Vec<String>)>::new();
+
+    for v in from {
+        grouped
+            .entry((v.model_field_id, v.locale))
+            .or_default()
+            .0
+            .push(v.value);
+    }
+
+    for v in to {
+        grouped
+            .entry((v.model_field_id, v.locale))
+            .or_default()
+            .1
+            .push(v.value);
+    }
+
+    grouped
+        .into_iter()
+        .filter(|(_, (from, to))| from != to)
+        .map(|((model_field_id, locale), (from, to))| ValueChange {
+            model_field_id,
+            locale,
+            from,
+            to,
+        })
+        .collect()
+}
+
+pub async fn fetch_revisions(
+    State(state): State<AppState>,
+    Query(req): Query<FilterByContent>,
+    Query(page): Query<PaginationRequest>,
+) -> Result<Json<Pagination<Revision>>, HttpError> {
+    content_revisions::table
+        .left_join(users::table)
+        .filter(content_revisions::content_id.eq(req.content_id))
+        .select((
+            (
+                content_revisions::id,
+                content_revisions::content_id,
+                content_revisions::action,
+                content_revisions::name,
+                content_revisions::stage,
+                (users::id, users::name).nullable(),
+                content_revisions::created_at,
+                content_revisions::publish_at,
+                content_revisions::unpublish_at,
+            ),
+            CountStarOver,
+        ))
+        .order(content_revisions::id.desc())
+        .paginate(page.page)
+        .per_page(page.per_page)
+        .load_and_count_pages::<Revision>(&mut state.pool.get().await?)
+        .await
+        .map(Json)
+        .map_err(Into::into)
+}
+
+pub async fn fetch_revision(
+    State(state): State<AppState>,
+    Path(revision_id): Path<i32>,
+) -> Result<Json<RevisionDetail>, HttpError> {
+    let (revision, user) = content_revisions::table
+        .left_join(users::table)
+        .filter(content_revisions::id.eq(revision_id))
+        .select((
+            content_revisions::all_columns,
+            (users::id, users::name).nullable(),
+        ))
+        .first::<(ContentRevision, Option<crate::responses::User>)>(&mut state.pool.get().await?)
+        .await
+        .optional()?
+        .ok_or_else(|| HttpError::not_found("revision_not_found"))?;
+
+    let values = parse_values(&revision)?;
+
+    Ok(Json(RevisionDetail {
+        revision: Revision {
+            id: revision.id,
+            content_id: revision.content_id,
+            action: revision.action,
+            name: revision.name,
+            stage: revision.stage,
+            user,
+            created_at: revision.created_at,
+            publish_at: revision.publish_at,
+            unpublish_at: revision.unpublish_at,
+        },
+        values,
+    }))
+}
+
+pub async fn diff_revisions(
+    State(state): State<AppState>,
+    Query(req): Query<DiffRevisions>,
+) -> Result<Json<RevisionDiff>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let from = fetch_content_revision(&mut conn, req.from).await?;
+    let to = fetch_content_revision(&mut conn, req.to).await?;
+
+    if from.content_id != to.content_id {
+        return Err(HttpError::unprocessable_entity(
+            "revisions_of_different_contents",
+        ));
+    }
+
+    let values = diff_values(parse_values(&from)?, parse_values(&to)?);
+
+    Ok(Json(RevisionDiff {
+        from: from.id,
+        to: to.id,
+        name: (from.name != to.name).then_some(Change {
+            from: from.name,
+            to: to.name,
+        }),
+        stage: (from.stage != to.stage).then_some(Change {
+            from: from.stage,
+            to: to.stage,
+        }),
+        publish_at: (from.publish_at != to.publish_at).then_some(Change {
+            from: from.publish_at,
+            to: to.publish_at,
+        }),
+        unpublish_at: (from.unpublish_at != to.unpublish_at).then_some(Change {
+            from: from.unpublish_at,
+            to: to.unpublish_at,
+        }),
+        values,
+    }))
+}
+
+/// Brings back the name and values of a content as they are in the given revision. Stage of the
+/// content is left untouched so that restoring an old revision does not publish or unpublish it.
+pub async fn restore_revision(
+    State(state): State<AppState>,
+    Path(revision_id): Path<i32>,
+    user: AuthUser,
+) -> Result<(), HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let revision = fetch_content_revision(&mut conn, revision_id).await?;
+
+    let values = parse_values(&revision)?;
+
+    conn.transaction(|conn| {
+        async move {
+            authorize_modification(conn, &user, revision.content_id).await?;
+
+            let model_id = contents::table
+                .filter(contents::id.eq(revision.content_id))
+                .select(contents::model_id)
+                .first::<i32>(conn)
+                .await?;
+
+            let model_fields = model_fields::table
+                .inner_join(fields::table)
+                .filter(model_fields::model_id.eq(model_id))
+                .select((model_fields::all_columns, fields::all_columns))
+                .load::<(ModelField, Field)>(conn)
+                .await?;
+
+            let locales = locales::table
+                .select(locales::key)
+                .load::<String>(conn)
+                .await?;
+
+            // Fields or locales may be removed after the revision is taken, skip their values.
+            let values = values
+                .into_iter()
+                .filter_map(|mut v| {
+                    let (_, field) = model_fields.iter().find(|mf| mf.0.id == v.model_field_id)?;
+
+                    if v.locale.as_ref().is_some_and(|l| !locales.contains(l)) {
+                        return None;
+                    }
+
+                    v.value = sanitize_value(FieldKind::of(&field.kind), v.value);
+
+                    Some(v)
+                })
+                .collect::<Vec<_>>();
+
+            // Rules may have changed after the revision is taken, restored values must satisfy
+            // the current ones.
+            let field_values = values
+                .iter()
+                .filter_map(|v| {
+                    let (model_field, field) =
+                        model_fields.iter().find(|mf| mf.0.id == v.model_field_id)?;
+
+                    Some(FieldValue {
+                        model_field,
+                        field,
+                        locale: v.locale.as_deref(),
+                        value: &v.value,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            validate_values(conn, Some(revision.content_id), &field_values).await?;
+
+            let values = values
+                .into_iter()
+                .map(|v| {
+                    let search_text = model_fields
+                        .iter()
+                        .find(|mf| mf.0.id == v.model_field_id)
+                        .and_then(|(_, field)| search_text(FieldKind::of(&field.kind), &v.value));
+
+                    (
+                        content_values::content_id.eq(revision.content_id),
+                        content_values::model_field_id.eq(v.model_field_id),
+                        content_values::locale.eq(v.locale),
+                        content_values::value.eq(v.value),
+                        content_values::search_text.eq(search_text),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            diesel::update(contents::table)
+                .filter(contents::id.eq(revision.content_id))
+                .set(contents::name.eq(revision.name))
+                .execute(conn)
+                .await?;
+
+            diesel::delete(content_values::table)
+                .filter(content_values::content_id.eq(revision.content_id))
+                .execute(conn)
+                .await?;
+
+            if !values.is_empty() {
+                diesel::insert_into(content_values::table)
+                    .values(values)
+                    .batched()
+                    .execute(conn)
+                    .await?;
+            }
+
+            create_revision(conn, revision.content_id, user.id, RevisionAction::Restore).await
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::requests::ContentValue;
+
+    use super::diff_values;
+
+    fn value(model_field_id: i32, locale: Option<&str>, value: &str) -> ContentValue {
+        ContentValue {
+            model_field_id,
+            value: value.to_string(),
+            locale: locale.map(ToString::to_string),
+        }
+    }
+
+    #[test]
+    fn it_returns_no_changes_for_same_values() {
+        let values = || vec![value(1, Some("en"), "title"), value(2, None, "10")];
+
+        assert!(diff_values(values(), values()).is_empty());
+    }
+
+    #[test]
+    fn it_reports_updated_created_and_deleted_values() {
+        let from = vec![
+            value(1, Some("en"), "title"),
+            value(1, Some("tr"), "baslik"),
+            value(2, None, "10"),
+        ];
+
+        let to = vec![
+            value(1, Some("en"), "new title"),
+            value(1, Some("tr"), "baslik"),
+            value(3, None, "summary"),
+        ];
+
+        let changes = diff_values(from, to);
+
+        assert_eq!(3, changes.len());
+
+        assert_eq!(1, changes[0].model_field_id);
+        assert_eq!(Some("en".to_string()), changes[0].locale);
+        assert_eq!(vec!["title".to_string()], changes[0].from);
+        assert_eq!(vec!["new title".to_string()], changes[0].to);
+
+        assert_eq!(2, changes[1].model_field_id);
+        assert_eq!(vec!["10".to_string()], changes[1].from);
+        assert!(changes[1].to.is_empty());
+
+        assert_eq!(3, changes[2].model_field_id);
+        assert!(changes[2].from.is_empty());
+        assert_eq!(vec!["summary".to_string()], changes[2].to);
+    }
+
+    #[test]
+    fn it_compares_multiple_values_in_order() {
+        let from = vec![value(1, None, "a"), value(1, None, "b")];
+        let to = vec![value(1, None, "b"), value(1, None, "a")];
+
+        let changes = diff_values(from, to);
+
+        assert_eq!(1, changes.len());
+        assert_eq!(vec!["a".to_string(), "b".to_string()], changes[0].from);
+        assert_eq!(vec!["b".to_string(), "a".to_string()], changes[0].to);
+    }
+}
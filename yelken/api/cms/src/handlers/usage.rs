@@ -0,0 +1,463 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use base::{
+    db::Connection,
+    field::FieldKind,
+    models::{Asset, PageKind},
+    responses::HttpError,
+    runtime::IntoSendFuture,
+    schema::{assets, content_revisions, content_values, contents, fields, model_fields, pages},
+    AppState,
+};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use opendal::{ErrorKind, Operator};
+
+use crate::{
+    requests::PurgeOrphanAssets,
+    responses::{AssetUsage, ContentReference, PageReference, RevisionReference},
+};
+
+use super::asset::remove_asset;
+
+/// Directories in storage that templates referencing assets are searched in.
+const TEMPLATE_DIRS: [&str; 2] = ["templates/", "themes/"];
+
+/// Keeps the number of bind parameters of a query within the limits of every backend.
+const CHUNK_SIZE: usize = 512;
+
+pub async fn fetch_asset_usage(
+    State(state): State<AppState>,
+    Path(asset_id): Path<i32>,
+) -> Result<Json<AssetUsage>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let filename = assets::table
+        .filter(assets::id.eq(asset_id))
+        .select(assets::filename)
+        .first::<String>(&mut conn)
+        .await
+        .optional()?
+        .ok_or_else(|| HttpError::not_found("asset_not_found"))?;
+
+    let mut usages =
+        find_usages(&mut conn, &state.storage, std::slice::from_ref(&filename)).await?;
+
+    Ok(Json(usages.remove(&filename).unwrap_or_default()))
+}
+
+pub async fn fetch_orphan_assets(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Asset>>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    find_orphans(&mut conn, &state.storage).await.map(Json)
+}
+
+pub async fn purge_orphan_assets(
+    State(state): State<AppState>,
+    Json(req): Json<PurgeOrphanAssets>,
+) -> Result<Json<Vec<Asset>>, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    let mut orphans = find_orphans(&mut conn, &state.storage).await?;
+
+    if let Some(asset_ids) = req.asset_ids {
+        let asset_ids = asset_ids.into_iter().collect::<HashSet<_>>();
+
+        orphans.retain(|asset| asset_ids.contains(&asset.id));
+    }
+
+    let mut purged = Vec::with_capacity(orphans.len());
+
+    for asset in orphans {
+        remove_asset(&mut conn, &state.storage, asset.id).await?;
+
+        log::info!("Purged orphan asset {}, {}", asset.id, asset.filename);
+
+        purged.push(asset);
+    }
+
+    Ok(Json(purged))
+}
+
+async fn find_orphans(conn: &mut Connection, storage: &Operator) -> Result<Vec<Asset>, HttpError> {
+    let assets = assets::table
+        .order(assets::id.asc())
+        .load::<Asset>(conn)
+        .await?;
+
+    let filenames = assets
+        .iter()
+        .map(|asset| asset.filename.clone())
+        .collect::<Vec<_>>();
+
+    let usages = find_usages(conn, storage, &filenames).await?;
+
+    Ok(assets
+        .into_iter()
+        .filter(|asset| !usages.contains_key(&asset.filename))
+        .collect())
+}
+
+/// Finds where the given assets are referenced, keyed by their filenames. Assets are referenced by
+/// asset fields of contents, urls in rich text and markdown values, asset pages, templates and
+/// revisions of contents. Assets without any reference are not present in the returned map.
+async fn find_usages(
+    conn: &mut Connection,
+    storage: &Operator,
+    filenames: &[String],
+) -> Result<HashMap<String, AssetUsage>, HttpError> {
+    let mut usages = HashMap::<String, AssetUsage>::new();
+
+    for chunk in filenames.chunks(CHUNK_SIZE) {
+        let values = content_values::table
+            .inner_join(contents::table)
+            .inner_join(model_fields::table.inner_join(fields::table))
+            .filter(fields::kind.eq(FieldKind::Asset.as_str()))
+            .filter(content_values::value.eq_any(chunk))
+            .select((
+                content_values::value,
+                contents::id,
+                contents::name,
+                model_fields::key,
+            ))
+            .distinct()
+            .order((contents::id.asc(), model_fields::key.asc()))
+            .load::<(String, i32, String, String)>(conn)
+            .await?;
+
+        for (filename, content_id, content_name, field) in values {
+            usages
+                .entry(filename)
+                .or_default()
+                .contents
+                .push(ContentReference {
+                    content_id,
+                    content_name,
+                    field,
+                });
+        }
+
+        let asset_pages = pages::table
+            .filter(pages::kind.eq(PageKind::Asset))
+            .filter(pages::value.eq_any(chunk))
+            .select((
+                pages::value,
+                pages::id,
+                pages::key,
+                pages::name,
+                pages::namespace,
+            ))
+            .order(pages::id.asc())
+            .load::<(String, i32, String, String, Option<String>)>(conn)
+            .await?;
+
+        for (filename, id, key, name, namespace) in asset_pages {
+            usages
+                .entry(filename)
+                .or_default()
+                .pages
+                .push(PageReference {
+                    id,
+                    key,
+                    name,
+                    namespace,
+                });
+        }
+    }
+
+    let texts = content_values::table
+        .inner_join(contents::table)
+        .inner_join(model_fields::table.inner_join(fields::table))
+        .filter(fields::kind.eq_any([FieldKind::RichText.as_str(), FieldKind::Markdown.as_str()]))
+        .select((
+            content_values::value,
+            contents::id,
+            contents::name,
+            model_fields::key,
+        ))
+        .order((contents::id.asc(), model_fields::key.asc()))
+        .load::<(String, i32, String, String)>(conn)
+        .await?;
+
+    for (text, content_id, content_name, field) in texts {
+        for filename in filenames.iter().filter(|f| text.contains(f.as_str())) {
+            let contents = &mut usages.entry(filename.clone()).or_default().contents;
+
+            if !contents
+                .iter()
+                .any(|c| c.content_id == content_id && c.field == field)
+            {
+                contents.push(ContentReference {
+                    content_id,
+                    content_name: content_name.clone(),
+                    field: field.clone(),
+                });
+            }
+        }
+    }
+
+    // Restoring a revision brings back the values it has, so the assets they refer to are kept
+    let revisions = content_revisions::table
+        .inner_join(contents::table)
+        .select((
+            content_revisions::values,
+            content_revisions::id,
+            contents::id,
+            contents::name,
+        ))
+        .order(content_revisions::id.asc())
+        .load::<(String, i32, i32, String)>(conn)
+        .await?;
+
+    for (values, revision_id, content_id, content_name) in revisions {
+        for filename in filenames.iter().filter(|f| values.contains(f.as_str())) {
+            usages
+                .entry(filename.clone())
+                .or_default()
+                .revisions
+                .push(RevisionReference {
+                    revision_id,
+                    content_id,
+                    content_name: content_name.clone(),
+                });
+        }
+    }
+
+    for (path, template) in load_templates(storage).await? {
+        for filename in filenames.iter().filter(|f| template.contains(f.as_str())) {
+            usages
+                .entry(filename.clone())
+                .or_default()
+                .templates
+                .push(path.clone());
+        }
+    }
+
+    Ok(usages)
+}
+
+/// Loads every template in storage, including the ones of themes, along with their paths.
+async fn load_templates(storage: &Operator) -> Result<Vec<(String, String)>, HttpError> {
+    let mut templates = vec![];
+
+    for dir in TEMPLATE_DIRS {
+        let entries = match storage
+            .list_with(dir)
+            .recursive(true)
+            .into_send_future()
+            .await
+        {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        for entry in entries {
+            if !entry.metadata().is_file() || !entry.path().ends_with(".html") {
+                continue;
+            }
+
+            let buf = storage.read(entry.path()).into_send_future().await?;
+
+            templates.push((
+                entry.path().to_string(),
+                String::from_utf8_lossy(&buf.to_bytes()).into_owned(),
+            ));
+        }
+    }
+
+    Ok(templates)
+}
+
+#[cfg(test)]
+mod tests {
+    use base::{
+        models::{ContentStage, RevisionAction},
+        schema::models,
+        test::{create_pool, DB_CONFIG},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn it_finds_assets_referenced_by_pages_and_templates() {
+        let pool = create_pool(DB_CONFIG).await;
+
+        let storage = Operator::new(opendal::services::Memory::default())
+            .unwrap()
+            .finish();
+
+        let suffix = rand::random::<u32>();
+        let in_page = format!("in-page-{suffix}.pdf");
+        let in_template = format!("in-template-{suffix}.png");
+        let orphan = format!("orphan-{suffix}.png");
+
+        diesel::insert_into(pages::table)
+            .values((
+                pages::key.eq(format!("brochure-{suffix}")),
+                pages::name.eq("Brochure"),
+                pages::path.eq(format!("/brochure-{suffix}")),
+                pages::kind.eq(PageKind::Asset),
+                pages::value.eq(&in_page),
+            ))
+            .execute(&mut pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        storage
+            .write(
+                "themes/default/templates/index.html",
+                format!("<img src=\"{{{{ asset_url('{in_template}', kind='content') }}}}\">"),
+            )
+            .await
+            .unwrap();
+
+        let usages = find_usages(
+            &mut pool.get().await.unwrap(),
+            &storage,
+            &[in_page.clone(), in_template.clone(), orphan.clone()],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(1, usages[&in_page].pages.len());
+        assert!(usages[&in_page].templates.is_empty());
+
+        assert_eq!(
+            vec!["themes/default/templates/index.html".to_string()],
+            usages[&in_template].templates
+        );
+
+        assert!(!usages.contains_key(&orphan));
+
+        diesel::delete(pages::table)
+            .filter(pages::value.eq(&in_page))
+            .execute(&mut pool.get().await.unwrap())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_finds_assets_embedded_in_rich_text_and_revisions() {
+        let pool = create_pool(DB_CONFIG).await;
+        let mut conn = pool.get().await.unwrap();
+
+        let storage = Operator::new(opendal::services::Memory::default())
+            .unwrap()
+            .finish();
+
+        let suffix = rand::random::<u32>();
+        let embedded = format!("embedded-{suffix}.png");
+        let in_revision = format!("in-revision-{suffix}.png");
+
+        let model_id = diesel::insert_into(models::table)
+            .values((
+                models::key.eq(format!("article-{suffix}")),
+                models::name.eq("Article"),
+            ))
+            .returning(models::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        let field_id = diesel::insert_into(fields::table)
+            .values((
+                fields::key.eq(format!("richtext-{suffix}")),
+                fields::name.eq("Rich Text"),
+                fields::kind.eq(FieldKind::RichText.as_str()),
+            ))
+            .returning(fields::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        let model_field_id = diesel::insert_into(model_fields::table)
+            .values((
+                model_fields::model_id.eq(model_id),
+                model_fields::field_id.eq(field_id),
+                model_fields::key.eq("body"),
+                model_fields::name.eq("Body"),
+            ))
+            .returning(model_fields::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        let content_id = diesel::insert_into(contents::table)
+            .values((
+                contents::model_id.eq(model_id),
+                contents::name.eq("Sailing"),
+                contents::stage.eq(ContentStage::Published),
+            ))
+            .returning(contents::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        diesel::insert_into(content_values::table)
+            .values((
+                content_values::content_id.eq(content_id),
+                content_values::model_field_id.eq(model_field_id),
+                content_values::value.eq(format!(
+                    "<p><img src=\"http://127.0.0.1/assets/content/{embedded}\"></p>"
+                )),
+            ))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let revision_id = diesel::insert_into(content_revisions::table)
+            .values((
+                content_revisions::content_id.eq(content_id),
+                content_revisions::action.eq(RevisionAction::Create),
+                content_revisions::name.eq("Sailing"),
+                content_revisions::stage.eq(ContentStage::Draft),
+                content_revisions::values.eq(format!(
+                    r#"[{{"modelFieldId":{model_field_id},"locale":null,"value":"{in_revision}"}}]"#
+                )),
+            ))
+            .returning(content_revisions::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        let usages = find_usages(
+            &mut conn,
+            &storage,
+            &[embedded.clone(), in_revision.clone()],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(1, usages[&embedded].contents.len());
+        assert_eq!("body", usages[&embedded].contents[0].field);
+        assert!(usages[&embedded].revisions.is_empty());
+
+        assert!(usages[&in_revision].contents.is_empty());
+        assert_eq!(revision_id, usages[&in_revision].revisions[0].revision_id);
+
+        diesel::delete(contents::table)
+            .filter(contents::id.eq(content_id))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        diesel::delete(models::table)
+            .filter(models::id.eq(model_id))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        diesel::delete(fields::table)
+            .filter(fields::id.eq(field_id))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+    }
+}
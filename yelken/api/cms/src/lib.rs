@@ -2,7 +2,7 @@ use axum::{
     extract::DefaultBodyLimit,
     middleware,
     routing::{delete, get, post, put},
-    Router,
+    Extension, Router,
 };
 use base::{
     middlewares::{
@@ -12,16 +12,24 @@ use base::{
     AppState,
 };
 
+#[cfg(feature = "graphql")]
+pub mod graphql;
 mod handlers;
+mod protection;
 mod requests;
 mod responses;
 
-use handlers::{asset, content, form, model, tag};
+pub use protection::FormProtection;
+
+use handlers::{asset, content, delivery, folder, form, model, revision, tag, usage};
 
 pub fn router(state: AppState) -> Router<AppState> {
     let asset_read = Router::new()
         .route("/all", get(asset::fetch_assets))
         .route("/view/{id}", get(asset::fetch_asset))
+        .route("/search", get(asset::search_assets))
+        .route("/folder/all", get(folder::fetch_folders))
+        .route("/usage/{id}", get(usage::fetch_asset_usage))
         .layer(PermissionLayer {
             pool: state.pool.clone(),
             perm: Permission::CMSRead,
@@ -34,14 +42,31 @@ pub fn router(state: AppState) -> Router<AppState> {
         )
         .route("/update/{id}", put(asset::update_asset))
         .route("/delete/{id}", delete(asset::delete_asset))
+        .route("/move", put(asset::move_assets))
+        .route("/alt/{id}", put(asset::update_asset_alts))
+        .route("/folder/create", post(folder::create_folder))
+        .route("/folder/update/{id}", put(folder::update_folder))
+        .route("/folder/delete/{id}", delete(folder::delete_folder))
         .layer(PermissionLayer {
             pool: state.pool.clone(),
             perm: Permission::AssetWrite,
         });
 
+    let asset_admin = Router::new()
+        .route("/orphan/all", get(usage::fetch_orphan_assets))
+        .route("/orphan/purge", post(usage::purge_orphan_assets))
+        .layer(PermissionLayer {
+            pool: state.pool.clone(),
+            perm: Permission::Admin,
+        });
+
     let content_read = Router::new()
         .route("/all", get(content::fetch_contents))
         .route("/view/{id}", get(content::fetch_content))
+        .route("/search", get(content::search_contents))
+        .route("/revision/all", get(revision::fetch_revisions))
+        .route("/revision/view/{id}", get(revision::fetch_revision))
+        .route("/revision/diff", get(revision::diff_revisions))
         .layer(PermissionLayer {
             pool: state.pool.clone(),
             perm: Permission::CMSRead,
@@ -50,17 +75,45 @@ pub fn router(state: AppState) -> Router<AppState> {
     let content_write = Router::new()
         .route("/create", post(content::create_content))
         .route("/update/{id}", put(content::update_content))
-        .route("/stage/{id}", put(content::update_content_stage))
+        .route("/schedule/{id}", put(content::update_content_schedule))
         .route("/delete/{id}", delete(content::delete_content))
         .route("/value/{id}/create", post(content::create_content_value))
         .route("/value/{id}/update", put(content::update_content_value))
         .route("/value/{id}/delete", delete(content::delete_content_value))
+        .route("/revision/restore/{id}", post(revision::restore_revision))
         .layer(PermissionLayer {
             pool: state.pool.clone(),
             perm: Permission::ContentWrite,
         });
 
-    let form_read = Router::new().route("/all", get(form::fetch_forms));
+    // Every stage transition requires its own permission which is checked by the handler.
+    let content_stage = Router::new()
+        .route("/stage/{id}", put(content::update_content_stage))
+        .layer(PermissionLayer {
+            pool: state.pool.clone(),
+            perm: Permission::CMSRead,
+        });
+
+    let form_read = Router::new()
+        .route("/all", get(form::fetch_forms))
+        .layer(PermissionLayer {
+            pool: state.pool.clone(),
+            perm: Permission::CMSRead,
+        });
+
+    // Submissions may contain personal data of visitors, reading them requires write permission too
+    let form_write = Router::new()
+        .route("/create", post(form::create_form))
+        .route("/update/{id}", put(form::update_form))
+        .route("/delete/{id}", delete(form::delete_form))
+        .route("/submission/all", get(form::fetch_submissions))
+        .route("/submission/view/{id}", get(form::fetch_submission))
+        .route("/submission/export/{id}", get(form::export_submissions))
+        .route("/submission/delete/{id}", delete(form::delete_submission))
+        .layer(PermissionLayer {
+            pool: state.pool.clone(),
+            perm: Permission::FormWrite,
+        });
 
     let field_read = Router::new()
         .route("/all", get(handlers::fetch_fields))
@@ -69,6 +122,13 @@ pub fn router(state: AppState) -> Router<AppState> {
             perm: Permission::CMSRead,
         });
 
+    let field_write = Router::new()
+        .route("/create", post(handlers::create_field))
+        .layer(PermissionLayer {
+            pool: state.pool.clone(),
+            perm: Permission::ModelWrite,
+        });
+
     let model_read = Router::new()
         .route("/all", get(model::fetch_models))
         .layer(PermissionLayer {
@@ -79,6 +139,7 @@ pub fn router(state: AppState) -> Router<AppState> {
     let model_write = Router::new()
         .route("/create", post(model::create_model))
         .route("/update/{id}", put(model::update_model))
+        .route("/public/{id}", put(model::update_model_public))
         .route("/delete/{id}", delete(model::delete_model))
         .route("/field/{id}/create", post(model::create_model_field))
         .route("/field/{id}/update", put(model::update_model_field))
@@ -88,6 +149,9 @@ pub fn router(state: AppState) -> Router<AppState> {
             perm: Permission::ModelWrite,
         });
 
+    #[cfg(feature = "graphql")]
+    let model_write = model_write.layer(middleware::from_fn(graphql::invalidate_schema));
+
     let tag_read = Router::new()
         .route("/all", get(tag::fetch_tags))
         .layer(PermissionLayer {
@@ -110,10 +174,13 @@ pub fn router(state: AppState) -> Router<AppState> {
         });
 
     Router::new()
-        .nest("/asset", asset_read.merge(asset_write))
-        .nest("/content", content_read.merge(content_write))
-        .nest("/form", form_read)
-        .nest("/field", field_read)
+        .nest("/asset", asset_read.merge(asset_write).merge(asset_admin))
+        .nest(
+            "/content",
+            content_read.merge(content_write).merge(content_stage),
+        )
+        .nest("/form", form_read.merge(form_write))
+        .nest("/field", field_read.merge(field_write))
         .nest("/model", model_read.merge(model_write))
         .nest(
             "/tag",
@@ -122,6 +189,20 @@ pub fn router(state: AppState) -> Router<AppState> {
         .layer(middleware::from_fn_with_state(state.clone(), from_token))
 }
 
-pub fn form_router() -> Router<AppState> {
-    Router::new().route("/submit/{form}", post(form::handle_form_submissions))
+/// Read-only access to the contents of public models, without authentication.
+pub fn delivery_router() -> Router<AppState> {
+    Router::new()
+        .route("/{model}", get(delivery::fetch_contents))
+        .route("/{model}/{id}", get(delivery::fetch_content))
+}
+
+#[cfg(feature = "graphql")]
+pub fn graphql_router() -> Router<AppState> {
+    Router::new().route("/graphql", post(graphql::handle_graphql))
+}
+
+pub fn form_router(protection: FormProtection) -> Router<AppState> {
+    Router::new()
+        .route("/submit/{form}", post(form::handle_form_submissions))
+        .layer(Extension(protection))
 }
@@ -0,0 +1,525 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    net::IpAddr,
+    ops::Deref,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use base::{
+    crypto::Crypto,
+    form::{verify_form_token, FORM_TOKEN_FIELD},
+    models::Form,
+    responses::HttpError,
+};
+use futures::future::BoxFuture;
+
+/// Number of tracked keys after which keys without any hit in the window are cleaned up.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+/// Counts hits per key in a sliding window.
+pub struct RateLimiter {
+    window: Duration,
+    hits: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            hits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records a hit for the key and returns true if the key is still under the limit.
+    pub fn hit(&self, key: &str, limit: usize, now: Instant) -> bool {
+        let mut hits = self.hits.lock().unwrap();
+
+        if hits.len() > SWEEP_THRESHOLD {
+            hits.retain(|_, h| {
+                h.back()
+                    .is_some_and(|last| now.duration_since(*last) < self.window)
+            });
+        }
+
+        let key_hits = hits.entry(key.to_string()).or_default();
+
+        while key_hits
+            .front()
+            .is_some_and(|first| now.duration_since(*first) >= self.window)
+        {
+            key_hits.pop_front();
+        }
+
+        if key_hits.len() >= limit {
+            return false;
+        }
+
+        key_hits.push_back(now);
+
+        true
+    }
+}
+
+/// Verifies the response of a challenge, e.g. a captcha, solved by the visitor while filling a form.
+pub trait ChallengeVerifier: Send + Sync {
+    /// Name of the form field that holds the response of the challenge.
+    fn response_field(&self) -> &str;
+
+    fn verify<'a>(
+        &'a self,
+        response: &'a str,
+        remote_ip: Option<IpAddr>,
+    ) -> BoxFuture<'a, anyhow::Result<bool>>;
+}
+
+/// Verifies challenge responses through a siteverify endpoint, the API shared by Cloudflare
+/// Turnstile, hCaptcha and reCAPTCHA.
+#[cfg(not(target_family = "wasm"))]
+pub struct SiteVerifyChallenge {
+    client: reqwest::Client,
+    verify_url: String,
+    secret: String,
+    response_field: String,
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl SiteVerifyChallenge {
+    pub fn new(verify_url: String, secret: String, response_field: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            verify_url,
+            secret,
+            response_field,
+        }
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[derive(serde::Deserialize)]
+struct SiteVerifyResponse {
+    success: bool,
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl ChallengeVerifier for SiteVerifyChallenge {
+    fn response_field(&self) -> &str {
+        &self.response_field
+    }
+
+    fn verify<'a>(
+        &'a self,
+        response: &'a str,
+        remote_ip: Option<IpAddr>,
+    ) -> BoxFuture<'a, anyhow::Result<bool>> {
+        Box::pin(async move {
+            let mut params = vec![
+                ("secret", self.secret.clone()),
+                ("response", response.to_string()),
+            ];
+
+            if let Some(ip) = remote_ip {
+                params.push(("remoteip", ip.to_string()));
+            }
+
+            let resp = self
+                .client
+                .post(&self.verify_url)
+                .form(&params)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<SiteVerifyResponse>()
+                .await?;
+
+            Ok(resp.success)
+        })
+    }
+}
+
+/// Accepts only the configured answer. It is meant for local development and tests where a real
+/// challenge provider is not reachable.
+pub struct LocalChallenge {
+    answer: String,
+}
+
+impl LocalChallenge {
+    pub const RESPONSE_FIELD: &'static str = "challenge";
+
+    pub fn new(answer: String) -> Self {
+        Self { answer }
+    }
+}
+
+impl ChallengeVerifier for LocalChallenge {
+    fn response_field(&self) -> &str {
+        Self::RESPONSE_FIELD
+    }
+
+    fn verify<'a>(
+        &'a self,
+        response: &'a str,
+        _: Option<IpAddr>,
+    ) -> BoxFuture<'a, anyhow::Result<bool>> {
+        Box::pin(async move { Ok(response == self.answer) })
+    }
+}
+
+pub struct FormProtectionInner {
+    pub limiter: RateLimiter,
+    pub challenge: Option<Box<dyn ChallengeVerifier>>,
+}
+
+#[derive(Clone)]
+pub struct FormProtection(Arc<FormProtectionInner>);
+
+impl FormProtection {
+    pub fn new(challenge: Option<Box<dyn ChallengeVerifier>>) -> Self {
+        Self(Arc::new(FormProtectionInner {
+            limiter: RateLimiter::new(Duration::from_secs(60 * 60)),
+            challenge,
+        }))
+    }
+
+    pub fn from_env() -> anyhow::Result<Self> {
+        use anyhow::Context;
+
+        let challenge: Option<Box<dyn ChallengeVerifier>> =
+            match std::env::var("YELKEN_FORM_CHALLENGE").ok().as_deref() {
+                None => None,
+                #[cfg(not(target_family = "wasm"))]
+                Some("siteverify") => {
+                    let verify_url = std::env::var("YELKEN_FORM_CHALLENGE_VERIFY_URL")
+                        .context("YELKEN_FORM_CHALLENGE_VERIFY_URL is not defined")?;
+                    let secret = std::env::var("YELKEN_FORM_CHALLENGE_SECRET")
+                        .context("YELKEN_FORM_CHALLENGE_SECRET is not defined")?;
+                    let response_field = std::env::var("YELKEN_FORM_CHALLENGE_FIELD")
+                        .context("YELKEN_FORM_CHALLENGE_FIELD is not defined")?;
+
+                    Some(Box::new(SiteVerifyChallenge::new(
+                        verify_url,
+                        secret,
+                        response_field,
+                    )))
+                }
+                Some("local") => {
+                    let answer = std::env::var("YELKEN_FORM_CHALLENGE_ANSWER")
+                        .context("YELKEN_FORM_CHALLENGE_ANSWER is not defined")?;
+
+                    Some(Box::new(LocalChallenge::new(answer)))
+                }
+                Some(unknown) => anyhow::bail!("unknown YELKEN_FORM_CHALLENGE {unknown} is given"),
+            };
+
+        Ok(Self::new(challenge))
+    }
+}
+
+impl Deref for FormProtection {
+    type Target = FormProtectionInner;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Verdict {
+    Accept,
+    /// Submission is made by a bot, it should look successful to the bot but not be stored.
+    Discard,
+}
+
+/// Runs the protections enabled for the form against a submission. Fields used by protections
+/// are removed from the submitted values.
+pub(crate) async fn protect(
+    protection: &FormProtection,
+    crypto: &Crypto,
+    form: &Form,
+    remote_ip: Option<IpAddr>,
+    submitted: &mut HashMap<String, String>,
+    now: Instant,
+    timestamp: i64,
+) -> Result<Verdict, HttpError> {
+    if let Some(limit) = form.hourly_limit {
+        if !protection
+            .limiter
+            .hit(&format!("form:{}", form.id), limit.max(0) as usize, now)
+        {
+            return Err(HttpError::too_many_requests("too_many_submissions"));
+        }
+    }
+
+    if let (Some(limit), Some(ip)) = (form.ip_hourly_limit, remote_ip) {
+        if !protection.limiter.hit(
+            &format!("form:{}:{ip}", form.id),
+            limit.max(0) as usize,
+            now,
+        ) {
+            return Err(HttpError::too_many_requests("too_many_submissions"));
+        }
+    }
+
+    if let Some(honeypot) = &form.honeypot {
+        if submitted
+            .remove(honeypot)
+            .is_some_and(|v| !v.trim().is_empty())
+        {
+            return Ok(Verdict::Discard);
+        }
+    }
+
+    let token = submitted.remove(FORM_TOKEN_FIELD);
+
+    if let Some(min_fill_seconds) = form.min_fill_seconds {
+        let Some(token) = token else {
+            return Err(HttpError::unprocessable_entity("missing_form_token"));
+        };
+
+        verify_form_token(
+            crypto,
+            &form.key,
+            &token,
+            timestamp,
+            min_fill_seconds as i64,
+        )
+        .map_err(HttpError::unprocessable_entity)?;
+    }
+
+    if form.challenge {
+        let Some(challenge) = &protection.challenge else {
+            return Err(HttpError::internal_server_error("challenge_not_configured"));
+        };
+
+        let Some(response) = submitted.remove(challenge.response_field()) else {
+            return Err(HttpError::forbidden("challenge_failed"));
+        };
+
+        let passed = challenge.verify(&response, remote_ip).await.map_err(|e| {
+            HttpError::internal_server_error("challenge_verification_failed")
+                .with_context(format!("{e:?}"))
+        })?;
+
+        if !passed {
+            return Err(HttpError::forbidden("challenge_failed"));
+        }
+    }
+
+    Ok(Verdict::Accept)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        net::IpAddr,
+        time::{Duration, Instant},
+    };
+
+    use base::{
+        crypto::Crypto,
+        form::{issue_form_token, FORM_TOKEN_FIELD},
+        models::Form,
+    };
+    use chrono::NaiveDate;
+
+    use super::{protect, FormProtection, LocalChallenge, RateLimiter, Verdict};
+
+    fn form() -> Form {
+        Form {
+            id: 1,
+            key: "contact".to_string(),
+            name: "Contact".to_string(),
+            desc: None,
+            created_at: NaiveDate::from_ymd_opt(2026, 10, 17)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+            honeypot: None,
+            min_fill_seconds: None,
+            ip_hourly_limit: None,
+            hourly_limit: None,
+            challenge: false,
+            notify_email: None,
+        }
+    }
+
+    fn submitted(values: &[(&str, &str)]) -> HashMap<String, String> {
+        values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn it_limits_hits_in_window() {
+        let limiter = RateLimiter::new(Duration::from_secs(60));
+        let now = Instant::now();
+
+        assert!(limiter.hit("a", 2, now));
+        assert!(limiter.hit("a", 2, now + Duration::from_secs(10)));
+        assert!(!limiter.hit("a", 2, now + Duration::from_secs(20)));
+        assert!(limiter.hit("b", 2, now + Duration::from_secs(20)));
+
+        assert!(limiter.hit("a", 2, now + Duration::from_secs(60)));
+        assert!(!limiter.hit("a", 2, now + Duration::from_secs(61)));
+    }
+
+    #[tokio::test]
+    async fn it_limits_submissions_per_ip() {
+        let protection = FormProtection::new(None);
+        let crypto = Crypto::new("secret");
+        let now = Instant::now();
+
+        let mut form = form();
+        form.ip_hourly_limit = Some(1);
+
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let other: IpAddr = "203.0.113.8".parse().unwrap();
+
+        for (ip, accepted) in [(ip, true), (ip, false), (other, true)] {
+            let verdict = protect(
+                &protection,
+                &crypto,
+                &form,
+                Some(ip),
+                &mut HashMap::new(),
+                now,
+                0,
+            )
+            .await;
+
+            assert_eq!(accepted, verdict.is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn it_discards_submissions_filling_honeypot() {
+        let protection = FormProtection::new(None);
+        let crypto = Crypto::new("secret");
+
+        let mut form = form();
+        form.honeypot = Some("website".to_string());
+
+        let mut values = submitted(&[("name", "John"), ("website", "http://spam.example")]);
+
+        let verdict = protect(
+            &protection,
+            &crypto,
+            &form,
+            None,
+            &mut values,
+            Instant::now(),
+            0,
+        )
+        .await;
+
+        assert_eq!(Verdict::Discard, verdict.unwrap());
+
+        let mut values = submitted(&[("name", "John"), ("website", "")]);
+
+        let verdict = protect(
+            &protection,
+            &crypto,
+            &form,
+            None,
+            &mut values,
+            Instant::now(),
+            0,
+        )
+        .await;
+
+        assert_eq!(Verdict::Accept, verdict.unwrap());
+        assert_eq!(submitted(&[("name", "John")]), values);
+    }
+
+    #[tokio::test]
+    async fn it_requires_min_fill_time() {
+        let protection = FormProtection::new(None);
+        let crypto = Crypto::new("secret");
+
+        let mut form = form();
+        form.min_fill_seconds = Some(5);
+
+        let token = issue_form_token(&crypto, "contact", 1000);
+
+        for (values, timestamp, error) in [
+            (submitted(&[]), 1010, Some("missing_form_token")),
+            (
+                submitted(&[(FORM_TOKEN_FIELD, &token)]),
+                1002,
+                Some("submitted_too_fast"),
+            ),
+            (submitted(&[(FORM_TOKEN_FIELD, &token)]), 1010, None),
+        ] {
+            let mut values = values;
+
+            let verdict = protect(
+                &protection,
+                &crypto,
+                &form,
+                None,
+                &mut values,
+                Instant::now(),
+                timestamp,
+            )
+            .await;
+
+            assert_eq!(error, verdict.err().map(|e| e.error));
+            assert!(values.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn it_verifies_challenge_with_hook() {
+        let protection =
+            FormProtection::new(Some(Box::new(LocalChallenge::new("solved".to_string()))));
+        let crypto = Crypto::new("secret");
+
+        let mut form = form();
+        form.challenge = true;
+
+        for (values, error) in [
+            (submitted(&[]), Some("challenge_failed")),
+            (
+                submitted(&[(LocalChallenge::RESPONSE_FIELD, "wrong")]),
+                Some("challenge_failed"),
+            ),
+            (
+                submitted(&[(LocalChallenge::RESPONSE_FIELD, "solved")]),
+                None,
+            ),
+        ] {
+            let mut values = values;
+
+            let verdict = protect(
+                &protection,
+                &crypto,
+                &form,
+                None,
+                &mut values,
+                Instant::now(),
+                0,
+            )
+            .await;
+
+            assert_eq!(error, verdict.err().map(|e| e.error));
+        }
+
+        let verdict = protect(
+            &FormProtection::new(None),
+            &crypto,
+            &form,
+            None,
+            &mut submitted(&[(LocalChallenge::RESPONSE_FIELD, "solved")]),
+            Instant::now(),
+            0,
+        )
+        .await;
+
+        assert_eq!(
+            Some("challenge_not_configured"),
+            verdict.err().map(|e| e.error)
+        );
+    }
+}
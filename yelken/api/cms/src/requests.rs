@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 
 use base::{
-    models::{ContentStage, TagResource},
+    field::FieldKind,
+    models::{ContentStage, FormFieldKind, TagResource, ValidationRules},
     sanitize::Sanitize,
-    validate::Validate,
+    validate::{is_email, Validate},
 };
+use chrono::NaiveDateTime;
 use derive::Sanitize;
 use serde::{Deserialize, Serialize};
 
@@ -29,6 +31,26 @@ pub struct FilterByModel {
     pub model_id: i32,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchContents {
+    pub query: String,
+    pub locale: Option<String>,
+    pub model_id: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterByContent {
+    pub content_id: i32,
+}
+
+#[derive(Deserialize)]
+pub struct DiffRevisions {
+    pub from: i32,
+    pub to: i32,
+}
+
 #[derive(Deserialize, Sanitize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateModelField {
@@ -39,6 +61,11 @@ pub struct CreateModelField {
     pub localized: bool,
     pub multiple: bool,
     pub required: bool,
+    #[serde(default)]
+    pub reference_model_id: Option<i32>,
+    #[serde(default)]
+    #[sanitizer(skip)]
+    pub validation: ValidationRules,
 }
 
 impl Validate for CreateModelField {
@@ -65,6 +92,48 @@ impl Validate for CreateModelField {
     }
 }
 
+#[derive(Deserialize, Sanitize)]
+pub struct CreateField {
+    pub key: String,
+    pub name: String,
+    #[sanitizer(skip)]
+    pub kind: FieldKind,
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+impl Validate for CreateField {
+    fn validate(&self) -> Result<(), base::validate::Errors> {
+        let mut errors = base::validate::Errors::new();
+
+        if self.key.len() < 3 {
+            errors.insert_field("key", "at_least_3_chars");
+        }
+
+        if self.name.len() < 3 {
+            errors.insert_field("name", "at_least_3_chars");
+        }
+
+        if self.kind == FieldKind::Enum && self.options.is_empty() {
+            errors.insert_field("options", "at_least_1_option");
+        }
+
+        if self.kind != FieldKind::Enum && !self.options.is_empty() {
+            errors.insert_field("options", "only_enum_has_options");
+        }
+
+        if self.options.iter().any(|o| o.is_empty() || o.len() > 128) {
+            errors.insert_field("options", "between_1_and_128_chars");
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Deserialize, Sanitize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateModel {
@@ -101,7 +170,7 @@ impl Validate for CreateModel {
 
         if !model_field_errors.is_empty() {
             errors.field_messages.insert(
-                "modelFields",
+                "modelFields".into(),
                 base::validate::Error::List(model_field_errors),
             );
         }
@@ -136,6 +205,31 @@ pub struct UpdateContentStage {
     pub stage: ContentStage,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateContentSchedule {
+    pub publish_at: Option<NaiveDateTime>,
+    pub unpublish_at: Option<NaiveDateTime>,
+}
+
+impl Validate for UpdateContentSchedule {
+    fn validate(&self) -> Result<(), base::validate::Errors> {
+        let mut errors = base::validate::Errors::new();
+
+        if let (Some(publish_at), Some(unpublish_at)) = (self.publish_at, self.unpublish_at) {
+            if unpublish_at <= publish_at {
+                errors.insert_field("unpublishAt", "must_be_after_publish_at");
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateContentValue {
@@ -149,12 +243,22 @@ pub struct UpdateModel {
 }
 
 #[derive(Deserialize)]
+pub struct UpdateModelPublic {
+    pub public: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct UpdateModelField {
     pub name: String,
     pub desc: Option<String>,
     pub localized: bool,
     pub required: bool,
     pub multiple: bool,
+    #[serde(default)]
+    pub reference_model_id: Option<i32>,
+    #[serde(default)]
+    pub validation: ValidationRules,
 }
 
 #[derive(Deserialize)]
@@ -166,3 +270,298 @@ pub struct UpdateContent {
 pub struct UpdateAsset {
     pub name: String,
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchAssets {
+    pub query: Option<String>,
+    pub folder_id: Option<i32>,
+    /// Only list the assets that are not in any folder, `folder_id` is ignored if it is set
+    #[serde(default)]
+    pub root: bool,
+    /// Prefix of the file type, e.g. `image/` or `application/pdf`
+    pub filetype: Option<String>,
+    pub tag: Option<String>,
+    pub created_by: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveAssets {
+    pub asset_ids: Vec<i32>,
+    pub folder_id: Option<i32>,
+}
+
+impl Validate for MoveAssets {
+    fn validate(&self) -> Result<(), base::validate::Errors> {
+        let mut errors = base::validate::Errors::new();
+
+        if self.asset_ids.is_empty() {
+            errors.insert_field("assetIds", "at_least_1_asset");
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+pub struct UpdateAssetAlts {
+    /// Alternative texts keyed by locale, replacing the existing ones
+    pub alts: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeOrphanAssets {
+    /// Only purge the given assets if they are orphaned, every orphaned asset is purged otherwise
+    pub asset_ids: Option<Vec<i32>>,
+}
+
+fn validate_folder_name(name: &str, errors: &mut base::validate::Errors) {
+    if name.trim().is_empty() || name.len() > 128 {
+        errors.insert_field("name", "between_1_and_128_chars");
+    }
+
+    if name.contains('/') {
+        errors.insert_field("name", "must_not_contain_slash");
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAssetFolder {
+    pub name: String,
+    pub parent_id: Option<i32>,
+}
+
+impl Validate for CreateAssetFolder {
+    fn validate(&self) -> Result<(), base::validate::Errors> {
+        let mut errors = base::validate::Errors::new();
+
+        validate_folder_name(&self.name, &mut errors);
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAssetFolder {
+    pub name: String,
+    pub parent_id: Option<i32>,
+}
+
+impl Validate for UpdateAssetFolder {
+    fn validate(&self) -> Result<(), base::validate::Errors> {
+        let mut errors = base::validate::Errors::new();
+
+        validate_folder_name(&self.name, &mut errors);
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterByForm {
+    pub form_id: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFormField {
+    pub key: String,
+    pub name: String,
+    pub kind: FormFieldKind,
+    pub required: bool,
+    pub min_length: Option<i32>,
+    pub max_length: Option<i32>,
+}
+
+impl Validate for CreateFormField {
+    fn validate(&self) -> Result<(), base::validate::Errors> {
+        let mut errors = base::validate::Errors::new();
+
+        if self.key.is_empty() {
+            errors.insert_field("key", "must_not_be_empty");
+        }
+
+        if self.name.is_empty() {
+            errors.insert_field("name", "must_not_be_empty");
+        }
+
+        if self.min_length.is_some_and(|min| min < 0) {
+            errors.insert_field("minLength", "must_not_be_negative");
+        }
+
+        if self.max_length.is_some_and(|max| max < 1) {
+            errors.insert_field("maxLength", "must_be_positive");
+        }
+
+        if let (Some(min), Some(max)) = (self.min_length, self.max_length) {
+            if max < min {
+                errors.insert_field("maxLength", "must_not_be_less_than_min_length");
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormProtectionOptions {
+    pub honeypot: Option<String>,
+    pub min_fill_seconds: Option<i32>,
+    pub ip_hourly_limit: Option<i32>,
+    pub hourly_limit: Option<i32>,
+    #[serde(default)]
+    pub challenge: bool,
+}
+
+impl FormProtectionOptions {
+    fn validate_into(&self, errors: &mut base::validate::Errors) {
+        if self.honeypot.as_ref().is_some_and(|h| h.is_empty()) {
+            errors.insert_field("honeypot", "must_not_be_empty");
+        }
+
+        if self.min_fill_seconds.is_some_and(|s| s < 0) {
+            errors.insert_field("minFillSeconds", "must_not_be_negative");
+        }
+
+        if self.ip_hourly_limit.is_some_and(|l| l < 1) {
+            errors.insert_field("ipHourlyLimit", "must_be_positive");
+        }
+
+        if self.hourly_limit.is_some_and(|l| l < 1) {
+            errors.insert_field("hourlyLimit", "must_be_positive");
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateForm {
+    pub key: String,
+    pub name: String,
+    pub desc: Option<String>,
+    pub fields: Vec<CreateFormField>,
+    pub notify_email: Option<String>,
+    #[serde(flatten)]
+    pub protection: FormProtectionOptions,
+}
+
+impl Validate for CreateForm {
+    fn validate(&self) -> Result<(), base::validate::Errors> {
+        let mut errors = base::validate::Errors::new();
+
+        if self.key.len() < 3 {
+            errors.insert_field("key", "at_least_3_chars");
+        }
+
+        if self.name.len() < 3 {
+            errors.insert_field("name", "at_least_3_chars");
+        }
+
+        if self.desc.as_ref().map(|d| d.len() < 3).unwrap_or(false) {
+            errors.insert_field("desc", "at_least_3_chars");
+        }
+
+        if self.fields.is_empty() {
+            errors.insert_field("fields", "at_least_1_field");
+        }
+
+        if self.notify_email.as_ref().is_some_and(|e| !is_email(e)) {
+            errors.insert_field("notifyEmail", "invalid_email");
+        }
+
+        self.protection.validate_into(&mut errors);
+
+        if let Some(honeypot) = &self.protection.honeypot {
+            if self.fields.iter().any(|f| &f.key == honeypot) {
+                errors.insert_field("honeypot", "must_not_be_a_field_key");
+            }
+        }
+
+        let mut field_errors = HashMap::new();
+
+        for (idx, field) in self.fields.iter().enumerate() {
+            let mut e = field
+                .validate()
+                .err()
+                .unwrap_or_else(base::validate::Errors::new);
+
+            if self.fields[..idx].iter().any(|f| f.key == field.key) {
+                e.insert_field("key", "duplicate_key");
+            }
+
+            if !e.is_empty() {
+                field_errors.insert(idx, base::validate::Error::Struct(e.field_messages));
+            }
+        }
+
+        if !field_errors.is_empty() {
+            errors
+                .field_messages
+                .insert("fields".into(), base::validate::Error::List(field_errors));
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateForm {
+    pub name: String,
+    pub desc: Option<String>,
+    pub notify_email: Option<String>,
+    #[serde(flatten)]
+    pub protection: FormProtectionOptions,
+}
+
+impl Validate for UpdateForm {
+    fn validate(&self) -> Result<(), base::validate::Errors> {
+        let mut errors = base::validate::Errors::new();
+
+        if self.name.len() < 3 {
+            errors.insert_field("name", "at_least_3_chars");
+        }
+
+        if self.desc.as_ref().map(|d| d.len() < 3).unwrap_or(false) {
+            errors.insert_field("desc", "at_least_3_chars");
+        }
+
+        if self.notify_email.as_ref().is_some_and(|e| !is_email(e)) {
+            errors.insert_field("notifyEmail", "invalid_email");
+        }
+
+        self.protection.validate_into(&mut errors);
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(())
+    }
+}
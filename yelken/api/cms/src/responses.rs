@@ -1,7 +1,9 @@
-use base::models::{Content, ContentValue};
+use base::models::{Content, ContentStage, ContentValue, RevisionAction};
+use chrono::NaiveDateTime;
+use diesel::prelude::Queryable;
 use serde::Serialize;
 
-#[derive(Serialize)]
+#[derive(Queryable, Serialize)]
 pub struct User {
     pub id: i32,
     pub name: String,
@@ -12,6 +14,56 @@ pub struct ContentDetails {
     pub content: Content,
     pub values: Vec<ContentValue>,
     pub user: Option<User>,
+    /// Stages the content can be moved to by the requesting user.
+    pub transitions: Vec<ContentStage>,
+}
+
+#[derive(Serialize)]
+pub struct AssetDetails {
+    #[serde(flatten)]
+    pub asset: base::models::Asset,
+    pub alts: std::collections::HashMap<String, String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentReference {
+    pub content_id: i32,
+    pub content_name: String,
+    pub field: String,
+}
+
+#[derive(Serialize)]
+pub struct PageReference {
+    pub id: i32,
+    pub key: String,
+    pub name: String,
+    pub namespace: Option<String>,
+}
+
+/// Places where an asset is referenced by its filename.
+#[derive(Default, Serialize)]
+pub struct AssetUsage {
+    pub contents: Vec<ContentReference>,
+    pub pages: Vec<PageReference>,
+    /// Paths of the templates in storage
+    pub templates: Vec<String>,
+    pub revisions: Vec<RevisionReference>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionReference {
+    pub revision_id: i32,
+    pub content_id: i32,
+    pub content_name: String,
+}
+
+#[derive(Serialize)]
+pub struct Field {
+    #[serde(flatten)]
+    pub field: base::models::Field,
+    pub options: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -21,3 +73,77 @@ pub struct Model {
     pub model: base::models::Model,
     pub fields: Vec<base::models::ModelField>,
 }
+
+#[derive(Queryable, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Revision {
+    pub id: i32,
+    pub content_id: i32,
+    pub action: RevisionAction,
+    pub name: String,
+    pub stage: ContentStage,
+    pub user: Option<User>,
+    pub created_at: NaiveDateTime,
+    pub publish_at: Option<NaiveDateTime>,
+    pub unpublish_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct RevisionDetail {
+    #[serde(flatten)]
+    pub revision: Revision,
+    pub values: Vec<crate::requests::ContentValue>,
+}
+
+#[derive(Serialize)]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueChange {
+    pub model_field_id: i32,
+    pub locale: Option<String>,
+    pub from: Vec<String>,
+    pub to: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    pub name: Option<Change<String>>,
+    pub stage: Option<Change<ContentStage>>,
+    pub publish_at: Option<Change<Option<NaiveDateTime>>>,
+    pub unpublish_at: Option<Change<Option<NaiveDateTime>>>,
+    pub values: Vec<ValueChange>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Form {
+    #[serde(flatten)]
+    pub form: base::models::Form,
+    pub fields: Vec<base::models::FormField>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Submission {
+    pub id: i32,
+    pub form_id: Option<i32>,
+    pub values: serde_json::Value,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveredContent {
+    pub id: i32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub values: serde_json::Map<String, serde_json::Value>,
+}
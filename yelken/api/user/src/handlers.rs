@@ -1,13 +1,22 @@
 use std::str::FromStr;
 
-use crate::responses::{User, UserWithPermissions};
-use axum::{extract::State, Json};
+use crate::{
+    requests::CreateApiToken,
+    responses::{CreatedApiToken, User, UserWithPermissions},
+};
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
 use base::{
+    api_token::{self, ApiTokenWithPermissions},
+    crypto::Crypto,
     middlewares::{auth::AuthUser, permission::Permission},
     responses::HttpError,
     schema::{permissions, users},
     AppState,
 };
+use chrono::Utc;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 
@@ -52,3 +61,72 @@ pub async fn fetch_profile(
             .collect(),
     }))
 }
+
+pub async fn fetch_api_tokens(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<Vec<ApiTokenWithPermissions>>, HttpError> {
+    user.require_session()?;
+
+    let mut conn = state.pool.get().await?;
+
+    api_token::list(&mut conn, user.id).await.map(Json)
+}
+
+pub async fn create_api_token(
+    State(state): State<AppState>,
+    Extension(crypto): Extension<Crypto>,
+    user: AuthUser,
+    Json(req): Json<CreateApiToken>,
+) -> Result<Json<CreatedApiToken>, HttpError> {
+    user.require_session()?;
+
+    let name = req.name.trim();
+
+    if name.is_empty() {
+        return Err(HttpError::unprocessable_entity("invalid_name"));
+    }
+
+    let perms = req
+        .permissions
+        .iter()
+        .map(|perm| Permission::from_str(perm))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| HttpError::unprocessable_entity("unknown_permission"))?;
+
+    let mut conn = state.pool.get().await?;
+
+    let (token, plain) = api_token::create(
+        &mut conn,
+        &crypto,
+        user.id,
+        name,
+        &perms,
+        req.expires_at,
+        Utc::now().naive_utc(),
+    )
+    .await?;
+
+    Ok(Json(CreatedApiToken {
+        token,
+        secret: plain,
+    }))
+}
+
+pub async fn revoke_api_token(
+    State(state): State<AppState>,
+    Path(token_id): Path<i32>,
+    user: AuthUser,
+) -> Result<(), HttpError> {
+    user.require_session()?;
+
+    let mut conn = state.pool.get().await?;
+
+    let effected_row = api_token::revoke(&mut conn, user.id, token_id).await?;
+
+    if effected_row == 0 {
+        return Err(HttpError::not_found("api_token_not_found"));
+    }
+
+    Ok(())
+}
@@ -1,11 +1,22 @@
-use axum::{middleware, routing::get, Router};
+use axum::{
+    middleware,
+    routing::{delete, get, post},
+    Router,
+};
 use base::{middlewares::auth::from_token, AppState};
 
 mod handlers;
+mod requests;
 mod responses;
 
 pub fn router(state: AppState) -> Router<AppState> {
+    let token = Router::new()
+        .route("/all", get(handlers::fetch_api_tokens))
+        .route("/create", post(handlers::create_api_token))
+        .route("/delete/{token_id}", delete(handlers::revoke_api_token));
+
     Router::new()
         .route("/profile", get(handlers::fetch_profile))
+        .nest("/token", token)
         .layer(middleware::from_fn_with_state(state, from_token))
 }
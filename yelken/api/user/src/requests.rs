@@ -0,0 +1,10 @@
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiToken {
+    pub name: String,
+    pub permissions: Vec<String>,
+    pub expires_at: NaiveDateTime,
+}
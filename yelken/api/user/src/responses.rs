@@ -1,3 +1,4 @@
+use base::api_token::ApiTokenWithPermissions;
 use base::middlewares::permission::Permission;
 use base::models::{LoginKind, UserState};
 use chrono::NaiveDateTime;
@@ -23,3 +24,11 @@ pub struct UserWithPermissions {
     pub user: User,
     pub permissions: Vec<Permission>,
 }
+
+/// Plain secret of the token is only returned once, on creation.
+#[derive(Serialize)]
+pub struct CreatedApiToken {
+    #[serde(flatten)]
+    pub token: ApiTokenWithPermissions,
+    pub secret: String,
+}
@@ -0,0 +1,25 @@
+mail-form-submission-subject = New submission to {$form}
+mail-form-submission-body =
+    A new submission is received for the form {$form}.
+
+    {$values}
+
+    You can view the submissions at {$url}
+
+mail-password-reset-subject = Reset your password
+mail-password-reset-body =
+    Hello {$name},
+
+    A password reset is requested for your account. You can set a new password by visiting the link below, which expires in {$hours} hours.
+
+    {$url}
+
+    If you did not request a password reset, you can safely ignore this email.
+
+mail-invitation-subject = You are invited to {$site}
+mail-invitation-body =
+    Hello {$name},
+
+    You are invited to manage {$site}. You can accept the invitation and set your password by visiting the link below, which expires in {$hours} hours.
+
+    {$url}
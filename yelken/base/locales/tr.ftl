@@ -0,0 +1,25 @@
+mail-form-submission-subject = {$form} formuna yeni gönderim
+mail-form-submission-body =
+    {$form} formu için yeni bir gönderim alındı.
+
+    {$values}
+
+    Gönderimleri {$url} adresinden görüntüleyebilirsiniz.
+
+mail-password-reset-subject = Şifrenizi sıfırlayın
+mail-password-reset-body =
+    Merhaba {$name},
+
+    Hesabınız için şifre sıfırlama talep edildi. Aşağıdaki bağlantıyı ziyaret ederek yeni bir şifre belirleyebilirsiniz. Bağlantı {$hours} saat içinde geçerliliğini yitirecektir.
+
+    {$url}
+
+    Şifre sıfırlama talebinde bulunmadıysanız bu e-postayı dikkate almayabilirsiniz.
+
+mail-invitation-subject = {$site} sitesine davet edildiniz
+mail-invitation-body =
+    Merhaba {$name},
+
+    {$site} sitesini yönetmeye davet edildiniz. Aşağıdaki bağlantıyı ziyaret ederek daveti kabul edebilir ve şifrenizi belirleyebilirsiniz. Bağlantı {$hours} saat içinde geçerliliğini yitirecektir.
+
+    {$url}
@@ -0,0 +1,363 @@
+use axum::http::StatusCode;
+use chrono::{NaiveDateTime, TimeDelta};
+use diesel::prelude::*;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use rand::{distr::Alphanumeric, rng, Rng};
+use serde::Serialize;
+
+use crate::{
+    crypto::Crypto,
+    db::Connection,
+    middlewares::{
+        auth::AuthUser,
+        permission::{has_permission, Permission},
+    },
+    models::UserState,
+    responses::HttpError,
+    schema::{api_token_permissions, api_tokens, users},
+};
+
+/// Personal API tokens are prefixed so that they can be told apart from session tokens, and be
+/// recognized by secret scanners.
+pub const PREFIX: &str = "yk_";
+
+const SECRET_LENGTH: usize = 48;
+
+/// Last used time is only updated once in this many seconds, avoiding a write on every request.
+const LAST_USED_PRECISION: i64 = 60;
+
+pub const INVALID_API_TOKEN: HttpError = HttpError {
+    code: StatusCode::UNAUTHORIZED,
+    error: "invalid_api_token",
+    context: None,
+};
+
+#[derive(Queryable, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiToken {
+    pub id: i32,
+    pub name: String,
+    pub expires_at: NaiveDateTime,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub struct ApiTokenWithPermissions {
+    #[serde(flatten)]
+    pub token: ApiToken,
+    pub permissions: Vec<Permission>,
+}
+
+/// Creates a token scoped to the given permissions. Only the hash of the token is stored, the
+/// returned plain one cannot be retrieved later.
+pub async fn create(
+    conn: &mut Connection,
+    crypto: &Crypto,
+    user_id: i32,
+    name: &str,
+    perms: &[Permission],
+    expires_at: NaiveDateTime,
+    now: NaiveDateTime,
+) -> Result<(ApiTokenWithPermissions, String), HttpError> {
+    if expires_at <= now {
+        return Err(HttpError::unprocessable_entity("expiry_in_past"));
+    }
+
+    for perm in perms {
+        if !has_permission(conn, user_id, *perm).await? {
+            return Err(HttpError::forbidden("permission_not_granted")
+                .with_context(perm.as_str().to_string()));
+        }
+    }
+
+    let secret: String = (0..SECRET_LENGTH)
+        .map(|_| rng().sample(Alphanumeric) as char)
+        .collect();
+    let token_hash = crypto.sign512(secret.as_bytes());
+
+    let token = conn
+        .transaction(|conn| {
+            async move {
+                let token = diesel::insert_into(api_tokens::table)
+                    .values((
+                        api_tokens::user_id.eq(user_id),
+                        api_tokens::name.eq(name),
+                        api_tokens::token_hash.eq(token_hash),
+                        api_tokens::expires_at.eq(expires_at),
+                        api_tokens::created_at.eq(now),
+                    ))
+                    .returning((
+                        api_tokens::id,
+                        api_tokens::name,
+                        api_tokens::expires_at,
+                        api_tokens::last_used_at,
+                        api_tokens::created_at,
+                    ))
+                    .get_result::<ApiToken>(conn)
+                    .await?;
+
+                for perm in perms {
+                    diesel::insert_into(api_token_permissions::table)
+                        .values((
+                            api_token_permissions::token_id.eq(token.id),
+                            api_token_permissions::key.eq(perm.as_str()),
+                        ))
+                        .on_conflict_do_nothing()
+                        .execute(conn)
+                        .await?;
+                }
+
+                Result::<ApiToken, HttpError>::Ok(token)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    let plain = format!("{PREFIX}{}_{secret}", token.id);
+
+    Ok((
+        ApiTokenWithPermissions {
+            token,
+            permissions: perms.to_vec(),
+        },
+        plain,
+    ))
+}
+
+/// Resolves the user of the token and records its usage.
+pub async fn authenticate(
+    conn: &mut Connection,
+    crypto: &Crypto,
+    token: &str,
+    now: NaiveDateTime,
+) -> Result<AuthUser, HttpError> {
+    let Some((token_id, secret)) = token
+        .strip_prefix(PREFIX)
+        .and_then(|token| token.split_once('_'))
+        .and_then(|(id, secret)| Some((id.parse::<i32>().ok()?, secret)))
+    else {
+        return Err(INVALID_API_TOKEN);
+    };
+
+    let Some((user_id, name, user_state, token_hash, expires_at, last_used_at)) = api_tokens::table
+        .inner_join(users::table)
+        .filter(api_tokens::id.eq(token_id))
+        .select((
+            users::id,
+            users::name,
+            users::state,
+            api_tokens::token_hash,
+            api_tokens::expires_at,
+            api_tokens::last_used_at,
+        ))
+        .first::<(
+            i32,
+            String,
+            UserState,
+            String,
+            NaiveDateTime,
+            Option<NaiveDateTime>,
+        )>(conn)
+        .await
+        .optional()?
+    else {
+        return Err(INVALID_API_TOKEN);
+    };
+
+    if !crypto.verify512(secret.as_bytes(), &token_hash) {
+        return Err(INVALID_API_TOKEN);
+    }
+
+    if expires_at <= now {
+        return Err(HttpError {
+            code: StatusCode::UNAUTHORIZED,
+            error: "expired_api_token",
+            context: None,
+        });
+    }
+
+    if UserState::Enabled != user_state {
+        return Err(HttpError {
+            code: StatusCode::FORBIDDEN,
+            error: "failed_authorization",
+            context: Some("User is not enabled".to_string()),
+        });
+    }
+
+    if last_used_at.is_none_or(|last| last + TimeDelta::seconds(LAST_USED_PRECISION) <= now) {
+        diesel::update(api_tokens::table)
+            .filter(api_tokens::id.eq(token_id))
+            .set(api_tokens::last_used_at.eq(now))
+            .execute(conn)
+            .await?;
+    }
+
+    Ok(AuthUser {
+        id: user_id,
+        name,
+        session_id: None,
+        api_token_id: Some(token_id),
+    })
+}
+
+/// Checks whether the permission is in the scope of the token.
+pub async fn has_scope(
+    conn: &mut Connection,
+    token_id: i32,
+    perm: Permission,
+) -> Result<bool, diesel::result::Error> {
+    diesel::select(diesel::dsl::exists(
+        api_token_permissions::table
+            .filter(api_token_permissions::token_id.eq(token_id))
+            .filter(api_token_permissions::key.eq(perm.as_str())),
+    ))
+    .get_result::<bool>(conn)
+    .await
+}
+
+pub async fn list(
+    conn: &mut Connection,
+    user_id: i32,
+) -> Result<Vec<ApiTokenWithPermissions>, HttpError> {
+    let tokens = api_tokens::table
+        .filter(api_tokens::user_id.eq(user_id))
+        .select((
+            api_tokens::id,
+            api_tokens::name,
+            api_tokens::expires_at,
+            api_tokens::last_used_at,
+            api_tokens::created_at,
+        ))
+        .order(api_tokens::id.asc())
+        .load::<ApiToken>(conn)
+        .await?;
+
+    let perms = api_token_permissions::table
+        .filter(api_token_permissions::token_id.eq_any(tokens.iter().map(|token| token.id)))
+        .select((api_token_permissions::token_id, api_token_permissions::key))
+        .load::<(i32, String)>(conn)
+        .await?;
+
+    Ok(tokens
+        .into_iter()
+        .map(|token| {
+            let permissions = perms
+                .iter()
+                .filter(|(token_id, _)| *token_id == token.id)
+                .flat_map(|(_, perm)| {
+                    perm.parse::<Permission>()
+                        .inspect_err(|e| log::error!("Invalid permission found {perm} {e}"))
+                        .ok()
+                })
+                .collect();
+
+            ApiTokenWithPermissions { token, permissions }
+        })
+        .collect())
+}
+
+pub async fn revoke(conn: &mut Connection, user_id: i32, token_id: i32) -> QueryResult<usize> {
+    diesel::delete(api_tokens::table)
+        .filter(api_tokens::id.eq(token_id))
+        .filter(api_tokens::user_id.eq(user_id))
+        .execute(conn)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeDelta, Utc};
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+
+    use super::{authenticate, create, has_scope, revoke, INVALID_API_TOKEN};
+    use crate::{
+        crypto::Crypto,
+        middlewares::permission::Permission,
+        schema::{permissions, users},
+        test::{create_pool, DB_CONFIG},
+    };
+
+    #[tokio::test]
+    async fn it_scopes_tokens_to_granted_permissions() {
+        let pool = create_pool(DB_CONFIG).await;
+        let mut conn = pool.get().await.unwrap();
+        let crypto = Crypto::new("secret");
+        let now = Utc::now().naive_utc();
+        let expires_at = now + TimeDelta::days(1);
+
+        let user_id = diesel::insert_into(users::table)
+            .values((
+                users::username.eq("token_user"),
+                users::name.eq("Token User"),
+                users::email.eq("token@example.com"),
+            ))
+            .returning(users::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        diesel::insert_into(permissions::table)
+            .values((
+                permissions::user_id.eq(user_id),
+                permissions::key.eq(Permission::CMSRead.as_str()),
+            ))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let not_granted = create(
+            &mut conn,
+            &crypto,
+            user_id,
+            "ci",
+            &[Permission::Admin],
+            expires_at,
+            now,
+        )
+        .await;
+
+        assert!(matches!(not_granted, Err(e) if e.error == "permission_not_granted"));
+
+        let (token, plain) = create(
+            &mut conn,
+            &crypto,
+            user_id,
+            "ci",
+            &[Permission::CMSRead],
+            expires_at,
+            now,
+        )
+        .await
+        .unwrap();
+
+        let user = authenticate(&mut conn, &crypto, &plain, now).await.unwrap();
+
+        assert_eq!(user_id, user.id);
+        assert_eq!(Some(token.token.id), user.api_token_id);
+        assert!(has_scope(&mut conn, token.token.id, Permission::CMSRead)
+            .await
+            .unwrap());
+        assert!(
+            !has_scope(&mut conn, token.token.id, Permission::ContentWrite)
+                .await
+                .unwrap()
+        );
+
+        let tampered = format!("{}{}_{}", super::PREFIX, token.token.id, "a".repeat(48));
+
+        assert!(matches!(
+            authenticate(&mut conn, &crypto, &tampered, now).await,
+            Err(e) if e.error == INVALID_API_TOKEN.error
+        ));
+
+        assert!(authenticate(&mut conn, &crypto, &plain, expires_at)
+            .await
+            .is_err());
+
+        revoke(&mut conn, user_id, token.token.id).await.unwrap();
+
+        assert!(authenticate(&mut conn, &crypto, &plain, now).await.is_err());
+    }
+}
@@ -17,6 +17,10 @@ pub struct Config {
     pub app_url: Url,
     pub reload_templates: bool,
     pub upload_size_limit: usize,
+    /// Uploaded images larger than this on either side are downscaled, 0 disables downscaling
+    pub image_max_dimension: u32,
+    /// Widths and heights that derivatives of images can be requested in
+    pub image_sizes: Arc<[u32]>,
 }
 
 #[derive(Clone)]
@@ -0,0 +1,1160 @@
+use std::marker::PhantomData;
+
+use chrono::NaiveDateTime;
+use diesel::{
+    dsl,
+    expression::{is_aggregate::No, ValidGrouping},
+    prelude::*,
+    query_builder::{AstPass, QueryFragment, QueryId},
+    sql_types::{BigInt, Double, Integer, Nullable, Text},
+    AppearsOnTable, Expression, SelectableExpression,
+};
+
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{Backend, Connection},
+    field::FieldKind,
+    middlewares::permission::Permission,
+    models::ContentStage,
+    schema::{content_values, contents, fields, model_fields, options},
+};
+
+/// Key of the option holding the workflow as a JSON list of transitions.
+pub const WORKFLOW_OPTION: &str = "content.workflow";
+
+/// Stage transition of a content and the permission it requires.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Transition {
+    pub from: ContentStage,
+    pub to: ContentStage,
+    pub permission: Permission,
+}
+
+/// Workflow used when no workflow is configured.
+pub const DEFAULT_WORKFLOW: [Transition; 8] = [
+    Transition {
+        from: ContentStage::Draft,
+        to: ContentStage::InReview,
+        permission: Permission::ContentWrite,
+    },
+    Transition {
+        from: ContentStage::InReview,
+        to: ContentStage::Draft,
+        permission: Permission::ContentWrite,
+    },
+    Transition {
+        from: ContentStage::InReview,
+        to: ContentStage::Approved,
+        permission: Permission::ContentReview,
+    },
+    Transition {
+        from: ContentStage::Approved,
+        to: ContentStage::Draft,
+        permission: Permission::ContentReview,
+    },
+    Transition {
+        from: ContentStage::Approved,
+        to: ContentStage::Published,
+        permission: Permission::ContentPublish,
+    },
+    Transition {
+        from: ContentStage::Published,
+        to: ContentStage::Draft,
+        permission: Permission::ContentPublish,
+    },
+    Transition {
+        from: ContentStage::Published,
+        to: ContentStage::Archived,
+        permission: Permission::ContentPublish,
+    },
+    Transition {
+        from: ContentStage::Archived,
+        to: ContentStage::Draft,
+        permission: Permission::ContentWrite,
+    },
+];
+
+/// Loads the configured workflow, falls back to [`DEFAULT_WORKFLOW`] if there is none.
+pub async fn load_workflow(
+    conn: &mut Connection,
+) -> Result<Vec<Transition>, diesel::result::Error> {
+    let workflow = options::table
+        .filter(
+            options::namespace
+                .is_null()
+                .and(options::key.eq(WORKFLOW_OPTION)),
+        )
+        .select(options::value)
+        .first::<String>(conn)
+        .await
+        .optional()?
+        .and_then(|value| {
+            serde_json::from_str(&value)
+                .inspect_err(|e| log::warn!("Invalid content workflow is configured, {e:?}"))
+                .ok()
+        });
+
+    Ok(workflow.unwrap_or_else(|| DEFAULT_WORKFLOW.to_vec()))
+}
+
+/// Checks that the workflow neither has transitions from a stage to itself nor duplicate
+/// transitions.
+pub fn validate_workflow(workflow: &[Transition]) -> Result<(), &'static str> {
+    for (i, transition) in workflow.iter().enumerate() {
+        if transition.from == transition.to {
+            return Err("invalid_transition");
+        }
+
+        if workflow[..i]
+            .iter()
+            .any(|t| t.from == transition.from && t.to == transition.to)
+        {
+            return Err("duplicate_transition");
+        }
+    }
+
+    Ok(())
+}
+
+pub fn transition_permission(
+    workflow: &[Transition],
+    from: &ContentStage,
+    to: &ContentStage,
+) -> Option<Permission> {
+    workflow
+        .iter()
+        .find(|t| &t.from == from && &t.to == to)
+        .map(|t| t.permission)
+}
+
+/// Permission required to modify a content in the given stage. Changes to a published content show
+/// up on the site right away, so they require the permission of publishing it.
+pub fn modify_permission(workflow: &[Transition], stage: &ContentStage) -> Permission {
+    if *stage != ContentStage::Published {
+        return Permission::ContentWrite;
+    }
+
+    workflow
+        .iter()
+        .find(|t| t.to == ContentStage::Published)
+        .map_or(Permission::ContentPublish, |t| t.permission)
+}
+
+/// Filter for contents that are visible on the site at `now`. A content needs to be published
+/// and `now` needs to be inside its schedule, if it has one.
+#[dsl::auto_type(no_type_alias)]
+pub fn visible_at(now: NaiveDateTime) -> _ {
+    let published: ContentStage = ContentStage::Published;
+
+    contents::stage
+        .eq(published)
+        .and(
+            contents::publish_at
+                .is_null()
+                .or(contents::publish_at.le(now)),
+        )
+        .and(
+            contents::unpublish_at
+                .is_null()
+                .or(contents::unpublish_at.gt(now)),
+        )
+}
+
+/// `content_values.value` read as an integer. Values not holding an integer are read as 0, the same
+/// way they are rendered for `int` fields.
+pub struct IntValue;
+
+impl Expression for IntValue {
+    type SqlType = BigInt;
+}
+
+impl QueryFragment<Backend> for IntValue {
+    fn walk_ast(&self, mut out: AstPass<Backend>) -> QueryResult<()> {
+        #[cfg(feature = "postgres")]
+        out.push_sql(
+            "CASE WHEN \"content_values\".\"value\" ~ '^[-+]?[0-9]{1,18}$' \
+            THEN CAST(\"content_values\".\"value\" AS BIGINT) ELSE 0 END",
+        );
+
+        #[cfg(feature = "sqlite")]
+        out.push_sql("CAST(\"content_values\".\"value\" AS INTEGER)");
+
+        Ok(())
+    }
+}
+
+impl<QS> AppearsOnTable<QS> for IntValue where content_values::value: AppearsOnTable<QS> {}
+
+impl<QS> SelectableExpression<QS> for IntValue where content_values::value: SelectableExpression<QS> {}
+
+impl ValidGrouping<()> for IntValue {
+    type IsAggregate = No;
+}
+
+impl QueryId for IntValue {
+    type QueryId = <Self as Expression>::SqlType;
+}
+
+/// `content_values.value` read as a floating point number. Values not holding a number are read as
+/// 0, the same way as [`IntValue`].
+struct FloatValue;
+
+impl QueryFragment<Backend> for FloatValue {
+    fn walk_ast(&self, mut out: AstPass<Backend>) -> QueryResult<()> {
+        #[cfg(feature = "postgres")]
+        out.push_sql(
+            "CASE WHEN \"content_values\".\"value\" ~ '^[-+]?([0-9]+(\\.[0-9]*)?|\\.[0-9]+)([eE][-+]?[0-9]+)?$' \
+            THEN CAST(\"content_values\".\"value\" AS DOUBLE PRECISION) ELSE 0 END",
+        );
+
+        #[cfg(feature = "sqlite")]
+        out.push_sql("CAST(\"content_values\".\"value\" AS REAL)");
+
+        Ok(())
+    }
+}
+
+/// `content_values.value` read as a point in time, seconds since the epoch on PostgreSQL and days
+/// since the julian epoch on SQLite. Values not holding a date or a date time are read as null.
+struct TimeValue;
+
+impl QueryFragment<Backend> for TimeValue {
+    fn walk_ast(&self, mut out: AstPass<Backend>) -> QueryResult<()> {
+        #[cfg(feature = "postgres")]
+        out.push_sql(
+            "CASE WHEN \"content_values\".\"value\" ~ '^[0-9]{4}-[0-9]{1,2}-[0-9]{1,2}([T ][0-9:.]+(Z|[-+][0-9:]+)?)?$' \
+            THEN CAST(EXTRACT(EPOCH FROM CAST(\"content_values\".\"value\" AS TIMESTAMPTZ)) AS DOUBLE PRECISION) END",
+        );
+
+        #[cfg(feature = "sqlite")]
+        out.push_sql("julianday(\"content_values\".\"value\")");
+
+        Ok(())
+    }
+}
+
+enum ValueCast {
+    Text,
+    Int,
+    Float,
+    Time,
+}
+
+/// Value of a model field of the content in the current row of `contents`, mainly to order
+/// contents by their values. Only the first value is taken for fields with multiple values.
+pub struct FieldValue<ST> {
+    model_field_id: i32,
+    locale: String,
+    cast: ValueCast,
+    sql_type: PhantomData<ST>,
+}
+
+impl FieldValue<Nullable<Text>> {
+    pub fn text(model_field_id: i32, locale: String) -> Self {
+        FieldValue {
+            model_field_id,
+            locale,
+            cast: ValueCast::Text,
+            sql_type: PhantomData,
+        }
+    }
+}
+
+impl FieldValue<Nullable<BigInt>> {
+    pub fn int(model_field_id: i32, locale: String) -> Self {
+        FieldValue {
+            model_field_id,
+            locale,
+            cast: ValueCast::Int,
+            sql_type: PhantomData,
+        }
+    }
+}
+
+impl FieldValue<Nullable<Double>> {
+    pub fn float(model_field_id: i32, locale: String) -> Self {
+        FieldValue {
+            model_field_id,
+            locale,
+            cast: ValueCast::Float,
+            sql_type: PhantomData,
+        }
+    }
+
+    /// Value of a `date` or `datetime` field, ordered chronologically rather than by its text.
+    pub fn time(model_field_id: i32, locale: String) -> Self {
+        FieldValue {
+            model_field_id,
+            locale,
+            cast: ValueCast::Time,
+            sql_type: PhantomData,
+        }
+    }
+}
+
+impl<ST> FieldValue<ST> {
+    fn walk_subquery<'b>(
+        &'b self,
+        mut out: AstPass<'_, 'b, Backend>,
+        value: impl FnOnce(AstPass<'_, 'b, Backend>) -> QueryResult<()>,
+    ) -> QueryResult<()> {
+        out.push_sql("(SELECT ");
+        value(out.reborrow())?;
+        out.push_sql(
+            " FROM \"content_values\" \
+            WHERE \"content_values\".\"content_id\" = \"contents\".\"id\" \
+            AND \"content_values\".\"model_field_id\" = ",
+        );
+        out.push_bind_param::<Integer, _>(&self.model_field_id)?;
+        out.push_sql(" AND (\"content_values\".\"locale\" = ");
+        out.push_bind_param::<Text, _>(&self.locale)?;
+        out.push_sql(
+            " OR \"content_values\".\"locale\" IS NULL) ORDER BY \"content_values\".\"id\" LIMIT 1)",
+        );
+
+        Ok(())
+    }
+}
+
+impl<ST> Expression for FieldValue<ST>
+where
+    ST: diesel::sql_types::SqlType + diesel::expression::TypedExpressionType,
+{
+    type SqlType = ST;
+}
+
+impl<ST> QueryFragment<Backend> for FieldValue<ST> {
+    fn walk_ast<'b>(&'b self, out: AstPass<'_, 'b, Backend>) -> QueryResult<()> {
+        self.walk_subquery(out, |mut out| match self.cast {
+            ValueCast::Text => {
+                out.push_sql("\"content_values\".\"value\"");
+                Ok(())
+            }
+            ValueCast::Int => IntValue.walk_ast(out),
+            ValueCast::Float => FloatValue.walk_ast(out),
+            ValueCast::Time => TimeValue.walk_ast(out),
+        })
+    }
+}
+
+impl<ST, QS> AppearsOnTable<QS> for FieldValue<ST>
+where
+    FieldValue<ST>: Expression,
+    contents::id: AppearsOnTable<QS>,
+{
+}
+
+impl<ST> ValidGrouping<()> for FieldValue<ST> {
+    type IsAggregate = No;
+}
+
+impl<ST: 'static> QueryId for FieldValue<ST> {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+/// Relevance of the content in the current row of `contents` for a full-text search query. Ranks of
+/// the matching values in the given locale and the ones without a locale are summed up, it is null
+/// if none of the values matches the query. On Postgres, the query is parsed with the text search
+/// configuration of each value's locale, the same one its text is indexed with.
+#[derive(Clone)]
+pub struct SearchRank {
+    query: String,
+    locale: String,
+}
+
+impl SearchRank {
+    pub fn new(query: &str, locale: String) -> Self {
+        // Every word is quoted to prevent user input being interpreted as FTS5 query syntax
+        #[cfg(feature = "sqlite")]
+        let query = query
+            .split_whitespace()
+            .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        #[cfg(feature = "postgres")]
+        let query = query.to_string();
+
+        SearchRank { query, locale }
+    }
+}
+
+impl Expression for SearchRank {
+    type SqlType = Nullable<Double>;
+}
+
+impl QueryFragment<Backend> for SearchRank {
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Backend>) -> QueryResult<()> {
+        #[cfg(feature = "postgres")]
+        {
+            out.push_sql(
+                "(SELECT CAST(SUM(ts_rank(\"content_values\".\"search\", \
+                websearch_to_tsquery(search_config(\"content_values\".\"locale\"), ",
+            );
+            out.push_bind_param::<Text, _>(&self.query)?;
+            out.push_sql(
+                "))) AS DOUBLE PRECISION) FROM \"content_values\" \
+                WHERE \"content_values\".\"search\" @@ \
+                websearch_to_tsquery(search_config(\"content_values\".\"locale\"), ",
+            );
+            out.push_bind_param::<Text, _>(&self.query)?;
+            out.push_sql(")");
+        }
+
+        // bm25 scores are negative with better matches having lower scores
+        #[cfg(feature = "sqlite")]
+        {
+            out.push_sql(
+                "(SELECT -SUM(\"search\".\"rank\") FROM (SELECT \"rowid\", \"rank\" \
+                FROM \"content_values_search\" WHERE \"content_values_search\" MATCH ",
+            );
+            out.push_bind_param::<Text, _>(&self.query)?;
+            out.push_sql(
+                ") AS \"search\", \"content_values\" \
+                WHERE \"content_values\".\"id\" = \"search\".\"rowid\"",
+            );
+        }
+
+        out.push_sql(
+            " AND \"content_values\".\"content_id\" = \"contents\".\"id\" \
+            AND (\"content_values\".\"locale\" = ",
+        );
+        out.push_bind_param::<Text, _>(&self.locale)?;
+        out.push_sql(" OR \"content_values\".\"locale\" IS NULL))");
+
+        Ok(())
+    }
+}
+
+impl<QS> AppearsOnTable<QS> for SearchRank where contents::id: AppearsOnTable<QS> {}
+
+impl ValidGrouping<()> for SearchRank {
+    type IsAggregate = No;
+}
+
+impl QueryId for SearchRank {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FilterOp {
+    Eq(String),
+    In(Vec<String>),
+    Lt(i64),
+    Le(i64),
+    Gt(i64),
+    Ge(i64),
+}
+
+/// Condition on a value of the content. `id` refers to the id of the content itself, any other
+/// field to the model field with that key.
+#[derive(Debug, PartialEq)]
+pub struct Filter {
+    pub field: String,
+    pub op: FilterOp,
+}
+
+enum IdOp {
+    Eq(i32),
+    In(Vec<i32>),
+    Lt(i32),
+    Le(i32),
+    Gt(i32),
+    Ge(i32),
+}
+
+impl FilterOp {
+    fn into_id_op(self) -> Option<IdOp> {
+        let id = |n: i64| i32::try_from(n).ok();
+
+        Some(match self {
+            FilterOp::Eq(value) => IdOp::Eq(value.parse().ok()?),
+            FilterOp::In(values) => IdOp::In(
+                values
+                    .iter()
+                    .map(|v| v.parse().ok())
+                    .collect::<Option<_>>()?,
+            ),
+            FilterOp::Lt(n) => IdOp::Lt(id(n)?),
+            FilterOp::Le(n) => IdOp::Le(id(n)?),
+            FilterOp::Gt(n) => IdOp::Gt(id(n)?),
+            FilterOp::Ge(n) => IdOp::Ge(id(n)?),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Order {
+    pub field: String,
+    pub desc: bool,
+}
+
+#[derive(Debug)]
+pub enum ContentQueryError {
+    /// Query refers to a field that does not exist, or applies an operator the field does not
+    /// support.
+    Invalid(String),
+    Database(diesel::result::Error),
+}
+
+impl From<diesel::result::Error> for ContentQueryError {
+    fn from(e: diesel::result::Error) -> Self {
+        ContentQueryError::Database(e)
+    }
+}
+
+/// Builds the query selecting ids of visible contents of the model, filtered and ordered by their
+/// values in the given locale. Contents are ordered by their id after the given order, so that the
+/// order is stable for pagination.
+pub async fn query_contents(
+    conn: &mut Connection,
+    model_id: i32,
+    locale: &str,
+    filters: Vec<Filter>,
+    order: Option<&Order>,
+    now: NaiveDateTime,
+) -> Result<contents::BoxedQuery<'static, Backend, Integer>, ContentQueryError> {
+    let mut contents_query = contents::table
+        .filter(contents::model_id.eq(model_id))
+        .filter(visible_at(now))
+        .select(contents::id)
+        .into_boxed();
+
+    for filter in filters {
+        if filter.field == "id" {
+            let Some(op) = filter.op.into_id_op() else {
+                return Err(ContentQueryError::Invalid(
+                    "Could not parse value as integer, filtering \"id\" requires integer values"
+                        .to_string(),
+                ));
+            };
+
+            contents_query = match op {
+                IdOp::Eq(id) => contents_query.filter(contents::id.eq(id)),
+                IdOp::In(ids) => contents_query.filter(contents::id.eq_any(ids)),
+                IdOp::Lt(id) => contents_query.filter(contents::id.lt(id)),
+                IdOp::Le(id) => contents_query.filter(contents::id.le(id)),
+                IdOp::Gt(id) => contents_query.filter(contents::id.gt(id)),
+                IdOp::Ge(id) => contents_query.filter(contents::id.ge(id)),
+            };
+
+            continue;
+        }
+
+        if !matches!(filter.op, FilterOp::Eq(_) | FilterOp::In(_)) {
+            let kind = model_fields::table
+                .inner_join(fields::table)
+                .filter(
+                    model_fields::model_id
+                        .eq(model_id)
+                        .and(model_fields::key.eq(&filter.field)),
+                )
+                .select(fields::kind)
+                .first::<String>(conn)
+                .await
+                .optional()?;
+
+            if kind.as_deref() != Some("int") {
+                return Err(ContentQueryError::Invalid(format!(
+                    "Numeric comparisons are only supported on int fields, {} is not",
+                    filter.field
+                )));
+            }
+        }
+
+        let values_query = content_values::table
+            .inner_join(model_fields::table)
+            .filter(
+                model_fields::model_id
+                    .eq(model_id)
+                    .and(model_fields::key.eq(filter.field)),
+            )
+            .filter(
+                content_values::locale
+                    .eq(locale.to_string())
+                    .or(content_values::locale.is_null()),
+            )
+            .select(content_values::content_id)
+            .into_boxed();
+
+        let values_query = match filter.op {
+            FilterOp::Eq(value) => values_query.filter(content_values::value.eq(value)),
+            FilterOp::In(values) => values_query.filter(content_values::value.eq_any(values)),
+            FilterOp::Lt(n) => values_query.filter(IntValue.lt(n)),
+            FilterOp::Le(n) => values_query.filter(IntValue.le(n)),
+            FilterOp::Gt(n) => values_query.filter(IntValue.gt(n)),
+            FilterOp::Ge(n) => values_query.filter(IntValue.ge(n)),
+        };
+
+        contents_query = contents_query.filter(contents::id.eq_any(values_query));
+    }
+
+    if let Some(order) = order {
+        contents_query = match order.field.as_str() {
+            "id" => contents_query,
+            "created_at" if order.desc => contents_query.order(contents::created_at.desc()),
+            "created_at" => contents_query.order(contents::created_at.asc()),
+            "updated_at" if order.desc => contents_query.order(contents::updated_at.desc()),
+            "updated_at" => contents_query.order(contents::updated_at.asc()),
+            field => {
+                let Some((model_field_id, kind)) = model_fields::table
+                    .inner_join(fields::table)
+                    .filter(
+                        model_fields::model_id
+                            .eq(model_id)
+                            .and(model_fields::key.eq(field)),
+                    )
+                    .select((model_fields::id, fields::kind))
+                    .first::<(i32, String)>(conn)
+                    .await
+                    .optional()?
+                else {
+                    return Err(ContentQueryError::Invalid(format!(
+                        "Could not find field {field} to order contents by"
+                    )));
+                };
+
+                let int = || FieldValue::int(model_field_id, locale.to_string());
+                let float = || FieldValue::float(model_field_id, locale.to_string());
+                let time = || FieldValue::time(model_field_id, locale.to_string());
+                let text = || FieldValue::text(model_field_id, locale.to_string());
+
+                // Contents without a value for the field come last in both directions
+                match (FieldKind::of(&kind), order.desc) {
+                    (FieldKind::Int, true) => contents_query.order((int().is_null(), int().desc())),
+                    (FieldKind::Int, false) => contents_query.order((int().is_null(), int().asc())),
+                    (FieldKind::Float, true) => {
+                        contents_query.order((float().is_null(), float().desc()))
+                    }
+                    (FieldKind::Float, false) => {
+                        contents_query.order((float().is_null(), float().asc()))
+                    }
+                    (FieldKind::Date | FieldKind::DateTime, true) => {
+                        contents_query.order((time().is_null(), time().desc()))
+                    }
+                    (FieldKind::Date | FieldKind::DateTime, false) => {
+                        contents_query.order((time().is_null(), time().asc()))
+                    }
+                    (_, true) => contents_query.order((text().is_null(), text().desc())),
+                    (_, false) => contents_query.order((text().is_null(), text().asc())),
+                }
+            }
+        };
+    }
+
+    Ok(if order.is_some_and(|o| o.desc) {
+        contents_query.then_order_by(contents::id.desc())
+    } else {
+        contents_query.then_order_by(contents::id.asc())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveDateTime};
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+
+    use crate::{
+        db::Pool,
+        middlewares::permission::Permission,
+        models::ContentStage,
+        schema::{content_values, contents, fields, locales, model_fields, models},
+        test::{create_pool, DB_CONFIG},
+    };
+
+    use super::{
+        modify_permission, query_contents, transition_permission, validate_workflow, visible_at,
+        IntValue, Order, SearchRank, Transition, DEFAULT_WORKFLOW,
+    };
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, 17)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    async fn create_content(
+        pool: &Pool,
+        model_id: i32,
+        stage: ContentStage,
+        publish_at: Option<NaiveDateTime>,
+        unpublish_at: Option<NaiveDateTime>,
+    ) -> i32 {
+        diesel::insert_into(contents::table)
+            .values((
+                contents::model_id.eq(model_id),
+                contents::name.eq("content"),
+                contents::stage.eq(stage),
+                contents::publish_at.eq(publish_at),
+                contents::unpublish_at.eq(unpublish_at),
+            ))
+            .returning(contents::id)
+            .get_result::<i32>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn visible_contents(pool: &Pool, model_id: i32, now: NaiveDateTime) -> Vec<i32> {
+        contents::table
+            .filter(contents::model_id.eq(model_id))
+            .filter(visible_at(now))
+            .order(contents::id.asc())
+            .select(contents::id)
+            .load::<i32>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn it_requires_publish_permission_to_publish() {
+        assert!(matches!(
+            transition_permission(
+                &DEFAULT_WORKFLOW,
+                &ContentStage::Draft,
+                &ContentStage::InReview
+            ),
+            Some(Permission::ContentWrite)
+        ));
+
+        assert!(matches!(
+            transition_permission(
+                &DEFAULT_WORKFLOW,
+                &ContentStage::InReview,
+                &ContentStage::Approved
+            ),
+            Some(Permission::ContentReview)
+        ));
+
+        assert!(matches!(
+            transition_permission(
+                &DEFAULT_WORKFLOW,
+                &ContentStage::Approved,
+                &ContentStage::Published
+            ),
+            Some(Permission::ContentPublish)
+        ));
+    }
+
+    #[test]
+    fn it_does_not_allow_skipping_stages() {
+        assert!(transition_permission(
+            &DEFAULT_WORKFLOW,
+            &ContentStage::Draft,
+            &ContentStage::Published
+        )
+        .is_none());
+
+        assert!(transition_permission(
+            &DEFAULT_WORKFLOW,
+            &ContentStage::InReview,
+            &ContentStage::Published
+        )
+        .is_none());
+
+        assert!(transition_permission(
+            &DEFAULT_WORKFLOW,
+            &ContentStage::Draft,
+            &ContentStage::Draft
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn it_parses_and_validates_configured_workflows() {
+        let workflow: Vec<Transition> = serde_json::from_str(
+            r#"[{"from":"draft","to":"published","permission":"content.publish"}]"#,
+        )
+        .unwrap();
+
+        assert!(validate_workflow(&workflow).is_ok());
+        assert!(matches!(
+            transition_permission(&workflow, &ContentStage::Draft, &ContentStage::Published),
+            Some(Permission::ContentPublish)
+        ));
+
+        assert!(serde_json::from_str::<Vec<Transition>>(
+            r#"[{"from":"draft","to":"published","permission":"content.anything"}]"#,
+        )
+        .is_err());
+
+        let mut workflow = DEFAULT_WORKFLOW.to_vec();
+        assert!(validate_workflow(&workflow).is_ok());
+
+        workflow.push(DEFAULT_WORKFLOW[0].clone());
+        assert_eq!(Err("duplicate_transition"), validate_workflow(&workflow));
+
+        workflow.pop();
+        workflow.push(Transition {
+            from: ContentStage::Draft,
+            to: ContentStage::Draft,
+            permission: Permission::ContentWrite,
+        });
+        assert_eq!(Err("invalid_transition"), validate_workflow(&workflow));
+    }
+
+    #[test]
+    fn it_requires_publish_permission_to_modify_published_contents() {
+        assert!(matches!(
+            modify_permission(&DEFAULT_WORKFLOW, &ContentStage::Draft),
+            Permission::ContentWrite
+        ));
+        assert!(matches!(
+            modify_permission(&DEFAULT_WORKFLOW, &ContentStage::Published),
+            Permission::ContentPublish
+        ));
+
+        let workflow: Vec<Transition> = serde_json::from_str(
+            r#"[{"from":"draft","to":"published","permission":"content.review"}]"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            modify_permission(&workflow, &ContentStage::Published),
+            Permission::ContentReview
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_respects_publish_and_unpublish_times() {
+        let pool = create_pool(DB_CONFIG).await;
+
+        let model_id = diesel::insert_into(models::table)
+            .values((models::key.eq("schedule"), models::name.eq("Schedule")))
+            .returning(models::id)
+            .get_result::<i32>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let always = create_content(&pool, model_id, ContentStage::Published, None, None).await;
+        let draft = create_content(&pool, model_id, ContentStage::Draft, Some(at(10)), None).await;
+        let embargoed =
+            create_content(&pool, model_id, ContentStage::Published, Some(at(12)), None).await;
+        let expiring =
+            create_content(&pool, model_id, ContentStage::Published, None, Some(at(14))).await;
+        let window = create_content(
+            &pool,
+            model_id,
+            ContentStage::Published,
+            Some(at(12)),
+            Some(at(14)),
+        )
+        .await;
+
+        assert_eq!(
+            vec![always, expiring],
+            visible_contents(&pool, model_id, at(11)).await
+        );
+
+        assert_eq!(
+            vec![always, embargoed, expiring, window],
+            visible_contents(&pool, model_id, at(12)).await
+        );
+
+        assert_eq!(
+            vec![always, embargoed],
+            visible_contents(&pool, model_id, at(14)).await
+        );
+
+        assert!(!visible_contents(&pool, model_id, at(23))
+            .await
+            .contains(&draft));
+    }
+
+    #[tokio::test]
+    async fn it_compares_values_as_integers() {
+        let pool = create_pool(DB_CONFIG).await;
+        let mut conn = pool.get().await.unwrap();
+
+        let model_id = diesel::insert_into(models::table)
+            .values((models::key.eq("numbers"), models::name.eq("Numbers")))
+            .returning(models::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        let field_id = diesel::insert_into(fields::table)
+            .values((
+                fields::key.eq("number"),
+                fields::name.eq("Number"),
+                fields::kind.eq("int"),
+            ))
+            .returning(fields::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        let model_field_id = diesel::insert_into(model_fields::table)
+            .values((
+                model_fields::field_id.eq(field_id),
+                model_fields::model_id.eq(model_id),
+                model_fields::key.eq("weight"),
+                model_fields::name.eq("Weight"),
+            ))
+            .returning(model_fields::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        drop(conn);
+
+        let mut content_ids = vec![];
+
+        for value in ["9", "10", "-3", "not a number"] {
+            let content_id =
+                create_content(&pool, model_id, ContentStage::Published, None, None).await;
+
+            diesel::insert_into(content_values::table)
+                .values((
+                    content_values::content_id.eq(content_id),
+                    content_values::model_field_id.eq(model_field_id),
+                    content_values::value.eq(value),
+                ))
+                .execute(&mut pool.get().await.unwrap())
+                .await
+                .unwrap();
+
+            content_ids.push(content_id);
+        }
+
+        let greater_than = |n: i64| {
+            content_values::table
+                .filter(content_values::model_field_id.eq(model_field_id))
+                .filter(IntValue.gt(n))
+                .order(content_values::content_id.asc())
+                .select(content_values::content_id)
+        };
+
+        assert_eq!(
+            vec![content_ids[0], content_ids[1]],
+            greater_than(2)
+                .load::<i32>(&mut pool.get().await.unwrap())
+                .await
+                .unwrap()
+        );
+
+        assert_eq!(
+            vec![content_ids[0], content_ids[1], content_ids[3]],
+            greater_than(-1)
+                .load::<i32>(&mut pool.get().await.unwrap())
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn it_orders_contents_by_int_and_date_values() {
+        let pool = create_pool(DB_CONFIG).await;
+        let mut conn = pool.get().await.unwrap();
+
+        let model_id = diesel::insert_into(models::table)
+            .values((models::key.eq("events"), models::name.eq("Events")))
+            .returning(models::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        let mut model_field_ids = vec![];
+
+        for (key, kind) in [
+            ("weight", "int"),
+            ("day", "date"),
+            ("starts_at", "datetime"),
+        ] {
+            let field_id = diesel::insert_into(fields::table)
+                .values((
+                    fields::key.eq(key),
+                    fields::name.eq(key),
+                    fields::kind.eq(kind),
+                ))
+                .returning(fields::id)
+                .get_result::<i32>(&mut conn)
+                .await
+                .unwrap();
+
+            let model_field_id = diesel::insert_into(model_fields::table)
+                .values((
+                    model_fields::field_id.eq(field_id),
+                    model_fields::model_id.eq(model_id),
+                    model_fields::key.eq(key),
+                    model_fields::name.eq(key),
+                ))
+                .returning(model_fields::id)
+                .get_result::<i32>(&mut conn)
+                .await
+                .unwrap();
+
+            model_field_ids.push(model_field_id);
+        }
+
+        drop(conn);
+
+        let mut content_ids = vec![];
+
+        // Numerical and chronological orders of weights and start times differ from their texts
+        for values in [
+            ["10", "2026-10-17", "2026-10-17T12:00:00+03:00"],
+            ["2", "2026-09-30", "2026-10-17T10:00:00Z"],
+            ["-1", "2027-01-01", "2026-10-17T09:30:00Z"],
+        ] {
+            let content_id =
+                create_content(&pool, model_id, ContentStage::Published, None, None).await;
+
+            for (model_field_id, value) in model_field_ids.iter().zip(values) {
+                diesel::insert_into(content_values::table)
+                    .values((
+                        content_values::content_id.eq(content_id),
+                        content_values::model_field_id.eq(model_field_id),
+                        content_values::value.eq(value),
+                    ))
+                    .execute(&mut pool.get().await.unwrap())
+                    .await
+                    .unwrap();
+            }
+
+            content_ids.push(content_id);
+        }
+
+        let without_values =
+            create_content(&pool, model_id, ContentStage::Published, None, None).await;
+
+        let ordered = |field: &'static str, desc: bool| {
+            let pool = pool.clone();
+
+            async move {
+                let mut conn = pool.get().await.unwrap();
+
+                let order = Order {
+                    field: field.to_string(),
+                    desc,
+                };
+
+                query_contents(&mut conn, model_id, "en", vec![], Some(&order), at(12))
+                    .await
+                    .unwrap()
+                    .load::<i32>(&mut conn)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let [ten, two, minus_one] = content_ids[..] else {
+            unreachable!()
+        };
+
+        assert_eq!(
+            vec![minus_one, two, ten, without_values],
+            ordered("weight", false).await
+        );
+        assert_eq!(
+            vec![ten, two, minus_one, without_values],
+            ordered("weight", true).await
+        );
+        assert_eq!(
+            vec![two, ten, minus_one, without_values],
+            ordered("day", false).await
+        );
+        assert_eq!(
+            vec![ten, minus_one, two, without_values],
+            ordered("starts_at", false).await
+        );
+        assert_eq!(
+            vec![two, minus_one, ten, without_values],
+            ordered("starts_at", true).await
+        );
+    }
+
+    #[tokio::test]
+    async fn it_ranks_search_results_of_visible_contents() {
+        let pool = create_pool(DB_CONFIG).await;
+        let mut conn = pool.get().await.unwrap();
+
+        diesel::insert_into(locales::table)
+            .values(vec![
+                (locales::key.eq("en"), locales::name.eq("English")),
+                (locales::key.eq("tr"), locales::name.eq("Türkçe")),
+            ])
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let model_id = diesel::insert_into(models::table)
+            .values((models::key.eq("articles"), models::name.eq("Articles")))
+            .returning(models::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        let field_id = diesel::insert_into(fields::table)
+            .values((
+                fields::key.eq("text"),
+                fields::name.eq("Text"),
+                fields::kind.eq("string"),
+            ))
+            .returning(fields::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        let model_field_id = diesel::insert_into(model_fields::table)
+            .values((
+                model_fields::field_id.eq(field_id),
+                model_fields::model_id.eq(model_id),
+                model_fields::key.eq("body"),
+                model_fields::name.eq("Body"),
+            ))
+            .returning(model_fields::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        drop(conn);
+
+        let mut content_ids = vec![];
+
+        for (stage, locale, value) in [
+            (ContentStage::Published, Some("en"), "rust"),
+            (ContentStage::Published, None, "rust and more rust"),
+            (ContentStage::Draft, Some("en"), "rust"),
+            (ContentStage::Published, Some("tr"), "rust"),
+            (ContentStage::Published, Some("en"), "python"),
+        ] {
+            let content_id = create_content(&pool, model_id, stage, None, None).await;
+
+            diesel::insert_into(content_values::table)
+                .values((
+                    content_values::content_id.eq(content_id),
+                    content_values::model_field_id.eq(model_field_id),
+                    content_values::locale.eq(locale),
+                    content_values::value.eq(value),
+                    content_values::search_text.eq(value),
+                ))
+                .execute(&mut pool.get().await.unwrap())
+                .await
+                .unwrap();
+
+            content_ids.push(content_id);
+        }
+
+        let rank = SearchRank::new("Rust", "en".to_string());
+
+        let found = contents::table
+            .filter(contents::model_id.eq(model_id))
+            .filter(visible_at(at(12)))
+            .filter(rank.clone().is_not_null())
+            .order(rank.desc())
+            .select(contents::id)
+            .load::<i32>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(vec![content_ids[1], content_ids[0]], found);
+    }
+}
@@ -30,6 +30,15 @@ impl Crypto {
     pub fn sign512(&self, message: &[u8]) -> String {
         BASE64_STANDARD.encode(hmac::sign(&self.0.hmac512_key, message))
     }
+
+    /// Verifies a signature created by [`Crypto::sign512`] in constant time.
+    pub fn verify512(&self, message: &[u8], signature: &str) -> bool {
+        let Ok(signature) = BASE64_STANDARD.decode(signature) else {
+            return false;
+        };
+
+        hmac::verify(&self.0.hmac512_key, message, &signature).is_ok()
+    }
 }
 
 struct Inner {
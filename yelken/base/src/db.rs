@@ -17,6 +17,11 @@ pub type BackendValue<'a> = <Backend as diesel::backend::Backend>::RawValue<'a>;
 pub type Pool = deadpool::Pool<Connection>;
 pub type PooledConnection = Object<Connection>;
 
+diesel::define_sql_function! {
+    /// Lowercases the given text, used for case insensitive matching on every backend
+    fn lower(x: diesel::sql_types::Text) -> diesel::sql_types::Text;
+}
+
 pub trait BatchQuery<Conn: AsyncConnection> {
     type Output;
 
@@ -0,0 +1,733 @@
+use std::{collections::HashMap, fmt, str::FromStr, sync::LazyLock};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::Connection,
+    models::{Field, ModelField, ValidationRules},
+    responses::HttpError,
+    schema::{assets, content_values, contents, enum_options},
+    validate::{is_email, Errors},
+};
+
+/// Kinds a field can have. Values of every kind are stored as strings, the kind determines how a
+/// value is validated and interpreted.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    String,
+    Multiline,
+    Int,
+    Float,
+    Bool,
+    Date,
+    DateTime,
+    Markdown,
+    RichText,
+    Json,
+    Url,
+    Email,
+    Enum,
+    Asset,
+    Reference,
+}
+
+impl FieldKind {
+    pub const ALL: [FieldKind; 15] = [
+        FieldKind::String,
+        FieldKind::Multiline,
+        FieldKind::Int,
+        FieldKind::Float,
+        FieldKind::Bool,
+        FieldKind::Date,
+        FieldKind::DateTime,
+        FieldKind::Markdown,
+        FieldKind::RichText,
+        FieldKind::Json,
+        FieldKind::Url,
+        FieldKind::Email,
+        FieldKind::Enum,
+        FieldKind::Asset,
+        FieldKind::Reference,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FieldKind::String => "string",
+            FieldKind::Multiline => "multiline",
+            FieldKind::Int => "int",
+            FieldKind::Float => "float",
+            FieldKind::Bool => "bool",
+            FieldKind::Date => "date",
+            FieldKind::DateTime => "datetime",
+            FieldKind::Markdown => "markdown",
+            FieldKind::RichText => "richtext",
+            FieldKind::Json => "json",
+            FieldKind::Url => "url",
+            FieldKind::Email => "email",
+            FieldKind::Enum => "enum",
+            FieldKind::Asset => "asset",
+            FieldKind::Reference => "reference",
+        }
+    }
+
+    /// Returns the kind of the field, unknown kinds are treated as plain strings.
+    pub fn of(kind: &str) -> FieldKind {
+        kind.parse().unwrap_or_else(|_| {
+            log::error!("Unhandled field kind is found, {kind}");
+
+            FieldKind::String
+        })
+    }
+}
+
+impl fmt::Display for FieldKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FieldKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FieldKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("Unknown field kind {s}"))
+    }
+}
+
+pub fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.naive_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f"))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+        .ok()
+}
+
+/// Checks whether the value is well formed for the kind. Kinds whose values refer to other
+/// resources are only checked for their form.
+pub fn check_value(kind: FieldKind, value: &str) -> Result<(), String> {
+    let valid = match kind {
+        FieldKind::String
+        | FieldKind::Multiline
+        | FieldKind::Markdown
+        | FieldKind::RichText
+        | FieldKind::Enum
+        | FieldKind::Asset => true,
+        FieldKind::Int => value.parse::<i64>().is_ok(),
+        FieldKind::Float => value.parse::<f64>().is_ok_and(f64::is_finite),
+        FieldKind::Bool => matches!(value, "true" | "false"),
+        FieldKind::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+        FieldKind::DateTime => parse_datetime(value).is_some(),
+        FieldKind::Json => serde_json::from_str::<serde_json::Value>(value).is_ok(),
+        FieldKind::Url => url::Url::parse(value).is_ok_and(|url| {
+            matches!(url.scheme(), "http" | "https" | "mailto" | "tel") || url.has_host()
+        }),
+        FieldKind::Email => is_email(value),
+        FieldKind::Reference => value.parse::<i32>().is_ok(),
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Value is not a valid {kind}"))
+    }
+}
+
+/// Brings the value into the form it is stored in. Rich text values are rendered as is, so they are
+/// sanitized before they are stored.
+pub fn sanitize_value(kind: FieldKind, value: String) -> String {
+    match kind {
+        FieldKind::RichText => sanitize_html(&value),
+        _ => value,
+    }
+}
+
+/// Keeps only the allowlisted markup of the html, scripts, event handler attributes and
+/// `javascript:` urls are removed.
+pub fn sanitize_html(html: &str) -> String {
+    ammonia::clean(html)
+}
+
+/// Text of the value that full-text search indexes. Only textual values are searched, markup of
+/// markdown and rich text values is stripped so that tags and their attributes are not matched.
+pub fn search_text(kind: FieldKind, value: &str) -> Option<String> {
+    static MARKUP: LazyLock<Regex> = LazyLock::new(|| Regex::new("</?[a-zA-Z][^>]*>").unwrap());
+
+    match kind {
+        FieldKind::String | FieldKind::Multiline => Some(value.to_string()),
+        FieldKind::Markdown | FieldKind::RichText => Some(
+            MARKUP
+                .replace_all(value, " ")
+                .replace("&nbsp;", " ")
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&amp;", "&"),
+        ),
+        _ => None,
+    }
+}
+
+/// Converts the stored value into JSON, values that cannot be converted are kept as strings.
+pub fn to_json(kind: FieldKind, value: String) -> serde_json::Value {
+    use serde_json::Value;
+
+    match kind {
+        FieldKind::Int | FieldKind::Reference => value
+            .parse::<i64>()
+            .map(Value::from)
+            .unwrap_or(Value::String(value)),
+        FieldKind::Float => value
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .unwrap_or(Value::String(value)),
+        FieldKind::Bool => Value::Bool(value == "true"),
+        FieldKind::Json => serde_json::from_str(&value).unwrap_or(Value::String(value)),
+        _ => Value::String(value),
+    }
+}
+
+/// Checks whether the rules are applicable to a model field of the kind and consistent in themselves.
+pub fn check_rules(
+    kind: FieldKind,
+    multiple: bool,
+    rules: &ValidationRules,
+) -> Result<(), &'static str> {
+    let textual = matches!(
+        kind,
+        FieldKind::String
+            | FieldKind::Multiline
+            | FieldKind::Markdown
+            | FieldKind::RichText
+            | FieldKind::Url
+            | FieldKind::Email
+    );
+
+    if (rules.min_length.is_some() || rules.max_length.is_some() || rules.pattern.is_some())
+        && !textual
+    {
+        return Err("length_and_pattern_only_for_text");
+    }
+
+    if (rules.min.is_some() || rules.max.is_some())
+        && !matches!(kind, FieldKind::Int | FieldKind::Float)
+    {
+        return Err("range_only_for_numbers");
+    }
+
+    if rules.mime_types.is_some() && kind != FieldKind::Asset {
+        return Err("mime_types_only_for_assets");
+    }
+
+    if rules.max_items.is_some() && !multiple {
+        return Err("max_items_only_for_multiple");
+    }
+
+    if rules
+        .min_length
+        .zip(rules.max_length)
+        .is_some_and(|(min, max)| min > max)
+        || rules.min.zip(rules.max).is_some_and(|(min, max)| min > max)
+    {
+        return Err("invalid_range");
+    }
+
+    if let Some(pattern) = &rules.pattern {
+        Regex::new(pattern).map_err(|_| "invalid_pattern")?;
+    }
+
+    Ok(())
+}
+
+/// A value being written into a content for a model field.
+pub struct FieldValue<'a> {
+    pub model_field: &'a ModelField,
+    pub field: &'a Field,
+    pub locale: Option<&'a str>,
+    pub value: &'a str,
+}
+
+/// Validates the values written into the content against their fields and the validation rules
+/// of their model fields. Violations are reported per model field. Values given together are also
+/// counted against the maximum item count of their model fields.
+pub async fn validate_values(
+    conn: &mut Connection,
+    content_id: Option<i32>,
+    values: &[FieldValue<'_>],
+) -> Result<(), HttpError> {
+    let mut errors = Errors::new();
+
+    for value in values {
+        for error in value_errors(conn, content_id, value).await? {
+            errors.insert_field(value.model_field.key.clone(), error);
+        }
+    }
+
+    let mut counts = HashMap::<(i32, Option<&str>), u32>::new();
+
+    for value in values {
+        *counts
+            .entry((value.model_field.id, value.locale))
+            .or_default() += 1;
+    }
+
+    for ((model_field_id, _), count) in counts {
+        let Some(model_field) = values
+            .iter()
+            .map(|v| v.model_field)
+            .find(|mf| mf.id == model_field_id)
+        else {
+            continue;
+        };
+
+        if model_field
+            .validation
+            .max_items
+            .is_some_and(|max| count > max)
+        {
+            errors.insert_field(model_field.key.clone(), "too_many_items");
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(HttpError::validation_errors(errors));
+    }
+
+    Ok(())
+}
+
+async fn value_errors(
+    conn: &mut Connection,
+    content_id: Option<i32>,
+    value: &FieldValue<'_>,
+) -> Result<Vec<&'static str>, HttpError> {
+    let kind = FieldKind::of(&value.field.kind);
+    let rules = &value.model_field.validation;
+
+    if check_value(kind, value.value).is_err() {
+        return Ok(vec!["invalid_value"]);
+    }
+
+    let mut errors = vec![];
+
+    match kind {
+        FieldKind::Enum => {
+            let exists = diesel::select(diesel::dsl::exists(
+                enum_options::table
+                    .filter(enum_options::field_id.eq(value.field.id))
+                    .filter(enum_options::value.eq(value.value)),
+            ))
+            .get_result::<bool>(conn)
+            .await?;
+
+            if !exists {
+                errors.push("unknown_option");
+            }
+        }
+        FieldKind::Reference => {
+            let content_id = value.value.parse::<i32>().unwrap_or_default();
+
+            let mut query = contents::table
+                .filter(contents::id.eq(content_id))
+                .into_boxed();
+
+            if let Some(model_id) = value.model_field.reference_model_id {
+                query = query.filter(contents::model_id.eq(model_id));
+            }
+
+            let exists = diesel::select(diesel::dsl::exists(query))
+                .get_result::<bool>(conn)
+                .await?;
+
+            if !exists {
+                errors.push("referenced_content_not_found");
+            }
+        }
+        FieldKind::Asset if rules.mime_types.is_some() => {
+            let filetype = assets::table
+                .filter(assets::filename.eq(value.value))
+                .select(assets::filetype)
+                .first::<Option<String>>(conn)
+                .await
+                .optional()?;
+
+            match filetype {
+                None => errors.push("asset_not_found"),
+                Some(filetype) => {
+                    if !rules
+                        .mime_types
+                        .iter()
+                        .flatten()
+                        .any(|allowed| mime_matches(allowed, filetype.as_deref()))
+                    {
+                        errors.push("mime_type_not_allowed");
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    let length = value.value.chars().count() as u64;
+
+    if rules.min_length.is_some_and(|min| length < min as u64) {
+        errors.push("too_short");
+    }
+
+    if rules.max_length.is_some_and(|max| length > max as u64) {
+        errors.push("too_long");
+    }
+
+    if let Some(pattern) = &rules.pattern {
+        // Patterns are checked when rules are set, an invalid one can only come from the database
+        match Regex::new(pattern) {
+            Ok(regex) if regex.is_match(value.value) => {}
+            Ok(_) => errors.push("pattern_mismatch"),
+            Err(e) => log::warn!("Invalid pattern in validation rules, {pattern}, {e:?}"),
+        }
+    }
+
+    if let Ok(number) = value.value.parse::<f64>() {
+        if rules.min.is_some_and(|min| number < min) {
+            errors.push("below_minimum");
+        }
+
+        if rules.max.is_some_and(|max| number > max) {
+            errors.push("above_maximum");
+        }
+    }
+
+    if rules.unique {
+        let mut query = content_values::table
+            .filter(content_values::model_field_id.eq(value.model_field.id))
+            .filter(content_values::value.eq(value.value))
+            .into_boxed();
+
+        query = match value.locale {
+            Some(locale) => query.filter(content_values::locale.eq(locale)),
+            None => query.filter(content_values::locale.is_null()),
+        };
+
+        if let Some(content_id) = content_id {
+            query = query.filter(content_values::content_id.ne(content_id));
+        }
+
+        let exists = diesel::select(diesel::dsl::exists(query))
+            .get_result::<bool>(conn)
+            .await?;
+
+        if exists {
+            errors.push("not_unique");
+        }
+    }
+
+    Ok(errors)
+}
+
+/// Matches the MIME type against an allowed one, which may have a wildcard subtype like `image/*`.
+fn mime_matches(allowed: &str, filetype: Option<&str>) -> bool {
+    let Some(filetype) = filetype else {
+        return false;
+    };
+
+    match allowed.strip_suffix("/*") {
+        Some(ty) => filetype
+            .split_once('/')
+            .is_some_and(|(filetype, _)| filetype.eq_ignore_ascii_case(ty)),
+        None => filetype.eq_ignore_ascii_case(allowed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+
+    use crate::{
+        db::Pool,
+        models::{ContentStage, Field, ModelField, ValidationRules},
+        schema::{content_values, contents, fields, model_fields, models},
+        test::{create_pool, DB_CONFIG},
+    };
+
+    use super::{
+        check_rules, check_value, mime_matches, sanitize_value, search_text, validate_values,
+        FieldKind, FieldValue,
+    };
+
+    async fn create_model(pool: &Pool, key: &str) -> i32 {
+        diesel::insert_into(models::table)
+            .values((models::key.eq(key), models::name.eq(key)))
+            .returning(models::id)
+            .get_result::<i32>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn create_content(pool: &Pool, model_id: i32) -> i32 {
+        diesel::insert_into(contents::table)
+            .values((
+                contents::model_id.eq(model_id),
+                contents::name.eq("content"),
+                contents::stage.eq(ContentStage::Published),
+            ))
+            .returning(contents::id)
+            .get_result::<i32>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn it_sanitizes_rich_text_values() {
+        let value = r#"<p onclick="steal()">Hi <a href="javascript:steal()">there</a></p><script>steal()</script><img src=x onerror=steal()>"#;
+
+        let sanitized = sanitize_value(FieldKind::RichText, value.to_string());
+
+        assert!(sanitized.starts_with("<p>Hi <a"));
+        assert!(!sanitized.contains("script"));
+        assert!(!sanitized.contains("steal"));
+
+        assert_eq!(
+            value,
+            sanitize_value(FieldKind::Markdown, value.to_string())
+        );
+    }
+
+    #[test]
+    fn it_indexes_text_of_textual_values_only() {
+        assert_eq!(
+            Some("  Fish & chips  <3 "),
+            search_text(
+                FieldKind::RichText,
+                r#"<p><img src="chips.png">Fish &amp; chips</p> &lt;3 "#
+            )
+            .as_deref()
+        );
+        assert_eq!(
+            Some("Sailing"),
+            search_text(FieldKind::String, "Sailing").as_deref()
+        );
+        assert_eq!(None, search_text(FieldKind::Json, r#"{"sailing":true}"#));
+        assert_eq!(None, search_text(FieldKind::Asset, "sailing.png"));
+    }
+
+    #[test]
+    fn it_checks_values_by_kind() {
+        let cases = [
+            (FieldKind::Int, "42", "4.2"),
+            (FieldKind::Float, "4.2", "NaN"),
+            (FieldKind::Bool, "true", "yes"),
+            (FieldKind::Date, "2026-10-17", "17/10/2026"),
+            (FieldKind::DateTime, "2026-10-17T12:30", "2026-10-17"),
+            (FieldKind::Json, r#"{"a": [1]}"#, "{a: 1}"),
+            (FieldKind::Url, "https://example.com/a", "example.com/a"),
+            (FieldKind::Email, "a@example.com", "a@example"),
+            (FieldKind::Reference, "7", "seven"),
+        ];
+
+        for (kind, valid, invalid) in cases {
+            assert!(check_value(kind, valid).is_ok(), "{kind} {valid}");
+            assert!(check_value(kind, invalid).is_err(), "{kind} {invalid}");
+        }
+
+        assert!(check_value(FieldKind::DateTime, "2026-10-17T12:30:00+03:00").is_ok());
+    }
+
+    #[test]
+    fn it_parses_kinds() {
+        for kind in FieldKind::ALL {
+            assert_eq!(Ok(kind), kind.as_str().parse());
+            assert_eq!(serde_json::json!(kind.as_str()), serde_json::json!(kind));
+        }
+
+        assert_eq!(FieldKind::String, FieldKind::of("unknown"));
+    }
+
+    #[tokio::test]
+    async fn it_validates_references_against_the_referenced_model() {
+        let pool = create_pool(DB_CONFIG).await;
+
+        let post = create_model(&pool, "post").await;
+        let author = create_model(&pool, "author").await;
+
+        let field = diesel::insert_into(fields::table)
+            .values((
+                fields::key.eq("reference_test"),
+                fields::name.eq("Reference"),
+                fields::kind.eq("reference"),
+            ))
+            .get_result::<Field>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let model_field = diesel::insert_into(model_fields::table)
+            .values((
+                model_fields::field_id.eq(field.id),
+                model_fields::model_id.eq(post),
+                model_fields::key.eq("author"),
+                model_fields::name.eq("Author"),
+                model_fields::reference_model_id.eq(author),
+            ))
+            .get_result::<ModelField>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let some_post = create_content(&pool, post).await;
+        let some_author = create_content(&pool, author).await;
+
+        let validate = |value: String| {
+            let pool = pool.clone();
+            let (model_field, field) = (&model_field, &field);
+
+            async move {
+                let value = FieldValue {
+                    model_field,
+                    field,
+                    locale: None,
+                    value: &value,
+                };
+
+                validate_values(&mut pool.get().await.unwrap(), None, &[value]).await
+            }
+        };
+
+        assert!(validate(some_author.to_string()).await.is_ok());
+        assert!(validate(some_post.to_string()).await.is_err());
+        assert!(validate((some_author + 1000).to_string()).await.is_err());
+    }
+
+    #[test]
+    fn it_checks_rules_against_kind() {
+        let rules = |json| serde_json::from_value::<ValidationRules>(json).unwrap();
+
+        let length = rules(serde_json::json!({ "minLength": 3, "maxLength": 10 }));
+        assert!(check_rules(FieldKind::String, false, &length).is_ok());
+        assert!(check_rules(FieldKind::Int, false, &length).is_err());
+
+        let range = rules(serde_json::json!({ "min": 10, "max": 1 }));
+        assert_eq!(
+            Err("invalid_range"),
+            check_rules(FieldKind::Int, false, &range)
+        );
+
+        let pattern = rules(serde_json::json!({ "pattern": "[a-z" }));
+        assert_eq!(
+            Err("invalid_pattern"),
+            check_rules(FieldKind::String, false, &pattern)
+        );
+
+        let max_items = rules(serde_json::json!({ "maxItems": 3 }));
+        assert!(check_rules(FieldKind::String, true, &max_items).is_ok());
+        assert!(check_rules(FieldKind::String, false, &max_items).is_err());
+
+        let mime_types = rules(serde_json::json!({ "mimeTypes": ["image/*"] }));
+        assert!(check_rules(FieldKind::Asset, false, &mime_types).is_ok());
+        assert!(check_rules(FieldKind::String, false, &mime_types).is_err());
+    }
+
+    #[test]
+    fn it_matches_mime_types() {
+        assert!(mime_matches("image/*", Some("image/png")));
+        assert!(mime_matches("application/pdf", Some("application/pdf")));
+        assert!(!mime_matches("image/*", Some("video/mp4")));
+        assert!(!mime_matches("image/png", Some("image/jpeg")));
+        assert!(!mime_matches("image/*", None));
+    }
+
+    #[tokio::test]
+    async fn it_validates_values_against_rules_of_model_fields() {
+        let pool = create_pool(DB_CONFIG).await;
+
+        let post = create_model(&pool, "post").await;
+
+        let field = diesel::insert_into(fields::table)
+            .values((
+                fields::key.eq("string_test"),
+                fields::name.eq("String"),
+                fields::kind.eq("string"),
+            ))
+            .get_result::<Field>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let model_field = diesel::insert_into(model_fields::table)
+            .values((
+                model_fields::field_id.eq(field.id),
+                model_fields::model_id.eq(post),
+                model_fields::key.eq("slug"),
+                model_fields::name.eq("Slug"),
+                model_fields::multiple.eq(true),
+                model_fields::validation.eq(ValidationRules {
+                    min_length: Some(3),
+                    max_length: Some(8),
+                    pattern: Some("^[a-z-]+$".to_string()),
+                    unique: true,
+                    max_items: Some(2),
+                    ..Default::default()
+                }),
+            ))
+            .get_result::<ModelField>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let some_post = create_content(&pool, post).await;
+
+        diesel::insert_into(content_values::table)
+            .values((
+                content_values::content_id.eq(some_post),
+                content_values::model_field_id.eq(model_field.id),
+                content_values::value.eq("taken"),
+            ))
+            .execute(&mut pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let validate = |values: &'static [&'static str], content_id: Option<i32>| {
+            let pool = pool.clone();
+            let (model_field, field) = (&model_field, &field);
+
+            async move {
+                let values = values
+                    .iter()
+                    .map(|value| FieldValue {
+                        model_field,
+                        field,
+                        locale: None,
+                        value,
+                    })
+                    .collect::<Vec<_>>();
+
+                validate_values(&mut pool.get().await.unwrap(), content_id, &values)
+                    .await
+                    .map_err(|e| e.context.unwrap_or_default())
+            }
+        };
+
+        assert!(validate(&["free", "slug"], None).await.is_ok());
+        assert!(validate(&["taken"], Some(some_post)).await.is_ok());
+
+        let errors = validate(&["ab", "too-long-slug", "Upper"], None)
+            .await
+            .unwrap_err();
+        for error in [
+            "too_short",
+            "too_long",
+            "pattern_mismatch",
+            "too_many_items",
+        ] {
+            assert!(errors.contains(error), "{error} in {errors}");
+        }
+
+        let errors = validate(&["taken"], None).await.unwrap_err();
+        assert!(errors.contains("not_unique"), "{errors}");
+    }
+}
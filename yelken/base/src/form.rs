@@ -0,0 +1,109 @@
+use crate::crypto::Crypto;
+
+/// Name of the form field that carries the fill token issued while rendering the form.
+pub const FORM_TOKEN_FIELD: &str = "_yk_token";
+
+/// Fill tokens older than this are rejected so that a token cannot be reused forever.
+const FORM_TOKEN_MAX_AGE: i64 = 60 * 60 * 24;
+
+fn token_message(form: &str, issued_at: i64) -> String {
+    format!("form:{form}:{issued_at}")
+}
+
+/// Issues a token for the given form that records when the form is rendered. It is used to reject
+/// submissions made faster than a human could fill the form.
+pub fn issue_form_token(crypto: &Crypto, form: &str, issued_at: i64) -> String {
+    let signature = crypto.sign512(token_message(form, issued_at).as_bytes());
+
+    format!("{issued_at}.{signature}")
+}
+
+pub fn verify_form_token(
+    crypto: &Crypto,
+    form: &str,
+    token: &str,
+    now: i64,
+    min_fill_seconds: i64,
+) -> Result<(), &'static str> {
+    let Some((issued_at, signature)) = token.split_once('.') else {
+        return Err("invalid_form_token");
+    };
+
+    let Ok(issued_at) = issued_at.parse::<i64>() else {
+        return Err("invalid_form_token");
+    };
+
+    if !crypto.verify512(token_message(form, issued_at).as_bytes(), signature) {
+        return Err("invalid_form_token");
+    }
+
+    if now - issued_at > FORM_TOKEN_MAX_AGE {
+        return Err("expired_form_token");
+    }
+
+    if now - issued_at < min_fill_seconds {
+        return Err("submitted_too_fast");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::Crypto;
+
+    use super::{issue_form_token, verify_form_token};
+
+    #[test]
+    fn it_accepts_tokens_after_min_fill_time() {
+        let crypto = Crypto::new("secret");
+
+        let token = issue_form_token(&crypto, "contact", 1000);
+
+        assert_eq!(
+            Err("submitted_too_fast"),
+            verify_form_token(&crypto, "contact", &token, 1002, 3)
+        );
+
+        assert_eq!(
+            Ok(()),
+            verify_form_token(&crypto, "contact", &token, 1003, 3)
+        );
+
+        assert_eq!(
+            Err("expired_form_token"),
+            verify_form_token(&crypto, "contact", &token, 1000 + 60 * 60 * 25, 3)
+        );
+    }
+
+    #[test]
+    fn it_rejects_forged_tokens() {
+        let crypto = Crypto::new("secret");
+
+        let token = issue_form_token(&crypto, "contact", 1000);
+
+        assert_eq!(
+            Err("invalid_form_token"),
+            verify_form_token(&crypto, "newsletter", &token, 1010, 3)
+        );
+
+        let (_, signature) = token.split_once('.').unwrap();
+
+        assert_eq!(
+            Err("invalid_form_token"),
+            verify_form_token(&crypto, "contact", &format!("900.{signature}"), 1010, 3)
+        );
+
+        let other = issue_form_token(&Crypto::new("other"), "contact", 1000);
+
+        assert_eq!(
+            Err("invalid_form_token"),
+            verify_form_token(&crypto, "contact", &other, 1010, 3)
+        );
+
+        assert_eq!(
+            Err("invalid_form_token"),
+            verify_form_token(&crypto, "contact", "garbage", 1010, 3)
+        );
+    }
+}
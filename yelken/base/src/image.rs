@@ -0,0 +1,353 @@
+use std::io::Cursor;
+
+use ::image::{
+    codecs::{avif::AvifEncoder, jpeg::JpegEncoder},
+    imageops::FilterType,
+    DynamicImage, ImageDecoder, ImageFormat, ImageReader,
+};
+use serde::Deserialize;
+
+pub use ::image::ImageError;
+
+/// Largest width or height a derivative can be requested with.
+pub const MAX_DIMENSION: u32 = 4096;
+
+const JPEG_QUALITY: u8 = 82;
+const AVIF_QUALITY: u8 = 70;
+const AVIF_SPEED: u8 = 8;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Fit {
+    /// Scale the image to fit in the given box while preserving its aspect ratio
+    #[default]
+    Contain,
+    /// Scale and crop the image to fill the given box
+    Cover,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Avif,
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl Format {
+    pub fn from_path(path: &str) -> Option<Self> {
+        ImageFormat::from_path(path)
+            .ok()
+            .and_then(Self::from_image_format)
+    }
+
+    fn from_image_format(format: ImageFormat) -> Option<Self> {
+        match format {
+            ImageFormat::Avif => Some(Format::Avif),
+            ImageFormat::Jpeg => Some(Format::Jpeg),
+            ImageFormat::Png => Some(Format::Png),
+            ImageFormat::WebP => Some(Format::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Avif => "avif",
+            Format::Jpeg => "jpg",
+            Format::Png => "png",
+            Format::Webp => "webp",
+        }
+    }
+}
+
+/// Transformation applied to an image to produce a derivative, parsed from the query string
+/// of an asset url, e.g. `?width=640&height=480&fit=cover&format=webp`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct Transform {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub fit: Fit,
+    pub format: Option<Format>,
+}
+
+impl Transform {
+    pub fn is_empty(&self) -> bool {
+        self.width.is_none() && self.height.is_none() && self.format.is_none()
+    }
+
+    /// Checks whether the transformation can be applied. Dimensions are limited to the given
+    /// sizes since every requested derivative is generated and stored.
+    pub fn validate(&self, sizes: &[u32]) -> Result<(), &'static str> {
+        let mut dimensions = [self.width, self.height].into_iter().flatten();
+
+        if dimensions.clone().any(|d| d == 0 || d > MAX_DIMENSION) {
+            return Err("invalid_dimension");
+        }
+
+        if dimensions.any(|d| !sizes.contains(&d)) {
+            return Err("unsupported_dimension");
+        }
+
+        if self.fit == Fit::Cover && (self.width.is_none() || self.height.is_none()) {
+            return Err("cover_needs_width_and_height");
+        }
+
+        Ok(())
+    }
+
+    /// Name of the derivative file, unique for each transformation of a source image. `version`
+    /// is expected to change whenever the source image changes so that stale derivatives are
+    /// not served.
+    pub fn derivative_name(&self, source: Format, version: &str) -> String {
+        let fit = match self.fit {
+            Fit::Contain => "contain",
+            Fit::Cover => "cover",
+        };
+
+        format!(
+            "{}x{}-{fit}-{version}.{}",
+            self.width.unwrap_or(0),
+            self.height.unwrap_or(0),
+            self.format.unwrap_or(source).extension()
+        )
+    }
+
+    /// Decodes the given image, applies the transformation and encodes the result. Metadata of
+    /// the source image, like EXIF, is not carried over to the derivative.
+    pub fn apply(&self, bytes: &[u8]) -> Result<(Vec<u8>, Format), ::image::ImageError> {
+        let (image, source) = decode(bytes)?;
+
+        let image = match (self.width, self.height, self.fit) {
+            (Some(width), Some(height), Fit::Cover) => {
+                image.resize_to_fill(width, height, FilterType::Lanczos3)
+            }
+            (None, None, _) => image,
+            (width, height, _) => {
+                let width = width.unwrap_or(MAX_DIMENSION);
+                let height = height.unwrap_or(MAX_DIMENSION);
+
+                // Derivatives are never upscaled
+                if width >= image.width() && height >= image.height() {
+                    image
+                } else {
+                    image.resize(width, height, FilterType::Lanczos3)
+                }
+            }
+        };
+
+        let format = self.format.unwrap_or(source);
+
+        Ok((encode(&image, format)?, format))
+    }
+}
+
+/// Prepares an uploaded image to be stored. Image is rotated according to its EXIF orientation,
+/// downscaled so that neither side exceeds `max_dimension` and re-encoded in its own format
+/// which strips the metadata, like GPS location. Returns `None` if the bytes are not in a
+/// supported image format.
+pub fn prepare_upload(
+    bytes: &[u8],
+    max_dimension: u32,
+) -> Result<Option<Vec<u8>>, ::image::ImageError> {
+    // Animated GIFs would lose their frames and AVIF decoding is not supported
+    let Some(format) = ::image::guess_format(bytes)
+        .ok()
+        .filter(|f| *f != ImageFormat::Avif)
+        .and_then(Format::from_image_format)
+    else {
+        return Ok(None);
+    };
+
+    let (mut image, _) = decode(bytes)?;
+
+    if max_dimension > 0 && (image.width() > max_dimension || image.height() > max_dimension) {
+        image = image.resize(max_dimension, max_dimension, FilterType::Lanczos3);
+    }
+
+    encode(&image, format).map(Some)
+}
+
+/// Reads the dimensions of the image from its header without decoding it. Returns `None` if the
+/// bytes are not in a supported image format.
+pub fn dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+fn decode(bytes: &[u8]) -> Result<(DynamicImage, Format), ::image::ImageError> {
+    let reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+
+    // Sources in formats that cannot be encoded, like GIF, are converted to PNG by default
+    let source = reader
+        .format()
+        .and_then(Format::from_image_format)
+        .unwrap_or(Format::Png);
+
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+
+    Ok((image, source))
+}
+
+fn encode(image: &DynamicImage, format: Format) -> Result<Vec<u8>, ::image::ImageError> {
+    let mut buf = Cursor::new(Vec::new());
+
+    match format {
+        Format::Avif => {
+            image.write_with_encoder(AvifEncoder::new_with_speed_quality(
+                &mut buf,
+                AVIF_SPEED,
+                AVIF_QUALITY,
+            ))?;
+        }
+        Format::Jpeg => {
+            // JPEG does not support transparency
+            DynamicImage::ImageRgb8(image.to_rgb8())
+                .write_with_encoder(JpegEncoder::new_with_quality(&mut buf, JPEG_QUALITY))?;
+        }
+        Format::Png => image.write_to(&mut buf, ImageFormat::Png)?,
+        Format::Webp => {
+            // WebP encoder only supports 8 bit images
+            let image = if image.color().has_alpha() {
+                DynamicImage::ImageRgba8(image.to_rgba8())
+            } else {
+                DynamicImage::ImageRgb8(image.to_rgb8())
+            };
+
+            image.write_to(&mut buf, ImageFormat::WebP)?;
+        }
+    }
+
+    Ok(buf.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use ::image::{GenericImageView, Rgb, RgbImage};
+
+    use super::*;
+
+    fn jpeg(width: u32, height: u32) -> Vec<u8> {
+        encode(
+            &DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, Rgb([200, 100, 50]))),
+            Format::Jpeg,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn it_validates_transforms() {
+        let sizes = [320, 640, MAX_DIMENSION + 1];
+
+        assert!(Transform {
+            width: Some(640),
+            ..Default::default()
+        }
+        .validate(&sizes)
+        .is_ok());
+
+        assert_eq!(
+            Err("invalid_dimension"),
+            Transform {
+                width: Some(MAX_DIMENSION + 1),
+                ..Default::default()
+            }
+            .validate(&sizes)
+        );
+
+        assert_eq!(
+            Err("unsupported_dimension"),
+            Transform {
+                width: Some(640),
+                height: Some(480),
+                ..Default::default()
+            }
+            .validate(&sizes)
+        );
+
+        assert!(Transform {
+            format: Some(Format::Webp),
+            ..Default::default()
+        }
+        .validate(&[])
+        .is_ok());
+
+        assert_eq!(
+            Err("cover_needs_width_and_height"),
+            Transform {
+                width: Some(640),
+                fit: Fit::Cover,
+                ..Default::default()
+            }
+            .validate(&sizes)
+        );
+    }
+
+    #[test]
+    fn it_resizes_and_converts_images() {
+        let source = jpeg(400, 200);
+
+        let (bytes, format) = Transform {
+            width: Some(100),
+            format: Some(Format::Webp),
+            ..Default::default()
+        }
+        .apply(&source)
+        .unwrap();
+
+        assert_eq!(Format::Webp, format);
+
+        let image = ::image::load_from_memory_with_format(&bytes, ImageFormat::WebP).unwrap();
+        assert_eq!((100, 50), image.dimensions());
+
+        let (bytes, format) = Transform {
+            width: Some(50),
+            height: Some(50),
+            fit: Fit::Cover,
+            format: None,
+        }
+        .apply(&source)
+        .unwrap();
+
+        assert_eq!(Format::Jpeg, format);
+        assert_eq!(
+            (50, 50),
+            ::image::load_from_memory(&bytes).unwrap().dimensions()
+        );
+
+        let (bytes, _) = Transform {
+            width: Some(800),
+            ..Default::default()
+        }
+        .apply(&source)
+        .unwrap();
+
+        assert_eq!(
+            (400, 200),
+            ::image::load_from_memory(&bytes).unwrap().dimensions()
+        );
+    }
+
+    #[test]
+    fn it_prepares_uploaded_images() {
+        let prepared = prepare_upload(&jpeg(300, 150), 100).unwrap().unwrap();
+
+        assert_eq!(
+            (100, 50),
+            ::image::load_from_memory(&prepared).unwrap().dimensions()
+        );
+
+        assert_eq!(None, prepare_upload(b"%PDF-1.4", 100).unwrap());
+        assert_eq!(Some((100, 50)), dimensions(&prepared));
+        assert_eq!(None, dimensions(b"%PDF-1.4"));
+    }
+}
@@ -1,8 +1,9 @@
 use std::{collections::HashMap, sync::Arc};
 
+use crate::runtime::IntoSendFuture;
 use anyhow::{anyhow, Result};
 use arc_swap::ArcSwap;
-use base::runtime::IntoSendFuture;
+use axum::http::{header, HeaderMap};
 use fluent::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
 use opendal::Operator;
 use unic_langid::LanguageIdentifier;
@@ -41,6 +42,46 @@ async fn load_locale(
     bundle
 }
 
+/// Picks the locale requested through the `yelken_locale` cookie or, failing that, the
+/// `Accept-Language` header. Only locales in `locales` are considered.
+pub fn negotiate_locale<'a, T>(
+    headers: &HeaderMap,
+    locales: impl Iterator<Item = &'a T> + Clone,
+    key: impl Fn(&'a T) -> &'a str,
+) -> Option<&'a T> {
+    // Cookie based resolution
+    if let Some(cookie) = headers
+        .get(header::COOKIE)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|cookie| cookie.split_once("yelken_locale=").map(|split| split.1))
+    {
+        let locale = cookie
+            .split_once(';')
+            .map(|split| split.0)
+            .unwrap_or(cookie);
+
+        if let Some(locale) = locales.clone().find(|l| key(l) == locale) {
+            return Some(locale);
+        }
+    }
+
+    // Header based resolution
+    if let Some(accept_language) = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|header| header.to_str().ok())
+    {
+        for lang in accept_language.split(',') {
+            let lang = lang.split_once(';').map(|split| split.0).unwrap_or(lang);
+
+            if let Some(locale) = locales.clone().find(|l| key(l) == lang) {
+                return Some(locale);
+            }
+        }
+    }
+
+    None
+}
+
 #[derive(Clone)]
 pub struct L10n(Arc<ArcSwap<Inner>>);
 
@@ -68,6 +109,26 @@ impl L10n {
         ))))
     }
 
+    /// Creates an instance from in memory Fluent sources instead of loading them from a storage.
+    pub fn from_sources(
+        sources: &[(LanguageIdentifier, &str)],
+        default: LanguageIdentifier,
+    ) -> Result<L10n> {
+        let mut bundles = HashMap::new();
+
+        for (locale, source) in sources {
+            let resource =
+                FluentResource::try_new(source.to_string()).map_err(|e| anyhow!("{e:?}"))?;
+
+            let mut bundle = FluentBundle::new_concurrent(vec![locale.clone(), default.clone()]);
+            bundle.add_resource_overriding(resource);
+
+            bundles.insert(locale.clone(), bundle);
+        }
+
+        Ok(Self(Arc::new(ArcSwap::new(Arc::new(Inner { bundles })))))
+    }
+
     pub fn localize<'a>(
         &'a self,
         locale: &LanguageIdentifier,
@@ -4,19 +4,32 @@ use config::Config;
 use db::Pool;
 use opendal::Operator;
 
+pub mod api_token;
 pub mod config;
+pub mod content;
 pub mod crypto;
 pub mod db;
+pub mod field;
+pub mod form;
+#[cfg(feature = "image")]
+pub mod image;
+pub mod l10n;
+pub mod mail;
 pub mod middlewares;
 pub mod models;
 pub mod paginate;
+pub mod password;
 pub mod permission;
 pub mod responses;
 pub mod runtime;
 pub mod sanitize;
 pub mod schema;
 pub mod services;
+pub mod session;
+#[cfg(not(target_family = "wasm"))]
+pub mod storage;
 pub mod test;
+pub mod two_factor;
 pub mod utils;
 pub mod validate;
 
@@ -8,7 +8,12 @@ use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 
 use crate::{
-    crypto::Crypto, db::PooledConnection, models::UserState, responses::HttpError, schema::users,
+    api_token,
+    crypto::Crypto,
+    db::PooledConnection,
+    models::UserState,
+    responses::HttpError,
+    schema::{sessions, users},
     AppState,
 };
 
@@ -32,18 +37,23 @@ pub struct Token {
     // expire time
     pub exp: i64,
     pub id: i32,
+    // session id
+    pub sid: i32,
 }
 
 impl Token {
-    pub fn new(id: i32) -> Token {
-        const TIMEOUT: i64 = 60 * 60 * 24 * 7;
+    /// Access tokens are short lived, clients are expected to get a new one with the refresh token
+    /// of the session, see [`crate::session::refresh`].
+    pub const TIMEOUT: i64 = 60 * 15;
 
+    pub fn new(id: i32, session_id: i32) -> Token {
         let now = Utc::now().timestamp();
 
         Self {
             iat: now,
-            exp: now + TIMEOUT,
+            exp: now + Self::TIMEOUT,
             id,
+            sid: session_id,
         }
     }
 }
@@ -52,6 +62,23 @@ impl Token {
 pub struct AuthUser {
     pub id: i32,
     pub name: String,
+    /// Set when the request is authenticated with an access token of a session.
+    pub session_id: Option<i32>,
+    /// Set when the request is authenticated with a personal API token, whose scope further limits
+    /// the permissions of the user.
+    pub api_token_id: Option<i32>,
+}
+
+impl AuthUser {
+    /// Returns the session of the user, failing for requests authenticated with an API token. Used
+    /// to guard account management, which API tokens must not be able to do.
+    pub fn require_session(&self) -> Result<i32, HttpError> {
+        self.session_id.ok_or(HttpError {
+            code: StatusCode::FORBIDDEN,
+            error: "session_required",
+            context: None,
+        })
+    }
 }
 
 impl<S> FromRequestParts<S> for AuthUser
@@ -88,11 +115,13 @@ pub async fn from_token(
     mut req: Request,
     next: Next,
 ) -> Result<Response, HttpError> {
-    let Some(token) = parse_token(&req)? else {
+    let Some(token) = parse_token(&req).map(str::to_string) else {
         return Err(TOKEN_NOT_FOUND_ERROR);
     };
 
-    let auth_user = fetch_user(&mut state.pool.get().await?, token.id).await?;
+    let crypto = req.extensions().get::<Crypto>().unwrap().clone();
+
+    let auth_user = authenticate(&state, &crypto, &token).await?;
 
     req.extensions_mut().insert(auth_user);
 
@@ -104,11 +133,13 @@ pub async fn try_from_token(
     mut req: Request,
     next: Next,
 ) -> Result<Response, HttpError> {
-    let Some(token) = parse_token(&req)? else {
+    let Some(token) = parse_token(&req).map(str::to_string) else {
         return Ok(next.run(req).await);
     };
 
-    let auth_user = fetch_user(&mut state.pool.get().await?, token.id).await?;
+    let crypto = req.extensions().get::<Crypto>().unwrap().clone();
+
+    let auth_user = authenticate(&state, &crypto, &token).await?;
 
     req.extensions_mut().insert(auth_user);
 
@@ -124,18 +155,44 @@ pub async fn try_from_cookie(
         return Ok(next.run(req).await);
     };
 
-    let auth_user = fetch_user(&mut state.pool.get().await?, token.id).await?;
+    let auth_user = fetch_user(&mut state.pool.get().await?, &token).await?;
 
     req.extensions_mut().insert(auth_user);
 
     Ok(next.run(req).await)
 }
 
-async fn fetch_user(conn: &mut PooledConnection, user_id: i32) -> Result<AuthUser, HttpError> {
-    let Some(user) = users::table
-        .select((users::id, users::name, users::state))
-        .filter(users::id.eq(user_id))
-        .first::<(i32, String, UserState)>(conn)
+/// Resolves the user either from a personal API token or from an access token of a session.
+async fn authenticate(
+    state: &AppState,
+    crypto: &Crypto,
+    token: &str,
+) -> Result<AuthUser, HttpError> {
+    let mut conn = state.pool.get().await?;
+
+    if token.starts_with(api_token::PREFIX) {
+        return api_token::authenticate(&mut conn, crypto, token, Utc::now().naive_utc()).await;
+    }
+
+    let token = crypto.decode::<Token>(token)?;
+
+    fetch_user(&mut conn, &token).await
+}
+
+async fn fetch_user(conn: &mut PooledConnection, token: &Token) -> Result<AuthUser, HttpError> {
+    let Some((id, name, state, session_exists)) = users::table
+        .select((
+            users::id,
+            users::name,
+            users::state,
+            diesel::dsl::exists(
+                sessions::table
+                    .filter(sessions::id.eq(token.sid))
+                    .filter(sessions::user_id.eq(token.id)),
+            ),
+        ))
+        .filter(users::id.eq(token.id))
+        .first::<(i32, String, UserState, bool)>(conn)
         .await
         .optional()?
     else {
@@ -146,7 +203,15 @@ async fn fetch_user(conn: &mut PooledConnection, user_id: i32) -> Result<AuthUse
         });
     };
 
-    if UserState::Enabled != user.2 {
+    if !session_exists {
+        return Err(HttpError {
+            code: StatusCode::UNAUTHORIZED,
+            error: "failed_authorization",
+            context: Some("Session is revoked".to_string()),
+        });
+    }
+
+    if UserState::Enabled != state {
         return Err(HttpError {
             code: StatusCode::FORBIDDEN,
             error: "failed_authorization",
@@ -155,42 +220,27 @@ async fn fetch_user(conn: &mut PooledConnection, user_id: i32) -> Result<AuthUse
     }
 
     Ok(AuthUser {
-        id: user.0,
-        name: user.1,
+        id,
+        name,
+        session_id: Some(token.sid),
+        api_token_id: None,
     })
 }
 
-fn parse_token(req: &Request) -> Result<Option<Token>, HttpError> {
-    let token = if let Some(auth_header) = req.headers().get(http::header::AUTHORIZATION) {
-        let Ok(auth_header) = auth_header.to_str() else {
-            return Ok(None);
-        };
-
-        let Some(token) = auth_header.split_once("Bearer ").map(|split| split.1) else {
-            return Ok(None);
-        };
+fn parse_token(req: &Request) -> Option<&str> {
+    if let Some(auth_header) = req.headers().get(http::header::AUTHORIZATION) {
+        let auth_header = auth_header.to_str().ok()?;
 
-        token
+        auth_header.split_once("Bearer ").map(|split| split.1)
     } else {
-        let Some(query_string) = req.uri().query() else {
-            return Ok(None);
-        };
+        let query_string = req.uri().query()?;
 
         // find the beginning of token
-        let Some(token) = query_string.split_once("token=").map(|split| split.1) else {
-            return Ok(None);
-        };
+        let token = query_string.split_once("token=").map(|split| split.1)?;
 
         // then find the end of the token, token can be at the end of or in the middle of query string
-        token.split_once("&").map(|t| t.0).unwrap_or(token)
-    };
-
-    let crypto = req.extensions().get::<Crypto>().unwrap();
-
-    crypto
-        .decode::<Token>(token)
-        .map(|token| Some(token))
-        .map_err(Into::into)
+        Some(token.split_once("&").map(|t| t.0).unwrap_or(token))
+    }
 }
 
 fn parse_token_from_cookie(req: &Request) -> Result<Option<Token>, HttpError> {
@@ -215,3 +265,213 @@ fn parse_token_from_cookie(req: &Request) -> Result<Option<Token>, HttpError> {
         .map(|token| Some(token))
         .map_err(Into::into)
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        middleware,
+        routing::get,
+        Extension, Router,
+    };
+    use chrono::Utc;
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+    use tower::ServiceExt;
+
+    use super::from_token;
+    use crate::{
+        api_token,
+        config::Config,
+        crypto::Crypto,
+        db::BatchQuery,
+        middlewares::permission::{Permission, PermissionLayer},
+        schema::{permissions, users},
+        session,
+        test::{create_pool, DB_CONFIG},
+        AppState,
+    };
+
+    async fn init_state() -> AppState {
+        let pool = create_pool(DB_CONFIG).await;
+
+        let storage = opendal::Operator::new(opendal::services::Memory::default())
+            .unwrap()
+            .finish();
+
+        let config = Config {
+            env: "test".to_string(),
+            site_url: "http://127.0.0.1".parse().unwrap(),
+            app_url: "http://127.0.0.1".parse().unwrap(),
+            reload_templates: false,
+            upload_size_limit: 0,
+            image_max_dimension: 0,
+            image_sizes: [].into(),
+        };
+
+        AppState::new(config, pool, storage.clone(), storage)
+    }
+
+    async fn status(state: &AppState, crypto: &Crypto, token: &str) -> StatusCode {
+        let app = Router::new()
+            .route("/", get(|| async {}))
+            .layer(middleware::from_fn_with_state(state.clone(), from_token))
+            .layer(Extension(crypto.clone()))
+            .with_state(state.clone());
+
+        let req = Request::builder()
+            .uri("/")
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .body(Body::empty())
+            .unwrap();
+
+        app.oneshot(req).await.unwrap().status()
+    }
+
+    // Connections are taken from the pool for each query since all of them must share the single
+    // connection running the test transaction
+    #[tokio::test]
+    async fn it_rejects_tokens_of_revoked_sessions() {
+        let state = init_state().await;
+        let crypto = Crypto::new("secret");
+
+        let user_id = diesel::insert_into(users::table)
+            .values((
+                users::username.eq("auth_user"),
+                users::name.eq("Auth User"),
+                users::email.eq("auth@example.com"),
+            ))
+            .returning(users::id)
+            .get_result::<i32>(&mut state.pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let now = Utc::now().naive_utc();
+
+        let first = session::create(&mut state.pool.get().await.unwrap(), &crypto, user_id, now)
+            .await
+            .unwrap();
+        let second = session::create(&mut state.pool.get().await.unwrap(), &crypto, user_id, now)
+            .await
+            .unwrap();
+        let refreshed = session::refresh(
+            &mut state.pool.get().await.unwrap(),
+            &crypto,
+            &first.refresh_token,
+            now,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(StatusCode::OK, status(&state, &crypto, &first.token).await);
+        assert_eq!(StatusCode::OK, status(&state, &crypto, &second.token).await);
+
+        let session_id = first.refresh_token.split_once('.').unwrap().0;
+
+        session::revoke(
+            &mut state.pool.get().await.unwrap(),
+            session_id.parse().unwrap(),
+        )
+        .await
+        .unwrap();
+
+        // Both the previously issued access token and the one issued by the refresh are rejected
+        assert_eq!(
+            StatusCode::UNAUTHORIZED,
+            status(&state, &crypto, &first.token).await
+        );
+        assert_eq!(
+            StatusCode::UNAUTHORIZED,
+            status(&state, &crypto, &refreshed.token).await
+        );
+        assert_eq!(StatusCode::OK, status(&state, &crypto, &second.token).await);
+
+        session::revoke_all(&mut state.pool.get().await.unwrap(), user_id)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            StatusCode::UNAUTHORIZED,
+            status(&state, &crypto, &second.token).await
+        );
+    }
+
+    #[tokio::test]
+    async fn it_limits_api_tokens_to_their_scope() {
+        let state = init_state().await;
+        let crypto = Crypto::new("secret");
+        let now = Utc::now().naive_utc();
+
+        let user_id = diesel::insert_into(users::table)
+            .values((
+                users::username.eq("api_user"),
+                users::name.eq("Api User"),
+                users::email.eq("api@example.com"),
+            ))
+            .returning(users::id)
+            .get_result::<i32>(&mut state.pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        diesel::insert_into(permissions::table)
+            .values(
+                [Permission::CMSRead, Permission::ContentWrite]
+                    .iter()
+                    .map(|perm| {
+                        (
+                            permissions::user_id.eq(user_id),
+                            permissions::key.eq(perm.as_str()),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .batched()
+            .execute(&mut state.pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let (_, token) = api_token::create(
+            &mut state.pool.get().await.unwrap(),
+            &crypto,
+            user_id,
+            "ci",
+            &[Permission::CMSRead],
+            now + chrono::TimeDelta::days(1),
+            now,
+        )
+        .await
+        .unwrap();
+
+        let status_with_perm = |perm: Permission| {
+            let app = Router::new()
+                .route("/", get(|| async {}))
+                .layer(PermissionLayer {
+                    pool: state.pool.clone(),
+                    perm,
+                })
+                .layer(middleware::from_fn_with_state(state.clone(), from_token))
+                .layer(Extension(crypto.clone()))
+                .with_state(state.clone());
+
+            let req = Request::builder()
+                .uri("/")
+                .header(header::AUTHORIZATION, format!("Bearer {token}"))
+                .body(Body::empty())
+                .unwrap();
+
+            async move { app.oneshot(req).await.unwrap().status() }
+        };
+
+        assert_eq!(StatusCode::OK, status_with_perm(Permission::CMSRead).await);
+        // User has the permission but the token is not scoped to it
+        assert_eq!(
+            StatusCode::FORBIDDEN,
+            status_with_perm(Permission::ContentWrite).await
+        );
+        assert_eq!(
+            StatusCode::UNAUTHORIZED,
+            status(&state, &crypto, "yk_0_invalid").await
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::net::IpAddr;
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+
+/// Address of the client that made the request. `X-Forwarded-For` is only taken into account for
+/// connections coming from the loopback interface, where a reverse proxy is expected to run, as
+/// the header can be set to anything by clients otherwise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl ClientIp {
+    fn from_parts(peer: Option<IpAddr>, forwarded_for: Option<&str>) -> Self {
+        let Some(peer) = peer else {
+            return ClientIp(None);
+        };
+
+        if !peer.is_loopback() {
+            return ClientIp(Some(peer));
+        }
+
+        // The last address is the one appended by the proxy in front of us
+        let forwarded = forwarded_for
+            .and_then(|header| header.rsplit(',').next())
+            .and_then(|addr| addr.trim().parse::<IpAddr>().ok());
+
+        ClientIp(Some(forwarded.unwrap_or(peer)))
+    }
+}
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        // There is no socket to take the peer address from when running in the browser
+        #[cfg(target_family = "wasm")]
+        let peer = None;
+        #[cfg(not(target_family = "wasm"))]
+        let peer = parts
+            .extensions
+            .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+            .map(|info| info.0.ip());
+
+        let forwarded_for = parts
+            .headers
+            .get("x-forwarded-for")
+            .and_then(|header| header.to_str().ok());
+
+        Ok(Self::from_parts(peer, forwarded_for))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::ClientIp;
+
+    #[test]
+    fn it_trusts_forwarded_for_only_from_loopback() {
+        let loopback: IpAddr = "127.0.0.1".parse().unwrap();
+        let remote: IpAddr = "203.0.113.7".parse().unwrap();
+
+        assert_eq!(
+            ClientIp(Some("198.51.100.2".parse().unwrap())),
+            ClientIp::from_parts(Some(loopback), Some("10.0.0.1, 198.51.100.2"))
+        );
+
+        assert_eq!(
+            ClientIp(Some(remote)),
+            ClientIp::from_parts(Some(remote), Some("198.51.100.2"))
+        );
+
+        assert_eq!(
+            ClientIp(Some(loopback)),
+            ClientIp::from_parts(Some(loopback), None)
+        );
+
+        assert_eq!(
+            ClientIp(None),
+            ClientIp::from_parts(None, Some("198.51.100.2"))
+        );
+    }
+}
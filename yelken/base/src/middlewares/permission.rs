@@ -11,11 +11,12 @@ use axum::{
 };
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tower::{Layer, Service};
 
 use crate::{
-    db::Pool,
+    api_token,
+    db::{Connection, Pool},
     responses::HttpError,
     schema::{permissions, users},
 };
@@ -25,11 +26,13 @@ use super::auth::AuthUser;
 use std::str::FromStr;
 
 pub const READ_ONLY_PERMS: [Permission; 2] = [Permission::CMSRead, Permission::AppearanceRead];
-pub const FULL_PERMS: [Permission; 10] = [
+pub const FULL_PERMS: [Permission; 12] = [
     Permission::Admin,
     Permission::CMSRead,
     Permission::AssetWrite,
     Permission::ContentWrite,
+    Permission::ContentReview,
+    Permission::ContentPublish,
     Permission::FormWrite,
     Permission::ModelWrite,
     Permission::AppearanceRead,
@@ -44,6 +47,8 @@ pub enum Permission {
     CMSRead,
     AssetWrite,
     ContentWrite,
+    ContentReview,
+    ContentPublish,
     FormWrite,
     ModelWrite,
     AppearanceRead,
@@ -59,6 +64,8 @@ impl Permission {
             Permission::CMSRead => "cms.read",
             Permission::AssetWrite => "asset.write",
             Permission::ContentWrite => "content.write",
+            Permission::ContentReview => "content.review",
+            Permission::ContentPublish => "content.publish",
             Permission::FormWrite => "form.write",
             Permission::ModelWrite => "model.write",
             Permission::AppearanceRead => "appearance.read",
@@ -78,6 +85,8 @@ impl FromStr for Permission {
             "cms.read" => Permission::CMSRead,
             "asset.write" => Permission::AssetWrite,
             "content.write" => Permission::ContentWrite,
+            "content.review" => Permission::ContentReview,
+            "content.publish" => Permission::ContentPublish,
             "form.write" => Permission::FormWrite,
             "model.write" => Permission::ModelWrite,
             "appearance.read" => Permission::AppearanceRead,
@@ -100,6 +109,57 @@ impl Serialize for Permission {
     }
 }
 
+impl<'de> Deserialize<'de> for Permission {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Checks whether the user has the permission either directly or through its role.
+pub async fn has_permission(
+    conn: &mut Connection,
+    user_id: i32,
+    perm: Permission,
+) -> Result<bool, diesel::result::Error> {
+    diesel::dsl::select(diesel::dsl::exists(
+        permissions::table
+            .inner_join(
+                users::table.on(users::id
+                    .nullable()
+                    .eq(permissions::user_id)
+                    .or(users::role_id.eq(permissions::role_id))),
+            )
+            .filter(
+                users::id
+                    .eq(user_id)
+                    .and(permissions::key.eq(perm.as_str())),
+            ),
+    ))
+    .get_result::<bool>(conn)
+    .await
+}
+
+/// Checks whether the authenticated user has the permission. Requests authenticated with an API
+/// token are further limited to the scope of the token.
+pub async fn has_auth_user_permission(
+    conn: &mut Connection,
+    user: &AuthUser,
+    perm: Permission,
+) -> Result<bool, diesel::result::Error> {
+    if let Some(token_id) = user.api_token_id {
+        if !api_token::has_scope(conn, token_id, perm).await? {
+            return Ok(false);
+        }
+    }
+
+    has_permission(conn, user.id, perm).await
+}
+
 #[derive(Clone)]
 pub struct PermissionLayer {
     pub pool: Pool,
@@ -156,23 +216,9 @@ where
             {
                 let mut conn = layer.pool.get().await.unwrap();
 
-                let has_perm = diesel::dsl::select(diesel::dsl::exists(
-                    permissions::table
-                        .inner_join(
-                            users::table.on(users::id
-                                .nullable()
-                                .eq(permissions::user_id)
-                                .or(users::role_id.eq(permissions::role_id))),
-                        )
-                        .filter(
-                            users::id
-                                .eq(user.id)
-                                .and(permissions::key.eq(*&layer.perm.as_str())),
-                        ),
-                ))
-                .get_result::<bool>(&mut conn)
-                .await
-                .unwrap();
+                let has_perm = has_auth_user_permission(&mut conn, &user, layer.perm)
+                    .await
+                    .unwrap();
 
                 if !has_perm {
                     return Ok(HttpError {
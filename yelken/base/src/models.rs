@@ -54,6 +54,7 @@ impl FromSql<Text, Backend> for UserState {
 pub enum LoginKind {
     Email,
     Cloud,
+    Oidc,
 }
 
 impl ToSql<Text, Backend> for LoginKind {
@@ -61,6 +62,7 @@ impl ToSql<Text, Backend> for LoginKind {
         let value = match self {
             LoginKind::Email => "email",
             LoginKind::Cloud => "cloud",
+            LoginKind::Oidc => "oidc",
         };
 
         <str as ToSql<Text, Backend>>::to_sql(value, out)
@@ -72,6 +74,7 @@ impl FromSql<Text, Backend> for LoginKind {
         match read_value_bytes(&mut value) {
             b"email" => Ok(LoginKind::Email),
             b"cloud" => Ok(LoginKind::Cloud),
+            b"oidc" => Ok(LoginKind::Oidc),
             _ => Err("Unrecognized enum variant".into()),
         }
     }
@@ -92,12 +95,14 @@ pub struct User {
 }
 
 #[derive(Queryable, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Role {
     pub id: i32,
     pub key: String,
     pub name: String,
     pub desc: Option<String>,
     pub created_at: NaiveDateTime,
+    pub require_two_factor: bool,
 }
 
 #[derive(Queryable, Serialize)]
@@ -116,6 +121,7 @@ pub struct Model {
     pub name: String,
     pub desc: Option<String>,
     pub created_at: NaiveDateTime,
+    pub public: bool,
 }
 
 #[derive(Queryable, Serialize)]
@@ -138,21 +144,80 @@ pub struct ModelField {
     pub localized: bool,
     pub multiple: bool,
     pub required: bool,
+    pub reference_model_id: Option<i32>,
+    pub validation: ValidationRules,
+}
+
+/// Validation rules of a model field, stored as JSON. Rules that are not set are not checked.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationRules {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unique: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_types: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<u32>,
+}
+
+impl ToSql<Text, Backend> for ValidationRules {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Backend>) -> diesel::serialize::Result {
+        let value = serde_json::to_string(self)?;
+
+        #[cfg(feature = "sqlite")]
+        {
+            out.set_value(value);
+
+            Ok(diesel::serialize::IsNull::No)
+        }
+
+        #[cfg(feature = "postgres")]
+        {
+            use std::io::Write;
+
+            out.write_all(value.as_bytes())?;
+
+            Ok(diesel::serialize::IsNull::No)
+        }
+    }
+}
+
+impl FromSql<Text, Backend> for ValidationRules {
+    fn from_sql(mut value: BackendValue) -> diesel::deserialize::Result<Self> {
+        serde_json::from_slice(read_value_bytes(&mut value)).map_err(Into::into)
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, AsExpression, FromSqlRow)]
 #[diesel(sql_type = Text)]
 #[serde(rename_all = "snake_case")]
 pub enum ContentStage {
-    Published,
     Draft,
+    InReview,
+    Approved,
+    Published,
+    Archived,
 }
 
 impl ToSql<Text, Backend> for ContentStage {
     fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Backend>) -> diesel::serialize::Result {
         let value = match self {
-            ContentStage::Published => "published",
             ContentStage::Draft => "draft",
+            ContentStage::InReview => "in_review",
+            ContentStage::Approved => "approved",
+            ContentStage::Published => "published",
+            ContentStage::Archived => "archived",
         };
 
         <str as ToSql<Text, Backend>>::to_sql(value, out)
@@ -162,8 +227,11 @@ impl ToSql<Text, Backend> for ContentStage {
 impl FromSql<Text, Backend> for ContentStage {
     fn from_sql(mut value: BackendValue) -> diesel::deserialize::Result<Self> {
         match read_value_bytes(&mut value) {
-            b"published" => Ok(ContentStage::Published),
             b"draft" => Ok(ContentStage::Draft),
+            b"in_review" => Ok(ContentStage::InReview),
+            b"approved" => Ok(ContentStage::Approved),
+            b"published" => Ok(ContentStage::Published),
+            b"archived" => Ok(ContentStage::Archived),
             _ => Err("Unrecognized enum variant".into()),
         }
     }
@@ -177,6 +245,8 @@ pub struct ContentValue {
     pub model_field_id: i32,
     pub locale: Option<String>,
     pub value: String,
+    #[serde(skip)]
+    pub search_text: Option<String>,
 }
 
 #[derive(Queryable, Serialize)]
@@ -189,6 +259,71 @@ pub struct Content {
     pub created_by: Option<i32>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub publish_at: Option<NaiveDateTime>,
+    pub unpublish_at: Option<NaiveDateTime>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "snake_case")]
+pub enum RevisionAction {
+    Create,
+    UpdateName,
+    UpdateStage,
+    UpdateSchedule,
+    CreateValue,
+    UpdateValue,
+    DeleteValue,
+    Restore,
+}
+
+impl ToSql<Text, Backend> for RevisionAction {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Backend>) -> diesel::serialize::Result {
+        let value = match self {
+            RevisionAction::Create => "create",
+            RevisionAction::UpdateName => "update_name",
+            RevisionAction::UpdateStage => "update_stage",
+            RevisionAction::UpdateSchedule => "update_schedule",
+            RevisionAction::CreateValue => "create_value",
+            RevisionAction::UpdateValue => "update_value",
+            RevisionAction::DeleteValue => "delete_value",
+            RevisionAction::Restore => "restore",
+        };
+
+        <str as ToSql<Text, Backend>>::to_sql(value, out)
+    }
+}
+
+impl FromSql<Text, Backend> for RevisionAction {
+    fn from_sql(mut value: BackendValue) -> diesel::deserialize::Result<Self> {
+        match read_value_bytes(&mut value) {
+            b"create" => Ok(RevisionAction::Create),
+            b"update_name" => Ok(RevisionAction::UpdateName),
+            b"update_stage" => Ok(RevisionAction::UpdateStage),
+            b"update_schedule" => Ok(RevisionAction::UpdateSchedule),
+            b"create_value" => Ok(RevisionAction::CreateValue),
+            b"update_value" => Ok(RevisionAction::UpdateValue),
+            b"delete_value" => Ok(RevisionAction::DeleteValue),
+            b"restore" => Ok(RevisionAction::Restore),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+/// A snapshot of a content taken right after it is changed. `values` holds the JSON encoded
+/// list of content values at that moment.
+#[derive(Queryable)]
+pub struct ContentRevision {
+    pub id: i32,
+    pub content_id: i32,
+    pub action: RevisionAction,
+    pub name: String,
+    pub stage: ContentStage,
+    pub values: String,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub publish_at: Option<NaiveDateTime>,
+    pub unpublish_at: Option<NaiveDateTime>,
 }
 
 #[derive(Queryable)]
@@ -298,6 +433,20 @@ pub struct Asset {
     pub created_by: Option<i32>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub folder_id: Option<i32>,
+    pub size: Option<i64>,
+    pub checksum: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
+#[derive(Queryable, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetFolder {
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub name: String,
+    pub created_at: NaiveDateTime,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, AsExpression, FromSqlRow)]
@@ -338,3 +487,78 @@ pub struct Tag {
     pub key: String,
     pub value: Option<String>,
 }
+
+#[derive(Queryable, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Form {
+    pub id: i32,
+    pub key: String,
+    pub name: String,
+    pub desc: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub honeypot: Option<String>,
+    pub min_fill_seconds: Option<i32>,
+    pub ip_hourly_limit: Option<i32>,
+    pub hourly_limit: Option<i32>,
+    pub challenge: bool,
+    pub notify_email: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "snake_case")]
+pub enum FormFieldKind {
+    String,
+    Multiline,
+    Email,
+    Int,
+}
+
+impl ToSql<Text, Backend> for FormFieldKind {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Backend>) -> diesel::serialize::Result {
+        let value = match self {
+            FormFieldKind::String => "string",
+            FormFieldKind::Multiline => "multiline",
+            FormFieldKind::Email => "email",
+            FormFieldKind::Int => "int",
+        };
+
+        <str as ToSql<Text, Backend>>::to_sql(value, out)
+    }
+}
+
+impl FromSql<Text, Backend> for FormFieldKind {
+    fn from_sql(mut value: BackendValue) -> diesel::deserialize::Result<Self> {
+        match read_value_bytes(&mut value) {
+            b"string" => Ok(FormFieldKind::String),
+            b"multiline" => Ok(FormFieldKind::Multiline),
+            b"email" => Ok(FormFieldKind::Email),
+            b"int" => Ok(FormFieldKind::Int),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+#[derive(Queryable, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormField {
+    pub id: i32,
+    pub form_id: i32,
+    pub key: String,
+    pub name: String,
+    pub kind: FormFieldKind,
+    pub required: bool,
+    pub min_length: Option<i32>,
+    pub max_length: Option<i32>,
+}
+
+/// `values` holds the JSON encoded object of submitted values keyed by form field keys. Submissions
+/// made before forms are defined do not have a form and hold the raw submitted values.
+#[derive(Queryable)]
+pub struct FormSubmission {
+    pub id: i32,
+    pub name: String,
+    pub values: String,
+    pub created_at: NaiveDateTime,
+    pub form_id: Option<i32>,
+}
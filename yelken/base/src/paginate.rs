@@ -118,6 +118,22 @@ pub struct Pagination<T> {
     items: Vec<T>,
 }
 
+impl<T> Pagination<T> {
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Pagination<U> {
+        Pagination {
+            per_page: self.per_page,
+            current_page: self.current_page,
+            total_pages: self.total_pages,
+            total_items: self.total_items,
+            items: self.items.into_iter().map(f).collect(),
+        }
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PaginationRequest {
@@ -0,0 +1,196 @@
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use jsonwebtoken::errors::Error as JWTError;
+use rand::{rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::Crypto;
+
+/// Length of the salt used by the legacy HMAC-SHA512 scheme, which prepends it to the hash.
+const LEGACY_SALT_LENGTH: usize = 32;
+
+/// Hash of a random password, created by [`hash`]. Logins of unknown users are verified against it,
+/// so that they take as long as the ones of known users.
+pub const DUMMY_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$9ByxeFywY11zTIahKvs3WA$8ym+AfYhvnaGpwoCDKfTV7hBxdypeI6acNrBJatDgzA";
+
+/// Hashes the password with Argon2id. Returned value is a PHC string, which carries the salt and
+/// the parameters along with the hash, and is what is stored in `users.password`.
+pub fn hash(password: &str) -> String {
+    let salt: [u8; 16] = rng().random();
+    let salt = SaltString::encode_b64(&salt).expect("16 bytes is a valid salt length");
+
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("hashing with default params cannot fail")
+        .to_string()
+}
+
+/// Checks the password against a stored value in constant time. Both PHC strings created by
+/// [`hash`] and values of the legacy HMAC-SHA512 scheme are supported.
+pub fn verify(crypto: &Crypto, password: &str, stored: &str) -> Result<bool, &'static str> {
+    if stored.starts_with('$') {
+        let hash = PasswordHash::new(stored).map_err(|_| "invalid_password_hash")?;
+
+        return match Argon2::default().verify_password(password.as_bytes(), &hash) {
+            Ok(()) => Ok(true),
+            Err(argon2::password_hash::Error::Password) => Ok(false),
+            Err(_) => Err("invalid_password_hash"),
+        };
+    }
+
+    let Some((salt, hash)) = stored.split_at_checked(LEGACY_SALT_LENGTH) else {
+        return Err("invalid_password_and_salt");
+    };
+
+    Ok(crypto.verify512(format!("{salt}{password}").as_bytes(), hash))
+}
+
+/// Returns true if the stored value is not created by [`hash`] with its current parameters, so
+/// it should be replaced once the password is known, e.g. after a successful login.
+pub fn needs_rehash(stored: &str) -> bool {
+    let Ok(hash) = PasswordHash::new(stored) else {
+        return true;
+    };
+
+    let params = argon2::Params::default();
+
+    hash.algorithm != argon2::Algorithm::Argon2id.ident()
+        || !argon2::Params::try_from(&hash).is_ok_and(|p| {
+            p.m_cost() == params.m_cost()
+                && p.t_cost() == params.t_cost()
+                && p.p_cost() == params.p_cost()
+        })
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenPurpose {
+    Reset,
+    Invitation,
+}
+
+/// Token that allows setting the password of a user without knowing the current one. It carries
+/// a fingerprint of the password the user had when the token was issued, so that the token can
+/// only be used once and is invalidated whenever the password changes.
+#[derive(Deserialize, Serialize)]
+pub struct PasswordToken {
+    // expire time
+    pub exp: i64,
+    pub id: i32,
+    pub purpose: TokenPurpose,
+    fingerprint: String,
+}
+
+impl PasswordToken {
+    pub fn issue(
+        crypto: &Crypto,
+        id: i32,
+        purpose: TokenPurpose,
+        current_password: Option<&str>,
+        exp: i64,
+    ) -> Result<String, JWTError> {
+        crypto.encode(&Self {
+            exp,
+            id,
+            purpose,
+            fingerprint: fingerprint(crypto, current_password),
+        })
+    }
+
+    /// Decodes the token if it is valid, not expired and issued for the given purpose.
+    pub fn decode(crypto: &Crypto, token: &str, purpose: TokenPurpose) -> Option<Self> {
+        crypto
+            .decode::<Self>(token)
+            .ok()
+            .filter(|token| token.purpose == purpose)
+    }
+
+    /// Returns true if the password of the user has not changed since the token is issued.
+    pub fn is_current(&self, crypto: &Crypto, current_password: Option<&str>) -> bool {
+        self.fingerprint == fingerprint(crypto, current_password)
+    }
+}
+
+fn fingerprint(crypto: &Crypto, password: Option<&str>) -> String {
+    let mut fingerprint =
+        crypto.sign512(format!("password_token:{}", password.unwrap_or("")).as_bytes());
+
+    fingerprint.truncate(22);
+
+    fingerprint
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::{hash, needs_rehash, verify, PasswordToken, TokenPurpose, DUMMY_HASH};
+    use crate::crypto::Crypto;
+
+    #[test]
+    fn it_keeps_dummy_hash_in_sync_with_hash_params() {
+        let crypto = Crypto::new("secret");
+
+        assert!(!needs_rehash(DUMMY_HASH));
+        assert_eq!(Ok(false), verify(&crypto, "password", DUMMY_HASH));
+    }
+
+    #[test]
+    fn it_verifies_hashed_passwords() {
+        let crypto = Crypto::new("secret");
+
+        let stored = hash("password");
+
+        assert!(stored.starts_with("$argon2id$"));
+        assert_eq!(Ok(true), verify(&crypto, "password", &stored));
+        assert_eq!(Ok(false), verify(&crypto, "Password", &stored));
+        assert_ne!(stored, hash("password"));
+        assert!(!needs_rehash(&stored));
+    }
+
+    #[test]
+    fn it_verifies_legacy_hashes_and_marks_them_for_rehash() {
+        let crypto = Crypto::new("secret");
+
+        let salt = "s".repeat(32);
+        let stored = format!(
+            "{salt}{}",
+            crypto.sign512(format!("{salt}password").as_bytes())
+        );
+
+        assert_eq!(Ok(true), verify(&crypto, "password", &stored));
+        assert_eq!(Ok(false), verify(&crypto, "Password", &stored));
+        assert!(needs_rehash(&stored));
+    }
+
+    #[test]
+    fn it_invalidates_tokens_when_password_changes() {
+        let crypto = Crypto::new("secret");
+        let exp = Utc::now().timestamp() + 60;
+
+        let token =
+            PasswordToken::issue(&crypto, 1, TokenPurpose::Reset, Some("old"), exp).unwrap();
+
+        assert!(PasswordToken::decode(&crypto, &token, TokenPurpose::Invitation).is_none());
+
+        let token = PasswordToken::decode(&crypto, &token, TokenPurpose::Reset).unwrap();
+
+        assert_eq!(1, token.id);
+        assert!(token.is_current(&crypto, Some("old")));
+        assert!(!token.is_current(&crypto, Some("new")));
+        assert!(!token.is_current(&crypto, None));
+    }
+
+    #[test]
+    fn it_rejects_expired_tokens() {
+        let crypto = Crypto::new("secret");
+        let exp = Utc::now().timestamp() - 60 * 60;
+
+        let token = PasswordToken::issue(&crypto, 1, TokenPurpose::Invitation, None, exp).unwrap();
+
+        assert!(PasswordToken::decode(&crypto, &token, TokenPurpose::Invitation).is_none());
+    }
+}
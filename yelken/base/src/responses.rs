@@ -40,6 +40,14 @@ impl HttpError {
         }
     }
 
+    pub const fn forbidden(error: &'static str) -> Self {
+        HttpError {
+            code: StatusCode::FORBIDDEN,
+            error,
+            context: None,
+        }
+    }
+
     pub const fn not_found(error: &'static str) -> Self {
         HttpError {
             code: StatusCode::NOT_FOUND,
@@ -64,6 +72,14 @@ impl HttpError {
         }
     }
 
+    pub const fn too_many_requests(error: &'static str) -> Self {
+        HttpError {
+            code: StatusCode::TOO_MANY_REQUESTS,
+            error,
+            context: None,
+        }
+    }
+
     pub fn validation_errors(errors: crate::validate::Errors) -> Self {
         HttpError {
             code: StatusCode::UNPROCESSABLE_ENTITY,
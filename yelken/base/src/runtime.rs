@@ -51,6 +51,24 @@ where
     async move { Ok(f()) }
 }
 
+/// Runs the future in the background, so that the caller does not wait for it to complete. Wasm
+/// has no executor to hand the future over to, it is awaited in place there.
+#[cfg(not(target_family = "wasm"))]
+pub async fn spawn<F>(f: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    tokio::runtime::Handle::current().spawn(f);
+}
+
+#[cfg(target_family = "wasm")]
+pub async fn spawn<F>(f: F)
+where
+    F: Future<Output = ()> + 'static,
+{
+    f.await
+}
+
 #[cfg(not(target_family = "wasm"))]
 pub fn block_on<F: std::future::Future>(f: F) -> F::Output {
     tokio::runtime::Handle::current().block_on(f)
@@ -1,5 +1,48 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    api_token_permissions (id) {
+        id -> Int4,
+        token_id -> Int4,
+        #[max_length = 32]
+        key -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    api_tokens (id) {
+        id -> Int4,
+        user_id -> Int4,
+        #[max_length = 128]
+        name -> Varchar,
+        #[max_length = 128]
+        token_hash -> Varchar,
+        expires_at -> Timestamp,
+        last_used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    asset_alts (asset_id, locale) {
+        asset_id -> Int4,
+        #[max_length = 8]
+        locale -> Varchar,
+        value -> Text,
+    }
+}
+
+diesel::table! {
+    asset_folders (id) {
+        id -> Int4,
+        parent_id -> Nullable<Int4>,
+        #[max_length = 128]
+        name -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     assets (id) {
         id -> Int4,
@@ -12,6 +55,29 @@ diesel::table! {
         created_by -> Nullable<Int4>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        folder_id -> Nullable<Int4>,
+        size -> Nullable<Int8>,
+        #[max_length = 64]
+        checksum -> Nullable<Varchar>,
+        width -> Nullable<Int4>,
+        height -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    content_revisions (id) {
+        id -> Int4,
+        content_id -> Int4,
+        #[max_length = 16]
+        action -> Varchar,
+        name -> Text,
+        #[max_length = 16]
+        stage -> Varchar,
+        values -> Text,
+        created_by -> Nullable<Int4>,
+        created_at -> Timestamp,
+        publish_at -> Nullable<Timestamp>,
+        unpublish_at -> Nullable<Timestamp>,
     }
 }
 
@@ -23,6 +89,7 @@ diesel::table! {
         #[max_length = 8]
         locale -> Nullable<Varchar>,
         value -> Text,
+        search_text -> Nullable<Text>,
     }
 }
 
@@ -36,6 +103,8 @@ diesel::table! {
         created_by -> Nullable<Int4>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        publish_at -> Nullable<Timestamp>,
+        unpublish_at -> Nullable<Timestamp>,
     }
 }
 
@@ -60,6 +129,22 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    form_fields (id) {
+        id -> Int4,
+        form_id -> Int4,
+        #[max_length = 128]
+        key -> Varchar,
+        #[max_length = 128]
+        name -> Varchar,
+        #[max_length = 16]
+        kind -> Varchar,
+        required -> Bool,
+        min_length -> Nullable<Int4>,
+        max_length -> Nullable<Int4>,
+    }
+}
+
 diesel::table! {
     form_submissions (id) {
         id -> Int4,
@@ -67,6 +152,27 @@ diesel::table! {
         name -> Varchar,
         values -> Text,
         created_at -> Timestamp,
+        form_id -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    forms (id) {
+        id -> Int4,
+        #[max_length = 128]
+        key -> Varchar,
+        #[max_length = 128]
+        name -> Varchar,
+        desc -> Nullable<Text>,
+        created_at -> Timestamp,
+        #[max_length = 128]
+        honeypot -> Nullable<Varchar>,
+        min_fill_seconds -> Nullable<Int4>,
+        ip_hourly_limit -> Nullable<Int4>,
+        hourly_limit -> Nullable<Int4>,
+        challenge -> Bool,
+        #[max_length = 256]
+        notify_email -> Nullable<Varchar>,
     }
 }
 
@@ -93,6 +199,8 @@ diesel::table! {
         localized -> Bool,
         multiple -> Bool,
         required -> Bool,
+        reference_model_id -> Nullable<Int4>,
+        validation -> Text,
     }
 }
 
@@ -107,6 +215,7 @@ diesel::table! {
         name -> Varchar,
         desc -> Nullable<Text>,
         created_at -> Timestamp,
+        public -> Bool,
     }
 }
 
@@ -177,6 +286,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    recovery_codes (id) {
+        id -> Int4,
+        user_id -> Int4,
+        #[max_length = 128]
+        code_hash -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     roles (id) {
         id -> Int4,
@@ -186,6 +305,21 @@ diesel::table! {
         name -> Varchar,
         desc -> Nullable<Text>,
         created_at -> Timestamp,
+        require_two_factor -> Bool,
+    }
+}
+
+diesel::table! {
+    sessions (id) {
+        id -> Int4,
+        user_id -> Int4,
+        #[max_length = 128]
+        refresh_hash -> Varchar,
+        expires_at -> Timestamp,
+        refreshed_at -> Timestamp,
+        created_at -> Timestamp,
+        #[max_length = 128]
+        previous_refresh_hash -> Nullable<Varchar>,
     }
 }
 
@@ -213,6 +347,31 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    two_factors (user_id) {
+        user_id -> Int4,
+        #[max_length = 64]
+        secret -> Varchar,
+        confirmed_at -> Nullable<Timestamp>,
+        last_step -> Nullable<Int8>,
+        failed_attempts -> Int4,
+        locked_until -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    user_identities (id) {
+        id -> Int4,
+        user_id -> Int4,
+        #[max_length = 64]
+        provider -> Varchar,
+        #[max_length = 255]
+        subject -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Int4,
@@ -235,13 +394,22 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(api_token_permissions -> api_tokens (token_id));
+diesel::joinable!(api_tokens -> users (user_id));
+diesel::joinable!(asset_alts -> assets (asset_id));
+diesel::joinable!(asset_alts -> locales (locale));
+diesel::joinable!(assets -> asset_folders (folder_id));
 diesel::joinable!(assets -> users (created_by));
+diesel::joinable!(content_revisions -> contents (content_id));
+diesel::joinable!(content_revisions -> users (created_by));
 diesel::joinable!(content_values -> contents (content_id));
 diesel::joinable!(content_values -> locales (locale));
 diesel::joinable!(content_values -> model_fields (model_field_id));
 diesel::joinable!(contents -> models (model_id));
 diesel::joinable!(contents -> users (created_by));
 diesel::joinable!(enum_options -> fields (field_id));
+diesel::joinable!(form_fields -> forms (form_id));
+diesel::joinable!(form_submissions -> forms (form_id));
 diesel::joinable!(model_fields -> fields (field_id));
 diesel::joinable!(model_fields -> models (model_id));
 diesel::joinable!(models -> namespaces (namespace));
@@ -249,15 +417,26 @@ diesel::joinable!(options -> namespaces (namespace));
 diesel::joinable!(pages -> locales (locale));
 diesel::joinable!(permissions -> roles (role_id));
 diesel::joinable!(permissions -> users (user_id));
+diesel::joinable!(recovery_codes -> users (user_id));
+diesel::joinable!(sessions -> users (user_id));
+diesel::joinable!(two_factors -> users (user_id));
+diesel::joinable!(user_identities -> users (user_id));
 diesel::joinable!(users -> roles (role_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_token_permissions,
+    api_tokens,
+    asset_alts,
+    asset_folders,
     assets,
+    content_revisions,
     content_values,
     contents,
     enum_options,
     fields,
+    form_fields,
     form_submissions,
+    forms,
     locales,
     model_fields,
     models,
@@ -266,8 +445,12 @@ diesel::allow_tables_to_appear_in_same_query!(
     pages,
     permissions,
     plugins,
+    recovery_codes,
     roles,
+    sessions,
     tags,
     themes,
+    two_factors,
+    user_identities,
     users,
 );
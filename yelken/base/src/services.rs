@@ -1,19 +1,21 @@
 use std::{
     convert::Infallible,
+    ops::{Range, RangeBounds},
     path::{Component, PathBuf},
     str::FromStr,
     task::Poll,
 };
 
 use axum::{
-    body::Body,
+    body::{Body, Bytes},
     extract::Request,
-    http::{header, HeaderValue, Method, StatusCode},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
     response::Response,
 };
-use futures::{future::BoxFuture, FutureExt};
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
 use mime_guess::mime;
 use opendal::{ErrorKind, Operator};
+use rand::{distr::Alphanumeric, rng, Rng};
 use serde::{Deserialize, Serialize};
 use tower::Service;
 
@@ -102,6 +104,9 @@ impl<const DEPTH: usize> Sanitize for SafePath<DEPTH> {
 pub struct ServeStorageDir<F> {
     storage: Operator,
     path: F,
+    cache_control: Option<HeaderValue>,
+    #[cfg(feature = "image")]
+    image_sizes: Option<std::sync::Arc<[u32]>>,
 }
 
 impl<F> ServeStorageDir<F>
@@ -109,7 +114,29 @@ where
     F: Fn() -> String + 'static,
 {
     pub fn new(storage: Operator, path: F) -> Self {
-        Self { storage, path }
+        Self {
+            storage,
+            path,
+            cache_control: None,
+            #[cfg(feature = "image")]
+            image_sizes: None,
+        }
+    }
+
+    /// Sets the `Cache-Control` header of the responses.
+    pub fn with_cache_control(mut self, cache_control: &'static str) -> Self {
+        self.cache_control = Some(HeaderValue::from_static(cache_control));
+        self
+    }
+
+    /// Serve resized or converted derivatives of images when the request has transformation
+    /// parameters, like `?width=640&format=webp`. Derivatives are generated on first request
+    /// and cached under the `derivatives` directory of the storage, so widths and heights are
+    /// limited to the given sizes.
+    #[cfg(feature = "image")]
+    pub fn with_image_derivatives(mut self, sizes: std::sync::Arc<[u32]>) -> Self {
+        self.image_sizes = Some(sizes);
+        self
     }
 }
 
@@ -147,20 +174,27 @@ where
 
         let path = format!("{}/{}", (self.path)(), path.inner());
 
-        let storage = self.storage.clone();
-
-        let if_modified_since = req
-            .headers()
-            .get(header::IF_MODIFIED_SINCE)
-            .and_then(|lm| lm.to_str().ok())
-            .and_then(|lm| chrono::DateTime::parse_from_rfc2822(lm).ok());
-
-        let if_unmodified_since = req
-            .headers()
-            .get(header::IF_UNMODIFIED_SINCE)
-            .and_then(|lm| lm.to_str().ok())
-            .and_then(|lm| chrono::DateTime::parse_from_rfc2822(lm).ok());
+        #[cfg(feature = "image")]
+        let transform = if let Some(sizes) = &self.image_sizes {
+            match axum::extract::Query::<crate::image::Transform>::try_from_uri(req.uri())
+                .map_err(|e| e.body_text())
+                .and_then(|t| t.0.validate(sizes).map(|_| t.0).map_err(str::to_string))
+            {
+                Ok(transform) => (!transform.is_empty()).then_some(transform),
+                Err(e) => {
+                    log::debug!("Invalid image transformation is requested, {e}");
+
+                    return async move { Ok(response_from_status(StatusCode::BAD_REQUEST)) }
+                        .boxed();
+                }
+            }
+        } else {
+            None
+        };
 
+        let storage = self.storage.clone();
+        let cache_control = self.cache_control.clone();
+        let conditions = Conditions::from_headers(req.headers());
         let method = req.method().clone();
 
         async move {
@@ -169,20 +203,35 @@ where
                 Err(e) => return Ok(response_from_opendal_error(e)),
             };
 
-            let mut response = Response::builder();
+            #[cfg(feature = "image")]
+            let (path, meta) = match transform {
+                Some(transform) => match derivative(&storage, &path, &meta, transform).await {
+                    Ok(derivative) => derivative,
+                    Err(response) => return Ok(response),
+                },
+                None => (path, meta),
+            };
 
-            if let Some(last_modified) = meta.last_modified() {
-                if if_modified_since
-                    .map(|since| since.timestamp() >= last_modified.timestamp())
-                    .unwrap_or(false)
-                    || if_unmodified_since
-                        .map(|since| since.timestamp() <= last_modified.timestamp())
-                        .unwrap_or(false)
-                {
-                    return Ok(response_from_status(StatusCode::NOT_MODIFIED));
-                }
+            let length = meta.content_length();
+            let last_modified = meta.last_modified();
+            let etag = etag(&meta);
+
+            let mut response = Response::builder().header(header::ACCEPT_RANGES, "bytes");
+
+            if let Some(etag) = &etag {
+                response = response.header(header::ETAG, etag);
+            }
 
-                response = response.header(header::LAST_MODIFIED, last_modified.to_rfc2822());
+            if let Some(last_modified) = last_modified {
+                response = response.header(header::LAST_MODIFIED, http_date(last_modified));
+            }
+
+            if let Some(cache_control) = cache_control {
+                response = response.header(header::CACHE_CONTROL, cache_control);
+            }
+
+            if let Some(status) = conditions.evaluate(etag.as_deref(), last_modified) {
+                return Ok(response.status(status).body(Body::empty()).unwrap());
             }
 
             let mime = mime_guess::from_path(&path)
@@ -192,44 +241,382 @@ where
                     HeaderValue::from_str(mime::APPLICATION_OCTET_STREAM.as_ref()).unwrap()
                 });
 
-            response = response
-                .header(header::CONTENT_TYPE, mime)
-                .header(header::CONTENT_LENGTH, meta.content_length().to_string());
+            let ranges = match (&method, &conditions.range) {
+                (&Method::GET, Some(range))
+                    if conditions.if_range_matches(etag.as_deref(), last_modified) =>
+                {
+                    ByteRanges::parse(range, length)
+                }
+                _ => ByteRanges::Full,
+            };
 
-            if method == Method::HEAD {
-                return Ok(response.status(StatusCode::OK).body(Body::empty()).unwrap());
-            }
+            let ranges = match ranges {
+                ByteRanges::Full => {
+                    response = response
+                        .status(StatusCode::OK)
+                        .header(header::CONTENT_TYPE, mime.clone())
+                        .header(header::CONTENT_LENGTH, length.to_string());
+
+                    if method == Method::HEAD {
+                        return Ok(response.body(Body::empty()).unwrap());
+                    }
+
+                    vec![]
+                }
+                ByteRanges::Unsatisfiable => {
+                    return Ok(response
+                        .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                        .header(header::CONTENT_RANGE, format!("bytes */{length}"))
+                        .body(Body::empty())
+                        .unwrap());
+                }
+                ByteRanges::Partial(ranges) => ranges,
+            };
 
             let reader = match storage.reader(&path).into_send_future().await {
                 Ok(reader) => reader,
                 Err(e) => return Ok(response_from_opendal_error(e)),
             };
 
-            // TODO implement support for ACCEPT_RANGES and RANGE header similar to tower-http's fs service.
-            let stream = match reader.into_bytes_stream(..).into_send_future().await {
-                Ok(stream) => {
-                    #[cfg(target_family = "wasm")]
-                    {
-                        send_wrapper::SendWrapper::new(stream)
-                    }
+            let body = match ranges.as_slice() {
+                [] => read_range(reader, ..).await.map(Body::from_stream),
+                [range] => {
+                    response = response
+                        .status(StatusCode::PARTIAL_CONTENT)
+                        .header(header::CONTENT_TYPE, mime)
+                        .header(
+                            header::CONTENT_LENGTH,
+                            (range.end - range.start).to_string(),
+                        )
+                        .header(header::CONTENT_RANGE, content_range(range, length));
+
+                    read_range(reader, range.clone())
+                        .await
+                        .map(Body::from_stream)
+                }
+                ranges => {
+                    let boundary = (0..24)
+                        .map(|_| rng().sample(Alphanumeric) as char)
+                        .collect::<String>();
+
+                    let mut parts = Vec::with_capacity(ranges.len() * 2 + 1);
+                    let mut content_length = 0;
 
-                    #[cfg(not(target_family = "wasm"))]
-                    {
-                        stream
+                    for range in ranges {
+                        let part_header = Bytes::from(format!(
+                            "\r\n--{boundary}\r\nContent-Type: {}\r\nContent-Range: {}\r\n\r\n",
+                            mime.to_str().unwrap_or_default(),
+                            content_range(range, length),
+                        ));
+
+                        content_length += part_header.len() as u64 + (range.end - range.start);
+
+                        parts.push(futures::stream::once(async move { Ok(part_header) }).boxed());
+
+                        match read_range(reader.clone(), range.clone()).await {
+                            Ok(stream) => parts.push(stream),
+                            Err(e) => return Ok(response_from_opendal_error(e)),
+                        }
                     }
+
+                    let closing = Bytes::from(format!("\r\n--{boundary}--\r\n"));
+                    content_length += closing.len() as u64;
+                    parts.push(futures::stream::once(async move { Ok(closing) }).boxed());
+
+                    response = response
+                        .status(StatusCode::PARTIAL_CONTENT)
+                        .header(
+                            header::CONTENT_TYPE,
+                            format!("multipart/byteranges; boundary={boundary}"),
+                        )
+                        .header(header::CONTENT_LENGTH, content_length.to_string());
+
+                    Ok(Body::from_stream(futures::stream::iter(parts).flatten()))
                 }
-                Err(e) => return Ok(response_from_opendal_error(e)),
             };
 
-            Ok(response
-                .status(StatusCode::OK)
-                .body(Body::from_stream(stream))
-                .unwrap())
+            match body {
+                Ok(body) => Ok(response.body(body).unwrap()),
+                Err(e) => Ok(response_from_opendal_error(e)),
+            }
         }
         .boxed()
     }
 }
 
+type ByteStream = BoxStream<'static, Result<Bytes, std::io::Error>>;
+
+async fn read_range(
+    reader: opendal::Reader,
+    range: impl RangeBounds<u64> + Send,
+) -> Result<ByteStream, opendal::Error> {
+    let stream = reader.into_bytes_stream(range).into_send_future().await?;
+
+    #[cfg(target_family = "wasm")]
+    let stream = send_wrapper::SendWrapper::new(stream);
+
+    Ok(stream.boxed())
+}
+
+/// Strong entity tag of a stored file. The one provided by the storage is preferred, otherwise
+/// it is derived from the size and modification time of the file.
+fn etag(meta: &opendal::Metadata) -> Option<String> {
+    if let Some(etag) = meta.etag() {
+        return Some(if etag.starts_with('"') {
+            etag.to_string()
+        } else {
+            format!("\"{etag}\"")
+        });
+    }
+
+    let last_modified = meta.last_modified()?;
+
+    Some(format!(
+        "\"{:x}-{:x}\"",
+        meta.content_length(),
+        last_modified
+            .timestamp_nanos_opt()
+            .unwrap_or(last_modified.timestamp())
+    ))
+}
+
+fn http_date(date: chrono::DateTime<chrono::Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+fn content_range(range: &Range<u64>, length: u64) -> String {
+    format!("bytes {}-{}/{length}", range.start, range.end - 1)
+}
+
+/// Conditional request headers, evaluated in the order defined by RFC 9110 section 13.2.2.
+#[derive(Default)]
+struct Conditions {
+    if_match: Option<String>,
+    if_none_match: Option<String>,
+    if_modified_since: Option<chrono::DateTime<chrono::FixedOffset>>,
+    if_unmodified_since: Option<chrono::DateTime<chrono::FixedOffset>>,
+    if_range: Option<String>,
+    range: Option<String>,
+}
+
+impl Conditions {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let string = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+
+        let date = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| chrono::DateTime::parse_from_rfc2822(v).ok())
+        };
+
+        Conditions {
+            if_match: string(header::IF_MATCH),
+            if_none_match: string(header::IF_NONE_MATCH),
+            if_modified_since: date(header::IF_MODIFIED_SINCE),
+            if_unmodified_since: date(header::IF_UNMODIFIED_SINCE),
+            if_range: string(header::IF_RANGE),
+            range: string(header::RANGE),
+        }
+    }
+
+    /// Returns the status that should be responded with if one of the conditions fails.
+    fn evaluate(
+        &self,
+        etag: Option<&str>,
+        last_modified: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Option<StatusCode> {
+        if let Some(if_match) = &self.if_match {
+            if !etag_matches(if_match, etag, true) {
+                return Some(StatusCode::PRECONDITION_FAILED);
+            }
+        } else if let (Some(since), Some(last_modified)) = (self.if_unmodified_since, last_modified)
+        {
+            if last_modified.timestamp() > since.timestamp() {
+                return Some(StatusCode::PRECONDITION_FAILED);
+            }
+        }
+
+        if let Some(if_none_match) = &self.if_none_match {
+            if etag_matches(if_none_match, etag, false) {
+                return Some(StatusCode::NOT_MODIFIED);
+            }
+        } else if let (Some(since), Some(last_modified)) = (self.if_modified_since, last_modified) {
+            if last_modified.timestamp() <= since.timestamp() {
+                return Some(StatusCode::NOT_MODIFIED);
+            }
+        }
+
+        None
+    }
+
+    /// Whether the range request should be honored, which is the case if there is no `If-Range`
+    /// header or the representation did not change since the one the header refers to.
+    fn if_range_matches(
+        &self,
+        etag: Option<&str>,
+        last_modified: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> bool {
+        let Some(if_range) = &self.if_range else {
+            return true;
+        };
+
+        if if_range.starts_with('"') {
+            return etag.is_some_and(|etag| etag == if_range);
+        }
+
+        chrono::DateTime::parse_from_rfc2822(if_range)
+            .ok()
+            .zip(last_modified)
+            .is_some_and(|(date, last_modified)| date.timestamp() == last_modified.timestamp())
+    }
+}
+
+/// Whether any of the entity tags listed in the header matches with given one. Weak tags never
+/// match when `strong` comparison is requested.
+fn etag_matches(header: &str, etag: Option<&str>, strong: bool) -> bool {
+    let Some(etag) = etag else {
+        return false;
+    };
+
+    if header.trim() == "*" {
+        return true;
+    }
+
+    let opaque = |tag: &str| -> Option<String> {
+        match tag.strip_prefix("W/") {
+            Some(_) if strong => None,
+            Some(weak) => Some(weak.to_string()),
+            None => Some(tag.to_string()),
+        }
+    };
+
+    let Some(etag) = opaque(etag) else {
+        return false;
+    };
+
+    header
+        .split(',')
+        .filter_map(|tag| opaque(tag.trim()))
+        .any(|tag| tag == etag)
+}
+
+#[derive(Debug, PartialEq)]
+enum ByteRanges {
+    Full,
+    Partial(Vec<Range<u64>>),
+    Unsatisfiable,
+}
+
+impl ByteRanges {
+    const MAX_RANGES: usize = 16;
+
+    /// Parses the `Range` header against a representation of `length` bytes. Invalid headers are
+    /// ignored, as RFC 9110 permits, and the full representation is served.
+    fn parse(header: &str, length: u64) -> Self {
+        let Some(specs) = header.trim().strip_prefix("bytes=") else {
+            return ByteRanges::Full;
+        };
+
+        if specs.split(',').count() > Self::MAX_RANGES {
+            return ByteRanges::Full;
+        }
+
+        let mut ranges = Vec::new();
+
+        for spec in specs.split(',').map(str::trim) {
+            let Some((start, end)) = spec.split_once('-') else {
+                return ByteRanges::Full;
+            };
+
+            let range = match (start.parse::<u64>(), end.parse::<u64>()) {
+                (Ok(start), Ok(end)) if start <= end => start..(end + 1).min(length),
+                (Ok(start), Err(_)) if end.is_empty() => start..length,
+                (Err(_), Ok(suffix)) if start.is_empty() => length.saturating_sub(suffix)..length,
+                _ => return ByteRanges::Full,
+            };
+
+            if range.start < range.end {
+                ranges.push(range);
+            }
+        }
+
+        if ranges.is_empty() {
+            return ByteRanges::Unsatisfiable;
+        }
+
+        ByteRanges::Partial(ranges)
+    }
+}
+
+/// Returns the path and metadata of the derivative of an image stored at `path`, generating
+/// and caching it if it does not exist yet.
+#[cfg(feature = "image")]
+async fn derivative(
+    storage: &Operator,
+    path: &str,
+    meta: &opendal::Metadata,
+    transform: crate::image::Transform,
+) -> Result<(String, opendal::Metadata), Response<Body>> {
+    let Some(source) = crate::image::Format::from_path(path) else {
+        return Err(response_from_status(StatusCode::BAD_REQUEST));
+    };
+
+    // Source's modification time is part of the name so that a replaced source does not
+    // serve stale derivatives
+    let version = meta
+        .last_modified()
+        .map(|lm| lm.timestamp().to_string())
+        .unwrap_or_else(|| meta.content_length().to_string());
+
+    let derivative_path = format!(
+        "derivatives/{path}/{}",
+        transform.derivative_name(source, &version)
+    );
+
+    match storage.stat(&derivative_path).into_send_future().await {
+        Ok(meta) => return Ok((derivative_path, meta)),
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(response_from_opendal_error(e)),
+    }
+
+    let bytes = storage
+        .read(path)
+        .into_send_future()
+        .await
+        .map_err(response_from_opendal_error)?
+        .to_vec();
+
+    let derived = crate::runtime::spawn_blocking(move || transform.apply(&bytes))
+        .await
+        .unwrap()
+        .map(|(bytes, _)| bytes)
+        .map_err(|e| {
+            log::debug!("Failed to transform image {path}, {e:?}");
+
+            response_from_status(StatusCode::UNPROCESSABLE_ENTITY)
+        })?;
+
+    storage
+        .write(&derivative_path, derived)
+        .into_send_future()
+        .await
+        .map_err(response_from_opendal_error)?;
+
+    let meta = storage
+        .stat(&derivative_path)
+        .into_send_future()
+        .await
+        .map_err(response_from_opendal_error)?;
+
+    Ok((derivative_path, meta))
+}
+
 fn response_from_opendal_error(e: opendal::Error) -> Response<Body> {
     if e.kind() == ErrorKind::NotFound || e.kind() == ErrorKind::PermissionDenied {
         return response_from_status(StatusCode::NOT_FOUND);
@@ -244,3 +631,149 @@ fn response_from_status(status: StatusCode) -> Response<Body> {
         .body(Body::empty())
         .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+    use chrono::TimeZone;
+
+    use super::*;
+
+    async fn serve(storage: &Operator, range: Option<&str>) -> Response<Body> {
+        let mut req = Request::builder().uri("/file.txt");
+
+        if let Some(range) = range {
+            req = req.header(header::RANGE, range);
+        }
+
+        ServeStorageDir::new(storage.clone(), || "assets".to_string())
+            .with_cache_control("public, no-cache")
+            .call(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn it_parses_byte_ranges() {
+        assert_eq!(
+            ByteRanges::Partial(vec![0..10, 90..100, 95..100]),
+            ByteRanges::parse("bytes=0-9, 90-, -5", 100)
+        );
+
+        let clamped = 50..100;
+        assert_eq!(
+            ByteRanges::Partial(vec![clamped]),
+            ByteRanges::parse("bytes=50-200", 100)
+        );
+
+        assert_eq!(
+            ByteRanges::Unsatisfiable,
+            ByteRanges::parse("bytes=100-", 100)
+        );
+        assert_eq!(ByteRanges::Full, ByteRanges::parse("bytes=9-0", 100));
+        assert_eq!(ByteRanges::Full, ByteRanges::parse("items=0-9", 100));
+    }
+
+    #[test]
+    fn it_evaluates_conditional_headers() {
+        let last_modified = chrono::Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let etag = Some("\"64-1\"");
+
+        let conditions = Conditions {
+            if_none_match: Some("\"other\", W/\"64-1\"".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(StatusCode::NOT_MODIFIED),
+            conditions.evaluate(etag, Some(last_modified))
+        );
+
+        let conditions = Conditions {
+            if_match: Some("\"other\"".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(StatusCode::PRECONDITION_FAILED),
+            conditions.evaluate(etag, Some(last_modified))
+        );
+
+        let conditions = Conditions {
+            if_unmodified_since: Some((last_modified - chrono::Duration::days(1)).fixed_offset()),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(StatusCode::PRECONDITION_FAILED),
+            conditions.evaluate(etag, Some(last_modified))
+        );
+
+        let conditions = Conditions {
+            if_match: Some("*".to_string()),
+            if_modified_since: Some((last_modified - chrono::Duration::days(1)).fixed_offset()),
+            ..Default::default()
+        };
+        assert_eq!(None, conditions.evaluate(etag, Some(last_modified)));
+
+        let conditions = Conditions {
+            if_range: Some(http_date(last_modified)),
+            ..Default::default()
+        };
+        assert!(conditions.if_range_matches(etag, Some(last_modified)));
+        assert!(!conditions.if_range_matches(etag, None));
+    }
+
+    #[tokio::test]
+    async fn it_serves_requested_byte_ranges() {
+        let storage = Operator::new(opendal::services::Memory::default())
+            .unwrap()
+            .finish();
+
+        storage
+            .write("assets/file.txt", "0123456789abcdefghij")
+            .await
+            .unwrap();
+
+        let response = serve(&storage, None).await;
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!("bytes", response.headers()[header::ACCEPT_RANGES]);
+        assert_eq!(
+            "public, no-cache",
+            response.headers()[header::CACHE_CONTROL]
+        );
+
+        let response = serve(&storage, Some("bytes=2-5")).await;
+        assert_eq!(StatusCode::PARTIAL_CONTENT, response.status());
+        assert_eq!("bytes 2-5/20", response.headers()[header::CONTENT_RANGE]);
+        assert_eq!(
+            "2345",
+            to_bytes(response.into_body(), usize::MAX).await.unwrap()
+        );
+
+        let response = serve(&storage, Some("bytes=0-1, -2")).await;
+        assert_eq!(StatusCode::PARTIAL_CONTENT, response.status());
+
+        let content_length: usize = response.headers()[header::CONTENT_LENGTH]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let content_type = response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let boundary = content_type
+            .strip_prefix("multipart/byteranges; boundary=")
+            .unwrap();
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = std::str::from_utf8(&body).unwrap();
+
+        assert_eq!(content_length, body.len());
+        assert!(body.contains("Content-Range: bytes 0-1/20\r\n\r\n01\r\n"));
+        assert!(body.contains("Content-Range: bytes 18-19/20\r\n\r\nij\r\n"));
+        assert!(body.ends_with(&format!("\r\n--{boundary}--\r\n")));
+
+        let response = serve(&storage, Some("bytes=20-")).await;
+        assert_eq!(StatusCode::RANGE_NOT_SATISFIABLE, response.status());
+        assert_eq!("bytes */20", response.headers()[header::CONTENT_RANGE]);
+    }
+}
@@ -0,0 +1,238 @@
+use axum::http::StatusCode;
+use chrono::{NaiveDateTime, TimeDelta};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use rand::{distr::Alphanumeric, rng, Rng};
+use serde::Serialize;
+
+use crate::{
+    crypto::Crypto,
+    db::Connection,
+    middlewares::auth::Token,
+    models::UserState,
+    responses::HttpError,
+    schema::{sessions, users},
+};
+
+/// Refresh tokens are valid for this many seconds after they are issued. Each refresh extends the
+/// session, so that only sessions which are not used for this long are expired.
+pub const REFRESH_TOKEN_TIMEOUT: i64 = 60 * 60 * 24 * 30;
+
+const SECRET_LENGTH: usize = 48;
+
+pub const INVALID_REFRESH_TOKEN: HttpError = HttpError {
+    code: StatusCode::UNAUTHORIZED,
+    error: "invalid_refresh_token",
+    context: None,
+};
+
+/// Access token along with the refresh token that can be exchanged for a new pair once the access
+/// token expires.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTokens {
+    pub token: String,
+    pub refresh_token: String,
+}
+
+fn generate_secret() -> String {
+    (0..SECRET_LENGTH)
+        .map(|_| rng().sample(Alphanumeric) as char)
+        .collect()
+}
+
+fn tokens(
+    crypto: &Crypto,
+    user_id: i32,
+    session_id: i32,
+    secret: &str,
+) -> Result<SessionTokens, HttpError> {
+    Ok(SessionTokens {
+        token: crypto.encode(&Token::new(user_id, session_id))?,
+        refresh_token: format!("{session_id}.{secret}"),
+    })
+}
+
+/// Starts a new session for the user. Expired sessions of the user are cleaned up along the way.
+pub async fn create(
+    conn: &mut Connection,
+    crypto: &Crypto,
+    user_id: i32,
+    now: NaiveDateTime,
+) -> Result<SessionTokens, HttpError> {
+    diesel::delete(sessions::table)
+        .filter(sessions::user_id.eq(user_id))
+        .filter(sessions::expires_at.le(now))
+        .execute(conn)
+        .await?;
+
+    let secret = generate_secret();
+
+    let session_id = diesel::insert_into(sessions::table)
+        .values((
+            sessions::user_id.eq(user_id),
+            sessions::refresh_hash.eq(crypto.sign512(secret.as_bytes())),
+            sessions::expires_at.eq(now + TimeDelta::seconds(REFRESH_TOKEN_TIMEOUT)),
+            sessions::refreshed_at.eq(now),
+            sessions::created_at.eq(now),
+        ))
+        .returning(sessions::id)
+        .get_result::<i32>(conn)
+        .await?;
+
+    tokens(crypto, user_id, session_id, &secret)
+}
+
+/// Exchanges the refresh token for a new pair of tokens. Refresh tokens are rotated, i.e. each one
+/// can be used once. Presenting the one that is rotated last means that it is leaked, so the whole
+/// session is revoked in that case. Any other mismatching token is rejected without touching the
+/// session, since session ids are easy to guess.
+pub async fn refresh(
+    conn: &mut Connection,
+    crypto: &Crypto,
+    refresh_token: &str,
+    now: NaiveDateTime,
+) -> Result<SessionTokens, HttpError> {
+    let Some((session_id, secret)) = refresh_token
+        .split_once('.')
+        .and_then(|(id, secret)| Some((id.parse::<i32>().ok()?, secret)))
+    else {
+        return Err(INVALID_REFRESH_TOKEN);
+    };
+
+    let Some((user_id, refresh_hash, previous_refresh_hash, expires_at, user_state)) =
+        sessions::table
+            .inner_join(users::table)
+            .filter(sessions::id.eq(session_id))
+            .select((
+                sessions::user_id,
+                sessions::refresh_hash,
+                sessions::previous_refresh_hash,
+                sessions::expires_at,
+                users::state,
+            ))
+            .first::<(i32, String, Option<String>, NaiveDateTime, UserState)>(conn)
+            .await
+            .optional()?
+    else {
+        return Err(INVALID_REFRESH_TOKEN);
+    };
+
+    if expires_at <= now || UserState::Enabled != user_state {
+        revoke(conn, session_id).await?;
+
+        return Err(INVALID_REFRESH_TOKEN);
+    }
+
+    if !crypto.verify512(secret.as_bytes(), &refresh_hash) {
+        if previous_refresh_hash.is_some_and(|hash| crypto.verify512(secret.as_bytes(), &hash)) {
+            revoke(conn, session_id).await?;
+        }
+
+        return Err(INVALID_REFRESH_TOKEN);
+    }
+
+    let secret = generate_secret();
+
+    // Only rotate if the token is not rotated by a concurrent request in the meantime
+    let effected_row = diesel::update(sessions::table)
+        .filter(sessions::id.eq(session_id))
+        .filter(sessions::refresh_hash.eq(&refresh_hash))
+        .set((
+            sessions::refresh_hash.eq(crypto.sign512(secret.as_bytes())),
+            sessions::previous_refresh_hash.eq(&refresh_hash),
+            sessions::expires_at.eq(now + TimeDelta::seconds(REFRESH_TOKEN_TIMEOUT)),
+            sessions::refreshed_at.eq(now),
+        ))
+        .execute(conn)
+        .await?;
+
+    if effected_row == 0 {
+        return Err(INVALID_REFRESH_TOKEN);
+    }
+
+    tokens(crypto, user_id, session_id, &secret)
+}
+
+/// Revokes the session, which invalidates both its refresh token and the access tokens issued for
+/// it.
+pub async fn revoke(conn: &mut Connection, session_id: i32) -> QueryResult<usize> {
+    diesel::delete(sessions::table)
+        .filter(sessions::id.eq(session_id))
+        .execute(conn)
+        .await
+}
+
+/// Revokes every session of the user, i.e. logs the user out of all devices.
+pub async fn revoke_all(conn: &mut Connection, user_id: i32) -> QueryResult<usize> {
+    diesel::delete(sessions::table)
+        .filter(sessions::user_id.eq(user_id))
+        .execute(conn)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeDelta, Utc};
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+
+    use super::{create, refresh, INVALID_REFRESH_TOKEN};
+    use crate::{
+        crypto::Crypto,
+        schema::users,
+        test::{create_pool, DB_CONFIG},
+    };
+
+    #[tokio::test]
+    async fn it_rotates_refresh_tokens_and_revokes_session_on_reuse() {
+        let pool = create_pool(DB_CONFIG).await;
+        let mut conn = pool.get().await.unwrap();
+        let crypto = Crypto::new("secret");
+        let now = Utc::now().naive_utc();
+
+        let user_id = diesel::insert_into(users::table)
+            .values((
+                users::username.eq("session_user"),
+                users::name.eq("Session User"),
+                users::email.eq("session@example.com"),
+            ))
+            .returning(users::id)
+            .get_result::<i32>(&mut conn)
+            .await
+            .unwrap();
+
+        let first = create(&mut conn, &crypto, user_id, now).await.unwrap();
+
+        let session_id = first.refresh_token.split_once('.').unwrap().0;
+
+        let guessed = refresh(&mut conn, &crypto, &format!("{session_id}.guess"), now).await;
+
+        assert!(matches!(guessed, Err(e) if e.error == INVALID_REFRESH_TOKEN.error));
+
+        // Session is kept since the guessed token is never issued, so the actual one still works
+
+        let second = refresh(&mut conn, &crypto, &first.refresh_token, now)
+            .await
+            .unwrap();
+
+        assert_ne!(first.refresh_token, second.refresh_token);
+
+        let reused = refresh(&mut conn, &crypto, &first.refresh_token, now).await;
+
+        assert!(matches!(reused, Err(e) if e.error == INVALID_REFRESH_TOKEN.error));
+
+        // Session is revoked due to reuse, so the latest token does not work either
+        assert!(refresh(&mut conn, &crypto, &second.refresh_token, now)
+            .await
+            .is_err());
+
+        let expired = create(&mut conn, &crypto, user_id, now).await.unwrap();
+
+        let later = now + TimeDelta::seconds(super::REFRESH_TOKEN_TIMEOUT + 1);
+
+        assert!(refresh(&mut conn, &crypto, &expired.refresh_token, later)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,636 @@
+use axum::http::StatusCode;
+use chrono::{NaiveDateTime, TimeDelta};
+use diesel::prelude::*;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use jsonwebtoken::errors::Error as JWTError;
+use rand::{rng, Rng};
+use serde::{Deserialize, Serialize};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use crate::{
+    crypto::Crypto,
+    db::Connection,
+    responses::HttpError,
+    schema::{recovery_codes, roles, two_factors, users},
+};
+
+/// Number of recovery codes given when two-factor authentication is enabled or the codes are
+/// regenerated.
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Second step of the login must be completed in this many seconds after the password is verified.
+pub const CHALLENGE_TIMEOUT: i64 = 60 * 5;
+
+const STEP: u64 = 30;
+
+const DIGITS: usize = 6;
+
+/// Verification is locked for [`LOCK_TIMEOUT`] seconds after this many failed attempts in a row.
+const MAX_FAILED_ATTEMPTS: i32 = 5;
+
+const LOCK_TIMEOUT: i64 = 60 * 15;
+
+const RECOVERY_CODE_CHARS: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+pub const INVALID_CODE: HttpError = HttpError {
+    code: StatusCode::FORBIDDEN,
+    error: "invalid_two_factor_code",
+    context: None,
+};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengeKind {
+    /// User needs to enter a code from the authenticator app or a recovery code.
+    Verify,
+    /// User is required to enable two-factor authentication by a role before logging in.
+    Enroll,
+}
+
+impl ChallengeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChallengeKind::Verify => "verify",
+            ChallengeKind::Enroll => "enroll",
+        }
+    }
+}
+
+/// Token issued after the password of a user with two-factor authentication is verified. It is
+/// exchanged for a session once the second step is completed.
+#[derive(Deserialize, Serialize)]
+pub struct ChallengeToken {
+    // expire time
+    pub exp: i64,
+    pub id: i32,
+    pub challenge: ChallengeKind,
+}
+
+impl ChallengeToken {
+    pub fn issue(
+        crypto: &Crypto,
+        id: i32,
+        challenge: ChallengeKind,
+        now: i64,
+    ) -> Result<String, JWTError> {
+        crypto.encode(&Self {
+            exp: now + CHALLENGE_TIMEOUT,
+            id,
+            challenge,
+        })
+    }
+
+    /// Decodes the token if it is valid, not expired and issued for the given challenge.
+    pub fn decode(crypto: &Crypto, token: &str, challenge: ChallengeKind) -> Option<Self> {
+        crypto
+            .decode::<Self>(token)
+            .ok()
+            .filter(|token| token.challenge == challenge)
+    }
+}
+
+/// Secret of a pending enrollment, which is added to an authenticator app either by scanning the
+/// `uri` as QR code or by entering the `secret` manually.
+#[derive(Serialize)]
+pub struct Enrollment {
+    pub secret: String,
+    pub uri: String,
+}
+
+fn totp(secret: &str, issuer: Option<&str>, account: &str) -> Option<TOTP> {
+    let secret = Secret::Encoded(secret.to_string()).to_bytes().ok()?;
+
+    Some(TOTP::new_unchecked(
+        Algorithm::SHA1,
+        DIGITS,
+        0,
+        STEP,
+        secret,
+        issuer.map(ToString::to_string),
+        account.to_string(),
+    ))
+}
+
+/// Returns the time step the code belongs to if it is valid at the given time. Codes of the
+/// previous and the next steps are accepted as well to tolerate clock drift.
+pub fn check_code(secret: &str, code: &str, now: i64) -> Option<i64> {
+    let totp = totp(secret, None, "")?;
+    let current = now / STEP as i64;
+
+    (current - 1..=current + 1).find(|step| totp.check(code, *step as u64 * STEP))
+}
+
+fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = rng();
+
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut code: String = (0..10)
+                .map(|_| {
+                    RECOVERY_CODE_CHARS[rng.random_range(0..RECOVERY_CODE_CHARS.len())] as char
+                })
+                .collect();
+
+            code.insert(5, '-');
+
+            code
+        })
+        .collect()
+}
+
+fn hash_recovery_code(crypto: &Crypto, code: &str) -> String {
+    let code = code
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|ch| ch.to_ascii_lowercase())
+        .collect::<String>();
+
+    crypto.sign512(format!("recovery_code:{code}").as_bytes())
+}
+
+async fn replace_recovery_codes(
+    conn: &mut Connection,
+    crypto: &Crypto,
+    user_id: i32,
+) -> QueryResult<Vec<String>> {
+    let codes = generate_recovery_codes();
+
+    diesel::delete(recovery_codes::table)
+        .filter(recovery_codes::user_id.eq(user_id))
+        .execute(conn)
+        .await?;
+
+    for code in &codes {
+        diesel::insert_into(recovery_codes::table)
+            .values((
+                recovery_codes::user_id.eq(user_id),
+                recovery_codes::code_hash.eq(hash_recovery_code(crypto, code)),
+            ))
+            .execute(conn)
+            .await?;
+    }
+
+    Ok(codes)
+}
+
+/// Returns true if the user has confirmed two-factor authentication.
+pub async fn is_enabled(conn: &mut Connection, user_id: i32) -> QueryResult<bool> {
+    diesel::select(diesel::dsl::exists(
+        two_factors::table
+            .filter(two_factors::user_id.eq(user_id))
+            .filter(two_factors::confirmed_at.is_not_null()),
+    ))
+    .get_result::<bool>(conn)
+    .await
+}
+
+/// Returns true if the role of the user requires two-factor authentication.
+pub async fn is_required(conn: &mut Connection, user_id: i32) -> QueryResult<bool> {
+    users::table
+        .inner_join(roles::table)
+        .filter(users::id.eq(user_id))
+        .select(roles::require_two_factor)
+        .first::<bool>(conn)
+        .await
+        .optional()
+        .map(|required| required.unwrap_or(false))
+}
+
+/// Starts enrolling the user with a new secret, replacing the one of a previous enrollment that is
+/// not confirmed.
+pub async fn start_enrollment(
+    conn: &mut Connection,
+    user_id: i32,
+    issuer: &str,
+    account: &str,
+) -> Result<Enrollment, HttpError> {
+    if is_enabled(conn, user_id).await? {
+        return Err(HttpError::conflict("two_factor_already_enabled"));
+    }
+
+    let secret = Secret::generate_secret().to_encoded().to_string();
+
+    let uri = totp(&secret, Some(issuer), account)
+        .ok_or(HttpError::internal_server_error(
+            "invalid_two_factor_secret",
+        ))?
+        .get_url();
+
+    diesel::delete(two_factors::table)
+        .filter(two_factors::user_id.eq(user_id))
+        .execute(conn)
+        .await?;
+
+    diesel::insert_into(two_factors::table)
+        .values((
+            two_factors::user_id.eq(user_id),
+            two_factors::secret.eq(&secret),
+        ))
+        .execute(conn)
+        .await?;
+
+    Ok(Enrollment { secret, uri })
+}
+
+/// Enables two-factor authentication if the code is valid for the pending enrollment of the user,
+/// and returns the recovery codes.
+pub async fn confirm_enrollment(
+    conn: &mut Connection,
+    crypto: &Crypto,
+    user_id: i32,
+    code: &str,
+    now: NaiveDateTime,
+) -> Result<Vec<String>, HttpError> {
+    let Some(secret) = two_factors::table
+        .filter(two_factors::user_id.eq(user_id))
+        .filter(two_factors::confirmed_at.is_null())
+        .select(two_factors::secret)
+        .first::<String>(conn)
+        .await
+        .optional()?
+    else {
+        return Err(HttpError::conflict("two_factor_enrollment_not_found"));
+    };
+
+    let Some(step) = check_code(&secret, code, now.and_utc().timestamp()) else {
+        return Err(INVALID_CODE);
+    };
+
+    conn.transaction(|conn| {
+        async move {
+            let effected_row = diesel::update(two_factors::table)
+                .filter(two_factors::user_id.eq(user_id))
+                .filter(two_factors::confirmed_at.is_null())
+                .set((
+                    two_factors::confirmed_at.eq(now),
+                    two_factors::last_step.eq(step),
+                ))
+                .execute(conn)
+                .await?;
+
+            if effected_row == 0 {
+                return Err(HttpError::conflict("two_factor_enrollment_not_found"));
+            }
+
+            replace_recovery_codes(conn, crypto, user_id)
+                .await
+                .map_err(Into::into)
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+/// Verifies either a code from the authenticator app or a recovery code of the user. Each code can
+/// be used once, recovery codes are removed and codes of the same or earlier time steps are
+/// rejected after one is used. Verification is locked for a while after too many failed attempts
+/// in a row, so that codes cannot be guessed.
+pub async fn verify(
+    conn: &mut Connection,
+    crypto: &Crypto,
+    user_id: i32,
+    code: &str,
+    now: NaiveDateTime,
+) -> Result<bool, HttpError> {
+    let Some((secret, last_step, locked_until)) = two_factors::table
+        .filter(two_factors::user_id.eq(user_id))
+        .filter(two_factors::confirmed_at.is_not_null())
+        .select((
+            two_factors::secret,
+            two_factors::last_step,
+            two_factors::locked_until,
+        ))
+        .first::<(String, Option<i64>, Option<NaiveDateTime>)>(conn)
+        .await
+        .optional()?
+    else {
+        return Ok(false);
+    };
+
+    if locked_until.is_some_and(|locked_until| locked_until > now) {
+        return Err(HttpError::too_many_requests("too_many_two_factor_attempts"));
+    }
+
+    if use_code(conn, crypto, user_id, &secret, last_step, code.trim(), now).await? {
+        diesel::update(two_factors::table)
+            .filter(two_factors::user_id.eq(user_id))
+            .set(two_factors::failed_attempts.eq(0))
+            .execute(conn)
+            .await?;
+
+        return Ok(true);
+    }
+
+    let failed_attempts = diesel::update(two_factors::table)
+        .filter(two_factors::user_id.eq(user_id))
+        .set(two_factors::failed_attempts.eq(two_factors::failed_attempts + 1))
+        .returning(two_factors::failed_attempts)
+        .get_result::<i32>(conn)
+        .await?;
+
+    if failed_attempts >= MAX_FAILED_ATTEMPTS {
+        diesel::update(two_factors::table)
+            .filter(two_factors::user_id.eq(user_id))
+            .set((
+                two_factors::failed_attempts.eq(0),
+                two_factors::locked_until.eq(now + TimeDelta::seconds(LOCK_TIMEOUT)),
+            ))
+            .execute(conn)
+            .await?;
+    }
+
+    Ok(false)
+}
+
+async fn use_code(
+    conn: &mut Connection,
+    crypto: &Crypto,
+    user_id: i32,
+    secret: &str,
+    last_step: Option<i64>,
+    code: &str,
+    now: NaiveDateTime,
+) -> QueryResult<bool> {
+    if code.len() == DIGITS && code.chars().all(|ch| ch.is_ascii_digit()) {
+        let Some(step) = check_code(secret, code, now.and_utc().timestamp()) else {
+            return Ok(false);
+        };
+
+        if last_step.is_some_and(|last_step| step <= last_step) {
+            return Ok(false);
+        }
+
+        let query = diesel::update(two_factors::table)
+            .filter(two_factors::user_id.eq(user_id))
+            .into_boxed();
+
+        // Only update if no other code is used in the meantime
+        let query = match last_step {
+            Some(last_step) => query.filter(two_factors::last_step.eq(last_step)),
+            None => query.filter(two_factors::last_step.is_null()),
+        };
+
+        let effected_row = query
+            .set(two_factors::last_step.eq(step))
+            .execute(conn)
+            .await?;
+
+        return Ok(effected_row == 1);
+    }
+
+    let effected_row = diesel::delete(recovery_codes::table)
+        .filter(recovery_codes::user_id.eq(user_id))
+        .filter(recovery_codes::code_hash.eq(hash_recovery_code(crypto, code)))
+        .execute(conn)
+        .await?;
+
+    Ok(effected_row == 1)
+}
+
+/// Replaces the recovery codes of the user with new ones.
+pub async fn regenerate_recovery_codes(
+    conn: &mut Connection,
+    crypto: &Crypto,
+    user_id: i32,
+) -> Result<Vec<String>, HttpError> {
+    conn.transaction(|conn| {
+        async move {
+            replace_recovery_codes(conn, crypto, user_id)
+                .await
+                .map_err(Into::into)
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+/// Disables two-factor authentication of the user, removing its secret and recovery codes.
+pub async fn disable(conn: &mut Connection, user_id: i32) -> QueryResult<usize> {
+    diesel::delete(recovery_codes::table)
+        .filter(recovery_codes::user_id.eq(user_id))
+        .execute(conn)
+        .await?;
+
+    diesel::delete(two_factors::table)
+        .filter(two_factors::user_id.eq(user_id))
+        .execute(conn)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeDelta};
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+
+    use super::{
+        check_code, confirm_enrollment, start_enrollment, totp, verify, ChallengeKind,
+        ChallengeToken,
+    };
+    use crate::{
+        crypto::Crypto,
+        schema::users,
+        test::{create_pool, DB_CONFIG},
+    };
+
+    #[test]
+    fn it_accepts_codes_of_adjacent_steps() {
+        let secret = "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP";
+        let totp = totp(secret, None, "").unwrap();
+        let now = 1_700_000_000;
+
+        let code = totp.generate(now as u64);
+
+        assert_eq!(Some(now / 30), check_code(secret, &code, now));
+        assert_eq!(Some(now / 30), check_code(secret, &code, now + 30));
+        assert_eq!(None, check_code(secret, &code, now + 90));
+        assert_eq!(None, check_code(secret, "000000x", now));
+    }
+
+    #[test]
+    fn it_does_not_mix_challenges() {
+        let crypto = Crypto::new("secret");
+        let now = chrono::Utc::now().timestamp();
+
+        let token = ChallengeToken::issue(&crypto, 1, ChallengeKind::Enroll, now).unwrap();
+
+        assert!(ChallengeToken::decode(&crypto, &token, ChallengeKind::Verify).is_none());
+        assert!(ChallengeToken::decode(&crypto, &token, ChallengeKind::Enroll).is_some());
+
+        let expired = ChallengeToken::issue(&crypto, 1, ChallengeKind::Verify, now - 3600).unwrap();
+
+        assert!(ChallengeToken::decode(&crypto, &expired, ChallengeKind::Verify).is_none());
+    }
+
+    #[tokio::test]
+    async fn it_accepts_each_code_once() {
+        let pool = create_pool(DB_CONFIG).await;
+        let crypto = Crypto::new("secret");
+        let now = DateTime::from_timestamp(1_700_000_000, 0)
+            .unwrap()
+            .naive_utc();
+
+        let user_id = diesel::insert_into(users::table)
+            .values((
+                users::username.eq("two_factor_user"),
+                users::name.eq("Two Factor"),
+                users::email.eq("two_factor@example.com"),
+            ))
+            .returning(users::id)
+            .get_result::<i32>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let enrollment = start_enrollment(
+            &mut pool.get().await.unwrap(),
+            user_id,
+            "localhost",
+            "two_factor@example.com",
+        )
+        .await
+        .unwrap();
+
+        assert!(enrollment.uri.starts_with("otpauth://totp/"));
+
+        let totp = totp(&enrollment.secret, None, "").unwrap();
+        let code_at =
+            |time: chrono::NaiveDateTime| totp.generate(time.and_utc().timestamp() as u64);
+
+        let recovery_codes = confirm_enrollment(
+            &mut pool.get().await.unwrap(),
+            &crypto,
+            user_id,
+            &code_at(now),
+            now,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(super::RECOVERY_CODE_COUNT, recovery_codes.len());
+
+        // Code used for the confirmation cannot be used again
+        assert!(!verify(
+            &mut pool.get().await.unwrap(),
+            &crypto,
+            user_id,
+            &code_at(now),
+            now
+        )
+        .await
+        .unwrap());
+
+        let later = now + TimeDelta::seconds(60);
+
+        assert!(verify(
+            &mut pool.get().await.unwrap(),
+            &crypto,
+            user_id,
+            &code_at(later),
+            later
+        )
+        .await
+        .unwrap());
+
+        let recovery_code = recovery_codes[0].to_uppercase();
+
+        assert!(verify(
+            &mut pool.get().await.unwrap(),
+            &crypto,
+            user_id,
+            &recovery_code,
+            later
+        )
+        .await
+        .unwrap());
+
+        assert!(!verify(
+            &mut pool.get().await.unwrap(),
+            &crypto,
+            user_id,
+            &recovery_code,
+            later
+        )
+        .await
+        .unwrap());
+    }
+
+    #[tokio::test]
+    async fn it_locks_after_too_many_failed_attempts() {
+        let pool = create_pool(DB_CONFIG).await;
+        let crypto = Crypto::new("secret");
+        let now = DateTime::from_timestamp(1_700_000_000, 0)
+            .unwrap()
+            .naive_utc();
+
+        let user_id = diesel::insert_into(users::table)
+            .values((
+                users::username.eq("locked_user"),
+                users::name.eq("Locked"),
+                users::email.eq("locked@example.com"),
+            ))
+            .returning(users::id)
+            .get_result::<i32>(&mut pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let enrollment = start_enrollment(
+            &mut pool.get().await.unwrap(),
+            user_id,
+            "localhost",
+            "locked@example.com",
+        )
+        .await
+        .unwrap();
+
+        let totp = totp(&enrollment.secret, None, "").unwrap();
+        let code_at =
+            |time: chrono::NaiveDateTime| totp.generate(time.and_utc().timestamp() as u64);
+
+        confirm_enrollment(
+            &mut pool.get().await.unwrap(),
+            &crypto,
+            user_id,
+            &code_at(now),
+            now,
+        )
+        .await
+        .unwrap();
+
+        for _ in 0..super::MAX_FAILED_ATTEMPTS {
+            assert!(!verify(
+                &mut pool.get().await.unwrap(),
+                &crypto,
+                user_id,
+                "wrong-code",
+                now
+            )
+            .await
+            .unwrap());
+        }
+
+        let later = now + TimeDelta::seconds(60);
+
+        let locked = verify(
+            &mut pool.get().await.unwrap(),
+            &crypto,
+            user_id,
+            &code_at(later),
+            later,
+        )
+        .await;
+
+        assert!(matches!(locked, Err(e) if e.error == "too_many_two_factor_attempts"));
+
+        let unlocked = now + TimeDelta::seconds(super::LOCK_TIMEOUT + 60);
+
+        assert!(verify(
+            &mut pool.get().await.unwrap(),
+            &crypto,
+            user_id,
+            &code_at(unlocked),
+            unlocked
+        )
+        .await
+        .unwrap());
+    }
+}
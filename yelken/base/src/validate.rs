@@ -1,4 +1,4 @@
-use std::{collections::HashMap, ops::Deref};
+use std::{borrow::Cow, collections::HashMap, ops::Deref};
 
 use axum::{
     extract::{FromRequest, Request},
@@ -12,14 +12,14 @@ use crate::{responses::HttpError, sanitize::Sanitized};
 #[serde(untagged)]
 pub enum Error {
     Field(Vec<&'static str>),
-    Struct(HashMap<&'static str, Error>),
+    Struct(HashMap<Cow<'static, str>, Error>),
     List(HashMap<usize, Error>),
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Errors {
-    pub field_messages: HashMap<&'static str, Error>,
+    pub field_messages: HashMap<Cow<'static, str>, Error>,
     pub messages: Vec<&'static str>,
 }
 
@@ -35,10 +35,10 @@ impl Errors {
         self.field_messages.len() == 0 && self.messages.len() == 0
     }
 
-    pub fn insert_field(&mut self, key: &'static str, error: &'static str) {
+    pub fn insert_field(&mut self, key: impl Into<Cow<'static, str>>, error: &'static str) {
         match self
             .field_messages
-            .entry(key)
+            .entry(key.into())
             .or_insert(Error::Field(vec![]))
         {
             Error::Field(v) => v.push(error),
@@ -47,6 +47,20 @@ impl Errors {
     }
 }
 
+/// A loose check for email addresses, meant to catch typos rather than to comply with RFC 5322.
+pub fn is_email(value: &str) -> bool {
+    let Some((local, domain)) = value.split_once('@') else {
+        return false;
+    };
+
+    !local.is_empty()
+        && !domain.contains('@')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !value.contains(char::is_whitespace)
+}
+
 pub trait Validate {
     fn validate(&self) -> Result<(), Errors>;
 }
@@ -3,7 +3,7 @@ use proc_macro2::Ident;
 use quote::quote;
 use syn::{DeriveInput, Meta, parse_macro_input};
 
-#[proc_macro_derive(Sanitize, attributes(sanitize))]
+#[proc_macro_derive(Sanitize, attributes(sanitizer))]
 pub fn sanitize_derive(input: TokenStream) -> TokenStream {
     // Construct a representation of Rust code as a syntax tree
     // that we can manipulate
@@ -33,7 +33,7 @@ fn impl_sanitize(ast: &DeriveInput) -> TokenStream {
         let mut skip = false;
 
         for attr in field.attrs {
-            if attr.path().is_ident("sanitize") {
+            if attr.path().is_ident("sanitizer") {
                 let Meta::List(list) = attr.meta else {
                     panic!("Unknown attribute meta");
                 };
@@ -0,0 +1,32 @@
+create table content_revisions(
+    id         serial primary key not null,
+    content_id int          not null,
+    action     varchar(16)  not null check (action in ('create', 'update_name', 'update_stage', 'create_value', 'update_value', 'delete_value', 'restore')),
+    name       text         not null,
+    stage      varchar(16)  not null,
+    values     text         not null,
+    created_by int          default null,
+    created_at timestamp    not null default current_timestamp,
+    constraint fk_content_revisions_content_id foreign key (content_id) references contents (id) on delete cascade on update no action,
+    constraint fk_content_revisions_created_by foreign key (created_by) references users (id) on delete set null on update no action
+);
+
+create index content_revisions_content_id on content_revisions (content_id);
+
+insert into content_revisions (content_id, action, name, stage, values, created_by, created_at)
+select
+    c.id,
+    'create',
+    c.name,
+    c.stage,
+    coalesce(
+        (
+            select json_agg(json_build_object('modelFieldId', cv.model_field_id, 'value', cv.value, 'locale', cv.locale) order by cv.id)
+            from content_values cv
+            where cv.content_id = c.id
+        )::text,
+        '[]'
+    ),
+    c.created_by,
+    c.updated_at
+from contents c;
@@ -0,0 +1,2 @@
+alter table contents drop column unpublish_at;
+alter table contents drop column publish_at;
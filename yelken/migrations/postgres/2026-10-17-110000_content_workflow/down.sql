@@ -0,0 +1,6 @@
+delete from permissions where key in ('content.review', 'content.publish');
+
+update contents set stage = 'draft' where stage not in ('draft', 'published');
+
+alter table contents drop constraint contents_stage_check;
+alter table contents add constraint contents_stage_check check (stage in ('published', 'draft'));
@@ -0,0 +1,9 @@
+alter table contents drop constraint contents_stage_check;
+alter table contents add constraint contents_stage_check check (stage in ('draft', 'in_review', 'approved', 'published', 'archived'));
+
+-- Only admins can review and publish contents, writers need to be granted these permissions explicitly.
+insert into permissions (user_id, role_id, key)
+select user_id, role_id, 'content.review' from permissions where key = 'admin';
+
+insert into permissions (user_id, role_id, key)
+select user_id, role_id, 'content.publish' from permissions where key = 'admin';
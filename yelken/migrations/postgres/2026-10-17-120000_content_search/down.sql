@@ -0,0 +1,2 @@
+drop index content_values_search_index;
+alter table content_values drop column search;
@@ -0,0 +1,4 @@
+-- 'simple' configuration is used since values of all locales and the ones without a locale share the same column
+alter table content_values add column search tsvector generated always as (to_tsvector('simple', value)) stored;
+
+create index content_values_search_index on content_values using gin (search);
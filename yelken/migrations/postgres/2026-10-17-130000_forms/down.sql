@@ -0,0 +1,5 @@
+alter table form_submissions drop constraint fk_form_submissions_form_id;
+alter table form_submissions drop column form_id;
+
+drop table form_fields;
+drop table forms;
@@ -0,0 +1,24 @@
+create table forms(
+    id         serial primary key not null,
+    key        varchar(128) not null unique,
+    name       varchar(128) not null,
+    "desc"     text         default null,
+    created_at timestamp    not null default current_timestamp
+);
+
+create table form_fields(
+    id         serial primary key not null,
+    form_id    int          not null,
+    key        varchar(128) not null,
+    name       varchar(128) not null,
+    kind       varchar(16)  not null check (kind in ('string', 'multiline', 'email', 'int')),
+    required   bool         not null default false,
+    min_length int          default null,
+    max_length int          default null,
+    unique (form_id, key),
+    constraint fk_form_fields_form_id foreign key (form_id) references forms (id) on delete cascade on update no action
+);
+
+-- Submissions made before forms are defined are kept without a form
+alter table form_submissions add column form_id int default null;
+alter table form_submissions add constraint fk_form_submissions_form_id foreign key (form_id) references forms (id) on delete cascade on update no action;
@@ -0,0 +1,5 @@
+alter table forms drop column challenge;
+alter table forms drop column hourly_limit;
+alter table forms drop column ip_hourly_limit;
+alter table forms drop column min_fill_seconds;
+alter table forms drop column honeypot;
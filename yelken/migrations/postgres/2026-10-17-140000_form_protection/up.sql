@@ -0,0 +1,5 @@
+alter table forms add column honeypot varchar(128) default null;
+alter table forms add column min_fill_seconds int default null;
+alter table forms add column ip_hourly_limit int default null;
+alter table forms add column hourly_limit int default null;
+alter table forms add column challenge bool not null default false;
@@ -0,0 +1 @@
+alter table forms drop column notify_email;
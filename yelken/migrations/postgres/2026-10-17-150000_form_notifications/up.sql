@@ -0,0 +1 @@
+alter table forms add column notify_email varchar(256) default null;
@@ -0,0 +1,12 @@
+-- Links users to accounts at OpenID Connect providers, subject is only unique within a provider
+create table user_identities(
+    id         serial       primary key not null,
+    user_id    int          not null,
+    provider   varchar(64)  not null,
+    subject    varchar(255) not null,
+    created_at timestamp    not null default current_timestamp,
+    unique (provider, subject),
+    constraint fk_user_identities_user_id foreign key (user_id) references users (id) on delete cascade on update no action
+);
+
+create index user_identities_user_id on user_identities (user_id);
@@ -0,0 +1,2 @@
+delete from fields where key in ('boolean', 'decimal', 'date', 'datetime', 'markdown', 'richtext', 'json', 'url', 'email', 'reference')
+    and not exists (select 1 from model_fields where model_fields.field_id = fields.id);
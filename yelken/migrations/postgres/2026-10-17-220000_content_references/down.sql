@@ -0,0 +1,2 @@
+alter table model_fields drop constraint fk_model_fields_reference_model_id;
+alter table model_fields drop column reference_model_id;
@@ -0,0 +1,3 @@
+-- Values of reference fields can optionally be restricted to the contents of a single model
+alter table model_fields add column reference_model_id int default null;
+alter table model_fields add constraint fk_model_fields_reference_model_id foreign key (reference_model_id) references models (id) on delete no action on update no action;
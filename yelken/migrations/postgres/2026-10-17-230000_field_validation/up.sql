@@ -0,0 +1,2 @@
+-- Validation rules of model fields are kept as a JSON object
+alter table model_fields add column validation text not null default '{}';
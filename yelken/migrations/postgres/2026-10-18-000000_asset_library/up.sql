@@ -0,0 +1,27 @@
+create table asset_folders(
+    id         serial primary key not null,
+    parent_id  int          default null,
+    name       varchar(128) not null,
+    created_at timestamp    not null default current_timestamp,
+    unique (parent_id, name),
+    constraint fk_asset_folders_parent_id foreign key (parent_id) references asset_folders (id) on delete no action on update no action
+);
+
+-- Metadata of assets uploaded before is not known, so the new columns are left empty for them
+alter table assets add column folder_id int default null;
+alter table assets add column size bigint default null;
+alter table assets add column checksum varchar(64) default null;
+alter table assets add column width int default null;
+alter table assets add column height int default null;
+alter table assets add constraint fk_assets_folder_id foreign key (folder_id) references asset_folders (id) on delete no action on update no action;
+
+create index assets_checksum on assets (checksum);
+
+create table asset_alts(
+    asset_id int         not null,
+    locale   varchar(8)  not null,
+    value    text        not null,
+    primary key (asset_id, locale),
+    constraint fk_asset_alts_asset_id foreign key (asset_id) references assets (id) on delete cascade on update no action,
+    constraint fk_asset_alts_locale foreign key (locale) references locales (key) on delete cascade on update no action
+);
@@ -0,0 +1,6 @@
+-- Only the first asset of a shared file is kept.
+delete from assets where id not in (select min(id) from assets group by filename);
+
+drop index assets_filename;
+
+alter table assets add constraint assets_filename_key unique (filename);
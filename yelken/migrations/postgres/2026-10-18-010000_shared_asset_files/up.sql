@@ -0,0 +1,5 @@
+-- Uploads of the same file get their own assets sharing the stored file, which is kept until
+-- the last of them is deleted.
+alter table assets drop constraint assets_filename_key;
+
+create index assets_filename on assets (filename);
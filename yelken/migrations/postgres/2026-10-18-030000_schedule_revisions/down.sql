@@ -0,0 +1,7 @@
+alter table content_revisions drop column unpublish_at;
+alter table content_revisions drop column publish_at;
+
+delete from content_revisions where action = 'update_schedule';
+
+alter table content_revisions drop constraint content_revisions_action_check;
+alter table content_revisions add constraint content_revisions_action_check check (action in ('create', 'update_name', 'update_stage', 'create_value', 'update_value', 'delete_value', 'restore'));
@@ -0,0 +1,10 @@
+-- Schedule changes are recorded as revisions too, along with the schedule at that moment.
+alter table content_revisions drop constraint content_revisions_action_check;
+alter table content_revisions add constraint content_revisions_action_check check (action in ('create', 'update_name', 'update_stage', 'update_schedule', 'create_value', 'update_value', 'delete_value', 'restore'));
+
+alter table content_revisions add column publish_at timestamp default null;
+alter table content_revisions add column unpublish_at timestamp default null;
+
+update content_revisions r set publish_at = c.publish_at, unpublish_at = c.unpublish_at
+from contents c
+where c.id = r.content_id;
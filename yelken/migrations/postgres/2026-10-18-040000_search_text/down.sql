@@ -0,0 +1,9 @@
+drop index content_values_search_index;
+alter table content_values drop column search;
+alter table content_values drop column search_text;
+
+drop function search_config;
+
+alter table content_values add column search tsvector generated always as (to_tsvector('simple', value)) stored;
+
+create index content_values_search_index on content_values using gin (search);
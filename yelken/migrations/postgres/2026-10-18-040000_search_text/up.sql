@@ -0,0 +1,41 @@
+-- Text search configuration of the locale, so that words of a localized value are stemmed in its
+-- language. Values without a locale and locales without a configuration use 'simple'.
+create function search_config(locale varchar) returns regconfig as $$
+  select case split_part(split_part(lower(locale), '-', 1), '_', 1)
+    when 'da' then 'danish'::regconfig
+    when 'de' then 'german'::regconfig
+    when 'en' then 'english'::regconfig
+    when 'es' then 'spanish'::regconfig
+    when 'fi' then 'finnish'::regconfig
+    when 'fr' then 'french'::regconfig
+    when 'hu' then 'hungarian'::regconfig
+    when 'it' then 'italian'::regconfig
+    when 'nb' then 'norwegian'::regconfig
+    when 'nl' then 'dutch'::regconfig
+    when 'no' then 'norwegian'::regconfig
+    when 'pt' then 'portuguese'::regconfig
+    when 'ro' then 'romanian'::regconfig
+    when 'ru' then 'russian'::regconfig
+    when 'sv' then 'swedish'::regconfig
+    when 'tr' then 'turkish'::regconfig
+    else 'simple'::regconfig
+  end
+$$ language sql immutable;
+
+-- Only textual values are searched. Their text is stored by the application with the markup of
+-- markdown and rich text values stripped.
+drop index content_values_search_index;
+alter table content_values drop column search;
+
+alter table content_values add column search_text text default null;
+
+update content_values cv set search_text = case
+    when f.kind in ('markdown', 'richtext') then regexp_replace(cv.value, '</?[a-zA-Z][^>]*>', ' ', 'g')
+    else cv.value
+  end
+from model_fields mf, fields f
+where mf.id = cv.model_field_id and f.id = mf.field_id and f.kind in ('string', 'multiline', 'markdown', 'richtext');
+
+alter table content_values add column search tsvector generated always as (to_tsvector(search_config(locale), coalesce(search_text, ''))) stored;
+
+create index content_values_search_index on content_values using gin (search);
@@ -0,0 +1,32 @@
+create table content_revisions(
+    id         integer primary key autoincrement,
+    content_id int          not null,
+    action     varchar(16)  not null,
+    name       text         not null,
+    stage      varchar(16)  not null,
+    `values`   text         not null,
+    created_by int          default null,
+    created_at timestamp    not null default current_timestamp,
+    foreign key (content_id) references contents (id) on delete cascade on update no action,
+    foreign key (created_by) references users (id) on delete set null on update no action,
+    check (action in ('create', 'update_name', 'update_stage', 'create_value', 'update_value', 'delete_value', 'restore'))
+);
+
+create index content_revisions_content_id on content_revisions (content_id);
+
+insert into content_revisions (content_id, action, name, stage, `values`, created_by, created_at)
+select
+    c.id,
+    'create',
+    c.name,
+    c.stage,
+    coalesce(
+        (
+            select json_group_array(json_object('modelFieldId', cv.model_field_id, 'value', cv.value, 'locale', cv.locale))
+            from (select * from content_values where content_id = c.id order by id) cv
+        ),
+        '[]'
+    ),
+    c.created_by,
+    c.updated_at
+from contents c;
@@ -0,0 +1,2 @@
+alter table contents add column publish_at timestamp default null;
+alter table contents add column unpublish_at timestamp default null;
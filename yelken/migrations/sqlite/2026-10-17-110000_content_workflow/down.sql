@@ -0,0 +1,39 @@
+pragma foreign_keys = off;
+
+begin;
+
+delete from permissions where key in ('content.review', 'content.publish');
+
+update contents set stage = 'draft' where stage not in ('draft', 'published');
+
+create table contents_new(
+    id           integer primary key autoincrement,
+    model_id     int          not null,
+    name         text         not null,
+    stage        varchar(16)  not null default 'draft',
+    created_by   int          default null,
+    created_at   timestamp    not null default current_timestamp,
+    updated_at   timestamp    not null default current_timestamp,
+    publish_at   timestamp    default null,
+    unpublish_at timestamp    default null,
+    foreign key (model_id) references models (id) on delete no action on update no action,
+    foreign key (created_by) references users (id) on delete no action on update no action,
+    check (stage in ('published', 'draft'))
+);
+
+insert into contents_new (id, model_id, name, stage, created_by, created_at, updated_at, publish_at, unpublish_at)
+select id, model_id, name, stage, created_by, created_at, updated_at, publish_at, unpublish_at from contents;
+
+drop table contents;
+
+alter table contents_new rename to contents;
+
+create trigger contents_updated_at update of name, stage on contents
+  for each row
+  begin
+    update contents set updated_at = current_timestamp where id = old.id;
+  end;
+
+commit;
+
+pragma foreign_keys = on;
@@ -0,0 +1,4 @@
+drop trigger content_values_search_update;
+drop trigger content_values_search_delete;
+drop trigger content_values_search_insert;
+drop table content_values_search;
@@ -0,0 +1,24 @@
+create virtual table content_values_search using fts5(
+    value,
+    content = 'content_values',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+insert into content_values_search (rowid, value) select id, value from content_values;
+
+create trigger content_values_search_insert after insert on content_values
+  begin
+    insert into content_values_search (rowid, value) values (new.id, new.value);
+  end;
+
+create trigger content_values_search_delete after delete on content_values
+  begin
+    insert into content_values_search (content_values_search, rowid, value) values ('delete', old.id, old.value);
+  end;
+
+create trigger content_values_search_update after update of value on content_values
+  begin
+    insert into content_values_search (content_values_search, rowid, value) values ('delete', old.id, old.value);
+    insert into content_values_search (rowid, value) values (new.id, new.value);
+  end;
@@ -0,0 +1,4 @@
+alter table form_submissions drop column form_id;
+
+drop table form_fields;
+drop table forms;
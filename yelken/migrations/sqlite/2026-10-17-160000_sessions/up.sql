@@ -0,0 +1,11 @@
+create table sessions(
+    id           integer primary key autoincrement,
+    user_id      int          not null,
+    refresh_hash varchar(128) not null,
+    expires_at   timestamp    not null,
+    refreshed_at timestamp    not null default current_timestamp,
+    created_at   timestamp    not null default current_timestamp,
+    foreign key (user_id) references users (id) on delete cascade on update no action
+);
+
+create index sessions_user_id on sessions (user_id);
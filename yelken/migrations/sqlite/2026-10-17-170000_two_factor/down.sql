@@ -0,0 +1,3 @@
+alter table roles drop column require_two_factor;
+drop table recovery_codes;
+drop table two_factors;
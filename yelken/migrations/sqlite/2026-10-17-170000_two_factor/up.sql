@@ -0,0 +1,22 @@
+-- Enrollment is pending until the first code is confirmed
+create table two_factors(
+    user_id         int         primary key not null,
+    secret          varchar(64) not null,
+    confirmed_at    timestamp   default null,
+    last_step       bigint      default null,
+    failed_attempts int         not null default 0,
+    locked_until    timestamp   default null,
+    created_at      timestamp   not null default current_timestamp,
+    foreign key (user_id) references users (id) on delete cascade on update no action
+);
+
+create table recovery_codes(
+    id         integer primary key autoincrement,
+    user_id    int          not null,
+    code_hash  varchar(128) not null,
+    created_at timestamp    not null default current_timestamp,
+    unique (user_id, code_hash),
+    foreign key (user_id) references users (id) on delete cascade on update no action
+);
+
+alter table roles add column require_two_factor bool not null default false;
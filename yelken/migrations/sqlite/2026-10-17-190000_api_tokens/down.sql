@@ -0,0 +1,2 @@
+drop table api_token_permissions;
+drop table api_tokens;
@@ -0,0 +1,22 @@
+create table api_tokens(
+    id           integer primary key autoincrement,
+    user_id      int          not null,
+    name         varchar(128) not null,
+    token_hash   varchar(128) not null,
+    expires_at   timestamp    not null,
+    last_used_at timestamp    default null,
+    created_at   timestamp    not null default current_timestamp,
+    foreign key (user_id) references users (id) on delete cascade on update no action
+);
+
+create index api_tokens_user_id on api_tokens (user_id);
+
+-- Scope of the token, which is a subset of the permissions of its user
+create table api_token_permissions(
+    id         integer primary key autoincrement,
+    token_id   int         not null,
+    key        varchar(32) not null,
+    created_at timestamp   not null default current_timestamp,
+    unique (token_id, key),
+    foreign key (token_id) references api_tokens (id) on delete cascade on update no action
+);
@@ -0,0 +1 @@
+alter table models drop column public;
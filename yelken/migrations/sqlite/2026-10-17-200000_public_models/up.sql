@@ -0,0 +1,2 @@
+-- Contents of public models are served by the content delivery API without authentication
+alter table models add column public bool not null default false;
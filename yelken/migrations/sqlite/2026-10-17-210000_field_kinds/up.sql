@@ -0,0 +1,16 @@
+-- Fields of the newly supported kinds are only added if the defaults are already created by setup
+insert into fields (key, name, kind)
+select v.key, v.name, v.kind from (
+    select 'boolean' as key, 'Boolean' as name, 'bool' as kind
+    union all select 'decimal', 'Decimal', 'float'
+    union all select 'date', 'Date', 'date'
+    union all select 'datetime', 'Date Time', 'datetime'
+    union all select 'markdown', 'Markdown', 'markdown'
+    union all select 'richtext', 'Rich Text', 'richtext'
+    union all select 'json', 'JSON', 'json'
+    union all select 'url', 'URL', 'url'
+    union all select 'email', 'Email', 'email'
+    union all select 'reference', 'Reference', 'reference'
+) v
+where exists (select 1 from options where key = 'setup.defaults_init' and value = 'true')
+    and not exists (select 1 from fields f where f.key = v.key);
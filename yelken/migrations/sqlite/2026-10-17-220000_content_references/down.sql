@@ -0,0 +1 @@
+alter table model_fields drop column reference_model_id;
@@ -0,0 +1,2 @@
+-- Values of reference fields can optionally be restricted to the contents of a single model
+alter table model_fields add column reference_model_id int default null references models (id) on delete no action on update no action;
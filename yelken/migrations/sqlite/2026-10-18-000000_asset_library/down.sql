@@ -0,0 +1,11 @@
+drop table asset_alts;
+
+drop index assets_checksum;
+
+alter table assets drop column height;
+alter table assets drop column width;
+alter table assets drop column checksum;
+alter table assets drop column size;
+alter table assets drop column folder_id;
+
+drop table asset_folders;
@@ -0,0 +1,26 @@
+create table asset_folders(
+    id         integer primary key autoincrement,
+    parent_id  int          default null,
+    name       varchar(128) not null,
+    created_at timestamp    not null default current_timestamp,
+    unique (parent_id, name),
+    foreign key (parent_id) references asset_folders (id) on delete no action on update no action
+);
+
+-- Metadata of assets uploaded before is not known, so the new columns are left empty for them
+alter table assets add column folder_id int default null references asset_folders (id) on delete no action on update no action;
+alter table assets add column size bigint default null;
+alter table assets add column checksum varchar(64) default null;
+alter table assets add column width int default null;
+alter table assets add column height int default null;
+
+create index assets_checksum on assets (checksum);
+
+create table asset_alts(
+    asset_id int         not null,
+    locale   varchar(8)  not null,
+    value    text        not null,
+    primary key (asset_id, locale),
+    foreign key (asset_id) references assets (id) on delete cascade on update no action,
+    foreign key (locale) references locales (key) on delete cascade on update no action
+);
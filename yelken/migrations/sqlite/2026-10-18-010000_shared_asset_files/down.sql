@@ -0,0 +1,42 @@
+pragma foreign_keys = off;
+
+begin;
+
+-- Only the first asset of a shared file is kept.
+delete from asset_alts where asset_id not in (select min(id) from assets group by filename);
+delete from assets where id not in (select min(id) from assets group by filename);
+
+create table assets_new(
+    id         integer primary key autoincrement,
+    name       varchar(128) not null,
+    filename   varchar(128) not null unique,
+    filetype   varchar(128) default null,
+    created_by int          default null,
+    created_at timestamp    not null default current_timestamp,
+    updated_at timestamp    not null default current_timestamp,
+    folder_id  int          default null references asset_folders (id) on delete no action on update no action,
+    size       bigint       default null,
+    checksum   varchar(64)  default null,
+    width      int          default null,
+    height     int          default null,
+    foreign key (created_by) references users (id) on delete set null on update no action
+);
+
+insert into assets_new (id, name, filename, filetype, created_by, created_at, updated_at, folder_id, size, checksum, width, height)
+select id, name, filename, filetype, created_by, created_at, updated_at, folder_id, size, checksum, width, height from assets;
+
+drop table assets;
+
+alter table assets_new rename to assets;
+
+create index assets_checksum on assets (checksum);
+
+create trigger assets_updated_at update of name on assets
+  for each row
+  begin
+    update assets set updated_at = current_timestamp where id = old.id;
+  end;
+
+commit;
+
+pragma foreign_keys = on;
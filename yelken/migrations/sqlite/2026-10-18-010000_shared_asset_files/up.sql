@@ -0,0 +1,42 @@
+-- SQLite cannot drop a unique constraint, assets is rebuilt with foreign keys disabled so that
+-- dropping the old table does not cascade into its alts. Uploads of the same file get their own
+-- assets sharing the stored file, which is kept until the last of them is deleted.
+pragma foreign_keys = off;
+
+begin;
+
+create table assets_new(
+    id         integer primary key autoincrement,
+    name       varchar(128) not null,
+    filename   varchar(128) not null,
+    filetype   varchar(128) default null,
+    created_by int          default null,
+    created_at timestamp    not null default current_timestamp,
+    updated_at timestamp    not null default current_timestamp,
+    folder_id  int          default null references asset_folders (id) on delete no action on update no action,
+    size       bigint       default null,
+    checksum   varchar(64)  default null,
+    width      int          default null,
+    height     int          default null,
+    foreign key (created_by) references users (id) on delete set null on update no action
+);
+
+insert into assets_new (id, name, filename, filetype, created_by, created_at, updated_at, folder_id, size, checksum, width, height)
+select id, name, filename, filetype, created_by, created_at, updated_at, folder_id, size, checksum, width, height from assets;
+
+drop table assets;
+
+alter table assets_new rename to assets;
+
+create index assets_filename on assets (filename);
+create index assets_checksum on assets (checksum);
+
+create trigger assets_updated_at update of name on assets
+  for each row
+  begin
+    update assets set updated_at = current_timestamp where id = old.id;
+  end;
+
+commit;
+
+pragma foreign_keys = on;
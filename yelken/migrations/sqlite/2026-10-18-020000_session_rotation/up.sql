@@ -0,0 +1,3 @@
+-- Hash of the refresh token that is rotated last, so that replaying it can be told apart from
+-- presenting a token that is never issued.
+alter table sessions add column previous_refresh_hash varchar(128) default null;
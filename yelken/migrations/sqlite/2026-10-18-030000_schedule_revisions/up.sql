@@ -0,0 +1,36 @@
+-- Schedule changes are recorded as revisions too, along with the schedule at that moment. SQLite
+-- cannot alter a check constraint, content_revisions is rebuilt instead.
+pragma foreign_keys = off;
+
+begin;
+
+create table content_revisions_new(
+    id           integer primary key autoincrement,
+    content_id   int          not null,
+    action       varchar(16)  not null,
+    name         text         not null,
+    stage        varchar(16)  not null,
+    `values`     text         not null,
+    created_by   int          default null,
+    created_at   timestamp    not null default current_timestamp,
+    publish_at   timestamp    default null,
+    unpublish_at timestamp    default null,
+    foreign key (content_id) references contents (id) on delete cascade on update no action,
+    foreign key (created_by) references users (id) on delete set null on update no action,
+    check (action in ('create', 'update_name', 'update_stage', 'update_schedule', 'create_value', 'update_value', 'delete_value', 'restore'))
+);
+
+insert into content_revisions_new (id, content_id, action, name, stage, `values`, created_by, created_at, publish_at, unpublish_at)
+select r.id, r.content_id, r.action, r.name, r.stage, r.`values`, r.created_by, r.created_at, c.publish_at, c.unpublish_at
+from content_revisions r
+left join contents c on c.id = r.content_id;
+
+drop table content_revisions;
+
+alter table content_revisions_new rename to content_revisions;
+
+create index content_revisions_content_id on content_revisions (content_id);
+
+commit;
+
+pragma foreign_keys = on;
@@ -0,0 +1,43 @@
+-- Only textual values are searched. Their text is stored by the application with the markup of
+-- markdown and rich text values stripped. SQLite cannot strip markup here, so existing markdown
+-- and rich text values are indexed as they are until they are updated.
+drop trigger content_values_search_update;
+drop trigger content_values_search_delete;
+drop trigger content_values_search_insert;
+drop table content_values_search;
+
+alter table content_values add column search_text text default null;
+
+update content_values set search_text = value
+where model_field_id in (
+    select mf.id from model_fields mf
+    inner join fields f on f.id = mf.field_id
+    where f.kind in ('string', 'multiline', 'markdown', 'richtext')
+);
+
+create virtual table content_values_search using fts5(
+    search_text,
+    content = 'content_values',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+insert into content_values_search (rowid, search_text) select id, search_text from content_values where search_text is not null;
+
+create trigger content_values_search_insert after insert on content_values
+  when new.search_text is not null
+  begin
+    insert into content_values_search (rowid, search_text) values (new.id, new.search_text);
+  end;
+
+create trigger content_values_search_delete after delete on content_values
+  when old.search_text is not null
+  begin
+    insert into content_values_search (content_values_search, rowid, search_text) values ('delete', old.id, old.search_text);
+  end;
+
+create trigger content_values_search_update after update of search_text on content_values
+  begin
+    insert into content_values_search (content_values_search, rowid, search_text) select 'delete', old.id, old.search_text where old.search_text is not null;
+    insert into content_values_search (rowid, search_text) select new.id, new.search_text where new.search_text is not null;
+  end;
@@ -1,6 +1,5 @@
 use std::error::Error;
 
-use base::crypto::Crypto;
 use base::db::{BatchQuery, Connection};
 use base::middlewares::permission::FULL_PERMS;
 use base::models::LoginKind;
@@ -79,6 +78,56 @@ async fn create_defaults(conn: &mut Connection) -> QueryResult<()> {
                 fields::name.eq("Asset"),
                 fields::kind.eq("asset"),
             ),
+            (
+                fields::key.eq("boolean"),
+                fields::name.eq("Boolean"),
+                fields::kind.eq("bool"),
+            ),
+            (
+                fields::key.eq("decimal"),
+                fields::name.eq("Decimal"),
+                fields::kind.eq("float"),
+            ),
+            (
+                fields::key.eq("date"),
+                fields::name.eq("Date"),
+                fields::kind.eq("date"),
+            ),
+            (
+                fields::key.eq("datetime"),
+                fields::name.eq("Date Time"),
+                fields::kind.eq("datetime"),
+            ),
+            (
+                fields::key.eq("markdown"),
+                fields::name.eq("Markdown"),
+                fields::kind.eq("markdown"),
+            ),
+            (
+                fields::key.eq("richtext"),
+                fields::name.eq("Rich Text"),
+                fields::kind.eq("richtext"),
+            ),
+            (
+                fields::key.eq("json"),
+                fields::name.eq("JSON"),
+                fields::kind.eq("json"),
+            ),
+            (
+                fields::key.eq("url"),
+                fields::name.eq("URL"),
+                fields::kind.eq("url"),
+            ),
+            (
+                fields::key.eq("email"),
+                fields::name.eq("Email"),
+                fields::kind.eq("email"),
+            ),
+            (
+                fields::key.eq("reference"),
+                fields::name.eq("Reference"),
+                fields::kind.eq("reference"),
+            ),
         ])
         .batched()
         .execute(conn)
@@ -87,7 +136,7 @@ async fn create_defaults(conn: &mut Connection) -> QueryResult<()> {
     Ok(())
 }
 
-async fn create_admin_user(conn: &mut Connection, crypto: &Crypto, user: User) -> QueryResult<()> {
+async fn create_admin_user(conn: &mut Connection, user: User) -> QueryResult<()> {
     use rand::{Rng, distr::Alphanumeric, rng};
 
     let username = user
@@ -101,11 +150,7 @@ async fn create_admin_user(conn: &mut Connection, crypto: &Crypto, user: User) -
             .collect::<String>()
             .as_str();
 
-    let salt: String = (0..32)
-        .map(|_| rng().sample(Alphanumeric) as char)
-        .collect();
-
-    let password = crypto.sign512(format!("{salt}{}", user.password).as_bytes());
+    let password = base::password::hash(&user.password);
 
     let user = diesel::insert_into(users::table)
         .values((
@@ -113,7 +158,7 @@ async fn create_admin_user(conn: &mut Connection, crypto: &Crypto, user: User) -
             users::name.eq(user.name),
             users::email.eq(user.email),
             users::login_kind.eq(LoginKind::Email),
-            users::password.eq(format!("{salt}{password}")),
+            users::password.eq(password),
         ))
         .get_result::<base::models::User>(conn)
         .await?;
@@ -139,7 +184,6 @@ async fn create_admin_user(conn: &mut Connection, crypto: &Crypto, user: User) -
 
 pub async fn init(
     conn: &mut Connection,
-    crypto: &Crypto,
     defaults: bool,
     admin: Option<User>,
     theme: Option<InstallTheme>,
@@ -159,7 +203,7 @@ pub async fn init(
             }
 
             if let Some(admin) = admin {
-                create_admin_user(conn, crypto, admin).await?;
+                create_admin_user(conn, admin).await?;
 
                 diesel::insert_into(options::table)
                     .values((
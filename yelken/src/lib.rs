@@ -9,6 +9,8 @@ use base::{
     config::{Config, Options},
     crypto::Crypto,
     db::{Pool, PooledConnection},
+    l10n::L10n,
+    mail::{MailConfig, Mailer},
     schema::options,
     services::ServeStorageDir,
 };
@@ -20,6 +22,9 @@ use tower_http::cors::CorsLayer;
 
 mod health;
 
+#[cfg(feature = "cms")]
+pub use cms::FormProtection;
+
 pub struct DatabaseConfig {
     pub url: String,
 }
@@ -62,15 +67,34 @@ async fn load_options(mut conn: PooledConnection) -> Options {
     Options::new(theme, locales, default_locale)
 }
 
-pub async fn router(
-    crypto: Crypto,
-    config: Config,
-    pool: Pool,
-    storage: Operator,
-    app_assets_storage: Operator,
-    tmp_storage: Operator,
-    cors_origins: Vec<HeaderValue>,
-) -> Router<()> {
+/// Configurations and resources the router is built with.
+pub struct RouterConfig {
+    pub crypto: Crypto,
+    pub config: Config,
+    pub mail_config: MailConfig,
+    pub pool: Pool,
+    pub storage: Operator,
+    pub app_assets_storage: Operator,
+    pub tmp_storage: Operator,
+    pub cors_origins: Vec<HeaderValue>,
+    #[cfg(feature = "cms")]
+    pub form_protection: FormProtection,
+}
+
+pub async fn router(router_config: RouterConfig) -> Router<()> {
+    let RouterConfig {
+        crypto,
+        config,
+        mail_config,
+        pool,
+        storage,
+        app_assets_storage,
+        tmp_storage,
+        cors_origins,
+        #[cfg(feature = "cms")]
+        form_protection,
+    } = router_config;
+
     let options = load_options(pool.get().await.unwrap()).await;
 
     let cors = CorsLayer::new()
@@ -85,10 +109,21 @@ pub async fn router(
 
     let state = AppState::new(config, pool, storage.clone(), tmp_storage);
 
+    let l10n = L10n::new(
+        &storage,
+        &options.locale_locations(),
+        &options.locales(),
+        options.default_locale(),
+    )
+    .await;
+
+    let mailer = Mailer::new(mail_config, l10n.clone());
+
     let layers = ServiceBuilder::new()
         .layer(cors)
-        .layer(Extension(crypto))
-        .layer(Extension(options.clone()));
+        .layer(Extension(crypto.clone()))
+        .layer(Extension(options.clone()))
+        .layer(Extension(mailer));
 
     let api = Router::new()
         .nest("/common", common::router(state.clone()))
@@ -98,13 +133,30 @@ pub async fn router(
     let api = api.nest("/admin", admin::router(state.clone()));
 
     #[cfg(feature = "appearance")]
-    let api = api.nest("/appearance", appearance::router(state.clone()));
+    let api = {
+        let appearance = appearance::router(state.clone());
+
+        // Installing, activating or removing a theme changes the models visible to GraphQL
+        #[cfg(feature = "cms-graphql")]
+        let appearance =
+            appearance.layer(axum::middleware::from_fn(cms::graphql::invalidate_schema));
+
+        api.nest("/appearance", appearance)
+    };
 
     #[cfg(feature = "auth")]
-    let api = api.nest("/auth", auth::router());
+    let api = api.nest("/auth", auth::router(state.clone()));
 
     #[cfg(feature = "cms")]
-    let api = api.nest("/cms", cms::router(state.clone()));
+    let api = api
+        .nest("/cms", cms::router(state.clone()))
+        .nest("/delivery", cms::delivery_router());
+
+    #[cfg(feature = "cms-graphql")]
+    let (api, layers) = (
+        api.merge(cms::graphql_router()),
+        layers.layer(Extension(cms::graphql::GraphQL::default())),
+    );
 
     #[cfg(feature = "user")]
     let api = api.nest("/user", user::router(state.clone()));
@@ -125,20 +177,28 @@ pub async fn router(
         )
     };
 
-    let app = Router::new().nest_service(
-        "/assets/content",
-        ServeStorageDir::new(storage.clone(), || "assets".to_string()),
-    );
+    // Content assets are stored under unique file names and never change, theme assets can be
+    // updated in place so they are revalidated on every use
+    let content_assets = ServeStorageDir::new(storage.clone(), || "assets".to_string())
+        .with_cache_control("public, max-age=31536000, immutable");
+
+    #[cfg(feature = "image")]
+    let content_assets = content_assets.with_image_derivatives(state.config.image_sizes.clone());
+
+    let app = Router::new().nest_service("/assets/content", content_assets);
 
     let app = {
         let options = options.clone();
 
-        app.nest_service(
-            "/assets/theme",
-            ServeStorageDir::new(storage.clone(), move || {
-                format!("themes/{}/assets", options.theme())
-            }),
-        )
+        let theme_assets = ServeStorageDir::new(storage.clone(), move || {
+            format!("themes/{}/assets", options.theme())
+        })
+        .with_cache_control("public, no-cache");
+
+        #[cfg(feature = "image")]
+        let theme_assets = theme_assets.with_image_derivatives(state.config.image_sizes.clone());
+
+        app.nest_service("/assets/theme", theme_assets)
     };
 
     #[cfg(feature = "app")]
@@ -172,22 +232,19 @@ pub async fn router(
     };
 
     #[cfg(feature = "cms")]
-    let app = app.nest("/yk/form", cms::form_router());
+    let app = app.nest("/yk/form", cms::form_router(form_protection));
 
     #[cfg(feature = "appearance")]
     let (app, layers) = {
-        let l10n = appearance::L10n::new(
-            &storage,
-            &options.locale_locations(),
-            &options.locales(),
-            options.default_locale(),
-        )
-        .await;
-
         #[cfg(feature = "plugin")]
-        let resources = (l10n.clone(), state.pool.clone(), plugin_host);
+        let resources = (
+            l10n.clone(),
+            state.pool.clone(),
+            crypto.clone(),
+            plugin_host,
+        );
         #[cfg(not(feature = "plugin"))]
-        let resources = (l10n.clone(), state.pool.clone());
+        let resources = (l10n.clone(), state.pool.clone(), crypto.clone());
 
         let render = appearance::Render::new(
             &storage,
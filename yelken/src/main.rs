@@ -2,7 +2,12 @@ use std::{net::SocketAddrV4, time::Instant};
 
 use anyhow::{Context, Result};
 use axum::{extract::Request, http::HeaderValue, middleware::Next, response::Response};
-use base::{config::Config, crypto::Crypto, db::Connection};
+use base::{
+    config::Config,
+    crypto::Crypto,
+    db::Connection,
+    mail::{MailConfig, Transport},
+};
 use clap::{Parser, Subcommand};
 use diesel_async::pooled_connection::{AsyncDieselConnectionManager, deadpool};
 use yelken::DatabaseConfig;
@@ -33,7 +38,7 @@ struct Args {
 struct ServerConfig {
     pub address: SocketAddrV4,
     pub app_assets_dir: String,
-    pub storage_dir: String,
+    pub storage_url: String,
     pub tmp_dir: String,
     pub cors_origins: Vec<HeaderValue>,
 }
@@ -50,8 +55,7 @@ impl ServerConfig {
         let app_assets_dir = std::env::var("YELKEN_APP_ASSETS_DIR")
             .context("YELKEN_APP_ASSETS_DIR is not defined")?;
 
-        let storage_dir =
-            std::env::var("YELKEN_STORAGE_DIR").context("YELKEN_STORAGE_DIR is not defined")?;
+        let storage_url = storage_url_from_env()?;
 
         let tmp_dir = std::env::var("YELKEN_TMP_DIR").context("YELKEN_TMP_DIR is not defined")?;
 
@@ -75,13 +79,21 @@ impl ServerConfig {
         Ok(Self {
             address,
             app_assets_dir,
-            storage_dir,
+            storage_url,
             tmp_dir,
             cors_origins,
         })
     }
 }
 
+/// Storage is shared by every replica, so it can be any backend given as a url while app assets and
+/// temporary files stay on the local file system.
+fn storage_url_from_env() -> Result<String> {
+    std::env::var("YELKEN_STORAGE_URL")
+        .or_else(|_| std::env::var("YELKEN_STORAGE_DIR"))
+        .context("neither YELKEN_STORAGE_URL nor YELKEN_STORAGE_DIR is defined")
+}
+
 fn db_config_from_env() -> Result<DatabaseConfig> {
     if let Ok(url) = std::env::var("YELKEN_DATABASE_URL") {
         return Ok(DatabaseConfig { url });
@@ -109,6 +121,8 @@ fn db_config_from_env() -> Result<DatabaseConfig> {
 
 fn config_from_env() -> Result<Config> {
     const DEFAULT_UPLOAD_SIZE_LIMIT: usize = 2048 * 1024;
+    const DEFAULT_IMAGE_MAX_DIMENSION: u32 = 2560;
+    const DEFAULT_IMAGE_SIZES: [u32; 5] = [320, 640, 960, 1280, 1920];
 
     let env = std::env::var("YELKEN_ENV").context("YELKEN_ENV is not defined")?;
 
@@ -136,15 +150,59 @@ fn config_from_env() -> Result<Config> {
         DEFAULT_UPLOAD_SIZE_LIMIT
     };
 
+    let image_max_dimension = if let Ok(var) = std::env::var("YELKEN_IMAGE_MAX_DIMENSION") {
+        var.parse()
+            .context("YELKEN_IMAGE_MAX_DIMENSION is not a valid number")?
+    } else {
+        DEFAULT_IMAGE_MAX_DIMENSION
+    };
+
+    let image_sizes = if let Ok(var) = std::env::var("YELKEN_IMAGE_SIZES") {
+        var.split(',')
+            .map(|size| size.trim().parse())
+            .collect::<Result<_, _>>()
+            .context("YELKEN_IMAGE_SIZES is not a valid list of numbers")?
+    } else {
+        DEFAULT_IMAGE_SIZES.into()
+    };
+
     Ok(Config {
         env,
         site_url,
         app_url,
         reload_templates,
         upload_size_limit,
+        image_max_dimension,
+        image_sizes,
     })
 }
 
+fn mail_config_from_env() -> Result<MailConfig> {
+    let transport = match std::env::var("YELKEN_MAIL_TRANSPORT").as_deref() {
+        Ok("smtp") => {
+            let url = std::env::var("YELKEN_SMTP_URL").context("YELKEN_SMTP_URL is not defined")?;
+
+            Transport::smtp(&url).context("YELKEN_SMTP_URL is not valid")?
+        }
+        Ok("file") => {
+            let dir = std::env::var("YELKEN_MAIL_DIR").context("YELKEN_MAIL_DIR is not defined")?;
+
+            Transport::file(dir)
+        }
+        Ok("log") | Err(_) => Transport::Log,
+        Ok(unknown) => anyhow::bail!("unknown YELKEN_MAIL_TRANSPORT {unknown} is given"),
+    };
+
+    let from = match std::env::var("YELKEN_MAIL_FROM") {
+        Ok(from) => from,
+        // Mails are only logged, there is no need to require a real mailbox
+        Err(_) if matches!(transport, Transport::Log) => "Yelken <yelken@localhost>".to_string(),
+        Err(_) => anyhow::bail!("YELKEN_MAIL_FROM is not defined"),
+    };
+
+    Ok(MailConfig { from, transport })
+}
+
 async fn logger(req: Request, next: Next) -> Response {
     let path = req.uri().path().to_owned();
     let method = req.method().to_owned();
@@ -164,7 +222,7 @@ async fn logger(req: Request, next: Next) -> Response {
     res
 }
 
-async fn run_command(command: Command, crypto: &Crypto, db_url: &str) {
+async fn run_command(command: Command, db_url: &str) {
     let mut conn = <Connection as diesel_async::AsyncConnection>::establish(&db_url)
         .await
         .unwrap();
@@ -200,18 +258,16 @@ async fn run_command(command: Command, crypto: &Crypto, db_url: &str) {
                     std::env::var("YELKEN_DEFAULT_THEME_DIR")
                         .expect("Neither `YELKEN_DEFAULT_THEME_DIR` nor `--theme-path` is specified. Please specify at least one.")
                 });
-                let storage_dir =
-                    std::env::var("YELKEN_STORAGE_DIR").expect("YELKEN_STORAGE_DIR is not defined");
+                let storage_url = storage_url_from_env().unwrap();
 
                 let src =
                     opendal::Operator::new(opendal::services::Fs::default().root(&theme_root))
                         .unwrap()
                         .finish();
 
-                let dst =
-                    opendal::Operator::new(opendal::services::Fs::default().root(&storage_dir))
-                        .unwrap()
-                        .finish();
+                let dst = base::storage::operator_from_url(&storage_url)
+                    .context("YELKEN_STORAGE_URL is not valid")
+                    .unwrap();
 
                 Some(setup::InstallTheme {
                     src,
@@ -222,15 +278,9 @@ async fn run_command(command: Command, crypto: &Crypto, db_url: &str) {
                 None
             };
 
-            setup::init(
-                &mut conn,
-                &crypto,
-                create_defaults,
-                admin_user,
-                install_theme,
-            )
-            .await
-            .unwrap();
+            setup::init(&mut conn, create_defaults, admin_user, install_theme)
+                .await
+                .unwrap();
         }
     }
 }
@@ -252,7 +302,7 @@ async fn main() {
     let db_config = db_config_from_env().unwrap();
 
     if let Some(command) = args.command {
-        run_command(command, &crypto, &db_config.url).await;
+        run_command(command, &db_config.url).await;
 
         return;
     }
@@ -261,13 +311,14 @@ async fn main() {
     let pool = deadpool::Pool::builder(db_config).build().unwrap();
 
     let config = config_from_env().unwrap();
+    let mail_config = mail_config_from_env().unwrap();
     let server_config = ServerConfig::from_env().unwrap();
+    #[cfg(feature = "cms")]
+    let form_protection = yelken::FormProtection::from_env().unwrap();
 
-    let storage = {
-        let builder = opendal::services::Fs::default().root(&server_config.storage_dir);
-
-        opendal::Operator::new(builder).unwrap().finish()
-    };
+    let storage = base::storage::operator_from_url(&server_config.storage_url)
+        .context("YELKEN_STORAGE_URL is not valid")
+        .unwrap();
 
     let app_assets_storage = {
         let builder = opendal::services::Fs::default().root(&server_config.app_assets_dir);
@@ -281,15 +332,18 @@ async fn main() {
         opendal::Operator::new(builder).unwrap().finish()
     };
 
-    let app = yelken::router(
+    let app = yelken::router(yelken::RouterConfig {
         crypto,
         config,
+        mail_config,
         pool,
         storage,
         app_assets_storage,
         tmp_storage,
-        server_config.cors_origins,
-    )
+        cors_origins: server_config.cors_origins,
+        #[cfg(feature = "cms")]
+        form_protection,
+    })
     .await
     .layer(axum::middleware::from_fn(logger));
 
@@ -297,10 +351,13 @@ async fn main() {
         .await
         .unwrap();
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await
+    .unwrap();
 }
 
 async fn shutdown_signal() {
@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 
 use base::db::{BatchQuery, Connection};
-use base::models::{ContentStage, Field, Locale, PageKind, Theme};
+use base::field::{FieldKind, check_rules, search_text};
+use base::models::{ContentStage, Field, Locale, PageKind, Theme, ValidationRules};
 use base::responses::HttpError;
 use base::schema::{
     content_values, contents, fields, locales, model_fields, models, namespaces, pages, themes,
@@ -36,6 +37,10 @@ struct ModelField {
     localized: Option<bool>,
     multiple: Option<bool>,
     required: Option<bool>,
+    /// Key of the theme model whose contents the reference field can refer to
+    reference: Option<String>,
+    #[serde(default)]
+    validation: ValidationRules,
 }
 
 #[derive(Debug, Deserialize)]
@@ -226,15 +231,46 @@ async fn create_theme_resources(
             .fields
             .iter()
             .map(|model_field| {
-                fields
-                    .get(&model_field.field)
-                    .map(|f| (f.id, model_field))
-                    .ok_or_else(|| {
-                        HttpError::unprocessable_entity("unknown_field")
-                            .with_context(format!("Field {} is not known", model_field.field))
+                let field = fields.get(&model_field.field).ok_or_else(|| {
+                    HttpError::unprocessable_entity("unknown_field")
+                        .with_context(format!("Field {} is not known", model_field.field))
+                })?;
+
+                check_rules(
+                    FieldKind::of(&field.kind),
+                    model_field.multiple.unwrap_or(false),
+                    &model_field.validation,
+                )
+                .map_err(|e| {
+                    HttpError::unprocessable_entity("invalid_validation_rules").with_context(
+                        format!("Validation rules of {} are invalid, {e}", model_field.key),
+                    )
+                })?;
+
+                let reference_model_id = model_field
+                    .reference
+                    .as_ref()
+                    .map(|reference| {
+                        if FieldKind::of(&field.kind) != FieldKind::Reference {
+                            return Err(HttpError::unprocessable_entity(
+                                "reference_model_for_non_reference_field",
+                            )
+                            .with_context(format!(
+                                "Field {} is not a reference field",
+                                model_field.key
+                            )));
+                        }
+
+                        models.get(reference).map(|m| m.id).ok_or_else(|| {
+                            HttpError::unprocessable_entity("unknown_model")
+                                .with_context(format!("Model {reference} is not known"))
+                        })
                     })
+                    .transpose()?;
+
+                Ok((field.id, reference_model_id, model_field))
             })
-            .collect::<Result<Vec<(i32, &ModelField)>, HttpError>>()?;
+            .collect::<Result<Vec<(i32, Option<i32>, &ModelField)>, HttpError>>()?;
 
         let model_fields = HashMap::<String, base::models::ModelField>::from_iter(
             diesel::insert_into(model_fields::table)
@@ -245,13 +281,15 @@ async fn create_theme_resources(
                             (
                                 model_fields::model_id.eq(model_id),
                                 model_fields::field_id.eq(model_field.0),
-                                model_fields::key.eq(model_field.1.key.clone()),
-                                model_fields::name.eq(model_field.1.name.clone()),
-                                model_fields::desc.eq(model_field.1.desc.clone()),
+                                model_fields::key.eq(model_field.2.key.clone()),
+                                model_fields::name.eq(model_field.2.name.clone()),
+                                model_fields::desc.eq(model_field.2.desc.clone()),
                                 model_fields::localized
-                                    .eq(model_field.1.localized.unwrap_or(false)),
-                                model_fields::multiple.eq(model_field.1.multiple.unwrap_or(false)),
-                                model_fields::required.eq(model_field.1.required.unwrap_or(false)),
+                                    .eq(model_field.2.localized.unwrap_or(false)),
+                                model_fields::multiple.eq(model_field.2.multiple.unwrap_or(false)),
+                                model_fields::required.eq(model_field.2.required.unwrap_or(false)),
+                                model_fields::reference_model_id.eq(model_field.1),
+                                model_fields::validation.eq(model_field.2.validation.clone()),
                             )
                         })
                         .collect::<Vec<_>>(),
@@ -270,7 +308,14 @@ async fn create_theme_resources(
                 .map(|v| {
                     model_fields
                         .get(&v.field)
-                        .map(|mf| (mf.id, v))
+                        .map(|mf| {
+                            let kind = fields
+                                .values()
+                                .find(|f| f.id == mf.field_id)
+                                .map_or(FieldKind::String, |f| FieldKind::of(&f.kind));
+
+                            (mf.id, search_text(kind, &v.value), v)
+                        })
                         .ok_or_else(|| {
                             HttpError::unprocessable_entity("unknown_field").with_context(format!(
                                 "Field in content value {} is not known",
@@ -278,7 +323,7 @@ async fn create_theme_resources(
                             ))
                         })
                 })
-                .collect::<Result<Vec<(i32, &ContentValue)>, HttpError>>()?;
+                .collect::<Result<Vec<(i32, Option<String>, &ContentValue)>, HttpError>>()?;
 
             let content_id = diesel::insert_into(contents::table)
                 .values((
@@ -295,7 +340,7 @@ async fn create_theme_resources(
                     values
                         .into_iter()
                         .filter_map(|v| {
-                            let locale = v.1.locale.as_ref().and_then(|cl| {
+                            let locale = v.2.locale.as_ref().and_then(|cl| {
                                 if cl == "DEFAULT" {
                                     Some(default_locale.clone())
                                 } else {
@@ -306,8 +351,9 @@ async fn create_theme_resources(
                             Some((
                                 content_values::content_id.eq(content_id),
                                 content_values::model_field_id.eq(v.0),
-                                content_values::value.eq(v.1.value.clone()),
+                                content_values::value.eq(v.2.value.clone()),
                                 content_values::locale.eq(locale),
+                                content_values::search_text.eq(v.1),
                             ))
                         })
                         .collect::<Vec<_>>(),
@@ -126,15 +126,9 @@ pub async fn app_init(base_url: String, name: String, email: String, password: S
         None
     };
 
-    setup::init(
-        &mut conn,
-        &crypto,
-        create_defaults,
-        admin_user,
-        install_theme,
-    )
-    .await
-    .unwrap();
+    setup::init(&mut conn, create_defaults, admin_user, install_theme)
+        .await
+        .unwrap();
 
     let site_url = base_url.parse().expect("Given base_url is not a valid url");
     let app_url = base_url.parse().expect("Given base_url is not a valid url");
@@ -145,19 +139,28 @@ pub async fn app_init(base_url: String, name: String, email: String, password: S
         app_url,
         reload_templates: true,
         upload_size_limit: 8192 * 1024,
+        image_max_dimension: 0,
+        image_sizes: [].into(),
+    };
+
+    let mail_config = base::mail::MailConfig {
+        from: "Yelken <yelken@localhost>".to_string(),
+        transport: base::mail::Transport::Log,
     };
 
     let cors_origins = vec![];
 
-    let app = yelken::router(
+    let app = yelken::router(yelken::RouterConfig {
         crypto,
         config,
+        mail_config,
         pool,
         storage,
         app_assets_storage,
         tmp_storage,
         cors_origins,
-    )
+        form_protection: yelken::FormProtection::new(None),
+    })
     .await
     .layer(axum::middleware::from_fn(logger));
 